@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
 use std::iter::zip;
 use std::ops::{Deref, DerefMut};
 
@@ -29,6 +30,10 @@ use starknet::core::types::contract::SierraClass;
 use starknet::core::types::FieldElement;
 use tracing::{debug, trace, trace_span};
 
+pub use crate::incremental::remove_build_cache;
+use crate::incremental::{
+    contract_source_hash, BuildCache, CachedContract, ModulePaths, BUILD_CACHE_FILE_NAME,
+};
 use crate::inline_macros::utils::{SYSTEM_READS, SYSTEM_WRITES};
 use crate::plugin::{ComputedValuesAuxData, DojoAuxData};
 use crate::semantics::utils::find_module_rw;
@@ -92,18 +97,46 @@ impl Compiler for DojoCompiler {
             .collect::<Vec<_>>();
         trace!(contracts = ?contract_paths);
 
-        let contracts = contracts.iter().collect::<Vec<_>>();
-
-        let classes = {
-            let _ = trace_span!("compile_starknet").enter();
-            compile_prepared_db(db, &contracts, compiler_config)?
-        };
+        let mut build_cache = target_dir
+            .open_ro(BUILD_CACHE_FILE_NAME, "output file", ws.config())
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, BuildCache>(file.deref()).ok())
+            .filter(BuildCache::is_current)
+            .unwrap_or_default();
 
         // (contract name, class hash)
         let mut compiled_classes: HashMap<SmolStr, (FieldElement, Option<abi::Contract>)> =
             HashMap::new();
 
-        for (decl, class) in zip(contracts, classes) {
+        // The contracts whose sources changed since the last build, or whose class is missing.
+        let module_paths = ModulePaths::collect(db);
+        let mut outdated_contracts = vec![];
+        for (decl, contract_path) in zip(&contracts, &contract_paths) {
+            let contract_name = decl.submodule_id.name(db.upcast_mut());
+            let file_name = format!("{}-{contract_name}.json", unit.target().name);
+            let source_hash = contract_source_hash(
+                db,
+                &module_paths,
+                decl.module_id(),
+                &(&unit.compiler_config, &props),
+            );
+
+            match build_cache.get(contract_path, source_hash) {
+                Some(cached) if target_dir.path_unchecked().join(&file_name).exists() => {
+                    trace!(contract = %contract_path, "unchanged since last build");
+                    compiled_classes.insert(contract_name, (cached.class_hash, cached.abi.clone()));
+                }
+                _ => outdated_contracts.push((decl, contract_path, source_hash)),
+            }
+        }
+
+        let classes = {
+            let _ = trace_span!("compile_starknet").enter();
+            let decls = outdated_contracts.iter().map(|(decl, ..)| *decl).collect::<Vec<_>>();
+            compile_prepared_db(db, &decls, compiler_config)?
+        };
+
+        for ((decl, contract_path, source_hash), class) in zip(outdated_contracts, classes) {
             let target_name = &unit.target().name;
             let contract_name = decl.submodule_id.name(db.upcast_mut());
             let file_name = format!("{target_name}-{contract_name}.json");
@@ -115,19 +148,33 @@ impl Compiler for DojoCompiler {
             let class_hash = compute_class_hash_of_contract_class(&class).with_context(|| {
                 format!("problem computing class hash for contract `{contract_name}`")
             })?;
+
+            build_cache.insert(
+                contract_path.clone(),
+                CachedContract { source_hash, class_hash, abi: class.abi.clone() },
+            );
             compiled_classes.insert(contract_name, (class_hash, class.abi));
         }
 
-        let mut manifest = target_dir
+        build_cache.retain(&contract_paths.iter().cloned().collect());
+        let file = target_dir.open_rw(BUILD_CACHE_FILE_NAME, "output file", ws.config())?;
+        fs::write(file.path(), serde_json::to_vec(&build_cache)?)
+            .context("failed to write the build cache")?;
+
+        let previous_manifest = target_dir
             .open_ro("manifest.json", "output file", ws.config())
-            .map(|file| dojo_world::manifest::Manifest::try_from(file.deref()).unwrap_or_default())
-            .unwrap_or_default();
+            .ok()
+            .and_then(|file| dojo_world::manifest::Manifest::try_from(file.deref()).ok());
+        let mut manifest = previous_manifest.clone().unwrap_or_default();
 
         update_manifest(&mut manifest, db, &main_crate_ids, compiled_classes)?;
 
-        manifest.write_to_path(
-            target_dir.open_rw("manifest.json", "output file", ws.config())?.path(),
-        )?;
+        // Rewriting an unchanged manifest would trigger the tools watching it for nothing.
+        if previous_manifest.as_ref() != Some(&manifest) {
+            manifest.write_to_path(
+                target_dir.open_rw("manifest.json", "output file", ws.config())?.path(),
+            )?;
+        }
 
         Ok(())
     }
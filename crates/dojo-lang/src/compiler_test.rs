@@ -4,14 +4,17 @@ use std::{env, fs};
 
 use cairo_lang_test_utils::parse_test_file::TestRunnerResult;
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
-use dojo_test_utils::compiler::build_test_config;
-use dojo_world::manifest::{BASE_CONTRACT_NAME, EXECUTOR_CONTRACT_NAME, WORLD_CONTRACT_NAME};
-use scarb::core::TargetKind;
+use dojo_test_utils::compiler::{build_test_config, copy_project};
+use dojo_world::manifest::{
+    Manifest, BASE_CONTRACT_NAME, EXECUTOR_CONTRACT_NAME, WORLD_CONTRACT_NAME,
+};
+use scarb::core::{TargetKind, Workspace};
 use scarb::ops::{self, CompileOpts};
 use smol_str::SmolStr;
 use starknet::macros::felt;
 
-use super::do_update_manifest;
+use super::{do_update_manifest, remove_build_cache};
+use crate::incremental::BUILD_CACHE_FILE_NAME;
 
 fn build_mock_manifest() -> dojo_world::manifest::Manifest {
     dojo_world::manifest::Manifest {
@@ -149,6 +152,19 @@ fn update_manifest_correctly() {
     assert!(mock_manifest.contracts[2].address.is_none(), "new contract do not have address");
 }
 
+fn compile(ws: &Workspace<'_>) {
+    let packages = ws.members().map(|p| p.id).collect();
+    ops::compile(
+        packages,
+        CompileOpts { include_targets: vec![], exclude_targets: vec![TargetKind::TEST] },
+        ws,
+    )
+    .unwrap_or_else(|op| panic!("Error compiling: {op:?}"));
+}
+
+/// Written over the classes of the previous build, to tell whether they're written again.
+const REBUILD_MARKER: &str = "not rebuilt";
+
 #[test]
 fn test_compiler() {
     let config = build_test_config("../../examples/spawn-and-move/Scarb.toml").unwrap();
@@ -165,6 +181,140 @@ fn test_compiler() {
     );
 }
 
+#[test]
+fn test_compiler_reuses_unchanged_contracts() {
+    let config = build_test_config("../../examples/spawn-and-move/Scarb.toml").unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+
+    let target_dir =
+        Path::new(config.target_dir_override().unwrap()).join(config.profile().as_str());
+
+    compile(&ws);
+    let cache = fs::read_to_string(target_dir.join(BUILD_CACHE_FILE_NAME)).unwrap();
+    assert!(cache.contains("dojo_examples::actions::actions"));
+
+    // A reused class is left as is, so the marker is only overwritten if it's rebuilt.
+    let actions_path = target_dir.join("dojo_examples-actions.json");
+    fs::write(&actions_path, REBUILD_MARKER).unwrap();
+
+    compile(&ws);
+    assert_eq!(
+        fs::read_to_string(&actions_path).unwrap(),
+        REBUILD_MARKER,
+        "unchanged contracts should not be rebuilt"
+    );
+
+    remove_build_cache(&target_dir).unwrap();
+    compile(&ws);
+    assert_ne!(
+        fs::read_to_string(&actions_path).unwrap(),
+        REBUILD_MARKER,
+        "every contract should be rebuilt without the build cache"
+    );
+}
+
+#[test]
+fn test_compiler_rebuilds_contracts_whose_dependencies_changed() {
+    let (project_dir, manifest_path) =
+        copy_project("../../examples/spawn-and-move/Scarb.toml").unwrap();
+    let config = build_test_config(manifest_path.as_str()).unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+
+    let target_dir =
+        Path::new(config.target_dir_override().unwrap()).join(config.profile().as_str());
+    let actions_class_hash = || {
+        let manifest = Manifest::load_from_path(target_dir.join("manifest.json")).unwrap();
+        manifest
+            .contracts
+            .iter()
+            .find(|c| c.name == "dojo_examples::actions::actions")
+            .unwrap()
+            .class_hash
+    };
+
+    compile(&ws);
+    let first_build = actions_class_hash();
+
+    // The actions contract moves players with a function of another module.
+    let utils_path = project_dir.path().join("src/utils.cairo");
+    let utils = fs::read_to_string(&utils_path).unwrap();
+    fs::write(&utils_path, utils.replace("position.vec.x -= 1;", "position.vec.x -= 2;")).unwrap();
+
+    compile(&ws);
+    assert_ne!(actions_class_hash(), first_build, "the actions contract should be rebuilt");
+}
+
+/// A contract using none of the modules of the spawn-and-move example.
+const COUNTER_CONTRACT: &str = r#"
+#[starknet::interface]
+trait ICounter<TContractState> {
+    fn increment(self: @TContractState);
+}
+
+#[dojo::contract]
+mod counter {
+    use starknet::{ContractAddress, get_caller_address};
+    use super::ICounter;
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        Incremented: Incremented,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct Incremented {
+        player: ContractAddress,
+    }
+
+    #[external(v0)]
+    impl CounterImpl of ICounter<ContractState> {
+        fn increment(self: @ContractState) {
+            let world = self.world_dispatcher.read();
+            let player = get_caller_address();
+            emit!(world, Incremented { player });
+        }
+    }
+}
+"#;
+
+#[test]
+fn test_compiler_keeps_contracts_whose_sibling_changed() {
+    let (project_dir, manifest_path) =
+        copy_project("../../examples/spawn-and-move/Scarb.toml").unwrap();
+
+    let lib_path = project_dir.path().join("src/lib.cairo");
+    let lib = fs::read_to_string(&lib_path).unwrap();
+    fs::write(&lib_path, format!("{lib}mod counter;\n")).unwrap();
+    let counter_path = project_dir.path().join("src/counter.cairo");
+    fs::write(&counter_path, COUNTER_CONTRACT).unwrap();
+
+    let config = build_test_config(manifest_path.as_str()).unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+
+    let target_dir =
+        Path::new(config.target_dir_override().unwrap()).join(config.profile().as_str());
+    let class_path = |contract: &str| target_dir.join(format!("dojo_examples-{contract}.json"));
+
+    compile(&ws);
+    fs::write(class_path("actions"), REBUILD_MARKER).unwrap();
+    fs::write(class_path("counter"), REBUILD_MARKER).unwrap();
+
+    fs::write(&counter_path, COUNTER_CONTRACT.replace("player", "caller")).unwrap();
+
+    compile(&ws);
+    assert_ne!(
+        fs::read_to_string(class_path("counter")).unwrap(),
+        REBUILD_MARKER,
+        "the changed contract should be rebuilt"
+    );
+    assert_eq!(
+        fs::read_to_string(class_path("actions")).unwrap(),
+        REBUILD_MARKER,
+        "its sibling should not be rebuilt"
+    );
+}
+
 cairo_lang_test_utils::test_file_test!(
     manifest_file,
     "src/manifest_test_data/",
@@ -0,0 +1,386 @@
+//! Incremental builds of the Dojo contracts.
+//!
+//! Each contract is keyed by a hash of the sources it is compiled from: the files of its module,
+//! of its submodules and of the module it's declared in, followed transitively through the
+//! modules they reference, with a `use` or a path. The code plugins generate is scanned like the
+//! sources, and their auxiliary data is part of the key. Referencing another crate makes every
+//! file of that crate part of the key, as the code inline macros expand to isn't scanned. The
+//! corelib is left out, it only changes along with the Cairo version, which is part of the key
+//! with the configuration of the compiler and the versions of Scarb and Dojo.
+//!
+//! The keys, class hashes and ABIs of the contracts compiled by the previous builds are kept in
+//! an index next to their Sierra classes, so unchanged contracts are neither compiled nor written
+//! again. Removing the index with [`remove_build_cache`] forces every contract to be rebuilt.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::path::Path;
+use std::{fs, io};
+
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::{GenericTypeId, LanguageElementId, ModuleId};
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::{CrateId, CrateLongId, FileId};
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_semantic::items::functions::GenericFunctionId;
+use cairo_lang_semantic::resolve::ResolvedGenericItem;
+use cairo_lang_starknet::abi;
+use cairo_lang_syntax::node::kind::SyntaxKind;
+use cairo_lang_syntax::node::{ast, SyntaxNode, TypedSyntaxNode};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use smol_str::SmolStr;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::starknet_keccak;
+
+pub const BUILD_CACHE_FILE_NAME: &str = "build-cache.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildCache {
+    /// The version of Dojo which wrote the cache, as its format may change.
+    version: String,
+    /// The cached contracts, by full path.
+    contracts: BTreeMap<String, CachedContract>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedContract {
+    #[serde_as(as = "UfeHex")]
+    pub source_hash: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    pub abi: Option<abi::Contract>,
+}
+
+impl Default for BuildCache {
+    fn default() -> Self {
+        Self { version: env!("CARGO_PKG_VERSION").into(), contracts: BTreeMap::new() }
+    }
+}
+
+impl BuildCache {
+    /// Whether the cache was written by this version of Dojo.
+    pub fn is_current(&self) -> bool {
+        self.version == env!("CARGO_PKG_VERSION")
+    }
+
+    /// Returns the cached contract if it was built from the same sources, as hashed by
+    /// [`contract_source_hash`].
+    pub fn get(&self, contract_path: &str, source_hash: FieldElement) -> Option<&CachedContract> {
+        self.contracts.get(contract_path).filter(|cached| cached.source_hash == source_hash)
+    }
+
+    pub fn insert(&mut self, contract_path: String, contract: CachedContract) {
+        self.contracts.insert(contract_path, contract);
+    }
+
+    /// Drops the contracts which aren't part of the project anymore.
+    pub fn retain(&mut self, contract_paths: &HashSet<String>) {
+        self.contracts.retain(|path, _| contract_paths.contains(path));
+    }
+}
+
+/// Removes the build cache of the profile whose artifacts are in `target_dir`, so that the next
+/// build compiles every contract.
+pub fn remove_build_cache(target_dir: &Path) -> io::Result<()> {
+    match fs::remove_file(target_dir.join(BUILD_CACHE_FILE_NAME)) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
+
+/// The paths written in the sources of each module of the compilation unit, the corelib aside.
+#[derive(Debug, Default)]
+pub struct ModulePaths {
+    paths: HashMap<ModuleId, Vec<Vec<SmolStr>>>,
+}
+
+impl ModulePaths {
+    pub fn collect(db: &RootDatabase) -> Self {
+        let mut module_paths = Self::default();
+
+        for crate_id in db.crates() {
+            if is_corelib(db, crate_id) {
+                continue;
+            }
+
+            for module_id in db.crate_modules(crate_id).iter() {
+                // Inline modules share the files of their parent, which are scanned from there.
+                let parent_files = match module_id {
+                    ModuleId::CrateRoot(_) => vec![],
+                    ModuleId::Submodule(id) => module_files(db, id.parent_module(db)),
+                };
+
+                for file_id in module_files(db, *module_id) {
+                    if parent_files.contains(&file_id) {
+                        continue;
+                    }
+
+                    if let Ok(node) = db.file_syntax(file_id) {
+                        module_paths.visit(db, *module_id, &node);
+                    }
+                }
+            }
+        }
+
+        module_paths
+    }
+
+    /// Records the paths of the syntax `node`, found in `module_id`.
+    fn visit(&mut self, db: &RootDatabase, mut module_id: ModuleId, node: &SyntaxNode) {
+        match node.kind(db) {
+            SyntaxKind::ItemModule => {
+                let name = ast::ItemModule::from_syntax_node(db, node.clone()).name(db).text(db);
+                if let Some(submodule_id) = submodule(db, module_id, &name) {
+                    module_id = submodule_id;
+                }
+            }
+            SyntaxKind::ExprPath => {
+                let path = ast::ExprPath::from_syntax_node(db, node.clone());
+                let segments = path
+                    .elements(db)
+                    .iter()
+                    .map(|segment| match segment {
+                        ast::PathSegment::Simple(segment) => segment.ident(db).text(db),
+                        ast::PathSegment::WithGenericArgs(segment) => segment.ident(db).text(db),
+                    })
+                    .collect();
+                self.paths.entry(module_id).or_default().push(segments);
+            }
+            _ => {}
+        }
+
+        for child in node.children(db) {
+            self.visit(db, module_id, &child);
+        }
+    }
+}
+
+/// Hashes the sources the contract defined in `module_id` is compiled from, along with the
+/// configuration of the compiler, as formatted by `Debug`.
+pub fn contract_source_hash(
+    db: &RootDatabase,
+    module_paths: &ModulePaths,
+    module_id: ModuleId,
+    compiler_config: &impl Debug,
+) -> FieldElement {
+    let mut files = BTreeMap::new();
+    let mut aux_data = vec![];
+
+    for module_id in contract_dependencies(db, module_paths, module_id) {
+        for file_id in module_files(db, module_id) {
+            let content = db.file_content(file_id).map(|content| content.to_string());
+            files.insert(file_id.full_path(db), content.unwrap_or_default());
+        }
+
+        let file_infos = db.module_generated_file_infos(module_id).unwrap_or_default();
+        for data in file_infos.iter().flatten().filter_map(|info| info.aux_data.as_ref()) {
+            aux_data.push(format!("{:?}", data.0));
+        }
+    }
+    aux_data.sort();
+
+    let version = scarb::version::get();
+    let mut data = format!(
+        "{}\0{}\0{}\0{compiler_config:?}\0{}\0",
+        env!("CARGO_PKG_VERSION"),
+        version.version,
+        version.cairo.version,
+        module_id.full_path(db)
+    );
+    for (path, content) in files {
+        data.push_str(&path);
+        data.push('\0');
+        data.push_str(&content);
+        data.push('\0');
+    }
+    for aux_data in aux_data {
+        data.push_str(&aux_data);
+        data.push('\0');
+    }
+
+    starknet_keccak(data.as_bytes())
+}
+
+/// Returns the modules whose sources the contract defined in `module_id` is compiled from.
+fn contract_dependencies(
+    db: &RootDatabase,
+    module_paths: &ModulePaths,
+    module_id: ModuleId,
+) -> HashSet<ModuleId> {
+    let mut modules = HashSet::new();
+    let mut queue = VecDeque::from([module_id]);
+
+    // The contract module is rewritten by the plugins of the module it's declared in.
+    if let ModuleId::Submodule(id) = module_id {
+        queue.push_back(id.parent_module(db));
+    }
+
+    let mut crates = HashSet::from([module_id.owning_crate(db)]);
+
+    while let Some(current) = queue.pop_front() {
+        if is_corelib(db, current.owning_crate(db)) || !modules.insert(current) {
+            continue;
+        }
+
+        // The submodules of the contract are compiled along with it.
+        if is_descendant(db, current, module_id) {
+            for submodule_id in db.module_submodules_ids(current).unwrap_or_default().iter() {
+                queue.push_back(ModuleId::Submodule(*submodule_id));
+            }
+        }
+
+        let mut referenced = vec![];
+        for use_id in db.module_uses_ids(current).unwrap_or_default().iter() {
+            if let Ok(item) = db.use_resolved_item(*use_id) {
+                referenced.extend(item_module(db, &item));
+            }
+        }
+        for path in module_paths.paths.get(&current).into_iter().flatten() {
+            referenced.extend(resolve_path(db, current, path));
+        }
+
+        for referenced_id in referenced {
+            // Other crates are taken whole, see the module documentation.
+            let crate_id = referenced_id.owning_crate(db);
+            if !is_corelib(db, crate_id) && crates.insert(crate_id) {
+                queue.extend(db.crate_modules(crate_id).iter().copied());
+            }
+            queue.push_back(referenced_id);
+        }
+    }
+
+    modules
+}
+
+/// Returns the module the item a `path` written in `module_id` refers to is defined in, when
+/// it's not `module_id` itself or the corelib prelude.
+fn resolve_path(db: &RootDatabase, module_id: ModuleId, path: &[SmolStr]) -> Option<ModuleId> {
+    let (first, rest) = path.split_first()?;
+
+    let mut current = match first.as_str() {
+        "crate" => ModuleId::CrateRoot(module_id.owning_crate(db)),
+        "super" => parent_module(db, module_id)?,
+        name => {
+            if let Some(submodule_id) = submodule(db, module_id, name) {
+                submodule_id
+            } else if let Some(use_id) = db
+                .module_uses_ids(module_id)
+                .unwrap_or_default()
+                .iter()
+                .find(|use_id| use_id.name(db) == name)
+            {
+                match db.use_resolved_item(*use_id).ok()? {
+                    ResolvedGenericItem::Module(used_module_id) => used_module_id,
+                    item => return item_module(db, &item),
+                }
+            } else {
+                ModuleId::CrateRoot(find_crate(db, name)?)
+            }
+        }
+    };
+
+    for segment in rest {
+        current = match segment.as_str() {
+            "super" => parent_module(db, current)?,
+            name => match submodule(db, current, name) {
+                Some(submodule_id) => submodule_id,
+                None => break,
+            },
+        };
+    }
+
+    Some(current)
+}
+
+/// Returns the module where a resolved item is defined.
+fn item_module(db: &RootDatabase, item: &ResolvedGenericItem) -> Option<ModuleId> {
+    let module_id = match item {
+        ResolvedGenericItem::Module(module_id) => *module_id,
+        ResolvedGenericItem::Constant(id) => id.parent_module(db),
+        ResolvedGenericItem::GenericType(GenericTypeId::Struct(id)) => id.parent_module(db),
+        ResolvedGenericItem::GenericType(GenericTypeId::Enum(id)) => id.parent_module(db),
+        ResolvedGenericItem::GenericType(GenericTypeId::Extern(id)) => id.parent_module(db),
+        ResolvedGenericItem::GenericFunction(GenericFunctionId::Free(id)) => id.parent_module(db),
+        ResolvedGenericItem::GenericFunction(GenericFunctionId::Extern(id)) => id.parent_module(db),
+        ResolvedGenericItem::Variant(variant) => variant.enum_id.parent_module(db),
+        ResolvedGenericItem::Trait(id) => id.parent_module(db),
+        ResolvedGenericItem::Impl(id) => id.parent_module(db),
+        _ => return None,
+    };
+
+    Some(module_id)
+}
+
+fn submodule(db: &RootDatabase, module_id: ModuleId, name: &str) -> Option<ModuleId> {
+    db.module_submodules_ids(module_id)
+        .unwrap_or_default()
+        .iter()
+        .find(|submodule_id| submodule_id.name(db) == name)
+        .map(|submodule_id| ModuleId::Submodule(*submodule_id))
+}
+
+fn parent_module(db: &RootDatabase, module_id: ModuleId) -> Option<ModuleId> {
+    match module_id {
+        ModuleId::CrateRoot(_) => None,
+        ModuleId::Submodule(id) => Some(id.parent_module(db)),
+    }
+}
+
+/// Whether `module_id` is `ancestor_id` or one of its submodules.
+fn is_descendant(db: &RootDatabase, module_id: ModuleId, ancestor_id: ModuleId) -> bool {
+    let mut current = Some(module_id);
+    while let Some(module_id) = current {
+        if module_id == ancestor_id {
+            return true;
+        }
+        current = parent_module(db, module_id);
+    }
+
+    false
+}
+
+fn module_files(db: &RootDatabase, module_id: ModuleId) -> Vec<FileId> {
+    db.module_files(module_id).map(|files| files.to_vec()).unwrap_or_default()
+}
+
+fn find_crate(db: &RootDatabase, name: &str) -> Option<CrateId> {
+    db.crates().into_iter().find(
+        |crate_id| matches!(db.lookup_intern_crate(*crate_id), CrateLongId::Real(n) if n == name),
+    )
+}
+
+fn is_corelib(db: &RootDatabase, crate_id: CrateId) -> bool {
+    matches!(db.lookup_intern_crate(crate_id), CrateLongId::Real(name) if name == "core")
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn reuse_contracts_built_from_same_sources() {
+        let mut cache = BuildCache::default();
+        let cached =
+            |source_hash| CachedContract { source_hash, class_hash: felt!("0x1"), abi: None };
+        cache.insert("game::actions".into(), cached(felt!("0x10")));
+        cache.insert("game::models::position".into(), cached(felt!("0x20")));
+
+        assert!(cache.get("game::actions", felt!("0x10")).is_some());
+        assert!(cache.get("game::actions", felt!("0x11")).is_none());
+        assert!(cache.get("game::moves", felt!("0x10")).is_none());
+
+        cache.retain(&HashSet::from(["game::actions".to_string()]));
+        assert!(cache.get("game::models::position", felt!("0x20")).is_none());
+
+        let cache: BuildCache =
+            serde_json::from_str(&serde_json::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(cache.get("game::actions", felt!("0x10")).unwrap().class_hash, felt!("0x1"));
+    }
+}
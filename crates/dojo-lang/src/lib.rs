@@ -5,6 +5,7 @@
 //! Learn more at [dojoengine.gg](http://dojoengine.gg).
 pub mod compiler;
 pub mod contract;
+pub(crate) mod incremental;
 pub mod inline_macros;
 pub mod introspect;
 pub mod model;
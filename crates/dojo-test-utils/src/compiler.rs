@@ -1,6 +1,7 @@
-use std::env;
 use std::path::PathBuf;
+use std::{env, fs};
 
+use assert_fs::fixture::PathCopy;
 use assert_fs::TempDir;
 use camino::{Utf8Path, Utf8PathBuf};
 use dojo_lang::compiler::DojoCompiler;
@@ -32,6 +33,30 @@ pub fn build_test_config(path: &str) -> anyhow::Result<Config> {
         .build()
 }
 
+/// Copies the sources of the project at `path` to a temporary directory, so tests can edit them.
+/// The path dependencies of the project are made absolute to keep resolving. Returns the
+/// directory, removed on drop, and the path of the copied manifest.
+pub fn copy_project(path: &str) -> anyhow::Result<(TempDir, Utf8PathBuf)> {
+    let manifest_path = Utf8PathBuf::from_path_buf(path.into()).unwrap().canonicalize_utf8()?;
+    let project_dir = manifest_path.parent().unwrap();
+
+    let dir = TempDir::new()?;
+    dir.copy_from(project_dir, &["Scarb.toml", "src/**/*.cairo"])?;
+
+    let copied_manifest_path = Utf8Path::from_path(dir.path()).unwrap().join("Scarb.toml");
+    let mut manifest: toml::Value = toml::from_str(&fs::read_to_string(&manifest_path)?)?;
+    if let Some(dependencies) = manifest.get_mut("dependencies").and_then(|d| d.as_table_mut()) {
+        for dependency in dependencies.values_mut() {
+            if let Some(toml::Value::String(path)) = dependency.get_mut("path") {
+                *path = project_dir.join(&*path).canonicalize_utf8()?.to_string();
+            }
+        }
+    }
+    fs::write(&copied_manifest_path, toml::to_string(&manifest)?)?;
+
+    Ok((dir, copied_manifest_path))
+}
+
 pub fn corelib() -> PathBuf {
     let config = build_test_config("./src/manifest_test_data/spawn-and-move/Scarb.toml").unwrap();
     let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
@@ -0,0 +1,644 @@
+//! Typed Cairo values, serialized and deserialized according to the ABI of a contract.
+//!
+//! Values are written as Cairo literals, e.g. `Direction::Left, 10_u64, 'name', Vec2 { x: 1, y: 2
+//! }`. Their type isn't inferred from the literal but taken from the ABI, so `10` is a valid
+//! argument for a `felt252` as well as for a `u256`.
+
+use std::fmt::Display;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use cairo_lang_starknet::abi;
+use num_bigint::{BigInt, BigUint, Sign};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{cairo_short_string_to_felt, CairoShortStringToFeltError};
+
+#[cfg(test)]
+#[path = "calldata_test.rs"]
+mod tests;
+
+const INTEGER_SUFFIXES: [&str; 13] = [
+    "felt252", "u8", "u16", "u32", "u64", "u128", "u256", "usize", "i8", "i16", "i32", "i64",
+    "i128",
+];
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CalldataError {
+    #[error("Invalid arguments at position {position}: {message}")]
+    Parse { position: usize, message: String },
+    #[error("Entrypoint `{0}` not found in the ABI.")]
+    UnknownEntrypoint(String),
+    #[error("Expected {expected} arguments, got {got}.")]
+    ArgumentCount { expected: usize, got: usize },
+    #[error("Type `{0}` not found in the ABI.")]
+    UnknownType(String),
+    #[error("Expected a value of type `{ty}`, got `{value}`.")]
+    TypeMismatch { ty: String, value: String },
+    #[error("Value `{value}` is out of range for type `{ty}`.")]
+    OutOfRange { ty: String, value: String },
+    #[error("Enum `{ty}` has no variant `{variant}`.")]
+    UnknownVariant { ty: String, variant: String },
+    #[error("Missing member `{member}` of struct `{ty}`.")]
+    MissingMember { ty: String, member: String },
+    #[error("Not enough felts to deserialize a value of type `{0}`.")]
+    MissingFelts(String),
+    #[error(transparent)]
+    ShortString(#[from] CairoShortStringToFeltError),
+}
+
+/// A Cairo value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// A `felt252` or a type represented as one, e.g. `ContractAddress`.
+    Felt(FieldElement),
+    Number(BigInt),
+    /// A short string.
+    String(String),
+    Bool(bool),
+    Tuple(Vec<Value>),
+    Array(Vec<Value>),
+    /// An enum variant. `name` is empty when the variant isn't written with its path.
+    Enum {
+        name: String,
+        variant: String,
+        payload: Option<Box<Value>>,
+    },
+    Struct {
+        name: String,
+        members: Vec<(String, Value)>,
+    },
+}
+
+impl Value {
+    /// Parses a comma separated list of values.
+    pub fn parse_list(input: &str) -> Result<Vec<Value>, CalldataError> {
+        let mut parser = Parser { input, chars: input.char_indices().peekable() };
+
+        let values = parser.values(None)?;
+        match parser.peek() {
+            None => Ok(values),
+            Some((position, c)) => Err(CalldataError::Parse {
+                position,
+                message: format!("unexpected character `{c}`"),
+            }),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn join(values: &[Value]) -> String {
+            values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+        }
+
+        match self {
+            Value::Felt(felt) => write!(f, "{felt:#x}"),
+            Value::Number(n) => write!(f, "{n}"),
+            Value::String(s) => write!(f, "'{s}'"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Tuple(values) => write!(f, "({})", join(values)),
+            Value::Array(values) => write!(f, "[{}]", join(values)),
+            Value::Enum { name, variant, payload } => {
+                if !name.is_empty() {
+                    write!(f, "{}::", short_name(name))?;
+                }
+                write!(f, "{variant}")?;
+                match payload.as_deref() {
+                    None => Ok(()),
+                    Some(Value::Tuple(values)) if values.is_empty() => Ok(()),
+                    Some(payload) => write!(f, "({payload})"),
+                }
+            }
+            Value::Struct { name, members } => {
+                let members = members
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "{} {{ {members} }}", short_name(name))
+            }
+        }
+    }
+}
+
+/// Serializes the `args` of `entrypoint` according to the types of its inputs.
+pub fn encode_inputs(
+    abi: &abi::Contract,
+    entrypoint: &str,
+    args: &[Value],
+) -> Result<Vec<FieldElement>, CalldataError> {
+    let function = find_function(abi, entrypoint)
+        .ok_or_else(|| CalldataError::UnknownEntrypoint(entrypoint.to_string()))?;
+
+    if function.inputs.len() != args.len() {
+        return Err(CalldataError::ArgumentCount {
+            expected: function.inputs.len(),
+            got: args.len(),
+        });
+    }
+
+    let mut calldata = vec![];
+    for (input, arg) in function.inputs.iter().zip(args) {
+        encode(abi, &input.ty, arg, &mut calldata)?;
+    }
+
+    Ok(calldata)
+}
+
+/// Deserializes the outputs of `entrypoint` from the felts it returned.
+pub fn decode_outputs(
+    abi: &abi::Contract,
+    entrypoint: &str,
+    felts: &[FieldElement],
+) -> Result<Vec<Value>, CalldataError> {
+    let function = find_function(abi, entrypoint)
+        .ok_or_else(|| CalldataError::UnknownEntrypoint(entrypoint.to_string()))?;
+
+    let mut felts = felts.iter().copied();
+    function.outputs.iter().map(|output| decode(abi, &output.ty, &mut felts)).collect()
+}
+
+/// Returns the function named `name`, either declared at the top level of the ABI or in one of
+/// its interfaces.
+pub fn find_function(abi: &abi::Contract, name: &str) -> Option<abi::Function> {
+    abi_items(abi).find_map(|item| match item {
+        abi::Item::Function(f) if f.name == name => Some(f),
+        abi::Item::Interface(interface) => {
+            interface.items.into_iter().find_map(|item| match item {
+                abi::Item::Function(f) if f.name == name => Some(f),
+                _ => None,
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Returns the items of the ABI, which [`abi::Contract`] only hands out by value.
+pub fn abi_items(abi: &abi::Contract) -> impl Iterator<Item = abi::Item> {
+    abi.clone().into_iter()
+}
+
+/// Serializes `value` as a value of type `ty`, appending the felts to `out`.
+pub fn encode(
+    abi: &abi::Contract,
+    ty: &str,
+    value: &Value,
+    out: &mut Vec<FieldElement>,
+) -> Result<(), CalldataError> {
+    let ty = ty.trim_start_matches('@');
+    let mismatch = || CalldataError::TypeMismatch { ty: ty.to_string(), value: value.to_string() };
+
+    if is_felt(ty) {
+        let felt = match value {
+            Value::Felt(felt) => *felt,
+            Value::Number(n) => number_to_felt(ty, n)?,
+            Value::String(s) => cairo_short_string_to_felt(s)?,
+            _ => return Err(mismatch()),
+        };
+        out.push(felt);
+    } else if let Some((signed, bits)) = integer_bits(ty) {
+        let n = match value {
+            Value::Number(n) => n.clone(),
+            Value::Felt(felt) => BigInt::from(felt_to_biguint(*felt)),
+            _ => return Err(mismatch()),
+        };
+
+        let (min, max) = if signed {
+            (-(BigInt::from(1) << (bits - 1)), (BigInt::from(1) << (bits - 1)) - 1)
+        } else {
+            (BigInt::from(0), (BigInt::from(1) << bits) - 1)
+        };
+        if n < min || n > max {
+            return Err(CalldataError::OutOfRange { ty: ty.to_string(), value: n.to_string() });
+        }
+
+        out.push(number_to_felt(ty, &n)?);
+    } else if ty == "core::integer::u256" {
+        let n = match value {
+            Value::Number(n) => n.clone(),
+            Value::Felt(felt) => BigInt::from(felt_to_biguint(*felt)),
+            _ => return Err(mismatch()),
+        };
+
+        let n = match n.to_biguint() {
+            Some(n) if n.bits() <= 256 => n,
+            _ => {
+                return Err(CalldataError::OutOfRange { ty: ty.to_string(), value: n.to_string() });
+            }
+        };
+
+        let mask = (BigUint::from(1u8) << 128) - 1u8;
+        out.push(biguint_to_felt(ty, &(&n & &mask))?);
+        out.push(biguint_to_felt(ty, &(n >> 128))?);
+    } else if ty == "core::bool" {
+        match value {
+            Value::Bool(b) => out.push(if *b { FieldElement::ONE } else { FieldElement::ZERO }),
+            _ => return Err(mismatch()),
+        }
+    } else if let Some(inner) = array_item(ty) {
+        let Value::Array(values) = value else { return Err(mismatch()) };
+
+        out.push(values.len().into());
+        for value in values {
+            encode(abi, inner, value, out)?;
+        }
+    } else if let Some(items) = tuple_items(ty) {
+        let values = match value {
+            Value::Tuple(values) if values.len() == items.len() => values,
+            _ => return Err(mismatch()),
+        };
+
+        for (item, value) in items.iter().zip(values) {
+            encode(abi, item, value, out)?;
+        }
+    } else if let Some(s) = find_struct(abi, ty) {
+        let Value::Struct { members, .. } = value else { return Err(mismatch()) };
+
+        for member in &s.members {
+            let value = members.iter().find(|(name, _)| *name == member.name).ok_or_else(|| {
+                CalldataError::MissingMember { ty: ty.to_string(), member: member.name.clone() }
+            })?;
+            encode(abi, &member.ty, &value.1, out)?;
+        }
+    } else if let Some(e) = find_enum(abi, ty) {
+        let Value::Enum { variant, payload, .. } = value else { return Err(mismatch()) };
+
+        let (index, v) =
+            e.variants.iter().enumerate().find(|(_, v)| v.name == *variant).ok_or_else(|| {
+                CalldataError::UnknownVariant { ty: ty.to_string(), variant: variant.clone() }
+            })?;
+
+        out.push(index.into());
+        match payload.as_deref() {
+            Some(payload) => encode(abi, &v.ty, payload, out)?,
+            None => encode(abi, &v.ty, &Value::Tuple(vec![]), out)?,
+        }
+    } else {
+        return Err(CalldataError::UnknownType(ty.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Deserializes a value of type `ty` from `felts`.
+pub fn decode(
+    abi: &abi::Contract,
+    ty: &str,
+    felts: &mut impl Iterator<Item = FieldElement>,
+) -> Result<Value, CalldataError> {
+    let ty = ty.trim_start_matches('@');
+
+    if is_felt(ty) {
+        Ok(Value::Felt(next_felt(ty, felts)?))
+    } else if let Some((signed, bits)) = integer_bits(ty) {
+        let n = BigInt::from(felt_to_biguint(next_felt(ty, felts)?));
+        if signed && n >= BigInt::from(1) << (bits - 1) {
+            let prime = BigInt::from(felt_to_biguint(FieldElement::ZERO - FieldElement::ONE)) + 1;
+            Ok(Value::Number(n - prime))
+        } else {
+            Ok(Value::Number(n))
+        }
+    } else if ty == "core::integer::u256" {
+        let low = felt_to_biguint(next_felt(ty, felts)?);
+        let high = felt_to_biguint(next_felt(ty, felts)?);
+        Ok(Value::Number(BigInt::from((high << 128) + low)))
+    } else if ty == "core::bool" {
+        Ok(Value::Bool(next_felt(ty, felts)? != FieldElement::ZERO))
+    } else if let Some(inner) = array_item(ty) {
+        let len = felt_to_usize(ty, next_felt(ty, felts)?)?;
+        let values = (0..len).map(|_| decode(abi, inner, felts)).collect::<Result<_, _>>()?;
+        Ok(Value::Array(values))
+    } else if let Some(items) = tuple_items(ty) {
+        let values = items.iter().map(|item| decode(abi, item, felts)).collect::<Result<_, _>>()?;
+        Ok(Value::Tuple(values))
+    } else if let Some(s) = find_struct(abi, ty) {
+        let members = s
+            .members
+            .iter()
+            .map(|m| Ok((m.name.clone(), decode(abi, &m.ty, felts)?)))
+            .collect::<Result<_, CalldataError>>()?;
+        Ok(Value::Struct { name: s.name.clone(), members })
+    } else if let Some(e) = find_enum(abi, ty) {
+        let index = felt_to_usize(ty, next_felt(ty, felts)?)?;
+        let variant = e.variants.get(index).ok_or_else(|| CalldataError::UnknownVariant {
+            ty: ty.to_string(),
+            variant: index.to_string(),
+        })?;
+
+        let payload = decode(abi, &variant.ty, felts)?;
+        let payload = match payload {
+            Value::Tuple(values) if values.is_empty() => None,
+            payload => Some(Box::new(payload)),
+        };
+
+        Ok(Value::Enum { name: e.name.clone(), variant: variant.name.clone(), payload })
+    } else {
+        Err(CalldataError::UnknownType(ty.to_string()))
+    }
+}
+
+/// Returns the name of a type without its path and generic arguments.
+pub fn short_name(ty: &str) -> &str {
+    let ty = ty.split_once("::<").map(|(ty, _)| ty).unwrap_or(ty);
+    ty.rsplit("::").next().unwrap_or(ty)
+}
+
+fn find_struct(abi: &abi::Contract, ty: &str) -> Option<abi::Struct> {
+    abi_items(abi).find_map(|item| match item {
+        abi::Item::Struct(s) if s.name == ty => Some(s),
+        _ => None,
+    })
+}
+
+fn find_enum(abi: &abi::Contract, ty: &str) -> Option<abi::Enum> {
+    abi_items(abi).find_map(|item| match item {
+        abi::Item::Enum(e) if e.name == ty => Some(e),
+        _ => None,
+    })
+}
+
+fn is_felt(ty: &str) -> bool {
+    matches!(
+        ty,
+        "core::felt252"
+            | "core::starknet::contract_address::ContractAddress"
+            | "core::starknet::class_hash::ClassHash"
+            | "core::starknet::eth_address::EthAddress"
+            | "core::starknet::storage_access::StorageAddress"
+    )
+}
+
+fn integer_bits(ty: &str) -> Option<(bool, usize)> {
+    let bits = match ty.strip_prefix("core::integer::")? {
+        "u8" | "i8" => 8,
+        "u16" | "i16" => 16,
+        "u32" | "i32" | "usize" => 32,
+        "u64" | "i64" => 64,
+        "u128" | "i128" => 128,
+        _ => return None,
+    };
+
+    Some((ty.starts_with("core::integer::i"), bits))
+}
+
+fn array_item(ty: &str) -> Option<&str> {
+    ty.strip_prefix("core::array::Array::<")
+        .or_else(|| ty.strip_prefix("core::array::Span::<"))
+        .and_then(|inner| inner.strip_suffix('>'))
+}
+
+/// Splits the items of a tuple type, e.g. `(core::felt252, (core::bool, core::integer::u8))`.
+pub fn tuple_items(ty: &str) -> Option<Vec<&str>> {
+    let inner = ty.strip_prefix('(')?.strip_suffix(')')?;
+    Some(split_types(inner))
+}
+
+/// Splits a comma separated list of types, like the items of a tuple without the parentheses or
+/// the generic arguments of a type.
+pub fn split_types(list: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = list[start..].trim();
+    if !last.is_empty() {
+        items.push(last);
+    }
+
+    items
+}
+
+pub(crate) fn next_felt(
+    ty: &str,
+    felts: &mut impl Iterator<Item = FieldElement>,
+) -> Result<FieldElement, CalldataError> {
+    felts.next().ok_or_else(|| CalldataError::MissingFelts(ty.to_string()))
+}
+
+pub(crate) fn felt_to_biguint(felt: FieldElement) -> BigUint {
+    BigUint::from_bytes_be(&felt.to_bytes_be())
+}
+
+pub(crate) fn felt_to_usize(ty: &str, felt: FieldElement) -> Result<usize, CalldataError> {
+    u64::try_from(felt).ok().and_then(|n| usize::try_from(n).ok()).ok_or_else(|| {
+        CalldataError::OutOfRange { ty: ty.to_string(), value: format!("{felt:#x}") }
+    })
+}
+
+fn biguint_to_felt(ty: &str, n: &BigUint) -> Result<FieldElement, CalldataError> {
+    let out_of_range = || CalldataError::OutOfRange { ty: ty.to_string(), value: n.to_string() };
+
+    let bytes = n.to_bytes_be();
+    if bytes.len() > 32 {
+        return Err(out_of_range());
+    }
+
+    FieldElement::from_byte_slice_be(&bytes).map_err(|_| out_of_range())
+}
+
+/// Converts a number to a felt, negative numbers wrapping around the field prime.
+fn number_to_felt(ty: &str, n: &BigInt) -> Result<FieldElement, CalldataError> {
+    let felt = biguint_to_felt(ty, n.magnitude())?;
+    Ok(if n.sign() == Sign::Minus { FieldElement::ZERO - felt } else { felt })
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<(usize, char)> {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn position(&mut self) -> usize {
+        self.peek().map(|(i, _)| i).unwrap_or(self.input.len())
+    }
+
+    fn error<T>(&mut self, message: impl Into<String>) -> Result<T, CalldataError> {
+        Err(CalldataError::Parse { position: self.position(), message: message.into() })
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), CalldataError> {
+        match self.peek() {
+            Some((_, c)) if c == expected => {
+                self.chars.next();
+                Ok(())
+            }
+            _ => self.error(format!("expected `{expected}`")),
+        }
+    }
+
+    /// Parses comma separated values until the `end` delimiter, which isn't consumed.
+    fn values(&mut self, end: Option<char>) -> Result<Vec<Value>, CalldataError> {
+        let mut values = vec![];
+
+        loop {
+            match self.peek() {
+                None => break,
+                Some((_, c)) if Some(c) == end => break,
+                _ => values.push(self.value()?),
+            }
+
+            match self.peek() {
+                Some((_, ',')) => {
+                    self.chars.next();
+                }
+                _ => break,
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn value(&mut self) -> Result<Value, CalldataError> {
+        match self.peek() {
+            Some((_, '(')) => {
+                self.chars.next();
+                let values = self.values(Some(')'))?;
+                self.expect(')')?;
+                Ok(Value::Tuple(values))
+            }
+            Some((_, '[')) => {
+                self.chars.next();
+                let values = self.values(Some(']'))?;
+                self.expect(']')?;
+                Ok(Value::Array(values))
+            }
+            Some((position, quote @ ('"' | '\''))) => {
+                self.chars.next();
+                let mut string = String::new();
+                loop {
+                    match self.chars.next() {
+                        Some((_, c)) if c == quote => break Ok(Value::String(string)),
+                        Some((_, c)) => string.push(c),
+                        None => {
+                            break Err(CalldataError::Parse {
+                                position,
+                                message: "unterminated string".to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+            Some((_, c)) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some((_, c)) if c.is_alphabetic() || c == '_' => self.path(),
+            Some((_, c)) => self.error(format!("unexpected character `{c}`")),
+            None => self.error("expected a value"),
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        let start = self.position();
+        let mut end = start;
+        while let Some((i, c)) = self.chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+            end = i + c.len_utf8();
+        }
+        &self.input[start..end]
+    }
+
+    fn number(&mut self) -> Result<Value, CalldataError> {
+        let position = self.position();
+
+        let negative = self.chars.next_if(|(_, c)| *c == '-').is_some();
+        let word = self.word();
+
+        let mut digits = word;
+        if let Some(suffix) = INTEGER_SUFFIXES.iter().find(|s| word.ends_with(*s)) {
+            digits = &word[..word.len() - suffix.len()];
+        }
+        let digits = digits.replace('_', "");
+
+        let n = match digits.strip_prefix("0x") {
+            Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+            None => BigUint::parse_bytes(digits.as_bytes(), 10),
+        };
+        let Some(n) = n else {
+            return Err(CalldataError::Parse {
+                position,
+                message: format!("invalid number `{word}`"),
+            });
+        };
+
+        let sign = if negative { Sign::Minus } else { Sign::Plus };
+        Ok(Value::Number(BigInt::from_biguint(sign, n)))
+    }
+
+    /// Parses a boolean, an enum variant or a struct.
+    fn path(&mut self) -> Result<Value, CalldataError> {
+        let mut segments = vec![self.word()];
+        while self.input[self.position()..].starts_with("::") {
+            self.chars.next();
+            self.chars.next();
+            segments.push(self.word());
+        }
+
+        let path = segments.join("::");
+        match (path.as_str(), self.peek()) {
+            ("true", _) => Ok(Value::Bool(true)),
+            ("false", _) => Ok(Value::Bool(false)),
+            // `array![..]` is accepted as an alias of `[..]`.
+            ("array", Some((_, '!'))) => {
+                self.chars.next();
+                match self.peek() {
+                    Some((_, '[')) => self.value(),
+                    _ => self.error("expected `[`"),
+                }
+            }
+            (_, Some((_, '('))) => {
+                self.chars.next();
+                let mut values = self.values(Some(')'))?;
+                self.expect(')')?;
+
+                let payload =
+                    if values.len() == 1 { values.remove(0) } else { Value::Tuple(values) };
+                Ok(enum_variant(&segments, Some(Box::new(payload))))
+            }
+            (_, Some((_, '{'))) => {
+                self.chars.next();
+
+                let mut members = vec![];
+                loop {
+                    if let Some((_, '}')) = self.peek() {
+                        break;
+                    }
+
+                    let member = self.word();
+                    if member.is_empty() {
+                        return self.error("expected a member name");
+                    }
+                    self.expect(':')?;
+                    members.push((member.to_string(), self.value()?));
+
+                    match self.peek() {
+                        Some((_, ',')) => {
+                            self.chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                self.expect('}')?;
+
+                Ok(Value::Struct { name: path, members })
+            }
+            _ => Ok(enum_variant(&segments, None)),
+        }
+    }
+}
+
+fn enum_variant(segments: &[&str], payload: Option<Box<Value>>) -> Value {
+    let (variant, name) = segments.split_last().expect("path has at least one segment");
+    Value::Enum { name: name.join("::"), variant: variant.to_string(), payload }
+}
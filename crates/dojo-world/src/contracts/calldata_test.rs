@@ -0,0 +1,172 @@
+use starknet::macros::felt;
+
+use super::*;
+
+fn abi() -> abi::Contract {
+    serde_json::from_value(serde_json::json!([
+        {
+            "type": "enum",
+            "name": "dojo_examples::models::Direction",
+            "variants": [
+                { "name": "None", "type": "()" },
+                { "name": "Left", "type": "()" },
+                { "name": "Right", "type": "()" }
+            ]
+        },
+        {
+            "type": "struct",
+            "name": "dojo_examples::models::Vec2",
+            "members": [
+                { "name": "x", "type": "core::integer::u32" },
+                { "name": "y", "type": "core::integer::u32" }
+            ]
+        },
+        {
+            "type": "enum",
+            "name": "core::option::Option::<core::integer::u8>",
+            "variants": [
+                { "name": "Some", "type": "core::integer::u8" },
+                { "name": "None", "type": "()" }
+            ]
+        },
+        {
+            "type": "interface",
+            "name": "dojo_examples::actions::IActions",
+            "items": [
+                {
+                    "type": "function",
+                    "name": "spawn",
+                    "inputs": [
+                        { "name": "direction", "type": "dojo_examples::models::Direction" },
+                        { "name": "amount", "type": "core::integer::u256" },
+                        { "name": "name", "type": "core::felt252" },
+                        { "name": "positions", "type": "core::array::Array::<dojo_examples::models::Vec2>" },
+                        { "name": "level", "type": "core::option::Option::<core::integer::u8>" },
+                        { "name": "pair", "type": "(core::bool, core::integer::i8)" }
+                    ],
+                    "outputs": [],
+                    "state_mutability": "external"
+                }
+            ]
+        }
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn parse_literals() {
+    let values =
+        Value::parse_list("Direction::Left, 10u64, -1_i8, \"name\", Vec2 { x: 1, y: 0x2 }, [1, 2]")
+            .unwrap();
+
+    assert_eq!(
+        values,
+        vec![
+            Value::Enum { name: "Direction".into(), variant: "Left".into(), payload: None },
+            Value::Number(10.into()),
+            Value::Number((-1).into()),
+            Value::String("name".into()),
+            Value::Struct {
+                name: "Vec2".into(),
+                members: vec![
+                    ("x".into(), Value::Number(1.into())),
+                    ("y".into(), Value::Number(2.into()))
+                ],
+            },
+            Value::Array(vec![Value::Number(1.into()), Value::Number(2.into())]),
+        ]
+    );
+
+    assert_eq!(Value::parse_list("").unwrap(), vec![]);
+    assert!(matches!(Value::parse_list("1, )"), Err(CalldataError::Parse { position: 3, .. })));
+}
+
+#[test]
+fn reject_unterminated_strings() {
+    assert!(matches!(
+        Value::parse_list("1, \"name"),
+        Err(CalldataError::Parse { position: 3, message }) if message == "unterminated string"
+    ));
+    assert!(matches!(Value::parse_list("'name\""), Err(CalldataError::Parse { position: 0, .. })));
+    assert_eq!(Value::parse_list("'a b'").unwrap(), vec![Value::String("a b".into())]);
+}
+
+#[test]
+fn encode_spawn_inputs() {
+    let args = Value::parse_list(
+        "Direction::Left, 0x100000000000000000000000000000002, 'name', [Vec2 { y: 2, x: 1 }], \
+         Option::Some(3), (true, -1)",
+    )
+    .unwrap();
+
+    let calldata = encode_inputs(&abi(), "spawn", &args).unwrap();
+
+    assert_eq!(
+        calldata,
+        vec![
+            felt!("1"),
+            felt!("2"),
+            felt!("1"),
+            cairo_short_string_to_felt("name").unwrap(),
+            felt!("1"),
+            felt!("1"),
+            felt!("2"),
+            felt!("0"),
+            felt!("3"),
+            felt!("1"),
+            FieldElement::ZERO - FieldElement::ONE,
+        ]
+    );
+}
+
+#[test]
+fn encode_errors() {
+    let abi = abi();
+
+    let args = Value::parse_list("Direction::Up, 1, 2, [], Option::None, (true, 1)").unwrap();
+    assert!(matches!(
+        encode_inputs(&abi, "spawn", &args),
+        Err(CalldataError::UnknownVariant { variant, .. }) if variant == "Up"
+    ));
+
+    let args =
+        Value::parse_list("Direction::Left, 1, 2, [], Option::Some(256), (true, 1)").unwrap();
+    assert!(matches!(
+        encode_inputs(&abi, "spawn", &args),
+        Err(CalldataError::OutOfRange { value, .. }) if value == "256"
+    ));
+
+    assert_eq!(
+        encode_inputs(&abi, "spawn", &[]),
+        Err(CalldataError::ArgumentCount { expected: 6, got: 0 })
+    );
+    assert_eq!(
+        encode_inputs(&abi, "move", &[]),
+        Err(CalldataError::UnknownEntrypoint("move".into()))
+    );
+}
+
+#[test]
+fn decode_round_trip() {
+    let abi = abi();
+
+    let values = Value::parse_list("Direction::Right, [Vec2 { x: 1, y: 2 }], (false, -5)").unwrap();
+    let types = [
+        "dojo_examples::models::Direction",
+        "core::array::Array::<dojo_examples::models::Vec2>",
+        "(core::bool, core::integer::i8)",
+    ];
+
+    let mut felts = vec![];
+    for (ty, value) in types.iter().zip(&values) {
+        encode(&abi, ty, value, &mut felts).unwrap();
+    }
+
+    let mut felts = felts.into_iter();
+    let decoded = types
+        .iter()
+        .map(|ty| decode(&abi, ty, &mut felts).unwrap().to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(decoded, vec!["Direction::Right", "[Vec2 { x: 1, y: 2 }]", "(false, -5)"]);
+}
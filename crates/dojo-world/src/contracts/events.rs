@@ -0,0 +1,279 @@
+//! Decoding of the events emitted by contracts, according to their ABI.
+//!
+//! Events are matched on their first key, the selector of their name. Events emitted through the
+//! `Event` enum of a contract are resolved by walking its variants, nested and flattened ones
+//! included, so the name of a variant doesn't have to match the name of its type.
+
+use cairo_lang_starknet::abi::{self, EventField, EventKind};
+use cairo_lang_starknet::plugin::events::EventFieldKind;
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::Ty;
+use num_bigint::BigInt;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{parse_cairo_short_string, starknet_keccak};
+
+use super::calldata::{
+    self, abi_items, felt_to_biguint, felt_to_usize, next_felt, short_name, Value,
+};
+
+#[cfg(test)]
+#[path = "events_test.rs"]
+mod tests;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum EventError {
+    #[error("Records setting a model from offset {0} can't be decoded without its current value.")]
+    PartialRecord(u8),
+    #[error("Schema of `{0}` isn't a struct.")]
+    NotAStruct(String),
+    #[error("Expected {expected} keys for model `{model}`, got {got}.")]
+    KeyCount { model: String, expected: usize, got: usize },
+    #[error("Not enough keys for model `{model}`, got {got}.")]
+    MissingKeys { model: String, got: usize },
+    #[error("Expected {expected} values for model `{model}`, got {got}.")]
+    ValueCount { model: String, expected: usize, got: usize },
+    #[error("Not enough values for model `{model}`, got {got}.")]
+    MissingValues { model: String, got: usize },
+    #[error(transparent)]
+    Calldata(#[from] calldata::CalldataError),
+}
+
+/// Decodes an event using the first of the `abis` declaring an event whose selector matches its
+/// first key. Returns `None` if no such event is found or if the event doesn't deserialize into
+/// it.
+pub fn decode_event(
+    abis: &[&abi::Contract],
+    keys: &[FieldElement],
+    data: &[FieldElement],
+) -> Option<Value> {
+    let (selector, keys) = keys.split_first()?;
+
+    abis.iter().find_map(|abi| {
+        let from_enum = events(abi).find_map(|e| match &e.kind {
+            EventKind::Enum { variants } => decode_variant(abi, variants, *selector, keys, data),
+            EventKind::Struct { .. } => None,
+        });
+
+        from_enum.or_else(|| {
+            events(abi).find_map(|e| match &e.kind {
+                EventKind::Struct { members }
+                    if starknet_keccak(short_name(&e.name).as_bytes()) == *selector =>
+                {
+                    decode_struct(abi, &e.name, members, keys, data)
+                }
+                _ => None,
+            })
+        })
+    })
+}
+
+/// A `StoreSetRecord` event of the World, emitted when values of an entity are set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreSetRecord {
+    pub table: FieldElement,
+    pub keys: Vec<FieldElement>,
+    pub offset: u8,
+    pub values: Vec<FieldElement>,
+}
+
+impl StoreSetRecord {
+    /// Parses the event from its keys and data. Returns `None` if it's another event.
+    pub fn parse(keys: &[FieldElement], data: &[FieldElement]) -> Option<Self> {
+        if keys.first() != Some(&starknet_keccak(b"StoreSetRecord")) {
+            return None;
+        }
+
+        let mut data = data.iter().copied();
+        let table = data.next()?;
+        let keys_len = u64::try_from(data.next()?).ok()? as usize;
+        let keys = data.by_ref().take(keys_len).collect::<Vec<_>>();
+        let offset = u8::try_from(data.next()?).ok()?;
+        let values_len = u64::try_from(data.next()?).ok()? as usize;
+        let values = data.by_ref().take(values_len).collect::<Vec<_>>();
+
+        if keys.len() != keys_len || values.len() != values_len || data.next().is_some() {
+            return None;
+        }
+
+        Some(Self { table, keys, offset, values })
+    }
+
+    /// The name of the model whose values are set.
+    pub fn model(&self) -> Option<String> {
+        parse_cairo_short_string(&self.table).ok()
+    }
+
+    /// Decodes the record into a value of the model `schema`, keys included. The members are
+    /// taken in the order they're declared, from the keys or the values of the record.
+    pub fn decode(&self, schema: &Ty) -> Result<Value, EventError> {
+        if self.offset != 0 {
+            return Err(EventError::PartialRecord(self.offset));
+        }
+
+        let Ty::Struct(model) = schema else {
+            return Err(EventError::NotAStruct(schema.name()));
+        };
+
+        let mut keys = self.keys.iter().copied();
+        let mut values = self.values.iter().copied();
+
+        let mut members = vec![];
+        for member in &model.children {
+            let felts = if member.key { &mut keys } else { &mut values };
+            let value = match decode_ty(&member.ty, felts) {
+                Ok(value) => value,
+                Err(calldata::CalldataError::MissingFelts(_)) if member.key => {
+                    return Err(EventError::MissingKeys {
+                        model: model.name.clone(),
+                        got: self.keys.len(),
+                    });
+                }
+                Err(calldata::CalldataError::MissingFelts(_)) => {
+                    return Err(EventError::MissingValues {
+                        model: model.name.clone(),
+                        got: self.values.len(),
+                    });
+                }
+                Err(err) => return Err(err.into()),
+            };
+            members.push((member.name.clone(), value));
+        }
+
+        let remaining = keys.count();
+        if remaining != 0 {
+            return Err(EventError::KeyCount {
+                model: model.name.clone(),
+                expected: self.keys.len() - remaining,
+                got: self.keys.len(),
+            });
+        }
+
+        let remaining = values.count();
+        if remaining != 0 {
+            return Err(EventError::ValueCount {
+                model: model.name.clone(),
+                expected: self.values.len() - remaining,
+                got: self.values.len(),
+            });
+        }
+
+        Ok(Value::Struct { name: model.name.clone(), members })
+    }
+}
+
+/// Deserializes a value of the schema type `ty` from `felts`.
+pub fn decode_ty(
+    ty: &Ty,
+    felts: &mut impl Iterator<Item = FieldElement>,
+) -> Result<Value, calldata::CalldataError> {
+    match ty {
+        Ty::Primitive(p) => {
+            let felt = next_felt(&ty.name(), felts)?;
+            Ok(match p {
+                Primitive::Felt252(_) | Primitive::ClassHash(_) | Primitive::ContractAddress(_) => {
+                    Value::Felt(felt)
+                }
+                Primitive::Bool(_) => Value::Bool(felt != FieldElement::ZERO),
+                Primitive::U256(_) => {
+                    let high = felt_to_biguint(next_felt(&ty.name(), felts)?);
+                    Value::Number(BigInt::from((high << 128) + felt_to_biguint(felt)))
+                }
+                _ => Value::Number(BigInt::from(felt_to_biguint(felt))),
+            })
+        }
+        Ty::Struct(s) => {
+            let members = s
+                .children
+                .iter()
+                .map(|m| Ok((m.name.clone(), decode_ty(&m.ty, felts)?)))
+                .collect::<Result<_, calldata::CalldataError>>()?;
+            Ok(Value::Struct { name: s.name.clone(), members })
+        }
+        Ty::Enum(e) => {
+            let index = felt_to_usize(&e.name, next_felt(&e.name, felts)?)?;
+            let option =
+                e.options.get(index).ok_or_else(|| calldata::CalldataError::UnknownVariant {
+                    ty: e.name.clone(),
+                    variant: index.to_string(),
+                })?;
+
+            let payload = match decode_ty(&option.ty, felts)? {
+                Value::Tuple(values) if values.is_empty() => None,
+                payload => Some(Box::new(payload)),
+            };
+
+            Ok(Value::Enum { name: e.name.clone(), variant: option.name.clone(), payload })
+        }
+        Ty::Tuple(tys) => {
+            let values = tys.iter().map(|ty| decode_ty(ty, felts)).collect::<Result<_, _>>()?;
+            Ok(Value::Tuple(values))
+        }
+    }
+}
+
+fn events(abi: &abi::Contract) -> impl Iterator<Item = abi::Event> {
+    abi_items(abi).filter_map(|item| match item {
+        abi::Item::Event(e) => Some(e),
+        _ => None,
+    })
+}
+
+fn find_event(abi: &abi::Contract, ty: &str) -> Option<abi::Event> {
+    events(abi).find(|e| e.name == ty)
+}
+
+/// Finds the variant of an event enum emitted with `selector`. The selector of a nested variant is
+/// the one of its name, the variants of a flattened enum are selected directly.
+fn decode_variant(
+    abi: &abi::Contract,
+    variants: &[EventField],
+    selector: FieldElement,
+    keys: &[FieldElement],
+    data: &[FieldElement],
+) -> Option<Value> {
+    variants.iter().find_map(|variant| match variant.kind {
+        EventFieldKind::Nested if starknet_keccak(variant.name.as_bytes()) == selector => {
+            match &find_event(abi, &variant.ty)?.kind {
+                EventKind::Struct { members } => {
+                    decode_struct(abi, &variant.ty, members, keys, data)
+                }
+                EventKind::Enum { variants } => {
+                    let (selector, keys) = keys.split_first()?;
+                    decode_variant(abi, variants, *selector, keys, data)
+                }
+            }
+        }
+        EventFieldKind::Flat => match &find_event(abi, &variant.ty)?.kind {
+            EventKind::Enum { variants } => decode_variant(abi, variants, selector, keys, data),
+            EventKind::Struct { .. } => None,
+        },
+        _ => None,
+    })
+}
+
+fn decode_struct(
+    abi: &abi::Contract,
+    name: &str,
+    members: &[EventField],
+    keys: &[FieldElement],
+    data: &[FieldElement],
+) -> Option<Value> {
+    let mut keys = keys.iter().copied();
+    let mut data = data.iter().copied();
+
+    let mut fields = vec![];
+    for member in members {
+        let value = match member.kind {
+            EventFieldKind::KeySerde => calldata::decode(abi, &member.ty, &mut keys),
+            EventFieldKind::DataSerde => calldata::decode(abi, &member.ty, &mut data),
+            EventFieldKind::Nested | EventFieldKind::Flat => return None,
+        };
+        fields.push((member.name.clone(), value.ok()?));
+    }
+
+    if keys.next().is_some() || data.next().is_some() {
+        return None;
+    }
+
+    Some(Value::Struct { name: name.to_string(), members: fields })
+}
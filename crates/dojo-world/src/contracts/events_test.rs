@@ -0,0 +1,242 @@
+use dojo_types::schema::{Enum, EnumOption, Member, Struct};
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::macros::felt;
+
+use super::*;
+
+fn abi() -> abi::Contract {
+    serde_json::from_value(serde_json::json!([
+        {
+            "type": "enum",
+            "name": "dojo_examples::models::Direction",
+            "variants": [
+                { "name": "None", "type": "()" },
+                { "name": "Left", "type": "()" },
+                { "name": "Right", "type": "()" }
+            ]
+        },
+        {
+            "type": "struct",
+            "name": "dojo_examples::models::Vec2",
+            "members": [
+                { "name": "x", "type": "core::integer::u32" },
+                { "name": "y", "type": "core::integer::u32" }
+            ]
+        },
+        {
+            "type": "struct",
+            "name": "core::integer::u256",
+            "members": [
+                { "name": "low", "type": "core::integer::u128" },
+                { "name": "high", "type": "core::integer::u128" }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "dojo_examples::actions::actions::Moved",
+            "kind": "struct",
+            "members": [
+                { "name": "player", "type": "core::starknet::contract_address::ContractAddress", "kind": "key" },
+                { "name": "direction", "type": "dojo_examples::models::Direction", "kind": "data" },
+                { "name": "path", "type": "core::array::Span::<dojo_examples::models::Vec2>", "kind": "data" }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "dojo_examples::actions::actions::Rewarded",
+            "kind": "struct",
+            "members": [
+                { "name": "amount", "type": "core::integer::u256", "kind": "data" },
+                { "name": "at", "type": "core::integer::u64", "kind": "data" }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "dojo_examples::actions::actions::Event",
+            "kind": "enum",
+            "variants": [
+                { "name": "PlayerMoved", "type": "dojo_examples::actions::actions::Moved", "kind": "nested" },
+                { "name": "Rewarded", "type": "dojo_examples::actions::actions::Rewarded", "kind": "nested" }
+            ]
+        }
+    ]))
+    .unwrap()
+}
+
+#[test]
+fn decode_nested_variant() {
+    let abi = abi();
+    let keys = [starknet_keccak(b"PlayerMoved"), felt!("0x1337")];
+    let data = [felt!("2"), felt!("2"), felt!("1"), felt!("2"), felt!("10"), felt!("3")];
+
+    let value = decode_event(&[&abi], &keys, &data).unwrap();
+    assert_eq!(
+        value.to_string(),
+        "Moved { player: 0x1337, direction: Direction::Right, path: [Vec2 { x: 1, y: 2 }, Vec2 { \
+         x: 10, y: 3 }] }"
+    );
+}
+
+#[test]
+fn decode_struct_event() {
+    let abi = abi();
+    let keys = [starknet_keccak(b"Rewarded")];
+    let data = [felt!("5"), felt!("1"), felt!("42")];
+
+    let value = decode_event(&[&abi], &keys, &data).unwrap();
+    assert_eq!(
+        value.to_string(),
+        "Rewarded { amount: 340282366920938463463374607431768211461, at: 42 }"
+    );
+}
+
+#[test]
+fn unknown_or_malformed_events() {
+    let abi = abi();
+
+    assert_eq!(decode_event(&[&abi], &[starknet_keccak(b"Spawned")], &[]), None);
+    // Missing the path of the move.
+    assert_eq!(
+        decode_event(&[&abi], &[starknet_keccak(b"Moved"), felt!("0x1")], &[felt!("0")]),
+        None
+    );
+    // Trailing data.
+    assert_eq!(
+        decode_event(
+            &[&abi],
+            &[starknet_keccak(b"Rewarded")],
+            &[felt!("5"), felt!("0"), felt!("1"), felt!("1")]
+        ),
+        None
+    );
+}
+
+fn position_schema() -> Ty {
+    Ty::Struct(Struct {
+        name: "Position".to_string(),
+        children: vec![
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                key: true,
+            },
+            Member { name: "x".to_string(), ty: Ty::Primitive(Primitive::U32(None)), key: false },
+            Member { name: "y".to_string(), ty: Ty::Primitive(Primitive::U32(None)), key: false },
+            Member {
+                name: "facing".to_string(),
+                ty: Ty::Enum(Enum {
+                    name: "Direction".to_string(),
+                    option: None,
+                    options: vec![
+                        EnumOption { name: "Left".to_string(), ty: Ty::Tuple(vec![]) },
+                        EnumOption { name: "Right".to_string(), ty: Ty::Tuple(vec![]) },
+                    ],
+                }),
+                key: false,
+            },
+        ],
+    })
+}
+
+#[test]
+fn decode_store_set_record() {
+    let table = cairo_short_string_to_felt("Position").unwrap();
+    let keys = [starknet_keccak(b"StoreSetRecord")];
+    let data = [
+        table,
+        felt!("1"),
+        felt!("0x1337"),
+        felt!("0"),
+        felt!("3"),
+        felt!("10"),
+        felt!("3"),
+        felt!("1"),
+    ];
+
+    let record = StoreSetRecord::parse(&keys, &data).unwrap();
+    assert_eq!(record.model().unwrap(), "Position");
+    assert_eq!(record.keys, vec![felt!("0x1337")]);
+
+    let value = record.decode(&position_schema()).unwrap();
+    assert_eq!(
+        value.to_string(),
+        "Position { player: 0x1337, x: 10, y: 3, facing: Direction::Right }"
+    );
+}
+
+#[test]
+fn decode_invalid_store_set_record() {
+    let table = cairo_short_string_to_felt("Position").unwrap();
+    let keys = [starknet_keccak(b"StoreSetRecord")];
+
+    assert_eq!(StoreSetRecord::parse(&[starknet_keccak(b"StoreDelRecord")], &[table]), None);
+    assert_eq!(StoreSetRecord::parse(&keys, &[table, felt!("2"), felt!("0x1")]), None);
+
+    let partial =
+        StoreSetRecord { table, keys: vec![felt!("0x1")], offset: 1, values: vec![felt!("3")] };
+    assert_eq!(partial.decode(&position_schema()), Err(EventError::PartialRecord(1)));
+
+    let extra =
+        StoreSetRecord { table, keys: vec![felt!("0x1")], offset: 0, values: vec![felt!("1"); 4] };
+    assert_eq!(
+        extra.decode(&position_schema()),
+        Err(EventError::ValueCount { model: "Position".to_string(), expected: 3, got: 4 })
+    );
+
+    let short =
+        StoreSetRecord { table, keys: vec![felt!("0x1")], offset: 0, values: vec![felt!("10")] };
+    assert_eq!(
+        short.decode(&position_schema()),
+        Err(EventError::MissingValues { model: "Position".to_string(), got: 1 })
+    );
+
+    let extra_keys = StoreSetRecord {
+        table,
+        keys: vec![felt!("0x1"), felt!("0x2")],
+        offset: 0,
+        values: vec![felt!("10"), felt!("3"), felt!("1")],
+    };
+    assert_eq!(
+        extra_keys.decode(&position_schema()),
+        Err(EventError::KeyCount { model: "Position".to_string(), expected: 1, got: 2 })
+    );
+
+    let no_keys = StoreSetRecord { table, keys: vec![], offset: 0, values: vec![felt!("1"); 3] };
+    assert_eq!(
+        no_keys.decode(&position_schema()),
+        Err(EventError::MissingKeys { model: "Position".to_string(), got: 0 })
+    );
+}
+
+#[test]
+fn decode_store_set_record_with_keys_after_values() {
+    let schema = Ty::Struct(Struct {
+        name: "Moves".to_string(),
+        children: vec![
+            Member {
+                name: "remaining".to_string(),
+                ty: Ty::Primitive(Primitive::U8(None)),
+                key: false,
+            },
+            Member {
+                name: "player".to_string(),
+                ty: Ty::Primitive(Primitive::ContractAddress(None)),
+                key: true,
+            },
+            Member { name: "last".to_string(), ty: Ty::Primitive(Primitive::U8(None)), key: false },
+        ],
+    });
+
+    let table = cairo_short_string_to_felt("Moves").unwrap();
+    let record = StoreSetRecord {
+        table,
+        keys: vec![felt!("0x1337")],
+        offset: 0,
+        values: vec![felt!("10"), felt!("2")],
+    };
+
+    assert_eq!(
+        record.decode(&schema).unwrap().to_string(),
+        "Moves { remaining: 10, player: 0x1337, last: 2 }"
+    );
+}
@@ -1,3 +1,5 @@
+pub mod calldata;
+pub mod events;
 pub mod model;
 pub mod world;
 
@@ -1,13 +1,15 @@
+use std::collections::HashMap;
 use std::vec;
 
 use async_trait::async_trait;
 use dojo_types::packing::{parse_ty, unpack, PackingError, ParseError};
 use dojo_types::primitive::PrimitiveError;
 use dojo_types::schema::Ty;
+use futures::future::try_join_all;
 use starknet::core::types::{FieldElement, FunctionCall, StarknetError};
 use starknet::core::utils::{
-    cairo_short_string_to_felt, get_selector_from_name, CairoShortStringToFeltError,
-    ParseCairoShortStringError,
+    cairo_short_string_to_felt, get_selector_from_name, starknet_keccak,
+    CairoShortStringToFeltError, ParseCairoShortStringError,
 };
 use starknet::macros::short_string;
 use starknet::providers::{
@@ -15,7 +17,9 @@ use starknet::providers::{
 };
 use starknet_crypto::poseidon_hash_many;
 
+use crate::contracts::events::StoreSetRecord;
 use crate::contracts::world::{ContractReaderError, WorldContractReader};
+use crate::manifest::get_events;
 
 const WORLD_MODEL_SELECTOR_STR: &str = "model";
 const SCHEMA_SELECTOR_STR: &str = "schema";
@@ -127,6 +131,46 @@ where
         Ok(packed)
     }
 
+    /// Returns all the entities of the model, keys included. The World indexes the entities by
+    /// the hash of their keys, so the live entities are listed from its index and their keys are
+    /// read from the `StoreSetRecord` events it emitted for the model.
+    pub async fn entities(&self) -> Result<Vec<Ty>, ModelError> {
+        let ids = self.world_reader.entity_ids(self.name).await?;
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let events = get_events(
+            self.world_reader.provider(),
+            self.world_reader.address(),
+            vec![vec![starknet_keccak(b"StoreSetRecord")]],
+        )
+        .await?;
+
+        let mut keys_by_id = HashMap::new();
+        for record in events
+            .iter()
+            .filter_map(|event| StoreSetRecord::parse(&event.keys, &event.data))
+            .filter(|record| record.table == self.name)
+        {
+            keys_by_id.entry(poseidon_hash_many(&record.keys)).or_insert(record.keys);
+        }
+
+        let schema = self.schema().await?;
+        let layout = self.layout().await?;
+
+        try_join_all(ids.iter().filter_map(|id| keys_by_id.get(id)).map(|keys| async {
+            let raw_values = self.entity_storage(keys).await?;
+            let unpacked = unpack(raw_values, layout.clone())?;
+            let mut keys_and_unpacked = [keys.as_slice(), &unpacked].concat();
+
+            let mut entity = schema.clone();
+            entity.deserialize(&mut keys_and_unpacked)?;
+            Ok::<_, ModelError>(entity)
+        }))
+        .await
+    }
+
     pub async fn entity(&self, keys: &[FieldElement]) -> Result<Ty, ModelError> {
         let mut schema = self.schema().await?;
 
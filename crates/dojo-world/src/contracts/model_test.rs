@@ -1,11 +1,15 @@
+use std::time::Duration;
+
 use camino::Utf8PathBuf;
 use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, TestSequencer,
 };
 use dojo_types::primitive::Primitive;
 use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
-use starknet::accounts::ConnectedAccount;
+use starknet::accounts::{Account, Call, ConnectedAccount};
 use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::macros::selector;
 
 use crate::contracts::model::ModelReader;
 use crate::contracts::world::test::deploy_world;
@@ -105,3 +109,47 @@ async fn test_model() {
         })
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_model_entities_skip_deleted() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let account = sequencer.account();
+    let provider = account.provider();
+    let (world_address, _) = deploy_world(
+        &sequencer,
+        Utf8PathBuf::from_path_buf("../../examples/spawn-and-move/target/dev".into()).unwrap(),
+    )
+    .await;
+
+    let world = WorldContractReader::new(world_address, provider);
+    let moves = world.model("Moves").await.unwrap();
+    let layout = moves.layout().await.unwrap();
+    let model = cairo_short_string_to_felt("Moves").unwrap();
+
+    // the account owns the World, so it can write the entities directly
+    let mut calls = vec![];
+    for player in [FieldElement::ONE, FieldElement::TWO] {
+        let mut calldata = vec![model, FieldElement::ONE, player, FieldElement::ZERO];
+        calldata.extend([FieldElement::TWO, FieldElement::from(10_u8), FieldElement::ONE]);
+        calldata.push(layout.len().into());
+        calldata.extend(layout.iter());
+        calls.push(Call { to: world_address, selector: selector!("set_entity"), calldata });
+    }
+
+    let mut calldata = vec![model, FieldElement::ONE, FieldElement::ONE];
+    calldata.push(layout.len().into());
+    calldata.extend(layout.iter());
+    calls.push(Call { to: world_address, selector: selector!("delete_entity"), calldata });
+
+    account.execute(calls).send().await.unwrap();
+
+    // wait for the tx to be mined
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    let entities = moves.entities().await.unwrap();
+    assert_eq!(entities.len(), 1);
+
+    let player = entities[0].as_struct().unwrap().children[0].ty.serialize().unwrap();
+    assert_eq!(player, vec![FieldElement::TWO]);
+}
@@ -6,7 +6,8 @@ use starknet::core::types::{
     BlockId, BlockTag, FieldElement, FunctionCall, InvokeTransactionResult,
 };
 use starknet::core::utils::{
-    cairo_short_string_to_felt, get_selector_from_name, CairoShortStringToFeltError,
+    cairo_short_string_to_felt, get_selector_from_name, parse_cairo_short_string,
+    CairoShortStringToFeltError, ParseCairoShortStringError,
 };
 use starknet::macros::selector;
 use starknet::providers::{Provider, ProviderError};
@@ -122,6 +123,57 @@ where
             .map_err(WorldContractError::AccountError)
     }
 
+    pub async fn revoke_writer(
+        &self,
+        model: &str,
+        contract: FieldElement,
+    ) -> Result<InvokeTransactionResult, WorldContractError<A::SignError>> {
+        let model = cairo_short_string_to_felt(model)
+            .map_err(WorldContractError::CairoShortStringToFeltError)?;
+
+        self.account
+            .execute(vec![Call {
+                calldata: vec![model, contract],
+                to: self.reader.address,
+                selector: get_selector_from_name("revoke_writer").unwrap(),
+            }])
+            .send()
+            .await
+            .map_err(WorldContractError::AccountError)
+    }
+
+    pub async fn grant_owner(
+        &self,
+        address: FieldElement,
+        resource: FieldElement,
+    ) -> Result<InvokeTransactionResult, WorldContractError<A::SignError>> {
+        self.account
+            .execute(vec![Call {
+                calldata: vec![address, resource],
+                to: self.reader.address,
+                selector: get_selector_from_name("grant_owner").unwrap(),
+            }])
+            .send()
+            .await
+            .map_err(WorldContractError::AccountError)
+    }
+
+    pub async fn revoke_owner(
+        &self,
+        address: FieldElement,
+        resource: FieldElement,
+    ) -> Result<InvokeTransactionResult, WorldContractError<A::SignError>> {
+        self.account
+            .execute(vec![Call {
+                calldata: vec![address, resource],
+                to: self.reader.address,
+                selector: get_selector_from_name("revoke_owner").unwrap(),
+            }])
+            .send()
+            .await
+            .map_err(WorldContractError::AccountError)
+    }
+
     pub async fn register_models(
         &self,
         models: &[FieldElement],
@@ -175,6 +227,8 @@ pub enum ContractReaderError {
     ProviderError(#[from] ProviderError),
     #[error(transparent)]
     CairoShortStringToFeltError(#[from] CairoShortStringToFeltError),
+    #[error(transparent)]
+    ParseCairoShortStringError(#[from] ParseCairoShortStringError),
 }
 
 pub struct WorldContractReader<P> {
@@ -237,6 +291,46 @@ where
         Ok(res[0] == FieldElement::ONE)
     }
 
+    pub async fn is_writer(
+        &self,
+        model: &str,
+        system: FieldElement,
+    ) -> Result<bool, ContractReaderError> {
+        let res = self
+            .provider
+            .call(
+                FunctionCall {
+                    calldata: vec![cairo_short_string_to_felt(model)?, system],
+                    contract_address: self.address,
+                    entry_point_selector: selector!("is_writer"),
+                },
+                self.block_id,
+            )
+            .await?;
+
+        Ok(res[0] == FieldElement::ONE)
+    }
+
+    pub async fn is_owner(
+        &self,
+        address: FieldElement,
+        resource: FieldElement,
+    ) -> Result<bool, ContractReaderError> {
+        let res = self
+            .provider
+            .call(
+                FunctionCall {
+                    calldata: vec![address, resource],
+                    contract_address: self.address,
+                    entry_point_selector: selector!("is_owner"),
+                },
+                self.block_id,
+            )
+            .await?;
+
+        Ok(res[0] == FieldElement::ONE)
+    }
+
     pub async fn is_account_admin(&self) -> Result<bool, ContractReaderError> {
         let res = self
             .provider
@@ -269,12 +363,17 @@ where
         Ok(res[0])
     }
 
-    pub async fn metadata_uri(&self) -> Result<FieldElement, ContractReaderError> {
+    /// Returns the metadata URI of a resource, the World itself being resource `0`. The URI is
+    /// stored as a span of short strings.
+    pub async fn metadata_uri(
+        &self,
+        resource: FieldElement,
+    ) -> Result<String, ContractReaderError> {
         let res = self
             .provider
             .call(
                 FunctionCall {
-                    calldata: vec![],
+                    calldata: vec![resource],
                     contract_address: self.address,
                     entry_point_selector: selector!("metadata_uri"),
                 },
@@ -282,7 +381,14 @@ where
             )
             .await?;
 
-        Ok(res[0])
+        let uri = res
+            .iter()
+            .skip(1)
+            .map(parse_cairo_short_string)
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+
+        Ok(uri)
     }
 
     pub async fn base(&self) -> Result<FieldElement, ContractReaderError> {
@@ -301,6 +407,27 @@ where
         Ok(res[0])
     }
 
+    /// Returns the ids of the entities of `model`, the hashes of their keys. Deleted entities are
+    /// removed from the World's index, so they aren't returned.
+    pub async fn entity_ids(
+        &self,
+        model: FieldElement,
+    ) -> Result<Vec<FieldElement>, ContractReaderError> {
+        let res = self
+            .provider
+            .call(
+                FunctionCall {
+                    calldata: vec![model],
+                    contract_address: self.address,
+                    entry_point_selector: selector!("entity_ids"),
+                },
+                self.block_id,
+            )
+            .await?;
+
+        Ok(res[1..].to_vec())
+    }
+
     pub async fn executor_call(
         &self,
         class_hash: FieldElement,
@@ -29,6 +29,7 @@ async fn test_world_contract_reader() {
     let executor = world.executor().await.unwrap();
 
     assert_eq!(executor, executor_address);
+    assert_eq!(world.metadata_uri(FieldElement::ZERO).await.unwrap(), "");
 }
 
 pub async fn deploy_world(
@@ -231,6 +231,21 @@ impl TryFrom<&std::fs::File> for Manifest {
     }
 }
 
+/// Returns the models registered to the World, by replaying its `ModelRegistered` events.
+pub async fn load_remote_models<P>(
+    provider: P,
+    world_address: FieldElement,
+) -> Result<Vec<Model>, ManifestError>
+where
+    P: Provider + Send + Sync,
+{
+    let registered_models_event_name = starknet_keccak("ModelRegistered".as_bytes());
+    let events =
+        get_events(&provider, world_address, vec![vec![registered_models_event_name]]).await?;
+
+    Ok(parse_models_events(events))
+}
+
 async fn get_remote_models_and_contracts<P: Provider>(
     world: FieldElement,
     provider: P,
@@ -304,7 +319,7 @@ where
     Ok((models, contracts))
 }
 
-async fn get_events<P: Provider>(
+pub(crate) async fn get_events<P: Provider>(
     provider: P,
     world: FieldElement,
     keys: Vec<Vec<FieldElement>>,
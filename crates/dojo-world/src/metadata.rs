@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 use std::io::Cursor;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use futures::TryStreamExt;
 use ipfs_api_backend_hyper::{IpfsApi, IpfsClient, TryFromUri};
 use scarb::core::{ManifestMetadata, Workspace};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -13,14 +14,20 @@ use url::Url;
 #[path = "metadata_test.rs"]
 mod test;
 
-pub fn dojo_metadata_from_workspace(ws: &Workspace<'_>) -> Option<Metadata> {
-    Some(ws.current_package().ok()?.manifest.metadata.dojo())
+/// Returns the dojo metadata of the current package of the workspace, or `None` if the workspace
+/// has no current package.
+pub fn dojo_metadata_from_workspace(ws: &Workspace<'_>) -> Result<Option<Metadata>> {
+    match ws.current_package() {
+        Ok(package) => package.manifest.metadata.dojo().map(Some),
+        Err(_) => Ok(None),
+    }
 }
 
 #[derive(Default, Deserialize, Debug, Clone)]
 pub struct Metadata {
     pub world: Option<WorldMetadata>,
     pub env: Option<Environment>,
+    pub auth: Option<AuthMetadata>,
 }
 
 #[derive(Debug)]
@@ -86,6 +93,12 @@ pub struct WorldMetadata {
     pub socials: Option<HashMap<String, String>>,
 }
 
+/// The network, account and World used by the commands, declared in `[tool.dojo.env]`.
+///
+/// Named environments are declared in `[tool.dojo.env.<name>]` tables. They inherit the
+/// `rpc_url` and `fee_estimate_multiplier` of `[tool.dojo.env]` if they don't set them, but never
+/// its account or World, which must be declared in every environment using them. Values may
+/// reference environment variables with `${NAME}`.
 #[derive(Default, Deserialize, Clone, Debug)]
 pub struct Environment {
     pub rpc_url: Option<String>,
@@ -94,9 +107,93 @@ pub struct Environment {
     pub keystore_path: Option<String>,
     pub keystore_password: Option<String>,
     pub world_address: Option<String>,
+    pub fee_estimate_multiplier: Option<f64>,
+    /// The named environments, from the tables of `[tool.dojo.env]`.
+    #[serde(flatten, deserialize_with = "deserialize_named_environments")]
+    pub named: HashMap<String, Environment>,
+}
+
+/// Collects the tables of `[tool.dojo.env]` as named environments. The other unknown keys are
+/// ignored, as for any other section of the metadata.
+fn deserialize_named_environments<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, Environment>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    HashMap::<String, toml::Value>::deserialize(deserializer)?
+        .into_iter()
+        .filter(|(_, value)| value.is_table())
+        .map(|(name, value)| match value.try_into::<Environment>() {
+            Ok(env) => Ok((name, env)),
+            Err(e) => Err(serde::de::Error::custom(format!("Invalid environment `{name}`: {e}"))),
+        })
+        .collect()
 }
 
 impl Environment {
+    /// Returns the environment named `name`, or the default one, with its variables interpolated.
+    pub fn select(&self, name: Option<&str>) -> Result<Environment> {
+        self.select_with(name, |name| {
+            std::env::var(name).with_context(|| format!("Environment variable `{name}` isn't set."))
+        })
+    }
+
+    /// Same as [`Environment::select`], with the values of the variables given by `lookup`.
+    pub fn select_with<F>(&self, name: Option<&str>, lookup: F) -> Result<Environment>
+    where
+        F: FnMut(&str) -> Result<String>,
+    {
+        let default = Environment { named: HashMap::new(), ..self.clone() };
+
+        let env = match name {
+            Some(name) => {
+                let named = self.named.get(name).ok_or_else(|| {
+                    let mut names = self.named.keys().map(String::as_str).collect::<Vec<_>>();
+                    names.sort();
+                    anyhow!(
+                        "Environment `{name}` isn't declared in `[tool.dojo.env]`, available \
+                         environments: [{}].",
+                        names.join(", ")
+                    )
+                })?;
+                named.clone().or(default)
+            }
+            None => default,
+        };
+
+        env.interpolate(lookup)
+    }
+
+    /// Fills the network settings that aren't set with the ones of `other`. The account and the
+    /// World are specific to each environment, so they're never inherited.
+    fn or(self, other: Environment) -> Environment {
+        Environment {
+            rpc_url: self.rpc_url.or(other.rpc_url),
+            fee_estimate_multiplier: self.fee_estimate_multiplier.or(other.fee_estimate_multiplier),
+            named: HashMap::new(),
+            ..self
+        }
+    }
+
+    fn interpolate<F>(self, mut lookup: F) -> Result<Environment>
+    where
+        F: FnMut(&str) -> Result<String>,
+    {
+        let mut resolve =
+            |value: Option<String>| value.map(|value| interpolate(&value, &mut lookup)).transpose();
+
+        Ok(Environment {
+            rpc_url: resolve(self.rpc_url)?,
+            account_address: resolve(self.account_address)?,
+            private_key: resolve(self.private_key)?,
+            keystore_path: resolve(self.keystore_path)?,
+            keystore_password: resolve(self.keystore_password)?,
+            world_address: resolve(self.world_address)?,
+            ..self
+        })
+    }
+
     pub fn world_address(&self) -> Option<&str> {
         self.world_address.as_deref()
     }
@@ -113,7 +210,6 @@ impl Environment {
         self.private_key.as_deref()
     }
 
-    #[allow(dead_code)]
     pub fn keystore_path(&self) -> Option<&str> {
         self.keystore_path.as_deref()
     }
@@ -121,6 +217,71 @@ impl Environment {
     pub fn keystore_password(&self) -> Option<&str> {
         self.keystore_password.as_deref()
     }
+
+    pub fn fee_estimate_multiplier(&self) -> Option<f64> {
+        self.fee_estimate_multiplier
+    }
+}
+
+/// Replaces the `${NAME}` references in `value` by the value `lookup` returns for `NAME`.
+pub fn interpolate<F>(value: &str, mut lookup: F) -> Result<String>
+where
+    F: FnMut(&str) -> Result<String>,
+{
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            bail!("Unterminated variable reference in `{value}`.");
+        };
+
+        let name = &rest[start + 2..start + end];
+        interpolated.push_str(&rest[..start]);
+        interpolated.push_str(&lookup(name)?);
+        rest = &rest[start + end + 1..];
+    }
+
+    interpolated.push_str(rest);
+    Ok(interpolated)
+}
+
+/// The permissions to enforce on the World, applied by `sozo migrate`.
+///
+/// Writers are declared per model and owners per resource, where a resource is either a model
+/// name or `world`. Contracts are referenced by name, or by address.
+#[derive(Default, Deserialize, Clone, Debug)]
+pub struct AuthMetadata {
+    /// A TOML file declaring additional permissions, relative to the package root.
+    pub path: Option<PathBuf>,
+    #[serde(default)]
+    pub writers: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub owners: HashMap<String, Vec<String>>,
+}
+
+impl AuthMetadata {
+    /// Merges the permissions declared in the dedicated file, if any, with the inline ones.
+    pub fn load(&self, root: impl AsRef<Path>) -> Result<AuthMetadata> {
+        let mut auth = self.clone();
+
+        if let Some(path) = auth.path.take() {
+            let path = root.as_ref().join(path);
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read auth file {}", path.display()))?;
+            let file: AuthMetadata = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse auth file {}", path.display()))?;
+
+            for (model, writers) in file.writers {
+                auth.writers.entry(model).or_default().extend(writers);
+            }
+            for (resource, owners) in file.owners {
+                auth.owners.entry(resource).or_default().extend(owners);
+            }
+        }
+
+        Ok(auth)
+    }
 }
 
 impl WorldMetadata {
@@ -136,8 +297,7 @@ impl WorldMetadata {
 impl WorldMetadata {
     pub async fn upload(&self) -> Result<String> {
         let mut meta = self.clone();
-        let client = IpfsClient::from_str("https://ipfs.infura.io:5001")?
-            .with_credentials("2EBrzr7ZASQZKH32sl2xWauXPSA", "12290b883db9138a8ae3363b6739d220");
+        let client = ipfs_client()?;
 
         if let Some(Uri::File(icon)) = &self.icon_uri {
             let icon_data = std::fs::read(icon)?;
@@ -159,6 +319,26 @@ impl WorldMetadata {
 
         Ok(response.hash)
     }
+
+    /// Fetches the metadata uploaded to IPFS at `uri`, as set on the World.
+    pub async fn fetch(uri: &str) -> Result<Self> {
+        let ipfs = Uri::Ipfs(uri.to_string());
+        let cid = ipfs.cid().ok_or_else(|| anyhow!("Metadata URI {uri} isn't an IPFS URI."))?;
+
+        let content = ipfs_client()?
+            .cat(cid)
+            .map_ok(|chunk| chunk.to_vec())
+            .try_concat()
+            .await
+            .with_context(|| format!("Failed to fetch metadata {uri}"))?;
+
+        serde_json::from_slice(&content).with_context(|| format!("Failed to parse metadata {uri}"))
+    }
+}
+
+fn ipfs_client() -> Result<IpfsClient> {
+    Ok(IpfsClient::from_str("https://ipfs.infura.io:5001")?
+        .with_credentials("2EBrzr7ZASQZKH32sl2xWauXPSA", "12290b883db9138a8ae3363b6739d220"))
 }
 
 impl Metadata {
@@ -166,21 +346,37 @@ impl Metadata {
         self.env.as_ref()
     }
 
+    /// Returns the environment named `name`, or the default one. See [`Environment::select`].
+    pub fn environment(&self, name: Option<&str>) -> Result<Option<Environment>> {
+        match (&self.env, name) {
+            (Some(env), name) => env.select(name).map(Some),
+            (None, Some(name)) => {
+                bail!("Environment `{name}` isn't declared, no `[tool.dojo.env]`.")
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
     pub fn world(&self) -> Option<&WorldMetadata> {
         self.world.as_ref()
     }
+
+    pub fn auth(&self) -> Option<&AuthMetadata> {
+        self.auth.as_ref()
+    }
 }
 trait MetadataExt {
-    fn dojo(&self) -> Metadata;
+    fn dojo(&self) -> Result<Metadata>;
 }
 
 impl MetadataExt for ManifestMetadata {
-    fn dojo(&self) -> Metadata {
-        self.tool_metadata
-            .as_ref()
-            .and_then(|e| e.get("dojo"))
-            .cloned()
-            .map(|v| v.try_into::<Metadata>().unwrap_or_default())
-            .unwrap_or_default()
+    fn dojo(&self) -> Result<Metadata> {
+        match self.tool_metadata.as_ref().and_then(|e| e.get("dojo")) {
+            Some(dojo) => dojo
+                .clone()
+                .try_into::<Metadata>()
+                .context("Failed to parse the `[tool.dojo]` metadata"),
+            None => Ok(Metadata::default()),
+        }
     }
 }
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use anyhow::anyhow;
 use url::Url;
 
 use super::WorldMetadata;
@@ -97,3 +98,111 @@ website = "https://dojoengine.org"
 
     assert!(metadata.world.is_some());
 }
+
+#[test]
+fn parse_auth_metadata() {
+    let metadata: Metadata = toml::from_str(
+        r#"
+[auth.writers]
+Position = ["actions", "0x1234"]
+Moves = ["actions"]
+
+[auth.owners]
+world = ["0x5678"]
+        "#,
+    )
+    .unwrap();
+
+    let auth = metadata.auth().unwrap();
+    assert_eq!(auth.path, None);
+    assert_eq!(auth.writers.get("Position").unwrap(), &vec!["actions", "0x1234"]);
+    assert_eq!(auth.writers.get("Moves").unwrap(), &vec!["actions"]);
+    assert_eq!(auth.owners.get("world").unwrap(), &vec!["0x5678"]);
+}
+
+#[test]
+fn select_named_environment() {
+    let metadata: Metadata = toml::from_str(
+        r#"
+[env]
+rpc_url = "http://localhost:5050/"
+account_address = "0x517ececd29116499f4a1b64b094da79ba08dfd54a3edaa316134c41f8160973"
+private_key = "0x1800000000300000180000000000030000000000003006001800006600"
+world_address = "0x0248cacaeac64c45be0c19ee8727e0bb86623ca7fa3f0d431a6c55e200697e5a"
+fee_estimate_multiplier = 1.5
+
+[env.sepolia]
+rpc_url = "https://starknet-sepolia.example.com/"
+account_address = "0x1"
+private_key = "${SEPOLIA_KEY}"
+
+[env.testnet]
+account_address = "0x2"
+keystore_path = "testnet.json"
+
+[env.mainnet]
+private_key = "${UNDEFINED_KEY}"
+        "#,
+    )
+    .unwrap();
+
+    let env = metadata.env().unwrap();
+    let vars = HashMap::from([("SEPOLIA_KEY", "0x1234")]);
+    let select = |name| {
+        env.select_with(name, |var| {
+            vars.get(var).map(|value| value.to_string()).ok_or_else(|| anyhow!("unset `{var}`"))
+        })
+    };
+
+    let default = select(None).unwrap();
+    assert_eq!(default.rpc_url(), Some("http://localhost:5050/"));
+    assert!(default.private_key().is_some());
+    assert!(default.named.is_empty());
+
+    let sepolia = select(Some("sepolia")).unwrap();
+    assert_eq!(sepolia.rpc_url(), Some("https://starknet-sepolia.example.com/"));
+    assert_eq!(sepolia.account_address(), Some("0x1"));
+    assert_eq!(sepolia.private_key(), Some("0x1234"));
+    assert_eq!(sepolia.fee_estimate_multiplier(), Some(1.5));
+    assert_eq!(sepolia.world_address(), None);
+
+    // only the network settings are inherited
+    let testnet = select(Some("testnet")).unwrap();
+    assert_eq!(testnet.rpc_url(), default.rpc_url());
+    assert_eq!(testnet.account_address(), Some("0x2"));
+    assert_eq!(testnet.keystore_path(), Some("testnet.json"));
+    assert_eq!(testnet.private_key(), None);
+    assert_eq!(testnet.world_address(), None);
+
+    assert!(select(Some("mainnet")).is_err());
+    assert!(select(Some("goerli")).is_err());
+    assert!(metadata.environment(Some("goerli")).is_err());
+}
+
+#[test]
+fn ignore_unknown_environment_keys() {
+    let metadata: Metadata = toml::from_str(
+        r#"
+[env]
+rpc_url = "http://localhost:5050/"
+chain_id = "KATANA"
+max_retries = 3
+
+[env.sepolia]
+rpc_url = "https://starknet-sepolia.example.com/"
+explorer = "https://sepolia.voyager.online/"
+        "#,
+    )
+    .unwrap();
+
+    let env = metadata.env().unwrap();
+    assert_eq!(env.named.keys().collect::<Vec<_>>(), vec!["sepolia"]);
+    assert_eq!(env.rpc_url(), Some("http://localhost:5050/"));
+
+    let sepolia = metadata.environment(Some("sepolia")).unwrap().unwrap();
+    assert_eq!(sepolia.rpc_url(), Some("https://starknet-sepolia.example.com/"));
+
+    // invalid values are still reported
+    assert!(toml::from_str::<Metadata>("[env]\nrpc_url = 3").is_err());
+    assert!(toml::from_str::<Metadata>("[env.sepolia]\nrpc_url = 3").is_err());
+}
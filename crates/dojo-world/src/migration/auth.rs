@@ -0,0 +1,240 @@
+//! Declarative World permissions.
+//!
+//! The writers of the models and the owners of the resources are declared in the project metadata
+//! and diffed against the permissions of the remote World. Only the models and resources that are
+//! declared are managed, the permissions of any other resource are left untouched.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Display;
+
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use starknet::accounts::Call;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{cairo_short_string_to_felt, CairoShortStringToFeltError};
+use starknet::macros::selector;
+use starknet::providers::Provider;
+use thiserror::Error;
+
+use crate::contracts::world::{ContractReaderError, WorldContractReader};
+
+#[cfg(test)]
+#[path = "auth_test.rs"]
+mod tests;
+
+/// The name of the resource representing the World itself.
+pub const WORLD_RESOURCE: &str = "world";
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Unknown contract `{0}`, expected a contract name or address.")]
+    UnknownContract(String),
+    #[error("Ambiguous contract `{name}`, use one of: {}.", .matches.join(", "))]
+    AmbiguousContract { name: String, matches: Vec<String> },
+    #[error(transparent)]
+    CairoShortStringToFelt(#[from] CairoShortStringToFeltError),
+    #[error(transparent)]
+    ContractReader(#[from] ContractReaderError),
+}
+
+/// A change to the permissions of the World.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AuthUpdate {
+    GrantWriter { model: String, contract: FieldElement },
+    RevokeWriter { model: String, contract: FieldElement },
+    GrantOwner { resource: String, address: FieldElement },
+    RevokeOwner { resource: String, address: FieldElement },
+}
+
+impl AuthUpdate {
+    /// Returns the World call applying the update.
+    pub fn call(&self, world_address: FieldElement) -> Result<Call, AuthError> {
+        let (selector, calldata) = match self {
+            AuthUpdate::GrantWriter { model, contract } => {
+                (selector!("grant_writer"), vec![cairo_short_string_to_felt(model)?, *contract])
+            }
+            AuthUpdate::RevokeWriter { model, contract } => {
+                (selector!("revoke_writer"), vec![cairo_short_string_to_felt(model)?, *contract])
+            }
+            AuthUpdate::GrantOwner { resource, address } => {
+                (selector!("grant_owner"), vec![*address, resource_to_felt(resource)?])
+            }
+            AuthUpdate::RevokeOwner { resource, address } => {
+                (selector!("revoke_owner"), vec![*address, resource_to_felt(resource)?])
+            }
+        };
+
+        Ok(Call { to: world_address, selector, calldata })
+    }
+}
+
+impl Display for AuthUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthUpdate::GrantWriter { model, contract } => {
+                write!(f, "Grant writer of {model} to {contract:#x}")
+            }
+            AuthUpdate::RevokeWriter { model, contract } => {
+                write!(f, "Revoke writer of {model} from {contract:#x}")
+            }
+            AuthUpdate::GrantOwner { resource, address } => {
+                write!(f, "Grant owner of {resource} to {address:#x}")
+            }
+            AuthUpdate::RevokeOwner { resource, address } => {
+                write!(f, "Revoke owner of {resource} from {address:#x}")
+            }
+        }
+    }
+}
+
+/// The declared permissions, with the contracts resolved to their addresses.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Permissions {
+    /// The contracts allowed to write to each model.
+    pub writers: BTreeMap<String, BTreeSet<FieldElement>>,
+    /// The owners of each resource.
+    pub owners: BTreeMap<String, BTreeSet<FieldElement>>,
+}
+
+impl Permissions {
+    /// Resolves the declared permissions. Contracts are referenced either by address, by their
+    /// full name or by the last segment of their name.
+    pub fn resolve(
+        writers: &HashMap<String, Vec<String>>,
+        owners: &HashMap<String, Vec<String>>,
+        contracts: &HashMap<String, FieldElement>,
+    ) -> Result<Self, AuthError> {
+        let resolve_all = |declared: &HashMap<String, Vec<String>>| {
+            declared
+                .iter()
+                .map(|(resource, names)| {
+                    let addresses = names
+                        .iter()
+                        .map(|name| resolve_contract(name, contracts))
+                        .collect::<Result<BTreeSet<_>, _>>()?;
+                    Ok((resource.clone(), addresses))
+                })
+                .collect::<Result<BTreeMap<_, _>, AuthError>>()
+        };
+
+        Ok(Self { writers: resolve_all(writers)?, owners: resolve_all(owners)? })
+    }
+
+    /// Returns the updates granting every declared permission, for a World which isn't deployed
+    /// yet and has no permissions to diff against.
+    pub fn grants(&self) -> Vec<AuthUpdate> {
+        let writers = self.writers.iter().flat_map(|(model, contracts)| {
+            contracts.iter().map(|contract| AuthUpdate::GrantWriter {
+                model: model.clone(),
+                contract: *contract,
+            })
+        });
+        let owners = self.owners.iter().flat_map(|(resource, addresses)| {
+            addresses.iter().map(|address| AuthUpdate::GrantOwner {
+                resource: resource.clone(),
+                address: *address,
+            })
+        });
+
+        writers.chain(owners).collect()
+    }
+
+    /// Computes the updates required for the remote World to match the declared permissions.
+    ///
+    /// Besides the declared ones, the permissions of the given `contracts` are checked so that
+    /// those which aren't declared anymore get revoked.
+    pub async fn diff<P>(
+        &self,
+        world: &WorldContractReader<P>,
+        contracts: &[FieldElement],
+    ) -> Result<Vec<AuthUpdate>, AuthError>
+    where
+        P: Provider + Sync + Send,
+    {
+        let writers = self.writers.iter().flat_map(|(model, declared)| {
+            candidates(declared, contracts).into_iter().map(move |contract| async move {
+                let expected = declared.contains(&contract);
+                if world.is_writer(model, contract).await? == expected {
+                    return Ok::<_, AuthError>(None);
+                }
+
+                let model = model.clone();
+                Ok(Some(if expected {
+                    AuthUpdate::GrantWriter { model, contract }
+                } else {
+                    AuthUpdate::RevokeWriter { model, contract }
+                }))
+            })
+        });
+        let owners = self
+            .owners
+            .iter()
+            .map(|(resource, declared)| Ok((resource, declared, resource_to_felt(resource)?)))
+            .collect::<Result<Vec<_>, AuthError>>()?;
+        let owners = owners.into_iter().flat_map(|(resource, declared, resource_felt)| {
+            candidates(declared, contracts).into_iter().map(move |address| async move {
+                let expected = declared.contains(&address);
+                if world.is_owner(address, resource_felt).await? == expected {
+                    return Ok::<_, AuthError>(None);
+                }
+
+                let resource = resource.clone();
+                Ok(Some(if expected {
+                    AuthUpdate::GrantOwner { resource, address }
+                } else {
+                    AuthUpdate::RevokeOwner { resource, address }
+                }))
+            })
+        });
+
+        let (writers, owners) = futures::try_join!(try_join_all(writers), try_join_all(owners))?;
+        Ok(writers.into_iter().chain(owners).flatten().collect())
+    }
+}
+
+/// Returns the felt identifying a resource in the World, which is `0` for the World itself and the
+/// model name otherwise.
+pub fn resource_to_felt(resource: &str) -> Result<FieldElement, AuthError> {
+    if resource == WORLD_RESOURCE {
+        Ok(FieldElement::ZERO)
+    } else {
+        Ok(cairo_short_string_to_felt(resource)?)
+    }
+}
+
+fn resolve_contract(
+    name: &str,
+    contracts: &HashMap<String, FieldElement>,
+) -> Result<FieldElement, AuthError> {
+    if name.starts_with("0x") {
+        return FieldElement::from_hex_be(name)
+            .map_err(|_| AuthError::UnknownContract(name.to_string()));
+    }
+
+    if let Some(address) = contracts.get(name) {
+        return Ok(*address);
+    }
+
+    let mut matches = contracts
+        .iter()
+        .filter(|(full_name, _)| full_name.rsplit("::").next() == Some(name))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(full_name, _)| *full_name);
+
+    match matches.as_slice() {
+        [] => Err(AuthError::UnknownContract(name.to_string())),
+        [(_, address)] => Ok(**address),
+        _ => Err(AuthError::AmbiguousContract {
+            name: name.to_string(),
+            matches: matches.iter().map(|(full_name, _)| full_name.to_string()).collect(),
+        }),
+    }
+}
+
+fn candidates(
+    declared: &BTreeSet<FieldElement>,
+    contracts: &[FieldElement],
+) -> BTreeSet<FieldElement> {
+    declared.iter().chain(contracts).copied().collect()
+}
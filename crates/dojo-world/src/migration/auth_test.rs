@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use assert_matches::assert_matches;
+use starknet::macros::felt;
+
+use super::*;
+
+fn contracts() -> HashMap<String, FieldElement> {
+    HashMap::from([
+        ("dojo_examples::actions::actions".to_string(), felt!("0x1")),
+        ("dojo_examples::other::other".to_string(), felt!("0x2")),
+    ])
+}
+
+#[test]
+fn resolve_contracts_by_name_and_address() {
+    let writers = HashMap::from([(
+        "Position".to_string(),
+        vec!["actions".to_string(), "dojo_examples::other::other".to_string()],
+    )]);
+    let owners = HashMap::from([(WORLD_RESOURCE.to_string(), vec!["0x1234".to_string()])]);
+
+    let permissions = Permissions::resolve(&writers, &owners, &contracts()).unwrap();
+
+    assert_eq!(
+        permissions.writers.get("Position").unwrap(),
+        &BTreeSet::from([felt!("0x1"), felt!("0x2")])
+    );
+    assert_eq!(permissions.owners.get("world").unwrap(), &BTreeSet::from([felt!("0x1234")]));
+}
+
+#[test]
+fn resolve_unknown_contract_fails() {
+    let writers = HashMap::from([("Position".to_string(), vec!["unknown".to_string()])]);
+
+    let res = Permissions::resolve(&writers, &HashMap::new(), &contracts());
+    assert_matches!(res, Err(AuthError::UnknownContract(name)) if name == "unknown");
+}
+
+#[test]
+fn resolve_ambiguous_contract_fails() {
+    let mut contracts = contracts();
+    contracts.insert("dojo_examples::legacy::actions".to_string(), felt!("0x3"));
+    let writers = HashMap::from([("Position".to_string(), vec!["actions".to_string()])]);
+
+    let res = Permissions::resolve(&writers, &HashMap::new(), &contracts);
+    assert_matches!(
+        res,
+        Err(AuthError::AmbiguousContract { name, matches }) if name == "actions"
+            && matches == ["dojo_examples::actions::actions", "dojo_examples::legacy::actions"]
+    );
+}
+
+#[test]
+fn update_calls() {
+    let world = felt!("0x99");
+
+    let call = AuthUpdate::RevokeWriter { model: "Position".to_string(), contract: felt!("0x1") }
+        .call(world)
+        .unwrap();
+    assert_eq!(call.to, world);
+    assert_eq!(call.selector, selector!("revoke_writer"));
+    assert_eq!(call.calldata, vec![cairo_short_string_to_felt("Position").unwrap(), felt!("0x1")]);
+
+    let call =
+        AuthUpdate::GrantOwner { resource: WORLD_RESOURCE.to_string(), address: felt!("0x2") }
+            .call(world)
+            .unwrap();
+    assert_eq!(call.selector, selector!("grant_owner"));
+    assert_eq!(call.calldata, vec![felt!("0x2"), FieldElement::ZERO]);
+}
+
+#[test]
+fn grant_all_declared_permissions() {
+    let writers = HashMap::from([("Position".to_string(), vec!["actions".to_string()])]);
+    let owners = HashMap::from([(WORLD_RESOURCE.to_string(), vec!["0x1234".to_string()])]);
+
+    let permissions = Permissions::resolve(&writers, &owners, &contracts()).unwrap();
+
+    assert_eq!(
+        permissions.grants(),
+        vec![
+            AuthUpdate::GrantWriter { model: "Position".to_string(), contract: felt!("0x1") },
+            AuthUpdate::GrantOwner { resource: "world".to_string(), address: felt!("0x1234") },
+        ]
+    );
+}
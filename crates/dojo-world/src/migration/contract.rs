@@ -14,6 +14,8 @@ pub struct ContractDiff {
     pub name: String,
     pub local: FieldElement,
     pub remote: Option<FieldElement>,
+    /// The address of the remote contract, if it is already deployed.
+    pub address: Option<FieldElement>,
 }
 
 impl StateDiff for ContractDiff {
@@ -31,6 +33,10 @@ impl Display for ContractDiff {
             writeln!(f, "   Remote: {remote:#x}")?;
         }
 
+        if let Some(address) = self.address {
+            writeln!(f, "   Address: {address:#x}")?;
+        }
+
         Ok(())
     }
 }
@@ -52,6 +58,7 @@ impl ContractMigration {
 
         match self.diff.local == remote {
             true => MigrationType::New,
+            false if self.diff.address.is_some() => MigrationType::Upgrade,
             false => MigrationType::Update,
         }
     }
@@ -24,8 +24,12 @@ use thiserror::Error;
 
 use crate::utils::{TransactionWaiter, TransactionWaitingError};
 
+#[cfg(feature = "contracts")]
+pub mod auth;
 pub mod class;
 pub mod contract;
+#[cfg(feature = "manifest")]
+pub mod schema;
 pub mod strategy;
 pub mod world;
 
@@ -71,6 +75,9 @@ pub enum MigrationType {
     /// When the class/contract does not exist on the remote state or
     /// when a new World is to be deployed.
     New,
+    /// When the remote contract has to be upgraded to the local class in place, keeping its
+    /// address and storage.
+    Upgrade,
 }
 
 pub trait StateDiff {
@@ -154,6 +161,40 @@ pub trait Deployable: Declarable + Sync {
             Err(e) => return Err(e),
         };
 
+        let (call, contract_address) =
+            self.prepare_world_deploy(world_address, class_hash, account).await?;
+
+        let mut txn = account.execute(vec![call]);
+
+        if let TxConfig { fee_estimate_multiplier: Some(multiplier) } = txn_config {
+            txn = txn.fee_estimate_multiplier(multiplier);
+        }
+
+        let InvokeTransactionResult { transaction_hash } =
+            txn.send().await.map_err(MigrationError::Migrator)?;
+
+        TransactionWaiter::new(transaction_hash, account.provider()).await?;
+
+        Ok(DeployOutput { transaction_hash, contract_address, declare })
+    }
+
+    /// Builds the World call that deploys the contract, or upgrades it if it's already deployed
+    /// with a different class, without sending it. The class must already be declared.
+    ///
+    /// Returns the call along with the address of the contract.
+    async fn prepare_world_deploy<P, S>(
+        &self,
+        world_address: FieldElement,
+        class_hash: FieldElement,
+        account: &SingleOwnerAccount<P, S>,
+    ) -> Result<
+        (Call, FieldElement),
+        MigrationError<<SingleOwnerAccount<P, S> as Account>::SignError>,
+    >
+    where
+        P: Provider + Sync + Send,
+        S: Signer + Sync + Send,
+    {
         let base_class_hash = account
             .provider()
             .call(
@@ -197,18 +238,7 @@ pub trait Deployable: Declarable + Sync {
             Err(e) => return Err(MigrationError::Provider(e)),
         };
 
-        let mut txn = account.execute(vec![call]);
-
-        if let TxConfig { fee_estimate_multiplier: Some(multiplier) } = txn_config {
-            txn = txn.fee_estimate_multiplier(multiplier);
-        }
-
-        let InvokeTransactionResult { transaction_hash } =
-            txn.send().await.map_err(MigrationError::Migrator)?;
-
-        TransactionWaiter::new(transaction_hash, account.provider()).await?;
-
-        Ok(DeployOutput { transaction_hash, contract_address, declare })
+        Ok((call, contract_address))
     }
 
     async fn deploy<P, S>(
@@ -282,7 +312,7 @@ pub trait Deployable: Declarable + Sync {
     fn salt(&self) -> FieldElement;
 }
 
-fn prepare_contract_declaration_params(
+pub fn prepare_contract_declaration_params(
     artifact_path: &PathBuf,
 ) -> Result<(FlattenedSierraClass, FieldElement)> {
     let flattened_class = read_class(artifact_path)?
@@ -0,0 +1,279 @@
+//! Compatibility checks between the registered and the local schema of a model.
+//!
+//! Entities are stored packed according to the layout of the model, so updating the class of a
+//! model changes how the existing entities are read. Appending value members or enum variants
+//! keeps the existing entities readable, any other change doesn't. The members of nested structs
+//! and the payloads of enum variants are compared the same way, a member appended to a nested
+//! struct being compatible only when nothing is packed after it.
+
+use std::fmt::Display;
+
+use cairo_lang_starknet::abi;
+use dojo_types::schema::Ty;
+
+use crate::contracts::calldata::{self, abi_items, tuple_items};
+use crate::manifest::Member;
+
+#[cfg(test)]
+#[path = "schema_test.rs"]
+mod tests;
+
+/// A change between the registered and the local schema of a model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// A value member was appended after the existing ones.
+    AddedMember { name: String, ty: String },
+    /// A member was appended to a nested struct, shifting the members packed after it.
+    InsertedMember { name: String, ty: String },
+    /// A variant was appended to an enum.
+    AddedVariant { ty: String, variant: String },
+    /// The keys were added, removed, reordered or changed type.
+    ChangedKeys { from: Vec<String>, to: Vec<String> },
+    /// A value member was removed.
+    RemovedMember { name: String, ty: String },
+    /// A value member was replaced by another one, or changed type.
+    ChangedMember { from: String, to: String },
+    /// The variants of an enum were removed, reordered or changed type.
+    ChangedVariants { ty: String, from: Vec<String>, to: Vec<String> },
+}
+
+impl SchemaChange {
+    /// Returns `true` if the existing entities can't be read with the local schema anymore.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, SchemaChange::AddedMember { .. } | SchemaChange::AddedVariant { .. })
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaChange::AddedMember { name, ty } => write!(f, "+ {name}: {ty}"),
+            SchemaChange::InsertedMember { name, ty } => write!(f, "+ {name}: {ty}"),
+            SchemaChange::AddedVariant { ty, variant } => write!(f, "+ {ty}::{variant}"),
+            SchemaChange::ChangedKeys { from, to } => {
+                write!(f, "~ keys ({}) -> ({})", from.join(", "), to.join(", "))
+            }
+            SchemaChange::RemovedMember { name, ty } => write!(f, "- {name}: {ty}"),
+            SchemaChange::ChangedMember { from, to } => write!(f, "~ {from} -> {to}"),
+            SchemaChange::ChangedVariants { ty, from, to } => {
+                write!(f, "~ {ty} variants ({}) -> ({})", from.join(", "), to.join(", "))
+            }
+        }
+    }
+}
+
+/// The changes between the registered and the local schema of a model.
+#[derive(Debug, Clone)]
+pub struct SchemaDiff {
+    pub model: String,
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Compares the `remote` schema of a model with its local members. The nested structs, enums
+    /// and tuples are compared against the local ABI of the model, if any.
+    pub fn compute(
+        model: &str,
+        remote: &Ty,
+        local: &[Member],
+        local_abi: Option<&abi::Contract>,
+    ) -> SchemaDiff {
+        let remote = remote.as_struct().map(|s| s.children.as_slice()).unwrap_or_default();
+
+        let mut changes = vec![];
+
+        let remote_keys = remote.iter().filter(|m| m.key).map(remote_member).collect::<Vec<_>>();
+        let local_keys = local.iter().filter(|m| m.key).map(local_member).collect::<Vec<_>>();
+
+        if remote_keys != local_keys {
+            changes.push(SchemaChange::ChangedKeys { from: remote_keys, to: local_keys });
+        }
+
+        let remote_values = remote.iter().filter(|m| !m.key).collect::<Vec<_>>();
+        let local_values = local.iter().filter(|m| !m.key).collect::<Vec<_>>();
+
+        for (i, remote) in remote_values.iter().enumerate() {
+            let Some(local) = local_values.get(i) else {
+                changes.push(SchemaChange::RemovedMember {
+                    name: remote.name.clone(),
+                    ty: remote.ty.name(),
+                });
+                continue;
+            };
+
+            if remote.name != local.name || remote.ty.name() != local.ty {
+                changes.push(SchemaChange::ChangedMember {
+                    from: remote_member(remote),
+                    to: local_member(local),
+                });
+            } else if let Some(abi) = local_abi {
+                let last = i + 1 == local_values.len();
+                diff_nested(&remote.name, &remote.ty, &local.ty, abi, last, &mut changes);
+            }
+        }
+
+        for local in local_values.iter().skip(remote_values.len()) {
+            changes
+                .push(SchemaChange::AddedMember { name: local.name.clone(), ty: local.ty.clone() });
+        }
+
+        SchemaDiff { model: model.to_string(), changes }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|c| c.is_breaking())
+    }
+}
+
+impl Display for SchemaDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            let kind = if change.is_breaking() { "breaking" } else { "compatible" };
+            writeln!(f, "{change} ({kind})")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn diff_variants(ty: &str, remote: Vec<String>, local: Vec<String>) -> Vec<SchemaChange> {
+    if local.starts_with(&remote) {
+        local[remote.len()..]
+            .iter()
+            .map(|variant| SchemaChange::AddedVariant {
+                ty: ty.to_string(),
+                variant: variant.clone(),
+            })
+            .collect()
+    } else {
+        vec![SchemaChange::ChangedVariants { ty: ty.to_string(), from: remote, to: local }]
+    }
+}
+
+/// Compares the members of the nested struct, the variants of the enum or the items of the tuple
+/// `remote` with their local definition `local_ty`, the member at `path` having the same type
+/// name on both sides. `last` is `true` if nothing is packed after the member.
+fn diff_nested(
+    path: &str,
+    remote: &Ty,
+    local_ty: &str,
+    abi: &abi::Contract,
+    last: bool,
+    changes: &mut Vec<SchemaChange>,
+) {
+    match remote {
+        Ty::Primitive(_) => {}
+        Ty::Struct(remote_struct) => {
+            let Some(local_struct) = abi_struct(abi, local_ty) else {
+                return;
+            };
+
+            for (i, remote) in remote_struct.children.iter().enumerate() {
+                let name = format!("{path}.{}", remote.name);
+                let Some(local) = local_struct.members.get(i) else {
+                    changes.push(SchemaChange::RemovedMember { name, ty: remote.ty.name() });
+                    continue;
+                };
+
+                let ty = short_name(&local.ty);
+                if remote.name != local.name || remote.ty.name() != ty {
+                    changes.push(SchemaChange::ChangedMember {
+                        from: format!("{name}: {}", remote.ty.name()),
+                        to: format!("{path}.{}: {ty}", local.name),
+                    });
+                } else {
+                    let last = last && i + 1 == local_struct.members.len();
+                    diff_nested(&name, &remote.ty, &local.ty, abi, last, changes);
+                }
+            }
+
+            for local in local_struct.members.iter().skip(remote_struct.children.len()) {
+                let (name, ty) = (format!("{path}.{}", local.name), short_name(&local.ty));
+                changes.push(if last {
+                    SchemaChange::AddedMember { name, ty }
+                } else {
+                    SchemaChange::InsertedMember { name, ty }
+                });
+            }
+        }
+        Ty::Enum(remote_enum) => {
+            let Some(local_enum) = abi_enum(abi, local_ty) else {
+                return;
+            };
+
+            let remote_variants =
+                remote_enum.options.iter().map(|o| o.name.clone()).collect::<Vec<_>>();
+            let local_variants =
+                local_enum.variants.iter().map(|v| v.name.clone()).collect::<Vec<_>>();
+            changes.extend(diff_variants(&remote_enum.name, remote_variants, local_variants));
+
+            // Only the index of the variant is packed, so the existing entities can't be read
+            // back with a payload that changed in any way.
+            for (remote, local) in remote_enum.options.iter().zip(&local_enum.variants) {
+                if remote.name != local.name {
+                    continue;
+                }
+
+                let name = format!("{path}::{}", remote.name);
+                let ty = short_name(&local.ty);
+                if remote.ty.name() != ty {
+                    changes.push(SchemaChange::ChangedMember {
+                        from: format!("{name}: {}", remote.ty.name()),
+                        to: format!("{name}: {ty}"),
+                    });
+                } else {
+                    diff_nested(&name, &remote.ty, &local.ty, abi, false, changes);
+                }
+            }
+        }
+        Ty::Tuple(remote_items) => {
+            let Some(local_items) = tuple_items(local_ty) else {
+                return;
+            };
+
+            for (i, (remote, local)) in remote_items.iter().zip(&local_items).enumerate() {
+                let last = last && i + 1 == local_items.len();
+                diff_nested(&format!("{path}.{i}"), remote, local, abi, last, changes);
+            }
+        }
+    }
+}
+
+/// Returns the struct `ty` declared in the ABI.
+fn abi_struct(abi: &abi::Contract, ty: &str) -> Option<abi::Struct> {
+    abi_items(abi).find_map(|item| match item {
+        abi::Item::Struct(s) if is_named(&s.name, ty) => Some(s),
+        _ => None,
+    })
+}
+
+/// Returns the enum `ty` declared in the ABI.
+fn abi_enum(abi: &abi::Contract, ty: &str) -> Option<abi::Enum> {
+    abi_items(abi).find_map(|item| match item {
+        abi::Item::Enum(e) if is_named(&e.name, ty) => Some(e),
+        _ => None,
+    })
+}
+
+fn is_named(full_name: &str, ty: &str) -> bool {
+    full_name == ty || full_name.ends_with(&format!("::{}", short_name(ty)))
+}
+
+/// Returns the name of an ABI type the way the registered schema names it, without the module
+/// path, e.g. `(core::integer::u32, dojo_examples::models::Vec2)` is `(u32, Vec2)`.
+fn short_name(ty: &str) -> String {
+    match tuple_items(ty) {
+        Some(items) => {
+            format!("({})", items.iter().map(|ty| short_name(ty)).collect::<Vec<_>>().join(", "))
+        }
+        None => calldata::short_name(ty).to_string(),
+    }
+}
+
+fn remote_member(member: &dojo_types::schema::Member) -> String {
+    format!("{}: {}", member.name, member.ty.name())
+}
+
+fn local_member(member: &Member) -> String {
+    format!("{}: {}", member.name, member.ty)
+}
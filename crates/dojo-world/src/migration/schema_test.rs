@@ -0,0 +1,170 @@
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{self, Struct};
+
+use super::*;
+
+fn remote_schema(members: &[(&str, Primitive, bool)]) -> Ty {
+    Ty::Struct(Struct {
+        name: "Position".into(),
+        children: members
+            .iter()
+            .map(|(name, ty, key)| schema::Member {
+                name: name.to_string(),
+                ty: Ty::Primitive(*ty),
+                key: *key,
+            })
+            .collect(),
+    })
+}
+
+fn local_members(members: &[(&str, &str, bool)]) -> Vec<Member> {
+    members
+        .iter()
+        .map(|(name, ty, key)| Member { name: name.to_string(), ty: ty.to_string(), key: *key })
+        .collect()
+}
+
+#[test]
+fn appended_members_are_compatible() {
+    let remote = remote_schema(&[
+        ("player", Primitive::ContractAddress(None), true),
+        ("x", Primitive::U32(None), false),
+    ]);
+    let local = local_members(&[
+        ("player", "ContractAddress", true),
+        ("x", "u32", false),
+        ("y", "u32", false),
+    ]);
+
+    let diff = SchemaDiff::compute("Position", &remote, &local, None);
+
+    assert_eq!(
+        diff.changes,
+        vec![SchemaChange::AddedMember { name: "y".into(), ty: "u32".into() }]
+    );
+    assert!(!diff.is_breaking());
+}
+
+#[test]
+fn reordered_keys_and_changed_types_are_breaking() {
+    let remote = remote_schema(&[
+        ("player", Primitive::ContractAddress(None), true),
+        ("game", Primitive::U32(None), true),
+        ("x", Primitive::U32(None), false),
+        ("y", Primitive::U32(None), false),
+    ]);
+    let local = local_members(&[
+        ("game", "u32", true),
+        ("player", "ContractAddress", true),
+        ("x", "u64", false),
+    ]);
+
+    let diff = SchemaDiff::compute("Position", &remote, &local, None);
+
+    assert_eq!(
+        diff.changes,
+        vec![
+            SchemaChange::ChangedKeys {
+                from: vec!["player: ContractAddress".into(), "game: u32".into()],
+                to: vec!["game: u32".into(), "player: ContractAddress".into()],
+            },
+            SchemaChange::ChangedMember { from: "x: u32".into(), to: "x: u64".into() },
+            SchemaChange::RemovedMember { name: "y".into(), ty: "u32".into() },
+        ]
+    );
+    assert!(diff.is_breaking());
+}
+
+#[test]
+fn appended_variants_are_compatible() {
+    let remote = vec!["None".to_string(), "Left".to_string()];
+
+    let changes = diff_variants(
+        "Direction",
+        remote.clone(),
+        vec!["None".into(), "Left".into(), "Right".into()],
+    );
+    assert_eq!(
+        changes,
+        vec![SchemaChange::AddedVariant { ty: "Direction".into(), variant: "Right".into() }]
+    );
+
+    let changes = diff_variants("Direction", remote, vec!["Left".into(), "None".into()]);
+    assert!(changes.iter().all(|c| c.is_breaking()));
+}
+
+#[test]
+fn nested_members_and_payloads_are_compared() {
+    let abi: abi::Contract = serde_json::from_value(serde_json::json!([
+        {
+            "type": "struct",
+            "name": "dojo_examples::models::Vec2",
+            "members": [
+                { "name": "x", "type": "core::integer::u32" },
+                { "name": "y", "type": "core::integer::u64" },
+                { "name": "z", "type": "core::integer::u32" }
+            ]
+        },
+        {
+            "type": "enum",
+            "name": "dojo_examples::models::Direction",
+            "variants": [
+                { "name": "None", "type": "()" },
+                { "name": "Jump", "type": "dojo_examples::models::Vec2" }
+            ]
+        }
+    ]))
+    .unwrap();
+
+    let vec2 = |last: Ty| {
+        Ty::Struct(Struct {
+            name: "Vec2".into(),
+            children: vec![
+                schema::Member {
+                    name: "x".into(),
+                    ty: Ty::Primitive(Primitive::U32(None)),
+                    key: false,
+                },
+                schema::Member { name: "y".into(), ty: last, key: false },
+            ],
+        })
+    };
+    let direction = Ty::Enum(schema::Enum {
+        name: "Direction".into(),
+        option: None,
+        options: vec![
+            schema::EnumOption { name: "None".into(), ty: Ty::Tuple(vec![]) },
+            schema::EnumOption {
+                name: "Jump".into(),
+                ty: vec2(Ty::Primitive(Primitive::U64(None))),
+            },
+        ],
+    });
+    let remote = Ty::Struct(Struct {
+        name: "Moves".into(),
+        children: vec![
+            schema::Member {
+                name: "position".into(),
+                ty: vec2(Ty::Primitive(Primitive::U32(None))),
+                key: false,
+            },
+            schema::Member { name: "direction".into(), ty: direction, key: false },
+        ],
+    });
+    let local = local_members(&[("position", "Vec2", false), ("direction", "Direction", false)]);
+
+    let diff = SchemaDiff::compute("Moves", &remote, &local, Some(&abi));
+
+    assert_eq!(
+        diff.changes,
+        vec![
+            SchemaChange::ChangedMember {
+                from: "position.y: u32".into(),
+                to: "position.y: u64".into()
+            },
+            SchemaChange::InsertedMember { name: "position.z".into(), ty: "u32".into() },
+            SchemaChange::InsertedMember { name: "direction::Jump.z".into(), ty: "u32".into() },
+        ]
+    );
+    assert!(diff.is_breaking());
+}
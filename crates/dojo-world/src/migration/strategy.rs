@@ -35,6 +35,7 @@ pub struct MigrationStrategy {
 pub struct MigrationItemsInfo {
     pub new: usize,
     pub update: usize,
+    pub upgrade: usize,
 }
 
 impl MigrationStrategy {
@@ -46,34 +47,26 @@ impl MigrationStrategy {
     }
 
     pub fn info(&self) -> MigrationItemsInfo {
-        let mut new = 0;
-        let mut update = 0;
+        let mut info = MigrationItemsInfo { new: 0, update: 0, upgrade: 0 };
+
+        let mut count = |migration_type| match migration_type {
+            MigrationType::New => info.new += 1,
+            MigrationType::Update => info.update += 1,
+            MigrationType::Upgrade => info.upgrade += 1,
+        };
 
         if let Some(item) = &self.world {
-            match item.migration_type() {
-                MigrationType::New => new += 1,
-                MigrationType::Update => update += 1,
-            }
+            count(item.migration_type());
         }
 
         if let Some(item) = &self.executor {
-            match item.migration_type() {
-                MigrationType::New => new += 1,
-                MigrationType::Update => update += 1,
-            }
+            count(item.migration_type());
         }
 
-        self.contracts.iter().for_each(|item| match item.migration_type() {
-            MigrationType::New => new += 1,
-            MigrationType::Update => update += 1,
-        });
+        self.contracts.iter().for_each(|item| count(item.migration_type()));
+        self.models.iter().for_each(|item| count(item.migration_type()));
 
-        self.models.iter().for_each(|item| match item.migration_type() {
-            MigrationType::New => new += 1,
-            MigrationType::Update => update += 1,
-        });
-
-        MigrationItemsInfo { new, update }
+        info
     }
 }
 
@@ -185,11 +178,18 @@ fn evaluate_contracts_to_migrate(
             _ => {
                 let path =
                     find_artifact_path(c.name.to_case(Case::Snake).as_str(), artifact_paths)?;
+
+                // Contracts of a new World are deployed from scratch, so none is upgraded.
+                let mut diff = c.clone();
+                if world_contract_will_migrate {
+                    diff.address = None;
+                }
+
                 comps_to_migrate.push(ContractMigration {
-                    diff: c.clone(),
+                    contract_address: diff.address.unwrap_or_default(),
+                    diff,
                     artifact_path: path.clone(),
                     salt: generate_salt(&c.name),
-                    ..Default::default()
                 });
             }
         }
@@ -1,9 +1,14 @@
 use std::fmt::Display;
 
+use starknet::core::utils::get_contract_address;
+
 use super::class::ClassDiff;
 use super::contract::ContractDiff;
+use super::strategy::generate_salt;
 use super::StateDiff;
-use crate::manifest::{Manifest, BASE_CONTRACT_NAME, EXECUTOR_CONTRACT_NAME, WORLD_CONTRACT_NAME};
+use crate::manifest::{
+    Contract, Manifest, BASE_CONTRACT_NAME, EXECUTOR_CONTRACT_NAME, WORLD_CONTRACT_NAME,
+};
 
 #[cfg(test)]
 #[path = "world_test.rs"]
@@ -33,18 +38,21 @@ impl WorldDiff {
             })
             .collect::<Vec<_>>();
 
+        // Contracts are matched by the address they were deployed at, which only depends on their
+        // name, so that a contract whose class changed is upgraded in place.
         let contracts = local
             .contracts
             .iter()
-            .map(|contract| ContractDiff {
-                name: contract.name.to_string(),
-                local: contract.class_hash,
-                remote: remote.as_ref().and_then(|m| {
-                    m.contracts
-                        .iter()
-                        .find(|r| r.class_hash == contract.class_hash)
-                        .map(|r| r.class_hash)
-                }),
+            .map(|contract| {
+                let remote_contract =
+                    remote.as_ref().and_then(|m| find_remote_contract(m, &contract.name));
+
+                ContractDiff {
+                    name: contract.name.to_string(),
+                    local: contract.class_hash,
+                    remote: remote_contract.map(|r| r.class_hash),
+                    address: remote_contract.and_then(|r| r.address),
+                }
             })
             .collect::<Vec<_>>();
 
@@ -52,6 +60,7 @@ impl WorldDiff {
             name: EXECUTOR_CONTRACT_NAME.into(),
             local: local.executor.class_hash,
             remote: remote.as_ref().map(|m| m.executor.class_hash),
+            address: None,
         };
 
         let base = ClassDiff {
@@ -64,6 +73,7 @@ impl WorldDiff {
             name: WORLD_CONTRACT_NAME.into(),
             local: local.world.class_hash,
             remote: remote.map(|m| m.world.class_hash),
+            address: None,
         };
 
         WorldDiff { world, executor, base, contracts, models }
@@ -86,6 +96,19 @@ impl WorldDiff {
     }
 }
 
+/// Finds the remote contract deployed for the local contract `name`. The contract is looked up by
+/// its address, derived from the salt of its name, and otherwise by its `dojo_resource` name.
+fn find_remote_contract<'a>(remote: &'a Manifest, name: &str) -> Option<&'a Contract> {
+    let address = remote.world.address.map(|world_address| {
+        get_contract_address(generate_salt(name), remote.base.class_hash, &[], world_address)
+    });
+
+    remote.contracts.iter().find(|r| address.is_some() && r.address == address).or_else(|| {
+        let short_name = name.rsplit("::").next().unwrap_or(name);
+        remote.contracts.iter().find(|r| !r.name.is_empty() && r.name == short_name)
+    })
+}
+
 impl Display for WorldDiff {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", self.world)?;
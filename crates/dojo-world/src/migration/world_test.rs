@@ -103,3 +103,46 @@ fn diff_when_local_and_remote_are_different() {
     assert!(diff.models.iter().any(|m| m.name == "Model2"));
     assert!(diff.contracts.iter().any(|c| c.name == "my_contract"));
 }
+
+#[test]
+fn updated_contracts_are_matched_by_address() {
+    let world_address = felt!("0x1234");
+    let base_class_hash = felt!("0x5678");
+
+    let name = "dojo_examples::actions::actions";
+    let address = get_contract_address(generate_salt(name), base_class_hash, &[], world_address);
+
+    let mut local = Manifest {
+        contracts: vec![Contract {
+            name: name.into(),
+            class_hash: felt!("0x2"),
+            ..Contract::default()
+        }],
+        ..Default::default()
+    };
+    local.base.class_hash = base_class_hash;
+
+    let mut remote = local.clone();
+    remote.world.address = Some(world_address);
+    remote.contracts = vec![
+        Contract {
+            name: "other".into(),
+            class_hash: felt!("0x3"),
+            address: Some(felt!("0x99")),
+            ..Contract::default()
+        },
+        Contract {
+            name: "".into(),
+            class_hash: felt!("0x1"),
+            address: Some(address),
+            ..Contract::default()
+        },
+    ];
+
+    let diff = WorldDiff::compute(local, Some(remote));
+
+    let contract = &diff.contracts[0];
+    assert_eq!(contract.remote, Some(felt!("0x1")));
+    assert_eq!(contract.address, Some(address));
+    assert!(!contract.is_same());
+}
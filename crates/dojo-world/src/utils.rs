@@ -248,6 +248,26 @@ fn finality_status_from_receipt(receipt: &TransactionReceipt) -> TransactionFina
     }
 }
 
+#[inline]
+pub fn actual_fee_from_receipt(receipt: &MaybePendingTransactionReceipt) -> FieldElement {
+    match receipt {
+        MaybePendingTransactionReceipt::Receipt(receipt) => match receipt {
+            TransactionReceipt::Invoke(tx) => tx.actual_fee,
+            TransactionReceipt::L1Handler(tx) => tx.actual_fee,
+            TransactionReceipt::Declare(tx) => tx.actual_fee,
+            TransactionReceipt::Deploy(tx) => tx.actual_fee,
+            TransactionReceipt::DeployAccount(tx) => tx.actual_fee,
+        },
+        MaybePendingTransactionReceipt::PendingReceipt(receipt) => match receipt {
+            PendingTransactionReceipt::Invoke(tx) => tx.actual_fee,
+            PendingTransactionReceipt::L1Handler(tx) => tx.actual_fee,
+            PendingTransactionReceipt::Declare(tx) => tx.actual_fee,
+            PendingTransactionReceipt::Deploy(tx) => tx.actual_fee,
+            PendingTransactionReceipt::DeployAccount(tx) => tx.actual_fee,
+        },
+    }
+}
+
 #[inline]
 pub fn block_number_from_receipt(receipt: &TransactionReceipt) -> u64 {
     match receipt {
@@ -5,9 +5,10 @@ use starknet_api::hash::StarkHash;
 use starknet_api::patricia_key;
 use url::Url;
 
+use crate::backend::storage::archive::ImportedChain;
 use crate::constants::{
-    DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS, FEE_TOKEN_ADDRESS,
-    SEQUENCER_ADDRESS,
+    DEFAULT_DATA_GAS_PRICE, DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS,
+    DEFAULT_VALIDATE_MAX_STEPS, FEE_TOKEN_ADDRESS, SEQUENCER_ADDRESS,
 };
 use crate::db::serde::state::SerializableState;
 use crate::env::{get_default_vm_resource_fee_cost, BlockContextGenerator};
@@ -21,6 +22,9 @@ pub struct StarknetConfig {
     pub fork_rpc_url: Option<Url>,
     pub fork_block_number: Option<u64>,
     pub init_state: Option<SerializableState>,
+    /// A chain imported from an archive, to start from.
+    pub init_chain: Option<ImportedChain>,
+    pub execution_engine: ExecutionEngine,
 }
 
 impl StarknetConfig {
@@ -48,12 +52,14 @@ impl Default for StarknetConfig {
     fn default() -> Self {
         Self {
             init_state: None,
+            init_chain: None,
             seed: [0; 32],
             total_accounts: 10,
             disable_fee: false,
             fork_rpc_url: None,
             fork_block_number: None,
             env: Environment::default(),
+            execution_engine: ExecutionEngine::default(),
         }
     }
 }
@@ -62,6 +68,8 @@ impl Default for StarknetConfig {
 pub struct Environment {
     pub chain_id: String,
     pub gas_price: u128,
+    /// The price of a single byte of state diff published to L1, in wei.
+    pub data_gas_price: u128,
     pub invoke_max_steps: u32,
     pub validate_max_steps: u32,
 }
@@ -70,9 +78,22 @@ impl Default for Environment {
     fn default() -> Self {
         Self {
             gas_price: DEFAULT_GAS_PRICE,
+            data_gas_price: DEFAULT_DATA_GAS_PRICE,
             chain_id: "KATANA".to_string(),
             invoke_max_steps: DEFAULT_INVOKE_MAX_STEPS,
             validate_max_steps: DEFAULT_VALIDATE_MAX_STEPS,
         }
     }
 }
+
+/// The engine used to execute Sierra classes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionEngine {
+    /// Execute the compiled CASM of every class using the Cairo VM.
+    #[default]
+    Vm,
+    /// Compile Sierra classes to native code and execute calls to them natively. Entry points that
+    /// can't be executed natively, and transactions, are executed using the Cairo VM. Requires the
+    /// `native` feature.
+    Native,
+}
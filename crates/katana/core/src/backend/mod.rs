@@ -6,16 +6,14 @@ use blockifier::execution::entry_point::{
     CallEntryPoint, CallInfo, EntryPointExecutionContext, ExecutionResources,
 };
 use blockifier::execution::errors::EntryPointExecutionError;
-use blockifier::fee::fee_utils::{calculate_l1_gas_by_vm_usage, extract_l1_gas_and_vm_usage};
 use blockifier::state::cached_state::{CachedState, MutRefState};
-use blockifier::state::state_api::StateReader;
 use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::AccountTransactionContext;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use parking_lot::RwLock;
 use starknet::core::types::{
-    BlockId, BlockTag, FeeEstimate, MaybePendingBlockWithTxHashes, TransactionFinalityStatus,
+    BlockId, BlockTag, MaybePendingBlockWithTxHashes, TransactionFinalityStatus,
 };
 use starknet::core::utils::parse_cairo_short_string;
 use starknet::providers::jsonrpc::HttpTransport;
@@ -33,7 +31,8 @@ pub mod contract;
 pub mod in_memory_db;
 pub mod storage;
 
-use self::config::StarknetConfig;
+use self::config::{ExecutionEngine, StarknetConfig};
+use self::storage::archive::{ArchivedBlock, ArchivedGenesis};
 use self::storage::block::{Block, PartialHeader};
 use self::storage::transaction::{IncludedTransaction, Transaction};
 use self::storage::{Blockchain, InMemoryBlockStates, Storage};
@@ -46,7 +45,10 @@ use crate::db::serde::state::SerializableState;
 use crate::db::{Database, StateRefDb};
 use crate::env::{BlockContextGenerator, Env};
 use crate::execution::{ExecutionOutcome, MaybeInvalidExecutedTransaction, TransactionExecutor};
+use crate::fee::FeeEstimateWithDataGas;
 use crate::fork::db::ForkedDb;
+#[cfg(feature = "native")]
+use crate::native::{self, NativeClassCache};
 use crate::sequencer_error::SequencerError;
 use crate::service::block_producer::MinedBlockOutcome;
 use crate::utils::{convert_state_diff_to_rpc_state_diff, get_current_timestamp};
@@ -71,20 +73,50 @@ pub struct Backend {
     pub state: Arc<AsyncRwLock<dyn Database>>,
     /// Prefunded dev accounts
     pub accounts: Vec<Account>,
+    /// The genesis of the chain, if it was imported from an archive.
+    pub imported_genesis: Option<ArchivedGenesis>,
+    /// Natively compiled classes, if the native execution engine is enabled.
+    #[cfg(feature = "native")]
+    pub native_classes: Option<NativeClassCache>,
 }
 
 impl Backend {
-    pub async fn new(config: StarknetConfig) -> Self {
+    pub async fn new(mut config: StarknetConfig) -> Self {
         let mut block_context = config.block_context();
         let block_context_generator = config.block_context_generator();
 
-        let accounts = DevAccountGenerator::new(config.total_accounts)
-            .with_seed(config.seed)
-            .with_balance((*DEFAULT_PREFUNDED_ACCOUNT_BALANCE).into())
-            .generate();
+        // The chain is moved out of the config, only its genesis is kept to export it again.
+        let imported = config.init_chain.take();
+        let imported_genesis = imported.as_ref().map(|chain| chain.genesis.clone());
+
+        // The accounts of an imported chain are the ones of its genesis, already deployed.
+        let accounts = match &imported {
+            Some(chain) => chain.genesis.accounts(),
+            None => DevAccountGenerator::new(config.total_accounts)
+                .with_seed(config.seed)
+                .with_balance((*DEFAULT_PREFUNDED_ACCOUNT_BALANCE).into())
+                .generate(),
+        };
+
+        let (state, storage, states): (Arc<AsyncRwLock<dyn Database>>, Arc<RwLock<Storage>>, _) =
+            if let Some(chain) = imported {
+                let header = chain.latest_header();
+                block_context.block_number = BlockNumber(header.number);
+                block_context.block_timestamp = BlockTimestamp(header.timestamp);
+                block_context.chain_id = ChainId(chain.genesis.chain_id.clone());
 
-        let (state, storage): (Arc<AsyncRwLock<dyn Database>>, Arc<RwLock<Storage>>) =
-            if let Some(forked_url) = config.fork_rpc_url.clone() {
+                info!(
+                    target: "backend",
+                    "Successfully imported chain archive with {} blocks",
+                    chain.storage.blocks.len()
+                );
+
+                (
+                    Arc::new(AsyncRwLock::new(chain.state)),
+                    Arc::new(RwLock::new(chain.storage)),
+                    chain.states,
+                )
+            } else if let Some(forked_url) = config.fork_rpc_url.clone() {
                 let provider = Arc::new(JsonRpcClient::new(HttpTransport::new(forked_url.clone())));
 
                 let forked_chain_id = provider.chain_id().await.unwrap();
@@ -122,31 +154,51 @@ impl Backend {
                         block.block_number,
                         block.block_hash,
                     ))),
+                    InMemoryBlockStates::default(),
                 )
             } else {
                 (
                     Arc::new(AsyncRwLock::new(MemDb::default())),
                     Arc::new(RwLock::new(Storage::new(&block_context))),
+                    InMemoryBlockStates::default(),
                 )
             };
 
-        for acc in &accounts {
-            acc.deploy_and_fund(&mut *state.write().await)
-                .expect("should be able to deploy and fund dev account");
-        }
+        // The accounts and the genesis state of an imported chain are already in its state.
+        if imported_genesis.is_none() {
+            for acc in &accounts {
+                acc.deploy_and_fund(&mut *state.write().await)
+                    .expect("should be able to deploy and fund dev account");
+            }
 
-        if let Some(ref init_state) = config.init_state {
-            state
-                .write()
-                .await
-                .load_state(init_state.clone())
-                .expect("failed to load initial state");
-            info!(target: "backend", "Successfully loaded initial state");
+            if let Some(ref init_state) = config.init_state {
+                state
+                    .write()
+                    .await
+                    .load_state(init_state.clone())
+                    .expect("failed to load initial state");
+                info!(target: "backend", "Successfully loaded initial state");
+            }
         }
 
         let blockchain = Blockchain::new(storage);
-        let states = InMemoryBlockStates::default();
-        let env = Env { block: block_context };
+        let env = Env { block: block_context, data_gas_price: config.env.data_gas_price };
+
+        #[cfg(feature = "native")]
+        let native_classes = if config.execution_engine == ExecutionEngine::Native {
+            info!(target: "backend", "Using native execution engine");
+            Some(NativeClassCache::new())
+        } else {
+            None
+        };
+
+        #[cfg(not(feature = "native"))]
+        if config.execution_engine == ExecutionEngine::Native {
+            warn!(
+                target: "backend",
+                "Native execution engine requires the `native` feature, using the Cairo VM instead"
+            );
+        }
 
         Self {
             state,
@@ -156,6 +208,9 @@ impl Backend {
             blockchain,
             block_context_generator: RwLock::new(block_context_generator),
             accounts,
+            imported_genesis,
+            #[cfg(feature = "native")]
+            native_classes,
         }
     }
 
@@ -175,17 +230,45 @@ impl Backend {
         Ok(encoder.finish().unwrap_or_default())
     }
 
+    /// Returns the genesis of the chain, on top of which its blocks are replayed when imported.
+    /// The genesis of a forked chain is the remote state, so it can't be exported.
+    pub fn export_genesis(&self) -> Result<ArchivedGenesis, SequencerError> {
+        let config = self.config.read();
+
+        if config.fork_rpc_url.is_some() {
+            return Err(SequencerError::DataUnavailable);
+        }
+
+        if let Some(genesis) = &self.imported_genesis {
+            return Ok(genesis.clone());
+        }
+
+        Ok(ArchivedGenesis {
+            chain_id: self.env.read().block.chain_id.0.clone(),
+            seed: config.seed,
+            total_accounts: config.total_accounts,
+            state: config.init_state.clone(),
+        })
+    }
+
+    /// Returns at most `count` archived blocks, starting from the block number `from`.
+    pub fn export_blocks(&self, from: u64, count: u64) -> Vec<ArchivedBlock> {
+        ArchivedBlock::range(&self.blockchain.storage.read(), from, count)
+    }
+
     pub fn estimate_fee(
         &self,
         transactions: Vec<Transaction>,
         state: StateRefDb,
-    ) -> Result<Vec<FeeEstimate>, TransactionExecutionError> {
+    ) -> Result<Vec<FeeEstimateWithDataGas>, TransactionExecutionError> {
         let mut state = CachedStateWrapper::new(state);
         let block_context = self.env.read().block.clone();
+        let data_gas_price = self.env.read().data_gas_price;
 
         let mut estimations = Vec::with_capacity(transactions.len());
 
         let results = TransactionExecutor::new(&mut state, &block_context, false, transactions)
+            .with_data_gas_price(data_gas_price)
             .with_error_log()
             .execute();
 
@@ -199,18 +282,11 @@ impl Backend {
                 ));
             }
 
-            let (l1_gas_usage, vm_resources) =
-                extract_l1_gas_and_vm_usage(&exec_info.actual_resources);
-            let l1_gas_by_vm_usage = calculate_l1_gas_by_vm_usage(&block_context, &vm_resources)?;
-            let total_l1_gas_usage = l1_gas_usage as f64 + l1_gas_by_vm_usage;
-
-            let gas_price = block_context.gas_price as u64;
-
-            estimations.push(FeeEstimate {
-                gas_consumed: total_l1_gas_usage.ceil() as u64,
-                gas_price,
-                overall_fee: total_l1_gas_usage.ceil() as u64 * gas_price,
-            })
+            estimations.push(FeeEstimateWithDataGas::new(
+                &block_context,
+                data_gas_price,
+                &exec_info.actual_resources,
+            )?);
         }
 
         Ok(estimations)
@@ -287,6 +363,14 @@ impl Backend {
         self.blockchain.append_block(block_hash, block.clone(), state_diff);
         // apply the pending state to the current state
         execution_outcome.apply_to(&mut *state);
+
+        // start compiling the newly declared classes ahead of their first execution
+        #[cfg(feature = "native")]
+        if let Some(cache) = &self.native_classes {
+            for class_hash in execution_outcome.declared_sierra_classes.keys() {
+                cache.get_or_compile(*class_hash, &mut *state);
+            }
+        }
         // store the current state
         self.states.write().await.insert(block_hash, state.as_ref_db());
 
@@ -296,28 +380,44 @@ impl Backend {
     }
 
     pub fn update_block_context(&self) {
-        let mut context_gen = self.block_context_generator.write();
+        let timestamp = self.next_block_timestamp();
         let block_context = &mut self.env.write().block;
+        block_context.block_number = block_context.block_number.next();
+        block_context.block_timestamp = BlockTimestamp(timestamp);
+    }
+
+    /// Updates the timestamp of the block being opened to the current time, without opening a new
+    /// block.
+    pub fn update_block_timestamp(&self) {
+        let timestamp = self.next_block_timestamp();
+        self.env.write().block.block_timestamp = BlockTimestamp(timestamp);
+    }
+
+    fn next_block_timestamp(&self) -> u64 {
+        let mut context_gen = self.block_context_generator.write();
         let current_timestamp_secs = get_current_timestamp().as_secs() as i64;
 
-        let timestamp = if context_gen.next_block_start_time == 0 {
+        if context_gen.next_block_start_time == 0 {
             (current_timestamp_secs + context_gen.block_timestamp_offset) as u64
         } else {
             let timestamp = context_gen.next_block_start_time;
             context_gen.block_timestamp_offset = timestamp as i64 - current_timestamp_secs;
             context_gen.next_block_start_time = 0;
             timestamp
-        };
-
-        block_context.block_number = block_context.block_number.next();
-        block_context.block_timestamp = BlockTimestamp(timestamp);
+        }
     }
 
     pub fn call(
         &self,
         call: ExternalFunctionCall,
-        state: impl StateReader,
+        state: StateRefDb,
     ) -> Result<CallInfo, EntryPointExecutionError> {
+        let block_context = self.env.read().block.clone();
+
+        // used to fetch the Sierra classes to compile natively
+        #[cfg(feature = "native")]
+        let classes = state.clone();
+
         let mut state = CachedState::new(state);
         let mut state = CachedState::new(MutRefState::new(&mut state));
 
@@ -329,11 +429,31 @@ impl Backend {
             ..Default::default()
         };
 
+        // classes that can't be executed natively fallback to the VM
+        #[cfg(feature = "native")]
+        if let Some(cache) = &self.native_classes {
+            match native::execute_call(
+                cache,
+                &mut state,
+                &classes,
+                &block_context,
+                &AccountTransactionContext::default(),
+                call.clone(),
+            ) {
+                Ok(Some(info)) => return Ok(info),
+                Ok(None) => {}
+                Err(err) => {
+                    warn!(target: "backend", "Call error: {err:?}");
+                    return Err(err);
+                }
+            }
+        }
+
         let res = call.execute(
             &mut state,
             &mut ExecutionResources::default(),
             &mut EntryPointExecutionContext::new(
-                self.env.read().block.clone(),
+                block_context,
                 AccountTransactionContext::default(),
                 1000000000,
             ),
@@ -0,0 +1,620 @@
+//! Portable archive of the full chain data.
+//!
+//! An archive contains the genesis of the chain and every block along with their transactions,
+//! receipts data, events and state updates. Loading an archive recreates the chain with the exact
+//! same block numbers and hashes, and rebuilds the state of every block by applying the archived
+//! state updates on top of the genesis state.
+//!
+//! # Format
+//!
+//! An archive is a gzip-compressed JSON document with the following top-level fields:
+//!
+//! - `version`: the version of the archive format, currently [`ARCHIVE_VERSION`].
+//! - `genesis`: what the genesis state is made of: the `chain_id` as a string (e.g. `KATANA`), the
+//!   `seed` and the `total_accounts` of the predeployed accounts, and the `state` loaded on top of
+//!   them with `--load-state` (if any).
+//! - `blocks`: the blocks of the chain, ordered by block number. Each block contains its `hash`,
+//!   its `header`, its `transactions` and the `state_update` it produced (absent for the genesis
+//!   block).
+//!
+//! Every archived transaction holds the transaction itself (externally tagged by its type:
+//! `Invoke`, `Declare`, `DeployAccount` or `L1Handler`) along with its execution output: the
+//! `actual_fee`, the `gas` it was charged for, the `revert_error` (if any), the emitted `events`
+//! and the `messages_sent` to L1.
+//!
+//! Changes made to the state outside of a block, such as with `katana_setStorageAt`, are not part
+//! of the archive.
+//!
+//! The version is bumped on every breaking change of the format, and loading an archive with a
+//! different version is rejected.
+
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::{fmt, fs};
+
+use anyhow::{anyhow, ensure, Result};
+use blockifier::state::state_api::State;
+use blockifier::transaction::objects::TransactionExecutionInfo;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{
+    Event, FieldElement, FlattenedSierraClass, MsgToL1, StateUpdate, TransactionFinalityStatus,
+};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey};
+use starknet_api::hash::StarkHash;
+use starknet_api::patricia_key;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{
+    DeclareTransaction as ApiDeclareTransaction,
+    DeployAccountTransaction as ApiDeployAccountTransaction, Fee,
+    InvokeTransaction as ApiInvokeTransaction, L1HandlerTransaction as ApiL1HandlerTransaction,
+};
+
+use super::block::{Block, BlockStatus, Header};
+use super::transaction::{
+    DeclareTransaction, DeployAccountTransaction, IncludedTransaction, InvokeTransaction,
+    KnownTransaction, L1HandlerTransaction, Transaction, TransactionOutput,
+};
+use super::{InMemoryBlockStates, Storage};
+use crate::accounts::{Account, DevAccountGenerator};
+use crate::backend::in_memory_db::MemDb;
+use crate::constants::DEFAULT_PREFUNDED_ACCOUNT_BALANCE;
+use crate::db::serde::contract::SerializableContractClass;
+use crate::db::serde::state::SerializableState;
+use crate::db::{AsStateRefDb, Database, StateExt};
+use crate::execution::ExecutedTransaction;
+use crate::fee::TransactionGas;
+
+/// The current version of the archive format.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainArchive {
+    /// The version of the archive format.
+    pub version: u32,
+    /// The genesis of the chain.
+    pub genesis: ArchivedGenesis,
+    /// All the blocks of the chain, ordered by block number.
+    pub blocks: Vec<ArchivedBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedGenesis {
+    /// The chain id.
+    pub chain_id: String,
+    /// The seed of the predeployed accounts.
+    pub seed: [u8; 32],
+    /// The number of predeployed accounts.
+    pub total_accounts: u8,
+    /// The state loaded on top of the predeployed accounts.
+    pub state: Option<SerializableState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedBlock {
+    pub hash: FieldElement,
+    pub header: ArchivedHeader,
+    pub transactions: Vec<ArchivedTransaction>,
+    pub state_update: Option<StateUpdate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedHeader {
+    pub parent_hash: FieldElement,
+    pub number: u64,
+    pub gas_price: u128,
+    pub timestamp: u64,
+    pub state_root: FieldElement,
+    pub sequencer_address: FieldElement,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTransaction {
+    pub transaction: ArchivedTransactionKind,
+    pub actual_fee: u128,
+    pub gas: TransactionGas,
+    pub revert_error: Option<String>,
+    pub events: Vec<Event>,
+    pub messages_sent: Vec<MsgToL1>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ArchivedTransactionKind {
+    Invoke(ApiInvokeTransaction),
+    Declare {
+        inner: ApiDeclareTransaction,
+        compiled_class: SerializableContractClass,
+        sierra_class: Option<FlattenedSierraClass>,
+    },
+    DeployAccount {
+        inner: ApiDeployAccountTransaction,
+        contract_address: FieldElement,
+    },
+    L1Handler {
+        inner: ApiL1HandlerTransaction,
+        paid_l1_fee: u128,
+    },
+}
+
+impl ChainArchive {
+    pub fn new(genesis: ArchivedGenesis, blocks: Vec<ArchivedBlock>) -> Self {
+        Self { version: ARCHIVE_VERSION, genesis, blocks }
+    }
+
+    /// Loads an archive from the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let buf = fs::read(path)?;
+
+        let mut decoder = GzDecoder::new(&buf[..]);
+        let mut decoded = Vec::new();
+        decoder.read_to_end(&mut decoded)?;
+
+        let archive: Self = serde_json::from_slice(&decoded)?;
+        ensure!(
+            archive.version == ARCHIVE_VERSION,
+            "unsupported archive version {} (expected {ARCHIVE_VERSION})",
+            archive.version
+        );
+
+        Ok(archive)
+    }
+
+    /// Writes the archive to the given path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&serde_json::to_vec(self)?)?;
+        fs::write(path, encoder.finish()?)
+    }
+
+    /// Recreates the chain storage from the archived blocks.
+    pub fn to_storage(&self) -> Result<Storage> {
+        let mut storage = Storage::default();
+
+        for archived in &self.blocks {
+            let transactions = archived
+                .transactions
+                .iter()
+                .map(|tx| tx.clone().try_into().map(Arc::new))
+                .collect::<Result<Vec<Arc<ExecutedTransaction>>>>()?;
+
+            let header = Header::from(archived.header.clone());
+            let hash = header.hash();
+            ensure!(
+                hash == archived.hash,
+                "hash mismatch for block {}: expected {:#x}, computed {:#x}",
+                header.number,
+                archived.hash,
+                hash
+            );
+
+            for tx in &transactions {
+                storage.transactions.insert(
+                    tx.inner.hash(),
+                    KnownTransaction::Included(IncludedTransaction {
+                        block_hash: hash,
+                        block_number: header.number,
+                        transaction: tx.clone(),
+                        finality_status: TransactionFinalityStatus::AcceptedOnL2,
+                    }),
+                );
+            }
+
+            if let Some(state_update) = archived.state_update.clone() {
+                storage.state_update.insert(hash, state_update);
+            }
+
+            storage.latest_hash = hash;
+            storage.latest_number = header.number;
+            storage.hashes.insert(header.number, hash);
+            storage.blocks.insert(
+                hash,
+                Block {
+                    header,
+                    status: BlockStatus::AcceptedOnL2,
+                    outputs: transactions.iter().map(|tx| tx.output.clone()).collect(),
+                    transactions,
+                },
+            );
+        }
+
+        if storage.blocks.is_empty() {
+            return Err(anyhow!("archive doesn't contain any block"));
+        }
+
+        Ok(storage)
+    }
+
+    /// Rebuilds the state of every archived block, on top of the given state which must only
+    /// contain the predeployed accounts. The state of every block is added to `states`, and
+    /// `state` is left with the latest state of the chain.
+    pub fn replay(&self, state: &mut dyn Database, states: &mut InMemoryBlockStates) -> Result<()> {
+        if let Some(genesis_state) = self.genesis.state.clone() {
+            state.load_state(genesis_state)?;
+        }
+
+        for block in &self.blocks {
+            block.apply_to(state)?;
+            states.insert(block.hash, state.as_ref_db());
+        }
+
+        Ok(())
+    }
+}
+
+impl ArchivedGenesis {
+    /// Returns the predeployed accounts of the chain.
+    pub fn accounts(&self) -> Vec<Account> {
+        DevAccountGenerator::new(self.total_accounts)
+            .with_seed(self.seed)
+            .with_balance((*DEFAULT_PREFUNDED_ACCOUNT_BALANCE).into())
+            .generate()
+    }
+}
+
+/// The chain recreated from an archive, which a node can start from.
+pub struct ImportedChain {
+    pub genesis: ArchivedGenesis,
+    pub storage: Storage,
+    /// The latest state of the chain.
+    pub state: MemDb,
+    /// The state of every block of the chain.
+    pub states: InMemoryBlockStates,
+}
+
+impl ImportedChain {
+    /// Recreates the chain of an archive on top of its genesis. Fails if a block doesn't match its
+    /// hash or can't be applied to the state of the previous one.
+    pub fn new(archive: ChainArchive) -> Result<Self> {
+        let storage = archive.to_storage()?;
+
+        let mut state = MemDb::default();
+        for account in archive.genesis.accounts() {
+            account.deploy_and_fund(&mut state)?;
+        }
+
+        let mut states = InMemoryBlockStates::default();
+        archive.replay(&mut state, &mut states)?;
+
+        Ok(Self { genesis: archive.genesis, storage, state, states })
+    }
+
+    /// Returns the header of the latest block of the chain.
+    pub fn latest_header(&self) -> &Header {
+        &self.storage.blocks[&self.storage.latest_hash].header
+    }
+}
+
+impl fmt::Debug for ImportedChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportedChain")
+            .field("genesis", &self.genesis)
+            .field("latest_number", &self.storage.latest_number)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArchivedBlock {
+    /// Archives at most `count` blocks of the storage, starting from the block number `from`.
+    pub fn range(storage: &Storage, from: u64, count: u64) -> Vec<Self> {
+        (from..from.saturating_add(count))
+            .map_while(|number| {
+                let hash = storage.hashes.get(&number)?;
+                let block = storage.blocks.get(hash)?;
+                Some(Self {
+                    hash: *hash,
+                    header: block.header.clone().into(),
+                    transactions: block
+                        .transactions
+                        .iter()
+                        .map(|tx| ArchivedTransaction::from(tx.as_ref()))
+                        .collect(),
+                    state_update: storage.state_update.get(hash).cloned(),
+                })
+            })
+            .collect()
+    }
+
+    /// Applies the classes declared in the block and its state update to the state.
+    pub fn apply_to(&self, state: &mut dyn Database) -> Result<()> {
+        for tx in &self.transactions {
+            if let ArchivedTransactionKind::Declare { inner, compiled_class, sierra_class } =
+                &tx.transaction
+            {
+                state
+                    .set_contract_class(&inner.class_hash(), compiled_class.clone().try_into()?)?;
+                if let Some(sierra_class) = sierra_class.clone() {
+                    state.set_sierra_class(inner.class_hash(), sierra_class)?;
+                }
+            }
+        }
+
+        let Some(StateUpdate { state_diff, .. }) = &self.state_update else {
+            return Ok(());
+        };
+
+        for item in &state_diff.storage_diffs {
+            let address = ContractAddress(patricia_key!(item.address));
+            for entry in &item.storage_entries {
+                state.set_storage_at(
+                    address,
+                    StorageKey(patricia_key!(entry.key)),
+                    entry.value.into(),
+                );
+            }
+        }
+
+        for item in &state_diff.declared_classes {
+            state.set_compiled_class_hash(
+                ClassHash(item.class_hash.into()),
+                CompiledClassHash(item.compiled_class_hash.into()),
+            )?;
+        }
+
+        for item in &state_diff.deployed_contracts {
+            state.set_class_hash_at(
+                ContractAddress(patricia_key!(item.address)),
+                ClassHash(item.class_hash.into()),
+            )?;
+        }
+
+        for item in &state_diff.replaced_classes {
+            state.set_class_hash_at(
+                ContractAddress(patricia_key!(item.contract_address)),
+                ClassHash(item.class_hash.into()),
+            )?;
+        }
+
+        for item in &state_diff.nonces {
+            state.set_nonce(
+                ContractAddress(patricia_key!(item.contract_address)),
+                Nonce(item.nonce.into()),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Header> for ArchivedHeader {
+    fn from(value: Header) -> Self {
+        Self {
+            number: value.number,
+            gas_price: value.gas_price,
+            timestamp: value.timestamp,
+            state_root: value.state_root,
+            parent_hash: value.parent_hash,
+            sequencer_address: value.sequencer_address,
+        }
+    }
+}
+
+impl From<ArchivedHeader> for Header {
+    fn from(value: ArchivedHeader) -> Self {
+        Self {
+            number: value.number,
+            gas_price: value.gas_price,
+            timestamp: value.timestamp,
+            state_root: value.state_root,
+            parent_hash: value.parent_hash,
+            sequencer_address: value.sequencer_address,
+        }
+    }
+}
+
+impl From<&ExecutedTransaction> for ArchivedTransaction {
+    fn from(value: &ExecutedTransaction) -> Self {
+        let transaction = match value.inner.clone() {
+            Transaction::Invoke(tx) => ArchivedTransactionKind::Invoke(tx.0),
+            Transaction::Declare(tx) => ArchivedTransactionKind::Declare {
+                inner: tx.inner,
+                compiled_class: tx.compiled_class.into(),
+                sierra_class: tx.sierra_class,
+            },
+            Transaction::DeployAccount(tx) => ArchivedTransactionKind::DeployAccount {
+                inner: tx.inner,
+                contract_address: tx.contract_address,
+            },
+            Transaction::L1Handler(tx) => {
+                ArchivedTransactionKind::L1Handler { inner: tx.inner, paid_l1_fee: tx.paid_l1_fee }
+            }
+        };
+
+        Self {
+            transaction,
+            actual_fee: value.output.actual_fee,
+            gas: value.output.gas.clone(),
+            revert_error: value.execution_info.revert_error.clone(),
+            events: value.output.events.clone(),
+            messages_sent: value.output.messages_sent.clone(),
+        }
+    }
+}
+
+impl TryFrom<ArchivedTransaction> for ExecutedTransaction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ArchivedTransaction) -> Result<Self, Self::Error> {
+        let inner = match value.transaction {
+            ArchivedTransactionKind::Invoke(tx) => Transaction::Invoke(InvokeTransaction(tx)),
+            ArchivedTransactionKind::Declare { inner, compiled_class, sierra_class } => {
+                Transaction::Declare(DeclareTransaction {
+                    inner,
+                    sierra_class,
+                    compiled_class: compiled_class.try_into()?,
+                })
+            }
+            ArchivedTransactionKind::DeployAccount { inner, contract_address } => {
+                Transaction::DeployAccount(DeployAccountTransaction { inner, contract_address })
+            }
+            ArchivedTransactionKind::L1Handler { inner, paid_l1_fee } => {
+                Transaction::L1Handler(L1HandlerTransaction { inner, paid_l1_fee })
+            }
+        };
+
+        // Only the parts of the execution info that are exposed through the receipts are
+        // archived.
+        let execution_info = TransactionExecutionInfo {
+            actual_fee: Fee(value.actual_fee),
+            revert_error: value.revert_error,
+            ..Default::default()
+        };
+
+        Ok(Self {
+            inner,
+            execution_info,
+            output: TransactionOutput {
+                actual_fee: value.actual_fee,
+                events: value.events,
+                messages_sent: value.messages_sent,
+                gas: value.gas,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blockifier::state::cached_state::CommitmentStateDiff;
+    use blockifier::state::state_api::StateReader;
+    use starknet_api::hash::StarkFelt;
+
+    use super::*;
+    use crate::backend::in_memory_db::MemDb;
+    use crate::backend::storage::block::PartialHeader;
+    use crate::env::Env;
+    use crate::utils::convert_state_diff_to_rpc_state_diff;
+
+    fn genesis() -> ArchivedGenesis {
+        ArchivedGenesis {
+            chain_id: "KATANA".into(),
+            seed: [0; 32],
+            total_accounts: 10,
+            state: None,
+        }
+    }
+
+    fn empty_diff() -> CommitmentStateDiff {
+        CommitmentStateDiff {
+            address_to_class_hash: Default::default(),
+            address_to_nonce: Default::default(),
+            storage_updates: Default::default(),
+            class_hash_to_compiled_class_hash: Default::default(),
+        }
+    }
+
+    /// Appends a block with the given state diff to the storage and returns its hash.
+    fn append_block(storage: &mut Storage, state_diff: CommitmentStateDiff) -> FieldElement {
+        let header = PartialHeader {
+            parent_hash: storage.latest_hash,
+            number: storage.latest_number + 1,
+            gas_price: 1,
+            timestamp: 1337,
+            sequencer_address: FieldElement::ONE,
+        };
+        let block = Block::new(header, vec![], vec![]);
+        let hash = block.header.hash();
+
+        storage.state_update.insert(
+            hash,
+            StateUpdate {
+                block_hash: hash,
+                new_root: block.header.state_root,
+                old_root: FieldElement::ZERO,
+                state_diff: convert_state_diff_to_rpc_state_diff(state_diff),
+            },
+        );
+        storage.hashes.insert(block.header.number, hash);
+        storage.latest_number = block.header.number;
+        storage.latest_hash = hash;
+        storage.blocks.insert(hash, block);
+
+        hash
+    }
+
+    #[test]
+    fn archive_roundtrip_preserves_blocks() {
+        let mut storage = Storage::new(&Env::default().block);
+        let hash = append_block(&mut storage, empty_diff());
+
+        let archive = ChainArchive::new(genesis(), ArchivedBlock::range(&storage, 0, 10));
+        let json = serde_json::to_vec(&archive).unwrap();
+        let archive: ChainArchive = serde_json::from_slice(&json).unwrap();
+
+        let imported = archive.to_storage().unwrap();
+
+        assert_eq!(imported.latest_number, 1);
+        assert_eq!(imported.latest_hash, hash);
+        assert_eq!(imported.hashes, storage.hashes);
+        assert_eq!(imported.block_by_number(1).unwrap().header.timestamp, 1337);
+    }
+
+    #[test]
+    fn archive_blocks_by_range() {
+        let mut storage = Storage::new(&Env::default().block);
+        append_block(&mut storage, empty_diff());
+        let hash = append_block(&mut storage, empty_diff());
+
+        assert_eq!(ArchivedBlock::range(&storage, 0, 2).len(), 2);
+        assert_eq!(ArchivedBlock::range(&storage, 2, 2)[0].hash, hash);
+        assert!(ArchivedBlock::range(&storage, 3, 2).is_empty());
+    }
+
+    #[test]
+    fn replay_rebuilds_the_state_of_every_block() {
+        let address = ContractAddress(patricia_key!("0x1"));
+        let key = StorageKey(patricia_key!("0x77"));
+        let state_diff = |value: u64| CommitmentStateDiff {
+            address_to_class_hash: Default::default(),
+            address_to_nonce: [(address, Nonce(value.into()))].into(),
+            storage_updates: [(address, [(key, StarkFelt::from(value))].into())].into(),
+            class_hash_to_compiled_class_hash: Default::default(),
+        };
+
+        let mut storage = Storage::new(&Env::default().block);
+        let genesis_hash = storage.latest_hash;
+        let first = append_block(&mut storage, state_diff(1));
+        let second = append_block(&mut storage, state_diff(2));
+
+        let archive = ChainArchive::new(genesis(), ArchivedBlock::range(&storage, 0, 10));
+        let mut state = MemDb::default();
+        let mut states = InMemoryBlockStates::default();
+        archive.replay(&mut state, &mut states).unwrap();
+
+        let value_at = |hash: &FieldElement| {
+            states.get(hash).cloned().unwrap().get_storage_at(address, key).unwrap()
+        };
+
+        assert_eq!(value_at(&genesis_hash), StarkFelt::default());
+        assert_eq!(value_at(&first), StarkFelt::from(1u64));
+        assert_eq!(value_at(&second), StarkFelt::from(2u64));
+        assert_eq!(state.get_storage_at(address, key).unwrap(), StarkFelt::from(2u64));
+        assert_eq!(state.get_nonce_at(address).unwrap(), Nonce(StarkFelt::from(2u64)));
+    }
+
+    #[test]
+    fn reject_tampered_block() {
+        let storage = Storage::new(&Env::default().block);
+        let mut archive = ChainArchive::new(genesis(), ArchivedBlock::range(&storage, 0, 1));
+        archive.blocks[0].header.timestamp += 1;
+        assert!(archive.to_storage().is_err());
+        assert!(ImportedChain::new(archive).is_err());
+    }
+
+    #[test]
+    fn import_chain_with_its_predeployed_accounts() {
+        let mut storage = Storage::new(&Env::default().block);
+        append_block(&mut storage, empty_diff());
+        let archive = ChainArchive::new(genesis(), ArchivedBlock::range(&storage, 0, 10));
+
+        let chain = ImportedChain::new(archive).unwrap();
+        assert_eq!(chain.latest_header().number, 1);
+
+        let account = &chain.genesis.accounts()[0];
+        let address = ContractAddress(patricia_key!(account.address));
+        let class_hash = chain.state.get_class_hash_at(address).unwrap();
+        assert_eq!(class_hash, ClassHash(account.class_hash.into()));
+    }
+}
@@ -10,6 +10,7 @@ use self::transaction::KnownTransaction;
 use crate::backend::storage::block::PartialHeader;
 use crate::db::StateRefDb;
 
+pub mod archive;
 pub mod block;
 pub mod transaction;
 
@@ -27,6 +27,7 @@ use starknet_api::transaction::{
 };
 
 use crate::execution::ExecutedTransaction;
+use crate::fee::TransactionGas;
 use crate::utils::transaction::api_to_rpc_transaction;
 
 /// Represents all transactions that are known to the sequencer.
@@ -77,6 +78,8 @@ pub struct TransactionOutput {
     pub actual_fee: u128,
     pub events: Vec<Event>,
     pub messages_sent: Vec<MsgToL1>,
+    /// The gas the transaction was charged for.
+    pub gas: TransactionGas,
 }
 
 #[derive(Debug, Clone)]
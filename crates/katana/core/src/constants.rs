@@ -7,6 +7,7 @@ use starknet_api::state::StorageKey;
 use crate::utils::contract::get_contract_class;
 
 pub const DEFAULT_GAS_PRICE: u128 = 100 * u128::pow(10, 9); // Given in units of wei.
+pub const DEFAULT_DATA_GAS_PRICE: u128 = 0; // Given in units of wei per byte.
 
 pub const DEFAULT_INVOKE_MAX_STEPS: u32 = 1_000_000;
 pub const DEFAULT_VALIDATE_MAX_STEPS: u32 = 1_000_000;
@@ -5,6 +5,7 @@ use blockifier::execution::contract_class::ContractClass;
 use blockifier::state::cached_state::{CachedState, CommitmentStateDiff};
 use blockifier::state::errors::StateError;
 use blockifier::state::state_api::{State, StateReader, StateResult};
+use parking_lot::{Mutex, MutexGuard};
 use starknet::core::types::FlattenedSierraClass;
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::hash::StarkFelt;
@@ -13,6 +14,7 @@ use tokio::sync::RwLock as AsyncRwLock;
 use tracing::trace;
 
 use super::{AsStateRefDb, StateExt, StateExtRef, StateRefDb};
+use crate::fee::StateDiffTracker;
 
 #[derive(Clone, Debug, Default)]
 pub struct StorageRecord {
@@ -288,6 +290,9 @@ pub trait MaybeAsCachedDb {
 pub struct CachedStateWrapper<Db: StateReader> {
     inner: Arc<AsyncRwLock<CachedState<Db>>>,
     sierra_class: Arc<AsyncRwLock<HashMap<ClassHash, FlattenedSierraClass>>>,
+    /// The entries of the state diff, so that the data gas of a transaction doesn't require going
+    /// over the whole diff.
+    state_diff: Arc<Mutex<StateDiffTracker>>,
 }
 
 impl<Db> CachedStateWrapper<Db>
@@ -297,10 +302,15 @@ where
     pub fn new(db: Db) -> Self {
         Self {
             sierra_class: Default::default(),
+            state_diff: Default::default(),
             inner: Arc::new(AsyncRwLock::new(CachedState::new(db))),
         }
     }
 
+    pub fn state_diff_tracker(&self) -> MutexGuard<'_, StateDiffTracker> {
+        self.state_diff.lock()
+    }
+
     pub fn inner_mut(&self) -> tokio::sync::RwLockWriteGuard<'_, CachedState<Db>> {
         tokio::task::block_in_place(|| self.inner.blocking_write())
     }
@@ -11,7 +11,9 @@ use starknet_api::core::{ChainId, ContractAddress, PatriciaKey};
 use starknet_api::hash::StarkHash;
 use starknet_api::patricia_key;
 
-use crate::constants::{DEFAULT_GAS_PRICE, FEE_TOKEN_ADDRESS, SEQUENCER_ADDRESS};
+use crate::constants::{
+    DEFAULT_DATA_GAS_PRICE, DEFAULT_GAS_PRICE, FEE_TOKEN_ADDRESS, SEQUENCER_ADDRESS,
+};
 
 /// Represents the chain environment.
 #[derive(Debug, Clone)]
@@ -19,6 +21,8 @@ pub struct Env {
     /// The block environment of the current block. This is the context that
     /// the transactions will be executed on.
     pub block: BlockContext,
+    /// The price of a single byte of state diff published to L1, in wei.
+    pub data_gas_price: u128,
 }
 
 #[derive(Debug, Default)]
@@ -42,6 +46,7 @@ impl Default for Env {
                 validate_max_n_steps: 1_000_000,
                 max_recursion_depth: 100,
             },
+            data_gas_price: DEFAULT_DATA_GAS_PRICE,
         }
     }
 }
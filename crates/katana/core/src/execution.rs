@@ -4,7 +4,7 @@ use std::sync::Arc;
 use blockifier::block_context::BlockContext;
 use blockifier::execution::contract_class::ContractClass;
 use blockifier::execution::entry_point::CallInfo;
-use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::state::cached_state::{CachedState, CommitmentStateDiff};
 use blockifier::state::state_api::{State, StateReader};
 use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::{ResourcesMapping, TransactionExecutionInfo};
@@ -13,14 +13,20 @@ use blockifier::transaction::transactions::ExecutableTransaction;
 use convert_case::{Case, Casing};
 use parking_lot::RwLock;
 use starknet::core::types::{Event, ExecutionResult, FieldElement, FlattenedSierraClass, MsgToL1};
-use starknet_api::core::ClassHash;
+use starknet_api::core::{ClassHash, ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkHash;
+use starknet_api::patricia_key;
+use starknet_api::transaction::{
+    DeclareTransaction as ApiDeclareTransaction, Fee, InvokeTransaction as ApiInvokeTransaction,
+};
 use tracing::{trace, warn};
 
 use crate::backend::storage::transaction::{
-    DeclareTransaction, RejectedTransaction, Transaction, TransactionOutput,
+    DeclareTransaction, InvokeTransaction, RejectedTransaction, Transaction, TransactionOutput,
 };
 use crate::db::cached::CachedStateWrapper;
 use crate::db::{Database, StateExt, StateRefDb};
+use crate::fee::{charge_data_fee, TransactionGas, DATA_GAS_USAGE};
 use crate::utils::transaction::warn_message_transaction_error_exec_error;
 
 /// The outcome that after executing a list of transactions.
@@ -112,6 +118,8 @@ pub struct TransactionExecutor<'a> {
     transactions: std::vec::IntoIter<Transaction>,
     /// The state the transactions will be executed on.
     state: &'a mut CachedStateWrapper<StateRefDb>,
+    /// The price of a single byte of state diff, charged on top of the execution fee.
+    data_gas_price: u128,
 
     // logs flags
     error_log: bool,
@@ -130,6 +138,7 @@ impl<'a> TransactionExecutor<'a> {
             state,
             charge_fee,
             block_context,
+            data_gas_price: 0,
             error_log: false,
             events_log: false,
             resources_log: false,
@@ -137,6 +146,10 @@ impl<'a> TransactionExecutor<'a> {
         }
     }
 
+    pub fn with_data_gas_price(self, data_gas_price: u128) -> Self {
+        Self { data_gas_price, ..self }
+    }
+
     pub fn with_events_log(self) -> Self {
         Self { events_log: true, ..self }
     }
@@ -170,14 +183,59 @@ impl<'a> Iterator for TransactionExecutor<'a> {
                 None
             };
 
+            let fee_payer = fee_payer(&tx);
+
+            // The transaction is executed on top of a transactional state so that its changes can
+            // be discarded if the data fee can't be charged.
+            let mut state = self.state.inner_mut();
+            let mut tx_state = CachedState::create_transactional(&mut *state);
+
             let res = match tx.into() {
                 ExecutionTransaction::AccountTransaction(tx) => {
-                    tx.execute(&mut self.state.inner_mut(), self.block_context, self.charge_fee)
+                    tx.execute(&mut tx_state, self.block_context, self.charge_fee)
                 }
                 ExecutionTransaction::L1HandlerTransaction(tx) => {
-                    tx.execute(&mut self.state.inner_mut(), self.block_context, self.charge_fee)
+                    tx.execute(&mut tx_state, self.block_context, self.charge_fee)
+                }
+            }
+            .and_then(|mut exec_info| {
+                let state_diff = tx_state.to_state_diff();
+                let mut state_diff_tracker = self.state.state_diff_tracker();
+
+                // The data gas is the number of bytes the transaction adds to the state diff.
+                let data_gas = state_diff_tracker.added_size(&state_diff);
+                exec_info.actual_resources.0.insert(DATA_GAS_USAGE.to_string(), data_gas);
+
+                let data_fee = data_gas as u128 * self.data_gas_price;
+                if let (true, Some((payer, max_fee))) = (self.charge_fee && data_fee > 0, fee_payer)
+                {
+                    let call_info = charge_data_fee(
+                        &mut tx_state,
+                        self.block_context,
+                        payer,
+                        max_fee,
+                        exec_info.actual_fee,
+                        data_fee,
+                    )?;
+
+                    exec_info.actual_fee.0 += data_fee;
+                    // Nested in the fee transfer call so that its `Transfer` event is part of the
+                    // transaction receipt.
+                    match exec_info.fee_transfer_call_info.as_mut() {
+                        Some(fee_transfer) => fee_transfer.inner_calls.push(call_info),
+                        None => exec_info.fee_transfer_call_info = Some(call_info),
+                    }
                 }
-            };
+
+                state_diff_tracker.record(&state_diff);
+                Ok(exec_info)
+            });
+
+            match &res {
+                Ok(_) => tx_state.commit(),
+                Err(_) => tx_state.abort(),
+            }
+            drop(state);
 
             match res {
                 Ok(exec_info) => {
@@ -242,15 +300,22 @@ pub struct ExecutedTransaction {
 }
 
 impl ExecutedTransaction {
-    pub fn new(transaction: Transaction, execution_info: TransactionExecutionInfo) -> Self {
+    pub fn new(
+        transaction: Transaction,
+        execution_info: TransactionExecutionInfo,
+        block_context: &BlockContext,
+        data_gas_price: u128,
+    ) -> Self {
         let actual_fee = execution_info.actual_fee.0;
         let events = events_from_exec_info(&execution_info);
         let messages_sent = l2_to_l1_messages_from_exec_info(&execution_info);
+        let gas =
+            TransactionGas::new(block_context, data_gas_price, &execution_info.actual_resources);
 
         Self {
             execution_info,
             inner: transaction,
-            output: TransactionOutput { actual_fee, events, messages_sent },
+            output: TransactionOutput { actual_fee, events, messages_sent, gas },
         }
     }
 
@@ -338,6 +403,7 @@ fn pretty_print_resources(resources: &ResourcesMapping) -> String {
         .iter()
         .filter_map(|(k, v)| match k.as_str() {
             "l1_gas_usage" => Some(format!("L1 Gas: {}", v)),
+            DATA_GAS_USAGE => Some(format!("L1 Data Gas: {}", v)),
             "range_check_builtin" => Some(format!("Range Checks: {}", v)),
             "ecdsa_builtin" => Some(format!("ECDSA: {}", v)),
             "n_steps" => None,
@@ -359,6 +425,29 @@ fn pretty_print_resources(resources: &ResourcesMapping) -> String {
     mapped_strings.join(" | ")
 }
 
+/// Returns the account paying the fee of the transaction and the maximum fee it pays. L1 handler
+/// transactions are paid on L1.
+fn fee_payer(transaction: &Transaction) -> Option<(ContractAddress, Fee)> {
+    match transaction {
+        Transaction::Invoke(InvokeTransaction(ApiInvokeTransaction::V0(tx))) => {
+            Some((tx.contract_address, tx.max_fee))
+        }
+        Transaction::Invoke(InvokeTransaction(ApiInvokeTransaction::V1(tx))) => {
+            Some((tx.sender_address, tx.max_fee))
+        }
+        Transaction::Declare(tx) => match &tx.inner {
+            ApiDeclareTransaction::V0(tx) | ApiDeclareTransaction::V1(tx) => {
+                Some((tx.sender_address, tx.max_fee))
+            }
+            ApiDeclareTransaction::V2(tx) => Some((tx.sender_address, tx.max_fee)),
+        },
+        Transaction::DeployAccount(tx) => {
+            Some((ContractAddress(patricia_key!(tx.contract_address)), tx.inner.max_fee))
+        }
+        Transaction::L1Handler(_) => None,
+    }
+}
+
 fn trace_events(events: &[Event]) {
     for e in events {
         let formatted_keys =
@@ -371,12 +460,14 @@ fn trace_events(events: &[Event]) {
 pub fn create_execution_outcome(
     state: &mut CachedStateWrapper<StateRefDb>,
     transactions: Vec<(Transaction, Result<TransactionExecutionInfo, TransactionExecutionError>)>,
+    block_context: &BlockContext,
+    data_gas_price: u128,
 ) -> ExecutionOutcome {
     let transactions = transactions
         .into_iter()
         .map(|(tx, res)| match res {
             Ok(exec_info) => MaybeInvalidExecutedTransaction::Valid(Arc::new(
-                ExecutedTransaction::new(tx, exec_info),
+                ExecutedTransaction::new(tx, exec_info, block_context, data_gas_price),
             )),
 
             Err(err) => MaybeInvalidExecutedTransaction::Invalid(Arc::new(RejectedTransaction {
@@ -0,0 +1,368 @@
+//! Data availability (DA) fee accounting.
+//!
+//! Every transaction contributes to the state diff of its block, which has to be published to L1.
+//! The number of bytes a transaction adds to the state diff is its *data gas*, which is charged at
+//! the configured data gas price on top of the fee computed from its execution resources. The data
+//! gas price is zero unless set, in which case nothing is charged.
+//!
+//! The L1 gas and data gas a transaction was charged for are reported in its receipt, see
+//! [`TransactionReceiptWithGas`].
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use blockifier::abi::abi_utils::selector_from_name;
+use blockifier::abi::constants::INITIAL_GAS_COST;
+use blockifier::block_context::BlockContext;
+use blockifier::execution::entry_point::{
+    CallEntryPoint, CallInfo, CallType, EntryPointExecutionContext, ExecutionResources,
+};
+use blockifier::fee::fee_utils::{calculate_l1_gas_by_vm_usage, extract_l1_gas_and_vm_usage};
+use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::state::state_api::State;
+use blockifier::transaction::errors::TransactionExecutionError;
+use blockifier::transaction::objects::{AccountTransactionContext, ResourcesMapping};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::{FeeEstimate, FieldElement, MaybePendingTransactionReceipt};
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use starknet_api::hash::StarkFelt;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{Calldata, Fee};
+
+/// The key under which the data gas of a transaction is recorded in its resources mapping.
+pub const DATA_GAS_USAGE: &str = "l1_data_gas_usage";
+
+/// The size of a single felt in the state diff published to L1.
+const FELT_SIZE_IN_BYTES: usize = 32;
+
+/// A fee estimate along with the data gas it accounts for.
+///
+/// The data fee is included in the `gas_consumed` of the estimate as the L1 gas it's worth at the
+/// gas price, rounded up, so that its `overall_fee` is still `gas_consumed * gas_price`. The
+/// estimate is saturated at `u64::MAX` if it doesn't fit in the fields of the spec.
+///
+/// The extra fields are ignored by the clients which only know the fee estimates of the spec.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeEstimateWithDataGas {
+    #[serde(flatten)]
+    pub estimate: FeeEstimate,
+    /// The number of bytes the transaction adds to the state diff.
+    #[serde_as(as = "UfeHex")]
+    pub data_gas_consumed: FieldElement,
+    /// The price of a single byte of the state diff, in wei.
+    #[serde_as(as = "UfeHex")]
+    pub data_gas_price: FieldElement,
+}
+
+impl FeeEstimateWithDataGas {
+    /// Estimates the fee of a transaction from the resources it used.
+    pub fn new(
+        block_context: &BlockContext,
+        data_gas_price: u128,
+        resources: &ResourcesMapping,
+    ) -> Result<Self, TransactionExecutionError> {
+        let l1_gas = l1_gas_usage(block_context, resources)?;
+        let data_gas = data_gas_usage(resources) as u128;
+        let gas_price = block_context.gas_price;
+
+        let data_fee = data_gas.saturating_mul(data_gas_price);
+        let data_fee_in_l1_gas = if gas_price == 0 {
+            0
+        } else {
+            data_fee / gas_price + u128::from(data_fee % gas_price != 0)
+        };
+        let gas_consumed = l1_gas.saturating_add(data_fee_in_l1_gas);
+        let overall_fee = gas_consumed.saturating_mul(gas_price);
+
+        Ok(Self {
+            estimate: FeeEstimate {
+                gas_consumed: saturating_u64(gas_consumed),
+                gas_price: saturating_u64(gas_price),
+                overall_fee: saturating_u64(overall_fee),
+            },
+            data_gas_consumed: data_gas.into(),
+            data_gas_price: data_gas_price.into(),
+        })
+    }
+}
+
+/// The gas an executed transaction was charged for, and the prices it was charged at.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionGas {
+    /// The L1 gas of the execution resources of the transaction.
+    #[serde_as(as = "UfeHex")]
+    pub l1_gas_consumed: FieldElement,
+    /// The price of a single unit of L1 gas when the transaction was executed, in wei.
+    #[serde_as(as = "UfeHex")]
+    pub l1_gas_price: FieldElement,
+    /// The number of bytes the transaction added to the state diff.
+    #[serde_as(as = "UfeHex")]
+    pub data_gas_consumed: FieldElement,
+    /// The price of a single byte of the state diff when the transaction was executed, in wei.
+    #[serde_as(as = "UfeHex")]
+    pub data_gas_price: FieldElement,
+}
+
+impl TransactionGas {
+    /// Computes the gas of a transaction from the resources it used.
+    pub fn new(
+        block_context: &BlockContext,
+        data_gas_price: u128,
+        resources: &ResourcesMapping,
+    ) -> Self {
+        Self {
+            // The resources of an executed transaction have already been priced once.
+            l1_gas_consumed: l1_gas_usage(block_context, resources).unwrap_or_default().into(),
+            l1_gas_price: block_context.gas_price.into(),
+            data_gas_consumed: (data_gas_usage(resources) as u128).into(),
+            data_gas_price: data_gas_price.into(),
+        }
+    }
+}
+
+/// A transaction receipt, along with the gas the transaction was charged for.
+///
+/// The extra fields are ignored by the clients which only know the receipts of the spec.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionReceiptWithGas {
+    #[serde(flatten)]
+    pub receipt: MaybePendingTransactionReceipt,
+    #[serde(flatten)]
+    pub gas: TransactionGas,
+}
+
+/// The entries of the state diff of a block, as it's built one transaction after the other.
+///
+/// Every updated contract is encoded as its address and a header word (nonce and number of
+/// updates), followed by the new class hash if it was (re)deployed and a key-value pair for every
+/// storage update. Every declared class is encoded as its class hash and compiled class hash.
+#[derive(Debug, Default)]
+pub struct StateDiffTracker {
+    contracts: HashSet<ContractAddress>,
+    deployed_contracts: HashSet<ContractAddress>,
+    storage: HashSet<(ContractAddress, StorageKey)>,
+    declared_classes: HashSet<ClassHash>,
+}
+
+impl StateDiffTracker {
+    /// Returns the number of bytes the state diff of a transaction adds to the state diff of the
+    /// block, without recording it.
+    pub fn added_size(&self, state_diff: &CommitmentStateDiff) -> usize {
+        let contracts = state_diff
+            .storage_updates
+            .keys()
+            .chain(state_diff.address_to_nonce.keys())
+            .chain(state_diff.address_to_class_hash.keys())
+            .filter(|address| !self.contracts.contains(*address))
+            .collect::<HashSet<_>>();
+
+        let deployed = state_diff
+            .address_to_class_hash
+            .keys()
+            .filter(|address| !self.deployed_contracts.contains(*address))
+            .count();
+
+        let storage_updates = state_diff
+            .storage_updates
+            .iter()
+            .flat_map(|(address, storage)| storage.keys().map(move |key| (*address, *key)))
+            .filter(|entry| !self.storage.contains(entry))
+            .count();
+
+        let declared = state_diff
+            .class_hash_to_compiled_class_hash
+            .keys()
+            .filter(|class_hash| !self.declared_classes.contains(*class_hash))
+            .count();
+
+        let felts = contracts.len() * 2 + deployed + storage_updates * 2 + declared * 2;
+        felts * FELT_SIZE_IN_BYTES
+    }
+
+    /// Adds the state diff of a transaction to the state diff of the block.
+    pub fn record(&mut self, state_diff: &CommitmentStateDiff) {
+        self.contracts.extend(
+            state_diff
+                .storage_updates
+                .keys()
+                .chain(state_diff.address_to_nonce.keys())
+                .chain(state_diff.address_to_class_hash.keys()),
+        );
+        self.deployed_contracts.extend(state_diff.address_to_class_hash.keys());
+        self.storage.extend(
+            state_diff
+                .storage_updates
+                .iter()
+                .flat_map(|(address, storage)| storage.keys().map(move |key| (*address, *key))),
+        );
+        self.declared_classes.extend(state_diff.class_hash_to_compiled_class_hash.keys());
+    }
+}
+
+fn saturating_u64(value: u128) -> u64 {
+    u64::try_from(value).unwrap_or(u64::MAX)
+}
+
+/// Returns the data gas recorded in the resources of a transaction.
+pub fn data_gas_usage(resources: &ResourcesMapping) -> usize {
+    resources.0.get(DATA_GAS_USAGE).copied().unwrap_or_default()
+}
+
+/// Returns the L1 gas of the execution resources of a transaction, leaving out its data gas.
+pub fn l1_gas_usage(
+    block_context: &BlockContext,
+    resources: &ResourcesMapping,
+) -> Result<u128, TransactionExecutionError> {
+    // The data gas is not a VM resource, so it must be removed before computing the gas used by
+    // the VM.
+    let mut resources = resources.clone();
+    resources.0.remove(DATA_GAS_USAGE);
+
+    let (l1_gas_usage, vm_resources) = extract_l1_gas_and_vm_usage(&resources);
+    let l1_gas_by_vm_usage = calculate_l1_gas_by_vm_usage(block_context, &vm_resources)?;
+    Ok((l1_gas_usage as f64 + l1_gas_by_vm_usage).ceil() as u128)
+}
+
+/// Transfers the data fee from the `payer` to the sequencer through the fee token, the same way
+/// the execution fee is transferred.
+///
+/// Fails if the execution and data fees together exceed the `max_fee` of the transaction, or if
+/// the payer can't afford them.
+pub fn charge_data_fee(
+    state: &mut dyn State,
+    block_context: &BlockContext,
+    payer: ContractAddress,
+    max_fee: Fee,
+    actual_fee: Fee,
+    data_fee: u128,
+) -> Result<CallInfo, TransactionExecutionError> {
+    let total_fee = Fee(actual_fee.0 + data_fee);
+    if total_fee.0 > max_fee.0 {
+        return Err(TransactionExecutionError::FeeTransferError { max_fee, actual_fee: total_fee });
+    }
+
+    let transfer = CallEntryPoint {
+        class_hash: None,
+        code_address: None,
+        entry_point_type: EntryPointType::External,
+        entry_point_selector: selector_from_name("transfer"),
+        calldata: Calldata(Arc::new(vec![
+            *block_context.sequencer_address.0.key(),
+            StarkFelt::from(data_fee),
+            StarkFelt::from(0u8),
+        ])),
+        storage_address: block_context.fee_token_address,
+        caller_address: payer,
+        call_type: CallType::Call,
+        initial_gas: INITIAL_GAS_COST,
+    };
+
+    let mut context = EntryPointExecutionContext::new(
+        block_context.clone(),
+        AccountTransactionContext { sender_address: payer, max_fee, ..Default::default() },
+        block_context.invoke_tx_max_n_steps as _,
+    );
+
+    Ok(transfer.execute(state, &mut ExecutionResources::default(), &mut context)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use starknet_api::core::{CompiledClassHash, Nonce, PatriciaKey};
+    use starknet_api::hash::StarkHash;
+    use starknet_api::{patricia_key, stark_felt};
+
+    use super::*;
+    use crate::env::Env;
+
+    #[test]
+    fn fee_estimate_includes_data_fee_in_gas_consumed() {
+        let block_context = Env::default().block;
+        let resources = ResourcesMapping(HashMap::from([
+            ("l1_gas_usage".to_string(), 1000),
+            ("n_steps".to_string(), 500),
+            (DATA_GAS_USAGE.to_string(), 64),
+        ]));
+
+        let FeeEstimateWithDataGas { estimate, data_gas_consumed, data_gas_price } =
+            FeeEstimateWithDataGas::new(&block_context, 3, &resources).unwrap();
+
+        assert_eq!(data_gas_consumed, FieldElement::from(64u64));
+        assert_eq!(data_gas_price, FieldElement::from(3u64));
+
+        // The data fee of 192 wei is worth a single unit of L1 gas once rounded up.
+        assert_eq!(estimate.gas_consumed, 1000 + 500 + 1);
+        assert_eq!(estimate.gas_price, block_context.gas_price as u64);
+        assert_eq!(estimate.overall_fee, estimate.gas_consumed * estimate.gas_price);
+
+        let gas = TransactionGas::new(&block_context, 3, &resources);
+        assert_eq!(gas.l1_gas_consumed, FieldElement::from(1500u64));
+        assert_eq!(gas.data_gas_consumed, FieldElement::from(64u64));
+    }
+
+    #[test]
+    fn fee_estimate_saturates_instead_of_truncating() {
+        let block_context = Env::default().block;
+        let resources = ResourcesMapping(HashMap::from([
+            ("l1_gas_usage".to_string(), 0),
+            (DATA_GAS_USAGE.to_string(), 64),
+        ]));
+
+        let FeeEstimateWithDataGas { estimate, data_gas_price, .. } =
+            FeeEstimateWithDataGas::new(&block_context, u128::MAX, &resources).unwrap();
+
+        assert_eq!(data_gas_price, FieldElement::from(u128::MAX));
+        assert_eq!(estimate.gas_consumed, u64::MAX);
+        assert_eq!(estimate.overall_fee, u64::MAX);
+    }
+
+    #[test]
+    fn size_of_state_diff() {
+        let address = ContractAddress(patricia_key!("0x1"));
+        let deployed = ContractAddress(patricia_key!("0x2"));
+
+        let storage = [
+            (StorageKey(patricia_key!("0x1")), stark_felt!("0x1")),
+            (StorageKey(patricia_key!("0x2")), stark_felt!("0x2")),
+        ];
+
+        let state_diff = CommitmentStateDiff {
+            storage_updates: [(address, storage.into_iter().collect())].into_iter().collect(),
+            address_to_nonce: [(address, Nonce(stark_felt!("0x1")))].into_iter().collect(),
+            address_to_class_hash: [(deployed, ClassHash(stark_felt!("0x3")))]
+                .into_iter()
+                .collect(),
+            class_hash_to_compiled_class_hash: [(
+                ClassHash(stark_felt!("0x4")),
+                CompiledClassHash(stark_felt!("0x5")),
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        let mut tracker = StateDiffTracker::default();
+
+        // 2 contracts * 2 + 1 class hash + 2 storage updates * 2 + 1 declared class * 2
+        assert_eq!(tracker.added_size(&state_diff), 11 * FELT_SIZE_IN_BYTES);
+
+        // Only the entries which aren't in the block's state diff yet are counted.
+        tracker.record(&state_diff);
+        assert_eq!(tracker.added_size(&state_diff), 0);
+
+        let new_storage = [(StorageKey(patricia_key!("0x3")), stark_felt!("0x3"))];
+        let state_diff = CommitmentStateDiff {
+            storage_updates: [(address, new_storage.into_iter().collect())].into_iter().collect(),
+            address_to_nonce: [(address, Nonce(stark_felt!("0x2")))].into_iter().collect(),
+            address_to_class_hash: Default::default(),
+            class_hash_to_compiled_class_hash: Default::default(),
+        };
+        assert_eq!(tracker.added_size(&state_diff), 2 * FELT_SIZE_IN_BYTES);
+    }
+}
@@ -4,7 +4,10 @@ pub mod constants;
 pub mod db;
 pub mod env;
 pub mod execution;
+pub mod fee;
 pub mod fork;
+#[cfg(feature = "native")]
+pub mod native;
 pub mod pool;
 pub mod sequencer;
 pub mod service;
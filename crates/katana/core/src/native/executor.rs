@@ -0,0 +1,669 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use blockifier::abi::constants::{
+    CALL_CONTRACT_GAS_COST, EMIT_EVENT_GAS_COST, GET_EXECUTION_INFO_GAS_COST,
+    LIBRARY_CALL_GAS_COST, STORAGE_READ_GAS_COST, STORAGE_WRITE_GAS_COST, SYSCALL_BASE_GAS_COST,
+};
+use blockifier::block_context::BlockContext;
+use blockifier::execution::entry_point::{
+    CallEntryPoint, CallExecution, CallInfo, CallType, EntryPointExecutionContext,
+    ExecutionResources, OrderedEvent, Retdata,
+};
+use blockifier::execution::errors::EntryPointExecutionError;
+use blockifier::execution::execution_utils::{felt_to_stark_felt, stark_felt_to_felt};
+use blockifier::state::cached_state::{CachedState, CommitmentStateDiff, MutRefState};
+use blockifier::state::errors::StateError;
+use blockifier::state::state_api::State;
+use blockifier::transaction::objects::AccountTransactionContext;
+use cairo_native::starknet::{
+    BlockInfo, ExecutionInfo, Secp256k1Point, Secp256r1Point, StarkNetSyscallHandler,
+    SyscallResult, TxInfo, U256,
+};
+use cairo_vm::felt::Felt252;
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, PatriciaKey};
+use starknet_api::hash::{StarkFelt, StarkHash};
+use starknet_api::patricia_key;
+use starknet_api::state::StorageKey;
+use starknet_api::transaction::{Calldata, EventContent, EventData, EventKey};
+
+use super::NativeClassCache;
+use crate::db::StateRefDb;
+
+/// Executes the given call using the native engine.
+///
+/// Returns `Ok(None)` if the called entry point can't be executed natively, in which case the call
+/// should be executed by the Cairo VM instead.
+///
+/// The returned call info has no VM resources, as the call didn't run on the Cairo VM. Only the
+/// inner calls that fell back to the VM report theirs.
+pub fn execute_call(
+    cache: &NativeClassCache,
+    state: &mut dyn State,
+    classes: &StateRefDb,
+    block_context: &BlockContext,
+    tx_context: &AccountTransactionContext,
+    call: CallEntryPoint,
+) -> Result<Option<CallInfo>, EntryPointExecutionError> {
+    let class_hash = match call.class_hash {
+        Some(class_hash) => class_hash,
+        None => state.get_class_hash_at(call.storage_address)?,
+    };
+
+    let Some(class) = cache.get_or_compile(class_hash, &mut classes.clone()) else {
+        return Ok(None);
+    };
+
+    let Some(function_id) = class.entry_point(call.entry_point_type, call.entry_point_selector)
+    else {
+        return Ok(None);
+    };
+
+    let calldata = call.calldata.0.iter().map(stark_felt_to_felt).collect::<Vec<_>>();
+
+    let mut handler = SyscallHandler {
+        cache,
+        state,
+        classes,
+        block_context,
+        tx_context,
+        resources: ExecutionResources::default(),
+        call: &call,
+        events: Vec::new(),
+        inner_calls: Vec::new(),
+        storage_read_values: Vec::new(),
+        accessed_storage_keys: HashSet::new(),
+    };
+
+    let result = class
+        .executor
+        .invoke_contract_dynamic(
+            function_id,
+            &calldata,
+            Some(call.initial_gas as u128),
+            &mut handler,
+        )
+        .map_err(|e| EntryPointExecutionError::InternalError(e.to_string()))?;
+
+    let retdata = result.return_values.iter().map(felt_to_stark_felt).collect::<Vec<_>>();
+
+    if result.failure_flag {
+        return Err(EntryPointExecutionError::ExecutionFailed { error_data: retdata });
+    }
+
+    let gas_consumed = call.initial_gas.saturating_sub(result.remaining_gas as u64);
+
+    let SyscallHandler { events, inner_calls, storage_read_values, accessed_storage_keys, .. } =
+        handler;
+
+    Ok(Some(CallInfo {
+        execution: CallExecution {
+            retdata: Retdata(retdata),
+            events,
+            l2_to_l1_messages: Vec::new(),
+            failed: false,
+            gas_consumed,
+        },
+        vm_resources: Default::default(),
+        call,
+        inner_calls,
+        storage_read_values,
+        accessed_storage_keys,
+    }))
+}
+
+/// Executes the given call using the native engine, or using the Cairo VM if its entry point can't
+/// be executed natively.
+fn execute_call_or_fallback(
+    cache: &NativeClassCache,
+    state: &mut dyn State,
+    classes: &StateRefDb,
+    block_context: &BlockContext,
+    tx_context: &AccountTransactionContext,
+    resources: &mut ExecutionResources,
+    call: CallEntryPoint,
+) -> Result<CallInfo, EntryPointExecutionError> {
+    match execute_call(cache, state, classes, block_context, tx_context, call.clone())? {
+        Some(info) => Ok(info),
+        None => call.execute(
+            state,
+            resources,
+            &mut EntryPointExecutionContext::new(
+                block_context.clone(),
+                tx_context.clone(),
+                block_context.invoke_tx_max_n_steps as usize,
+            ),
+        ),
+    }
+}
+
+/// Handles the syscalls made by a natively executed contract.
+struct SyscallHandler<'a> {
+    cache: &'a NativeClassCache,
+    state: &'a mut dyn State,
+    classes: &'a StateRefDb,
+    block_context: &'a BlockContext,
+    tx_context: &'a AccountTransactionContext,
+    /// The resources of the inner calls executed by the Cairo VM.
+    resources: ExecutionResources,
+    call: &'a CallEntryPoint,
+
+    events: Vec<OrderedEvent>,
+    inner_calls: Vec<CallInfo>,
+    storage_read_values: Vec<StarkFelt>,
+    accessed_storage_keys: HashSet<StorageKey>,
+}
+
+impl SyscallHandler<'_> {
+    /// Executes an inner call on top of its own state, so that its changes are only applied to the
+    /// state of the caller if it succeeds.
+    fn execute_inner_call(
+        &mut self,
+        call: CallEntryPoint,
+        remaining_gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt252>> {
+        let (result, state_diff) = {
+            let mut inner_state = CachedState::new(MutRefState::new(&mut *self.state));
+            let result = execute_call_or_fallback(
+                self.cache,
+                &mut inner_state,
+                self.classes,
+                self.block_context,
+                self.tx_context,
+                &mut self.resources,
+                call,
+            );
+            (result, inner_state.to_state_diff())
+        };
+
+        match result {
+            Ok(info) if info.execution.failed => {
+                Err(info.execution.retdata.0.iter().map(stark_felt_to_felt).collect())
+            }
+            Ok(info) => {
+                apply_state_diff(self.state, state_diff)
+                    .map_err(|e| vec![error_felt(&e.to_string())])?;
+
+                *remaining_gas = remaining_gas.saturating_sub(info.execution.gas_consumed as u128);
+                let retdata = info.execution.retdata.0.iter().map(stark_felt_to_felt).collect();
+                self.inner_calls.push(info);
+                Ok(retdata)
+            }
+            Err(EntryPointExecutionError::ExecutionFailed { error_data }) => {
+                Err(error_data.iter().map(stark_felt_to_felt).collect())
+            }
+            Err(err) => Err(vec![error_felt(&err.to_string())]),
+        }
+    }
+
+    /// Charges the gas of a syscall from the remaining gas, as the Cairo VM does. Its base cost is
+    /// already charged by the libfunc making the syscall.
+    fn charge_gas(gas_cost: u64, remaining_gas: &mut u128) -> SyscallResult<()> {
+        let required_gas = u128::from(gas_cost - SYSCALL_BASE_GAS_COST);
+        if *remaining_gas < required_gas {
+            return Err(vec![error_felt("Out of gas")]);
+        }
+
+        *remaining_gas -= required_gas;
+        Ok(())
+    }
+
+    fn storage_key(address: &Felt252) -> SyscallResult<StorageKey> {
+        StorageKey::try_from(felt_to_stark_felt(address))
+            .map_err(|_| vec![error_felt("invalid storage key")])
+    }
+}
+
+impl StarkNetSyscallHandler for SyscallHandler<'_> {
+    fn get_execution_info(&mut self, remaining_gas: &mut u128) -> SyscallResult<ExecutionInfo> {
+        Self::charge_gas(GET_EXECUTION_INFO_GAS_COST, remaining_gas)?;
+
+        let block = self.block_context;
+        let tx = self.tx_context;
+
+        Ok(ExecutionInfo {
+            block_info: BlockInfo {
+                block_number: block.block_number.0,
+                block_timestamp: block.block_timestamp.0,
+                sequencer_address: stark_felt_to_felt(block.sequencer_address.0.key()),
+            },
+            tx_info: TxInfo {
+                version: stark_felt_to_felt(&tx.version.0),
+                account_contract_address: stark_felt_to_felt(tx.sender_address.0.key()),
+                max_fee: tx.max_fee.0,
+                signature: tx.signature.0.iter().map(stark_felt_to_felt).collect(),
+                transaction_hash: stark_felt_to_felt(&tx.transaction_hash.0),
+                chain_id: Felt252::from_bytes_be(block.chain_id.0.as_bytes()),
+                nonce: stark_felt_to_felt(&tx.nonce.0),
+            },
+            caller_address: stark_felt_to_felt(self.call.caller_address.0.key()),
+            contract_address: stark_felt_to_felt(self.call.storage_address.0.key()),
+            entry_point_selector: stark_felt_to_felt(&self.call.entry_point_selector.0),
+        })
+    }
+
+    fn library_call(
+        &mut self,
+        class_hash: Felt252,
+        function_selector: Felt252,
+        calldata: &[Felt252],
+        remaining_gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt252>> {
+        Self::charge_gas(LIBRARY_CALL_GAS_COST, remaining_gas)?;
+
+        let call = CallEntryPoint {
+            class_hash: Some(ClassHash(felt_to_stark_felt(&class_hash))),
+            entry_point_selector: EntryPointSelector(felt_to_stark_felt(&function_selector)),
+            calldata: to_calldata(calldata),
+            storage_address: self.call.storage_address,
+            caller_address: self.call.caller_address,
+            call_type: CallType::Delegate,
+            initial_gas: *remaining_gas as u64,
+            ..Default::default()
+        };
+
+        self.execute_inner_call(call, remaining_gas)
+    }
+
+    fn call_contract(
+        &mut self,
+        address: Felt252,
+        entry_point_selector: Felt252,
+        calldata: &[Felt252],
+        remaining_gas: &mut u128,
+    ) -> SyscallResult<Vec<Felt252>> {
+        Self::charge_gas(CALL_CONTRACT_GAS_COST, remaining_gas)?;
+
+        let call = CallEntryPoint {
+            entry_point_selector: EntryPointSelector(felt_to_stark_felt(&entry_point_selector)),
+            calldata: to_calldata(calldata),
+            storage_address: ContractAddress(patricia_key!(felt_to_stark_felt(&address))),
+            caller_address: self.call.storage_address,
+            call_type: CallType::Call,
+            initial_gas: *remaining_gas as u64,
+            ..Default::default()
+        };
+
+        self.execute_inner_call(call, remaining_gas)
+    }
+
+    fn storage_read(
+        &mut self,
+        _: u32,
+        address: Felt252,
+        remaining_gas: &mut u128,
+    ) -> SyscallResult<Felt252> {
+        Self::charge_gas(STORAGE_READ_GAS_COST, remaining_gas)?;
+
+        let key = Self::storage_key(&address)?;
+        let value = self
+            .state
+            .get_storage_at(self.call.storage_address, key)
+            .map_err(|e| vec![error_felt(&e.to_string())])?;
+
+        self.accessed_storage_keys.insert(key);
+        self.storage_read_values.push(value);
+
+        Ok(stark_felt_to_felt(&value))
+    }
+
+    fn storage_write(
+        &mut self,
+        _: u32,
+        address: Felt252,
+        value: Felt252,
+        remaining_gas: &mut u128,
+    ) -> SyscallResult<()> {
+        Self::charge_gas(STORAGE_WRITE_GAS_COST, remaining_gas)?;
+
+        let key = Self::storage_key(&address)?;
+        self.accessed_storage_keys.insert(key);
+        self.state.set_storage_at(self.call.storage_address, key, felt_to_stark_felt(&value));
+        Ok(())
+    }
+
+    fn emit_event(
+        &mut self,
+        keys: &[Felt252],
+        data: &[Felt252],
+        remaining_gas: &mut u128,
+    ) -> SyscallResult<()> {
+        Self::charge_gas(EMIT_EVENT_GAS_COST, remaining_gas)?;
+
+        let order = self.events.len();
+        self.events.push(OrderedEvent {
+            order,
+            event: EventContent {
+                keys: keys.iter().map(|k| EventKey(felt_to_stark_felt(k))).collect(),
+                data: EventData(data.iter().map(felt_to_stark_felt).collect()),
+            },
+        });
+        Ok(())
+    }
+
+    // Classes using the following syscalls are never compiled natively (see
+    // `UNSUPPORTED_LIBFUNCS`), so these are unreachable in practice.
+
+    fn get_block_hash(&mut self, _: u64, _: &mut u128) -> SyscallResult<Felt252> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn deploy(
+        &mut self,
+        _: Felt252,
+        _: Felt252,
+        _: &[Felt252],
+        _: bool,
+        _: &mut u128,
+    ) -> SyscallResult<(Felt252, Vec<Felt252>)> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn replace_class(&mut self, _: Felt252, _: &mut u128) -> SyscallResult<()> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn send_message_to_l1(&mut self, _: Felt252, _: &[Felt252], _: &mut u128) -> SyscallResult<()> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn keccak(&mut self, _: &[u64], _: &mut u128) -> SyscallResult<U256> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256k1_add(
+        &mut self,
+        _: Secp256k1Point,
+        _: Secp256k1Point,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256k1_get_point_from_x(
+        &mut self,
+        _: U256,
+        _: bool,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256k1_get_xy(&mut self, _: Secp256k1Point, _: &mut u128) -> SyscallResult<(U256, U256)> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256k1_mul(
+        &mut self,
+        _: Secp256k1Point,
+        _: U256,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256k1_new(
+        &mut self,
+        _: U256,
+        _: U256,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256k1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256r1_add(
+        &mut self,
+        _: Secp256r1Point,
+        _: Secp256r1Point,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256r1_get_point_from_x(
+        &mut self,
+        _: U256,
+        _: bool,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256r1_get_xy(&mut self, _: Secp256r1Point, _: &mut u128) -> SyscallResult<(U256, U256)> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256r1_mul(
+        &mut self,
+        _: Secp256r1Point,
+        _: U256,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+
+    fn secp256r1_new(
+        &mut self,
+        _: U256,
+        _: U256,
+        _: &mut u128,
+    ) -> SyscallResult<Option<Secp256r1Point>> {
+        Err(vec![error_felt("unsupported syscall")])
+    }
+}
+
+/// Applies the changes made by an inner call to the state of its caller. Calls don't declare
+/// classes nor increment nonces, so only the storage updates and deployed contracts are applied.
+fn apply_state_diff(
+    state: &mut dyn State,
+    state_diff: CommitmentStateDiff,
+) -> Result<(), StateError> {
+    for (address, storage) in state_diff.storage_updates {
+        for (key, value) in storage {
+            state.set_storage_at(address, key, value);
+        }
+    }
+
+    for (address, class_hash) in state_diff.address_to_class_hash {
+        state.set_class_hash_at(address, class_hash)?;
+    }
+
+    Ok(())
+}
+
+fn to_calldata(calldata: &[Felt252]) -> Calldata {
+    Calldata(Arc::new(calldata.iter().map(felt_to_stark_felt).collect()))
+}
+
+/// Encodes an error message as a Cairo short string, truncated to fit in a single felt. Short
+/// strings are ASCII only, so the other characters are dropped.
+fn error_felt(message: &str) -> Felt252 {
+    let message = message.chars().filter(char::is_ascii).take(31).collect::<String>();
+    let felt = cairo_short_string_to_felt(&message).unwrap_or_default();
+    Felt252::from_bytes_be(&felt.to_bytes_be())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use blockifier::abi::abi_utils::selector_from_name;
+    use starknet::core::types::contract::SierraClass;
+    use starknet_api::stark_felt;
+
+    use super::*;
+    use crate::backend::config::StarknetConfig;
+    use crate::backend::in_memory_db::MemDb;
+    use crate::db::{AsStateRefDb, StateExt};
+    use crate::utils::contract::rpc_to_inner_class;
+
+    /// Returns a state with the test contract deployed, along with its address and class hash.
+    fn setup() -> (StateRefDb, ContractAddress, ClassHash) {
+        let class = serde_json::from_str::<SierraClass>(include_str!(
+            "../../contracts/compiled/cairo1_contract.json"
+        ))
+        .unwrap()
+        .flatten()
+        .unwrap();
+
+        let (class_hash, contract_class) = rpc_to_inner_class(&class).unwrap();
+        let class_hash = ClassHash(class_hash.into());
+        let address = ContractAddress(patricia_key!("0x100"));
+
+        let mut db = MemDb::new();
+        db.set_contract_class(&class_hash, contract_class).unwrap();
+        db.set_sierra_class(class_hash, class).unwrap();
+        db.set_class_hash_at(address, class_hash).unwrap();
+
+        (db.as_ref_db(), address, class_hash)
+    }
+
+    /// Executes the call with both the native engine and the Cairo VM, each on its own state.
+    fn execute(
+        cache: &NativeClassCache,
+        classes: &StateRefDb,
+        call: CallEntryPoint,
+    ) -> (Option<CallInfo>, CallInfo) {
+        let block_context = StarknetConfig::default().block_context();
+
+        let native = execute_call(
+            cache,
+            &mut CachedState::new(classes.clone()),
+            classes,
+            &block_context,
+            &AccountTransactionContext::default(),
+            call.clone(),
+        )
+        .unwrap();
+
+        let vm = call
+            .execute(
+                &mut CachedState::new(classes.clone()),
+                &mut ExecutionResources::default(),
+                &mut EntryPointExecutionContext::new(
+                    block_context.clone(),
+                    AccountTransactionContext::default(),
+                    block_context.invoke_tx_max_n_steps as usize,
+                ),
+            )
+            .unwrap();
+
+        (native, vm)
+    }
+
+    /// Waits for the class to be compiled in the background.
+    fn compile(cache: &NativeClassCache, classes: &StateRefDb, class_hash: ClassHash) {
+        for _ in 0..600 {
+            if cache.get_or_compile(class_hash, &mut classes.clone()).is_some() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("class {class_hash} wasn't compiled in time");
+    }
+
+    fn call(
+        address: ContractAddress,
+        entry_point: &str,
+        calldata: Vec<StarkFelt>,
+    ) -> CallEntryPoint {
+        CallEntryPoint {
+            entry_point_selector: selector_from_name(entry_point),
+            calldata: Calldata(Arc::new(calldata)),
+            storage_address: address,
+            initial_gas: 1_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn storage_read_write_matches_vm() {
+        let (classes, address, class_hash) = setup();
+        let cache = NativeClassCache::new();
+        compile(&cache, &classes, class_hash);
+
+        let call = call(
+            address,
+            "test_storage_read_write",
+            vec![stark_felt!("0x10"), stark_felt!("0x2a")],
+        );
+        let (native, vm) = execute(&cache, &classes, call);
+        let native = native.expect("should be executed natively");
+
+        assert_eq!(native.execution.retdata, vm.execution.retdata);
+        assert_eq!(native.execution.retdata, Retdata(vec![stark_felt!("0x2a")]));
+        assert_eq!(native.accessed_storage_keys, vm.accessed_storage_keys);
+        assert_eq!(native.execution.gas_consumed, vm.execution.gas_consumed);
+    }
+
+    #[test]
+    fn emitted_events_match_vm() {
+        let (classes, address, class_hash) = setup();
+        let cache = NativeClassCache::new();
+        compile(&cache, &classes, class_hash);
+
+        let calldata = vec![
+            stark_felt!("0x1"),
+            stark_felt!("0x7"),
+            stark_felt!("0x2"),
+            stark_felt!("0x8"),
+            stark_felt!("0x9"),
+        ];
+        let (native, vm) = execute(&cache, &classes, call(address, "test_emit_event", calldata));
+        let native = native.expect("should be executed natively");
+
+        assert_eq!(native.execution.events, vm.execution.events);
+        assert_eq!(native.execution.events.len(), 1);
+        assert_eq!(native.execution.gas_consumed, vm.execution.gas_consumed);
+    }
+
+    #[test]
+    fn library_call_matches_vm() {
+        let (classes, address, class_hash) = setup();
+        let cache = NativeClassCache::new();
+        compile(&cache, &classes, class_hash);
+
+        let calldata = vec![
+            class_hash.0,
+            selector_from_name("test_storage_read_write").0,
+            stark_felt!("0x2"),
+            stark_felt!("0x10"),
+            stark_felt!("0x2a"),
+        ];
+        let (native, vm) = execute(&cache, &classes, call(address, "test_library_call", calldata));
+        let native = native.expect("should be executed natively");
+
+        assert_eq!(native.execution.retdata, vm.execution.retdata);
+        assert_eq!(native.inner_calls.len(), vm.inner_calls.len());
+        assert_eq!(native.inner_calls[0].execution.retdata, vm.inner_calls[0].execution.retdata);
+        assert_eq!(native.execution.gas_consumed, vm.execution.gas_consumed);
+    }
+
+    #[test]
+    fn unsupported_entry_point_falls_back_to_vm() {
+        let (classes, address, class_hash) = setup();
+        let cache = NativeClassCache::new();
+        compile(&cache, &classes, class_hash);
+
+        let native = execute_call(
+            &cache,
+            &mut CachedState::new(classes.clone()),
+            &classes,
+            &StarknetConfig::default().block_context(),
+            &AccountTransactionContext::default(),
+            call(address, "test_get_block_hash", vec![stark_felt!("0x0")]),
+        )
+        .unwrap();
+
+        assert!(native.is_none());
+    }
+
+    #[test]
+    fn truncate_error_message_to_short_string() {
+        let message = "é".repeat(20) + "execution failed because of an unexpected error";
+        let expected = cairo_short_string_to_felt("execution failed because of an ").unwrap();
+        assert_eq!(error_felt(&message), Felt252::from_bytes_be(&expected.to_bytes_be()));
+    }
+}
@@ -0,0 +1,251 @@
+//! Native execution engine.
+//!
+//! Sierra classes are compiled ahead-of-time into native code using `cairo-native`. The compiled
+//! artifacts are cached by class hash so that every class is only compiled once throughout the
+//! lifetime of the node. Classes that can't be compiled or that rely on features the native
+//! engine doesn't support are remembered as such, and are always executed by the Cairo VM.
+//!
+//! Classes are compiled on a dedicated thread so that block production never waits on the
+//! compiler. Until its compilation is done, a class is executed by the Cairo VM.
+//!
+//! Only calls are executed natively. A native execution doesn't run on the Cairo VM, so it has no
+//! VM resources to report. Transactions, whose fee is computed from those resources, are always
+//! executed by blockifier so that their validation, fee charging and resources stay the same as
+//! with the Cairo VM.
+
+mod executor;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+
+use anyhow::{anyhow, Result};
+use blockifier::execution::execution_utils::felt_to_stark_felt;
+use cairo_lang_sierra::ids::FunctionId;
+use cairo_lang_sierra::program::{BranchTarget, GenericArg, Program, Statement};
+use cairo_native::context::NativeContext;
+use cairo_native::executor::AotNativeExecutor;
+use cairo_native::OptLevel;
+use cairo_vm::felt::Felt252;
+use parking_lot::{Mutex, RwLock};
+use starknet::core::types::FlattenedSierraClass;
+use starknet_api::core::{ClassHash, EntryPointSelector};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use tracing::{trace, warn};
+
+pub use self::executor::execute_call;
+use crate::db::StateExtRef;
+use crate::utils::contract::rpc_to_cairo_contract_class;
+
+/// Libfuncs whose syscalls are not handled by the native engine. Entry points using any of them
+/// are executed using the Cairo VM instead.
+const UNSUPPORTED_LIBFUNCS: [&str; 7] = [
+    "deploy_syscall",
+    "replace_class_syscall",
+    "get_block_hash_syscall",
+    "send_message_to_l1_syscall",
+    "keccak_syscall",
+    "secp256k1",
+    "secp256r1",
+];
+
+/// A Sierra class compiled to native code.
+pub struct NativeClass {
+    executor: AotNativeExecutor,
+    /// The entry points that can be executed natively.
+    entry_points: HashMap<(EntryPointType, EntryPointSelector), FunctionId>,
+}
+
+impl NativeClass {
+    /// Returns the id of the Sierra function for the given entry point, if it can be executed
+    /// natively.
+    pub fn entry_point(
+        &self,
+        entry_point_type: EntryPointType,
+        selector: EntryPointSelector,
+    ) -> Option<&FunctionId> {
+        self.entry_points.get(&(entry_point_type, selector))
+    }
+}
+
+/// The compilation status of a class.
+#[derive(Clone)]
+enum CachedClass {
+    Native(Arc<NativeClass>),
+    /// The class is being compiled, and is executed by the Cairo VM in the meantime.
+    Compiling,
+    /// The class can't be executed natively and must be executed by the Cairo VM.
+    Unsupported,
+}
+
+/// A cache of natively compiled classes, keyed by class hash.
+pub struct NativeClassCache {
+    classes: Arc<RwLock<HashMap<ClassHash, CachedClass>>>,
+    /// Sends the classes to compile to the compiler thread.
+    compiler: Mutex<Sender<(ClassHash, FlattenedSierraClass)>>,
+}
+
+impl NativeClassCache {
+    pub fn new() -> Self {
+        let classes = Arc::new(RwLock::new(HashMap::new()));
+        let (compiler, requests) = mpsc::channel::<(ClassHash, FlattenedSierraClass)>();
+
+        let compiled = Arc::clone(&classes);
+        thread::Builder::new()
+            .name("native-compiler".into())
+            .spawn(move || {
+                let context = NativeContext::new();
+
+                // the thread stops once the cache is dropped
+                for (class_hash, class) in requests {
+                    let entry = match compile(&context, &class) {
+                        Ok(class) => {
+                            trace!(target: "native", "compiled class {class_hash} to native code");
+                            CachedClass::Native(Arc::new(class))
+                        }
+                        Err(err) => {
+                            warn!(
+                                target: "native",
+                                "Falling back to VM for class {class_hash}: {err}"
+                            );
+                            CachedClass::Unsupported
+                        }
+                    };
+
+                    compiled.write().insert(class_hash, entry);
+                }
+            })
+            .expect("failed to spawn native compiler thread");
+
+        Self { classes, compiler: Mutex::new(compiler) }
+    }
+
+    /// Returns the natively compiled class for the given class hash. Returns `None` if the class
+    /// must be executed by the Cairo VM, either because it can't be executed natively or because
+    /// it hasn't been compiled yet, in which case its compilation is started in the background.
+    pub fn get_or_compile<S>(
+        &self,
+        class_hash: ClassHash,
+        state: &mut S,
+    ) -> Option<Arc<NativeClass>>
+    where
+        S: StateExtRef + ?Sized,
+    {
+        if let Some(class) = self.classes.read().get(&class_hash) {
+            return match class {
+                CachedClass::Native(class) => Some(class.clone()),
+                CachedClass::Compiling | CachedClass::Unsupported => None,
+            };
+        }
+
+        // Legacy classes don't have a Sierra representation.
+        let entry = match state.get_sierra_class(&class_hash) {
+            Ok(sierra) => match self.compiler.lock().send((class_hash, sierra)) {
+                Ok(()) => CachedClass::Compiling,
+                Err(_) => CachedClass::Unsupported,
+            },
+            Err(_) => CachedClass::Unsupported,
+        };
+
+        // Another caller may have requested the compilation of the class in the meantime, in
+        // which case the class is compiled twice, with the same result.
+        self.classes.write().entry(class_hash).or_insert(entry);
+
+        None
+    }
+}
+
+impl Default for NativeClassCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn compile(context: &NativeContext, class: &FlattenedSierraClass) -> Result<NativeClass> {
+    let contract_class = rpc_to_cairo_contract_class(class)?;
+    let program = contract_class.extract_sierra_program()?;
+
+    let mut entry_points = HashMap::new();
+    let entry_points_by_type = contract_class.entry_points_by_type;
+
+    for (ty, list) in [
+        (EntryPointType::External, entry_points_by_type.external),
+        (EntryPointType::L1Handler, entry_points_by_type.l1_handler),
+        (EntryPointType::Constructor, entry_points_by_type.constructor),
+    ] {
+        for entry_point in list {
+            let felt = Felt252::from(entry_point.selector);
+            let function = program
+                .funcs
+                .get(entry_point.function_idx)
+                .ok_or(anyhow!("missing function for entry point {}", felt))?;
+
+            if let Some(libfunc) = unsupported_libfunc(&program, &function.id) {
+                trace!(target: "native", "entry point {felt} uses unsupported libfunc `{libfunc}`");
+                continue;
+            }
+
+            let selector = EntryPointSelector(felt_to_stark_felt(&felt));
+            entry_points.insert((ty, selector), function.id.clone());
+        }
+    }
+
+    if entry_points.is_empty() {
+        return Err(anyhow!("no entry point can be executed natively"));
+    }
+
+    // The executor owns the shared library the module is compiled to, so it doesn't outlive the
+    // context.
+    let module = context.compile(&program).map_err(|e| anyhow!(e.to_string()))?;
+    let executor = AotNativeExecutor::from_native_module(module, OptLevel::Default);
+
+    Ok(NativeClass { executor, entry_points })
+}
+
+/// Returns the first unsupported libfunc the given function may invoke, either directly or through
+/// the functions it calls.
+fn unsupported_libfunc<'a>(program: &'a Program, function: &FunctionId) -> Option<&'a str> {
+    let libfuncs = program
+        .libfunc_declarations
+        .iter()
+        .map(|libfunc| (&libfunc.id, &libfunc.long_id))
+        .collect::<HashMap<_, _>>();
+    let functions =
+        program.funcs.iter().map(|func| (&func.id, func.entry_point.0)).collect::<HashMap<_, _>>();
+
+    let mut visited = HashSet::new();
+    let mut statements = vec![*functions.get(function)?];
+
+    while let Some(idx) = statements.pop() {
+        if !visited.insert(idx) {
+            continue;
+        }
+
+        let Some(Statement::Invocation(invocation)) = program.statements.get(idx) else {
+            continue;
+        };
+
+        let long_id = libfuncs.get(&invocation.libfunc_id)?;
+        let name = long_id.generic_id.0.as_str();
+        if UNSUPPORTED_LIBFUNCS.iter().any(|unsupported| name.starts_with(unsupported)) {
+            return Some(name);
+        }
+
+        // the body of the called functions
+        for arg in &long_id.generic_args {
+            if let GenericArg::UserFunc(callee) = arg {
+                statements.extend(functions.get(callee));
+            }
+        }
+
+        for branch in &invocation.branches {
+            statements.push(match branch.target {
+                BranchTarget::Fallthrough => idx + 1,
+                BranchTarget::Statement(target) => target.0,
+            });
+        }
+    }
+
+    None
+}
@@ -7,7 +7,7 @@ use anyhow::Result;
 use blockifier::execution::contract_class::ContractClass;
 use blockifier::state::state_api::{State, StateReader};
 use starknet::core::types::{
-    BlockId, BlockTag, EmittedEvent, Event, EventsPage, FeeEstimate, FieldElement,
+    BlockId, BlockTag, EmittedEvent, Event, EventsPage, FieldElement,
     MaybePendingTransactionReceipt, StateUpdate,
 };
 use starknet_api::core::{ChainId, ClassHash, ContractAddress, Nonce};
@@ -24,9 +24,12 @@ use crate::backend::storage::transaction::{
 use crate::backend::{Backend, ExternalFunctionCall};
 use crate::db::{AsStateRefDb, StateExtRef, StateRefDb};
 use crate::execution::{MaybeInvalidExecutedTransaction, PendingState};
+use crate::fee::{FeeEstimateWithDataGas, TransactionReceiptWithGas};
 use crate::pool::TransactionPool;
 use crate::sequencer_error::SequencerError;
-use crate::service::block_producer::{BlockProducer, BlockProducerMode};
+use crate::service::block_producer::{
+    BlockProducer, BlockProducerMode, InstantBlockProducer, IntervalBlockProducer,
+};
 #[cfg(feature = "messaging")]
 use crate::service::messaging::MessagingConfig;
 #[cfg(feature = "messaging")]
@@ -40,6 +43,11 @@ type SequencerResult<T> = Result<T, SequencerError>;
 pub struct SequencerConfig {
     pub block_time: Option<u64>,
     pub no_mining: bool,
+    /// Only mine blocks that contain transactions when mining on interval.
+    pub skip_empty_blocks: bool,
+    /// The maximum time without a new block, in milliseconds, before an empty block is mined
+    /// when empty blocks are skipped.
+    pub max_idle_time: Option<u64>,
     #[cfg(feature = "messaging")]
     pub messaging: Option<MessagingConfig>,
 }
@@ -59,11 +67,20 @@ impl KatanaSequencer {
         let miner = TransactionMiner::new(pool.add_listener());
 
         let block_producer = if let Some(block_time) = config.block_time {
-            BlockProducer::interval(
-                Arc::clone(&backend),
-                backend.state.read().await.as_ref_db(),
-                block_time,
-            )
+            if config.skip_empty_blocks {
+                BlockProducer::hybrid(
+                    Arc::clone(&backend),
+                    backend.state.read().await.as_ref_db(),
+                    block_time,
+                    config.max_idle_time,
+                )
+            } else {
+                BlockProducer::interval(
+                    Arc::clone(&backend),
+                    backend.state.read().await.as_ref_db(),
+                    block_time,
+                )
+            }
         } else if config.no_mining {
             BlockProducer::on_demand(Arc::clone(&backend), backend.state.read().await.as_ref_db())
         } else {
@@ -168,7 +185,7 @@ impl KatanaSequencer {
         &self,
         transactions: Vec<Transaction>,
         block_id: BlockId,
-    ) -> SequencerResult<Vec<FeeEstimate>> {
+    ) -> SequencerResult<Vec<FeeEstimateWithDataGas>> {
         let state = self.state(&block_id).await?;
         self.backend.estimate_fee(transactions, state).map_err(SequencerError::TransactionExecution)
     }
@@ -313,18 +330,22 @@ impl KatanaSequencer {
     pub async fn transaction_receipt(
         &self,
         hash: &FieldElement,
-    ) -> Option<MaybePendingTransactionReceipt> {
+    ) -> Option<TransactionReceiptWithGas> {
         let transaction = self.transaction(hash).await?;
 
-        match transaction {
-            KnownTransaction::Rejected(_) => None,
-            KnownTransaction::Pending(tx) => {
-                Some(MaybePendingTransactionReceipt::PendingReceipt(tx.receipt()))
-            }
-            KnownTransaction::Included(tx) => {
-                Some(MaybePendingTransactionReceipt::Receipt(tx.receipt()))
-            }
-        }
+        let (receipt, gas) = match transaction {
+            KnownTransaction::Rejected(_) => return None,
+            KnownTransaction::Pending(tx) => (
+                MaybePendingTransactionReceipt::PendingReceipt(tx.receipt()),
+                tx.0.output.gas.clone(),
+            ),
+            KnownTransaction::Included(tx) => (
+                MaybePendingTransactionReceipt::Receipt(tx.receipt()),
+                tx.transaction.output.gas.clone(),
+            ),
+        };
+
+        Some(TransactionReceiptWithGas { receipt, gas })
     }
 
     pub async fn transaction(&self, hash: &FieldElement) -> Option<KnownTransaction> {
@@ -514,6 +535,34 @@ impl KatanaSequencer {
         Ok(())
     }
 
+    /// Switches the block production mode at runtime. An `interval` of `0` switches to instant
+    /// mining, otherwise blocks are mined every `interval` milliseconds, following the empty block
+    /// configuration of the sequencer.
+    pub async fn set_interval_mining(&self, interval: u64) -> Result<(), SequencerError> {
+        let backend = Arc::clone(&self.backend);
+
+        let mode = if interval == 0 {
+            BlockProducerMode::Instant(InstantBlockProducer::new(backend))
+        } else {
+            let state = backend.state.read().await.as_ref_db();
+            let producer = IntervalBlockProducer::new(backend, state, interval);
+
+            BlockProducerMode::Interval(if self.config.skip_empty_blocks {
+                producer.with_empty_blocks_skipped(self.config.max_idle_time)
+            } else {
+                producer
+            })
+        };
+
+        // The block producer checks that no block is being mined and that the pending block is
+        // empty under the same lock it switches the mode with.
+        if !self.block_producer.switch_mode(mode) {
+            return Err(SequencerError::PendingTransactions);
+        }
+
+        Ok(())
+    }
+
     pub async fn has_pending_transactions(&self) -> bool {
         if let Some(ref pending) = self.pending_state() {
             !pending.executed_transactions.read().is_empty()
@@ -7,8 +7,10 @@ use std::time::Duration;
 
 use blockifier::state::state_api::{State, StateReader};
 use futures::stream::{Stream, StreamExt};
+use futures::task::AtomicWaker;
 use futures::FutureExt;
 use parking_lot::RwLock;
+use starknet_api::block::BlockNumber;
 use tokio::time::{interval_at, Instant, Interval};
 use tracing::trace;
 
@@ -36,38 +38,53 @@ type IntervalBlockMiningFuture = ServiceFuture<MinedBlockOutcome>;
 pub struct BlockProducer {
     /// The inner mode of mining.
     pub inner: Arc<RwLock<BlockProducerMode>>,
+    /// Wakes the task polling the block producer when the mode is switched, so that the new mode
+    /// starts producing blocks right away.
+    waker: Arc<AtomicWaker>,
 }
 
 impl BlockProducer {
     /// Creates a block producer that mines a new block every `interval` milliseconds.
     pub fn interval(backend: Arc<Backend>, initial_state: StateRefDb, interval: u64) -> Self {
-        Self {
-            inner: Arc::new(RwLock::new(BlockProducerMode::Interval(IntervalBlockProducer::new(
-                backend,
-                initial_state,
-                interval,
-            )))),
-        }
+        Self::new(BlockProducerMode::Interval(IntervalBlockProducer::new(
+            backend,
+            initial_state,
+            interval,
+        )))
+    }
+
+    /// Creates a block producer that mines a new block at most every `interval` milliseconds, and
+    /// only if there are transactions to be included in it. If `max_idle` is set, an empty block
+    /// is mined once no block has been mined for `max_idle` milliseconds.
+    pub fn hybrid(
+        backend: Arc<Backend>,
+        initial_state: StateRefDb,
+        interval: u64,
+        max_idle: Option<u64>,
+    ) -> Self {
+        Self::new(BlockProducerMode::Interval(
+            IntervalBlockProducer::new(backend, initial_state, interval)
+                .with_empty_blocks_skipped(max_idle),
+        ))
     }
 
     /// Creates a new block producer that will only be possible to mine by calling the
     /// `katana_generateBlock` RPC method.
     pub fn on_demand(backend: Arc<Backend>, initial_state: StateRefDb) -> Self {
-        Self {
-            inner: Arc::new(RwLock::new(BlockProducerMode::Interval(
-                IntervalBlockProducer::new_no_mining(backend, initial_state),
-            ))),
-        }
+        Self::new(BlockProducerMode::Interval(IntervalBlockProducer::new_no_mining(
+            backend,
+            initial_state,
+        )))
     }
 
     /// Creates a block producer that mines a new block as soon as there are ready transactions in
     /// the transactions pool.
     pub fn instant(backend: Arc<Backend>) -> Self {
-        Self {
-            inner: Arc::new(RwLock::new(BlockProducerMode::Instant(InstantBlockProducer::new(
-                backend,
-            )))),
-        }
+        Self::new(BlockProducerMode::Instant(InstantBlockProducer::new(backend)))
+    }
+
+    fn new(mode: BlockProducerMode) -> Self {
+        Self { inner: Arc::new(RwLock::new(mode)), waker: Arc::new(AtomicWaker::new()) }
     }
 
     pub(super) fn queue(&self, transactions: Vec<Transaction>) {
@@ -78,6 +95,50 @@ impl BlockProducer {
         }
     }
 
+    /// Switches the block producer to a different mining mode. Transactions that have been queued
+    /// but not yet executed are carried over to the new mode. Returns `false`, keeping the current
+    /// mode, if a block is being mined or if transactions were executed in the pending block.
+    pub fn switch_mode(&self, mut new_mode: BlockProducerMode) -> bool {
+        let mut mode = self.inner.write();
+
+        let queued = match &mut *mode {
+            BlockProducerMode::Instant(producer) if producer.block_mining.is_none() => {
+                std::mem::take(&mut producer.queued)
+            }
+            BlockProducerMode::Interval(producer)
+                if producer.block_mining.is_none()
+                    && producer.state.executed_transactions.read().is_empty() =>
+            {
+                std::mem::take(&mut producer.queued)
+            }
+            _ => return false,
+        };
+
+        match &mut new_mode {
+            BlockProducerMode::Instant(producer) => {
+                // In interval mode, the block context is already pointing to the next block to be
+                // opened, whereas the instant block producer updates it before mining a new block.
+                let latest_number = producer.backend.blockchain.storage.read().latest_number;
+                producer.backend.env.write().block.block_number = BlockNumber(latest_number);
+                producer.queued.extend(queued)
+            }
+            BlockProducerMode::Interval(producer) => producer.queued.extend(queued),
+        }
+
+        *mode = new_mode;
+        self.waker.wake();
+
+        true
+    }
+
+    /// Returns `true` if a block is currently being mined.
+    pub fn is_mining(&self) -> bool {
+        match &*self.inner.read() {
+            BlockProducerMode::Instant(producer) => producer.block_mining.is_some(),
+            BlockProducerMode::Interval(producer) => producer.block_mining.is_some(),
+        }
+    }
+
     /// Returns `true` if the block producer is running in _interval_ mode. Otherwise, `fales`.
     pub fn is_interval_mining(&self) -> bool {
         matches!(*self.inner.read(), BlockProducerMode::Interval(_))
@@ -106,6 +167,7 @@ impl BlockProducer {
 impl Stream for BlockProducer {
     type Item = MinedBlockOutcome;
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.waker.register(cx.waker());
         let mut mode = self.inner.write();
         match &mut *mode {
             BlockProducerMode::Instant(producer) => producer.poll_next_unpin(cx),
@@ -127,6 +189,11 @@ impl Stream for BlockProducer {
 /// block producer will execute all the transactions in the mempool and mine a new block with the
 /// resulting state. The block context is only updated every time a new block is mined as opposed to
 /// updating it when the block is opened (in _interval_ mode).
+///
+/// On _interval_ mining, the block producer can be configured to skip empty blocks. In that case,
+/// a block is only mined at the end of an interval if at least one transaction has been executed
+/// within it. An empty block may still be mined if no block has been mined for a maximum idle
+/// time, so that the chain keeps progressing.
 pub enum BlockProducerMode {
     Interval(IntervalBlockProducer),
     Instant(InstantBlockProducer),
@@ -145,6 +212,13 @@ pub struct IntervalBlockProducer {
     /// This is to make sure that the block context is updated
     /// before the first block is opened.
     is_initialized: bool,
+    /// Whether to skip mining blocks that don't contain any transactions.
+    skip_empty_blocks: bool,
+    /// The maximum amount of time without a new block, after which an empty block is mined even
+    /// if empty blocks are skipped.
+    max_idle: Option<Duration>,
+    /// The time at which the last block was mined.
+    last_block_at: Instant,
 }
 
 impl IntervalBlockProducer {
@@ -168,9 +242,19 @@ impl IntervalBlockProducer {
             is_initialized: false,
             interval: Some(interval),
             queued: VecDeque::default(),
+            skip_empty_blocks: false,
+            max_idle: None,
+            last_block_at: Instant::now(),
         }
     }
 
+    /// Only mine a block at the end of an interval if it contains transactions. If `max_idle` is
+    /// set, an empty block is mined at the end of the first interval after `max_idle`
+    /// milliseconds have elapsed without a new block.
+    pub fn with_empty_blocks_skipped(self, max_idle: Option<u64>) -> Self {
+        Self { skip_empty_blocks: true, max_idle: max_idle.map(Duration::from_millis), ..self }
+    }
+
     /// Creates a new [IntervalBlockProducer] with no `interval`. This mode will not produce blocks
     /// for every fixed interval, although it will still execute all queued transactions and
     /// keep hold of the pending state.
@@ -187,6 +271,9 @@ impl IntervalBlockProducer {
             block_mining: None,
             is_initialized: false,
             queued: VecDeque::default(),
+            skip_empty_blocks: false,
+            max_idle: None,
+            last_block_at: Instant::now(),
         }
     }
 
@@ -195,8 +282,9 @@ impl IntervalBlockProducer {
     }
 
     /// Force mine a new block. It will only able to mine if there is no ongoing mining process.
-    pub async fn force_mine(&self) {
+    pub async fn force_mine(&mut self) {
         if self.block_mining.is_none() {
+            self.last_block_at = Instant::now();
             let outcome = self.outcome();
             let _ = Self::do_mine(outcome, self.backend.clone(), self.state.clone()).await;
         } else {
@@ -221,22 +309,39 @@ impl IntervalBlockProducer {
         outcome
     }
 
+    /// Returns `true` if a block should be mined at the end of the current interval.
+    fn should_mine(&self) -> bool {
+        if !self.skip_empty_blocks || !self.state.executed_transactions.read().is_empty() {
+            return true;
+        }
+
+        self.max_idle.is_some_and(|max_idle| self.last_block_at.elapsed() >= max_idle)
+    }
+
     fn execute_transactions(&self, transactions: Vec<Transaction>) {
         let transactions = {
+            let data_gas_price = self.backend.env.read().data_gas_price;
+            let block_context = self.backend.env.read().block.clone();
             let mut state = self.state.state.write();
             TransactionExecutor::new(
                 &mut state,
-                &self.backend.env.read().block,
+                &block_context,
                 !self.backend.config.read().disable_fee,
                 transactions.clone(),
             )
+            .with_data_gas_price(data_gas_price)
             .with_error_log()
             .with_events_log()
             .with_resources_log()
             .zip(transactions)
             .map(|(res, tx)| match res {
                 Ok(execution_info) => {
-                    let executed_tx = ExecutedTransaction::new(tx, execution_info);
+                    let executed_tx = ExecutedTransaction::new(
+                        tx,
+                        execution_info,
+                        &block_context,
+                        data_gas_price,
+                    );
                     MaybeInvalidExecutedTransaction::Valid(Arc::new(executed_tx))
                 }
                 Err(err) => {
@@ -288,8 +393,11 @@ impl Stream for IntervalBlockProducer {
             pin.is_initialized = true;
         }
 
-        if let Some(interval) = &mut pin.interval {
-            if interval.poll_tick(cx).is_ready() && pin.block_mining.is_none() {
+        // The interval is polled until it's pending, even on the ticks that don't mine a block, so
+        // that the task is woken up on the next tick.
+        while pin.interval.as_mut().is_some_and(|interval| interval.poll_tick(cx).is_ready()) {
+            if pin.block_mining.is_none() && pin.should_mine() {
+                pin.last_block_at = Instant::now();
                 pin.block_mining = Some(Box::pin(Self::do_mine(
                     pin.outcome(),
                     pin.backend.clone(),
@@ -300,6 +408,12 @@ impl Stream for IntervalBlockProducer {
 
         // only execute transactions if there is no mining in progress
         if !pin.queued.is_empty() && pin.block_mining.is_none() {
+            // An empty block may stay open for long when empty blocks are skipped, so its
+            // timestamp is the time of its first transaction rather than the time it was opened.
+            if pin.skip_empty_blocks && pin.state.executed_transactions.read().is_empty() {
+                pin.backend.update_block_timestamp();
+            }
+
             let transactions = pin.queued.pop_front().expect("not empty; qed");
             pin.execute_transactions(transactions);
         }
@@ -348,6 +462,7 @@ impl InstantBlockProducer {
 
         let mut state = CachedStateWrapper::new(backend.state.read().await.as_ref_db());
         let block_context = backend.env.read().block.clone();
+        let data_gas_price = backend.env.read().data_gas_price;
 
         let results = TransactionExecutor::new(
             &mut state,
@@ -355,6 +470,7 @@ impl InstantBlockProducer {
             !backend.config.read().disable_fee,
             transactions.clone(),
         )
+        .with_data_gas_price(data_gas_price)
         .with_error_log()
         .with_events_log()
         .with_resources_log()
@@ -364,6 +480,8 @@ impl InstantBlockProducer {
             .do_mine_block(create_execution_outcome(
                 &mut state,
                 transactions.into_iter().zip(results).collect(),
+                &block_context,
+                data_gas_price,
             ))
             .await;
 
@@ -397,3 +515,79 @@ impl Stream for InstantBlockProducer {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures::task::{waker, ArcWake};
+
+    use super::*;
+    use crate::backend::config::StarknetConfig;
+
+    /// Counts the number of times the task polling a block producer is woken up.
+    #[derive(Default)]
+    struct WakeCounter(AtomicUsize);
+
+    impl ArcWake for WakeCounter {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    async fn create_test_backend() -> Arc<Backend> {
+        Arc::new(Backend::new(StarknetConfig { disable_fee: true, ..Default::default() }).await)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wake_up_on_every_tick_when_skipping_empty_blocks() {
+        let backend = create_test_backend().await;
+        let state = backend.state.read().await.as_ref_db();
+        let mut producer =
+            IntervalBlockProducer::new(backend, state, 100).with_empty_blocks_skipped(None);
+
+        let counter = Arc::new(WakeCounter::default());
+        let waker = waker(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        for ticks in 1..=3 {
+            assert!(producer.poll_next_unpin(&mut cx).is_pending(), "no block should be mined");
+            tokio::time::advance(Duration::from_millis(100)).await;
+            assert_eq!(counter.0.load(Ordering::SeqCst), ticks, "should be woken up on tick");
+        }
+
+        assert!(producer.poll_next_unpin(&mut cx).is_pending(), "no block should be mined");
+        assert_eq!(producer.backend.blockchain.storage.read().latest_number, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn force_mined_block_resets_idle_time() {
+        let backend = create_test_backend().await;
+        let state = backend.state.read().await.as_ref_db();
+        let mut producer =
+            IntervalBlockProducer::new(backend, state, 100).with_empty_blocks_skipped(Some(1000));
+
+        tokio::time::advance(Duration::from_millis(1000)).await;
+        assert!(producer.should_mine(), "an empty block should be mined after the idle time");
+
+        producer.force_mine().await;
+        assert!(!producer.should_mine(), "the forced block should reset the idle time");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wake_up_on_mode_switch() {
+        let backend = create_test_backend().await;
+        let mut producer = BlockProducer::instant(backend.clone());
+
+        let counter = Arc::new(WakeCounter::default());
+        let waker = waker(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+        assert!(producer.poll_next_unpin(&mut cx).is_pending());
+
+        let state = backend.state.read().await.as_ref_db();
+        assert!(producer.switch_mode(BlockProducerMode::Interval(IntervalBlockProducer::new(
+            backend, state, 100,
+        ))));
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1, "should be woken up to use the new mode");
+    }
+}
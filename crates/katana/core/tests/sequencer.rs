@@ -27,6 +27,20 @@ fn create_test_sequencer_config() -> (SequencerConfig, StarknetConfig) {
     )
 }
 
+/// Waits until the block `number` has been mined by the node service spawned by the sequencer.
+/// The timeout is only there to fail the test instead of hanging, it's far above the block times
+/// used in the tests.
+async fn wait_for_block(sequencer: &KatanaSequencer, number: u64) {
+    let wait = async {
+        while sequencer.block_number().await < number {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), wait)
+        .await
+        .unwrap_or_else(|_| panic!("block {number} should have been mined"));
+}
+
 async fn create_test_sequencer() -> KatanaSequencer {
     let (sequencer_config, starknet_config) = create_test_sequencer_config();
     KatanaSequencer::new(sequencer_config, starknet_config).await
@@ -219,3 +233,68 @@ async fn dump_and_load_state() {
 
     assert_eq!(old_contract, new_contract);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn skip_empty_blocks_on_interval_mining() {
+    let (_, starknet_config) = create_test_sequencer_config();
+    let sequencer_config =
+        SequencerConfig { block_time: Some(100), skip_empty_blocks: true, ..Default::default() };
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+
+    // Executing transactions blocks in place, which the current-thread runtime of a paused clock
+    // doesn't support, so the intervals are waited for in real time here.
+    sleep(Duration::from_millis(350)).await;
+    assert_eq!(sequencer.block_number().await, 0, "no empty block should have been mined");
+
+    let declare_tx = create_declare_transaction(ContractAddress(patricia_key!(
+        sequencer.backend.accounts[0].address
+    )));
+    let tx_hash = declare_tx.inner.transaction_hash();
+    sequencer.add_declare_transaction(declare_tx);
+
+    wait_for_block(&sequencer, 1).await;
+    let block = sequencer.backend.blockchain.storage.read().block_by_number(1).unwrap();
+    assert_eq!(block.transactions.len(), 1, "the first block should hold the transaction");
+    assert_eq!(block.transactions[0].inner.hash(), FieldElement::from(tx_hash.0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mine_empty_block_after_max_idle_time() {
+    let (_, starknet_config) = create_test_sequencer_config();
+    let sequencer_config = SequencerConfig {
+        block_time: Some(100),
+        skip_empty_blocks: true,
+        max_idle_time: Some(300),
+        ..Default::default()
+    };
+    let sequencer = KatanaSequencer::new(sequencer_config, starknet_config).await;
+
+    wait_for_block(&sequencer, 2).await;
+    let storage = sequencer.backend.blockchain.storage.read();
+    assert!(storage.block_by_number(1).unwrap().transactions.is_empty());
+    assert!(storage.block_by_number(2).unwrap().transactions.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn switch_mining_mode_at_runtime() {
+    let sequencer = create_test_sequencer().await;
+    assert!(sequencer.block_producer().is_instant_mining());
+
+    sequencer.set_interval_mining(100).await.unwrap();
+    assert!(sequencer.block_producer().is_interval_mining());
+    wait_for_block(&sequencer, 2).await;
+
+    // Wait for the ongoing mining to be over before switching back.
+    while sequencer.set_interval_mining(0).await.is_err() {
+        tokio::task::yield_now().await;
+    }
+    assert!(sequencer.block_producer().is_instant_mining());
+    let block_number = sequencer.block_number().await;
+
+    let declare_tx = create_declare_transaction(ContractAddress(patricia_key!(
+        sequencer.backend.accounts[0].address
+    )));
+    sequencer.add_declare_transaction(declare_tx);
+
+    wait_for_block(&sequencer, block_number + 1).await;
+}
@@ -3,6 +3,7 @@ use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::error::CallError;
 use jsonrpsee::types::ErrorObject;
 use katana_core::accounts::Account;
+use katana_core::backend::storage::archive::{ArchivedBlock, ArchivedGenesis};
 use starknet::core::types::FieldElement;
 
 #[derive(thiserror::Error, Clone, Copy, Debug)]
@@ -14,6 +15,10 @@ pub enum KatanaApiError {
     FailedToDumpState = 2,
     #[error("Failed to update storage.")]
     FailedToUpdateStorage = 3,
+    #[error("Failed to change mining mode.")]
+    FailedToChangeMiningMode = 4,
+    #[error("Failed to export chain.")]
+    FailedToExportChain = 5,
 }
 
 impl From<KatanaApiError> for Error {
@@ -36,6 +41,15 @@ pub trait KatanaApi {
     #[method(name = "increaseNextBlockTimestamp")]
     async fn increase_next_block_timestamp(&self, timestamp: u64) -> Result<(), Error>;
 
+    #[method(name = "setIntervalMining")]
+    async fn set_interval_mining(&self, interval: u64) -> Result<(), Error>;
+
+    #[method(name = "exportGenesis")]
+    async fn export_genesis(&self) -> Result<ArchivedGenesis, Error>;
+
+    #[method(name = "exportBlocks")]
+    async fn export_blocks(&self, from: u64, count: u64) -> Result<Vec<ArchivedBlock>, Error>;
+
     #[method(name = "predeployedAccounts")]
     async fn predeployed_accounts(&self) -> Result<Vec<Account>, Error>;
 
@@ -1,6 +1,7 @@
 use jsonrpsee::core::Error;
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::error::{CallError, ErrorObject};
+use katana_core::fee::{FeeEstimateWithDataGas, TransactionReceiptWithGas};
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use starknet::core::serde::unsigned_field_element::UfeHex;
@@ -9,8 +10,7 @@ use starknet::core::types::{
     BroadcastedDeployAccountTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
     ContractClass, DeclareTransactionResult, DeployAccountTransactionResult, EventFilterWithPage,
     EventsPage, FeeEstimate, FieldElement, FunctionCall, InvokeTransactionResult,
-    MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, MaybePendingTransactionReceipt,
-    MsgFromL1, StateUpdate, Transaction,
+    MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, MsgFromL1, StateUpdate, Transaction,
 };
 
 #[serde_as]
@@ -95,7 +95,7 @@ pub trait StarknetApi {
 
     #[method(name = "getNonce")]
     async fn nonce(&self, block_id: BlockId, contract_address: FieldElement)
-    -> Result<Felt, Error>;
+        -> Result<Felt, Error>;
 
     #[method(name = "blockNumber")]
     async fn block_number(&self) -> Result<u64, Error>;
@@ -138,11 +138,12 @@ pub trait StarknetApi {
     #[method(name = "getStateUpdate")]
     async fn state_update(&self, block_id: BlockId) -> Result<StateUpdate, Error>;
 
+    /// Same as the spec, with the L1 gas and data gas the transaction was charged for.
     #[method(name = "getTransactionReceipt")]
     async fn transaction_receipt(
         &self,
         transaction_hash: FieldElement,
-    ) -> Result<MaybePendingTransactionReceipt, Error>;
+    ) -> Result<TransactionReceiptWithGas, Error>;
 
     #[method(name = "getClassHashAt")]
     async fn class_hash_at(
@@ -164,12 +165,13 @@ pub trait StarknetApi {
     #[method(name = "pendingTransactions")]
     async fn pending_transactions(&self) -> Result<Vec<Transaction>, Error>;
 
+    /// Same as the spec, with the data gas included in the estimates.
     #[method(name = "estimateFee")]
     async fn estimate_fee(
         &self,
         request: Vec<BroadcastedTransaction>,
         block_id: BlockId,
-    ) -> Result<Vec<FeeEstimate>, Error>;
+    ) -> Result<Vec<FeeEstimateWithDataGas>, Error>;
 
     #[method(name = "estimateMessageFee")]
     async fn estimate_message_fee(
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use jsonrpsee::core::{async_trait, Error};
 use katana_core::accounts::Account;
+use katana_core::backend::storage::archive::{ArchivedBlock, ArchivedGenesis};
 use katana_core::sequencer::KatanaSequencer;
 use starknet::core::types::FieldElement;
 use starknet_api::core::{ContractAddress, PatriciaKey};
@@ -46,6 +47,24 @@ impl KatanaApiServer for KatanaApi {
             .map_err(|_| Error::from(KatanaApiError::FailedToChangeNextBlockTimestamp))
     }
 
+    async fn set_interval_mining(&self, interval: u64) -> Result<(), Error> {
+        self.sequencer
+            .set_interval_mining(interval)
+            .await
+            .map_err(|_| Error::from(KatanaApiError::FailedToChangeMiningMode))
+    }
+
+    async fn export_genesis(&self) -> Result<ArchivedGenesis, Error> {
+        self.sequencer
+            .backend()
+            .export_genesis()
+            .map_err(|_| Error::from(KatanaApiError::FailedToExportChain))
+    }
+
+    async fn export_blocks(&self, from: u64, count: u64) -> Result<Vec<ArchivedBlock>, Error> {
+        Ok(self.sequencer.backend().export_blocks(from, count))
+    }
+
     async fn predeployed_accounts(&self) -> Result<Vec<Account>, Error> {
         Ok(self.sequencer.backend().accounts.clone())
     }
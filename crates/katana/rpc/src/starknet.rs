@@ -8,6 +8,7 @@ use katana_core::backend::storage::transaction::{
     L1HandlerTransaction, PendingTransaction, Transaction,
 };
 use katana_core::backend::ExternalFunctionCall;
+use katana_core::fee::{FeeEstimateWithDataGas, TransactionReceiptWithGas};
 use katana_core::sequencer::KatanaSequencer;
 use katana_core::sequencer_error::SequencerError;
 use katana_core::utils::contract::legacy_inner_to_rpc_class;
@@ -20,8 +21,8 @@ use starknet::core::types::{
     BroadcastedDeployAccountTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
     ContractClass, DeclareTransactionResult, DeployAccountTransactionResult, EventFilterWithPage,
     EventsPage, FeeEstimate, FieldElement, FunctionCall, InvokeTransactionResult,
-    MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, MaybePendingTransactionReceipt,
-    MsgFromL1, StateUpdate, Transaction as RpcTransaction,
+    MaybePendingBlockWithTxHashes, MaybePendingBlockWithTxs, MsgFromL1, StateUpdate,
+    Transaction as RpcTransaction,
 };
 use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, PatriciaKey};
 use starknet_api::hash::{StarkFelt, StarkHash};
@@ -144,7 +145,7 @@ impl StarknetApiServer for StarknetApi {
     async fn transaction_receipt(
         &self,
         transaction_hash: FieldElement,
-    ) -> Result<MaybePendingTransactionReceipt, Error> {
+    ) -> Result<TransactionReceiptWithGas, Error> {
         self.sequencer
             .transaction_receipt(&transaction_hash)
             .await
@@ -310,7 +311,7 @@ impl StarknetApiServer for StarknetApi {
         &self,
         request: Vec<BroadcastedTransaction>,
         block_id: BlockId,
-    ) -> Result<Vec<FeeEstimate>, Error> {
+    ) -> Result<Vec<FeeEstimateWithDataGas>, Error> {
         let chain_id = FieldElement::from_hex_be(&self.sequencer.chain_id().await.as_hex())
             .map_err(|_| StarknetApiError::UnexpectedError)?;
 
@@ -391,7 +392,7 @@ impl StarknetApiServer for StarknetApi {
             .pop()
             .expect("should have estimate result");
 
-        Ok(res)
+        Ok(res.estimate)
     }
 
     async fn add_declare_transaction(
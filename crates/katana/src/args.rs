@@ -1,10 +1,11 @@
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
-use katana_core::backend::config::{Environment, StarknetConfig};
+use katana_core::backend::config::{Environment, ExecutionEngine, StarknetConfig};
 use katana_core::constants::{
-    DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS,
+    DEFAULT_DATA_GAS_PRICE, DEFAULT_GAS_PRICE, DEFAULT_INVOKE_MAX_STEPS, DEFAULT_VALIDATE_MAX_STEPS,
 };
 use katana_core::db::serde::state::SerializableState;
 use katana_core::sequencer::SequencerConfig;
@@ -32,6 +33,20 @@ pub struct KatanaArgs {
     #[arg(help = "Block time in milliseconds for interval mining.")]
     pub block_time: Option<u64>,
 
+    #[arg(long)]
+    #[arg(requires = "block_time")]
+    #[arg(help = "Only mine blocks that contain transactions on interval mining.")]
+    pub no_empty_blocks: bool,
+
+    #[arg(long)]
+    #[arg(requires = "no_empty_blocks")]
+    #[arg(value_name = "MILLISECONDS")]
+    #[arg(help = "Maximum time without a new block before an empty block is mined.")]
+    #[arg(long_help = "Maximum time in milliseconds without a new block before an empty block \
+                       is mined, when empty blocks are skipped. The empty block is mined at the \
+                       end of the first interval after the idle time has elapsed.")]
+    pub max_idle_time: Option<u64>,
+
     #[arg(long)]
     #[arg(value_name = "PATH")]
     #[arg(help = "Dump the state of chain on exit to the given file.")]
@@ -89,6 +104,26 @@ pub struct KatanaArgs {
 pub enum Commands {
     #[command(about = "Generate shell completion file for specified shell")]
     Completions { shell: Shell },
+
+    #[command(about = "Export the chain of a running node to an archive file")]
+    Export {
+        #[arg(value_name = "PATH")]
+        #[arg(help = "Path of the archive file to write.")]
+        path: PathBuf,
+
+        #[arg(long)]
+        #[arg(value_name = "URL")]
+        #[arg(default_value = "http://localhost:5050")]
+        #[arg(help = "The JSON-RPC endpoint of the node to export the chain from.")]
+        rpc_url: Url,
+    },
+
+    #[command(about = "Start the node from a chain archive")]
+    Import {
+        #[arg(value_name = "PATH")]
+        #[arg(help = "Path of the archive file to load the chain from.")]
+        path: PathBuf,
+    },
 }
 
 #[derive(Debug, Args, Clone)]
@@ -125,6 +160,15 @@ pub struct StarknetOptions {
     #[arg(help = "Disable charging fee for transactions.")]
     pub disable_fee: bool,
 
+    #[cfg(feature = "native")]
+    #[arg(long)]
+    #[arg(help = "Execute calls to Sierra classes natively instead of using the Cairo VM.")]
+    #[arg(long_help = "Compile declared Sierra classes to native code and execute calls to them \
+                       natively instead of using the Cairo VM. Entry points that can't be \
+                       executed natively, and transactions, are executed using the Cairo VM so \
+                       that fees don't depend on the engine.")]
+    pub native: bool,
+
     #[command(flatten)]
     #[command(next_help_heading = "Environment options")]
     pub environment: EnvironmentOptions,
@@ -141,6 +185,12 @@ pub struct EnvironmentOptions {
     #[arg(help = "The gas price.")]
     pub gas_price: Option<u128>,
 
+    #[arg(long)]
+    #[arg(value_name = "WEI")]
+    #[arg(help = "The price of a single byte of state diff published to L1, charged on top of \
+                  the execution fee. No data fee is charged by default.")]
+    pub data_gas_price: Option<u128>,
+
     #[arg(long)]
     #[arg(help = "The maximum number of steps available for the account validation logic.")]
     pub validate_max_steps: Option<u32>,
@@ -169,10 +219,33 @@ impl KatanaArgs {
         Ok(tracing::subscriber::set_global_default(subscriber)?)
     }
 
+    /// Checks the conflicts between the options and the subcommand, which clap can't express.
+    pub fn validate(&self) -> Result<(), clap::Error> {
+        if let Some(Commands::Import { .. }) = self.command {
+            if self.rpc_url.is_some() {
+                return Err(Self::command().error(
+                    ErrorKind::ArgumentConflict,
+                    "`import` can't be used with `--rpc-url`",
+                ));
+            }
+
+            if self.load_state.is_some() {
+                return Err(Self::command().error(
+                    ErrorKind::ArgumentConflict,
+                    "`import` can't be used with `--load-state`",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn sequencer_config(&self) -> SequencerConfig {
         SequencerConfig {
             block_time: self.block_time,
             no_mining: self.no_mining,
+            skip_empty_blocks: self.no_empty_blocks,
+            max_idle_time: self.max_idle_time,
             #[cfg(feature = "messaging")]
             messaging: self.messaging.clone(),
         }
@@ -199,11 +272,18 @@ impl KatanaArgs {
             seed: parse_seed(&self.starknet.seed),
             disable_fee: self.starknet.disable_fee,
             init_state: self.load_state.clone(),
+            init_chain: None,
             fork_rpc_url: self.rpc_url.clone(),
             fork_block_number: self.fork_block_number,
+            execution_engine: self.execution_engine(),
             env: Environment {
                 chain_id: self.starknet.environment.chain_id.clone(),
                 gas_price: self.starknet.environment.gas_price.unwrap_or(DEFAULT_GAS_PRICE),
+                data_gas_price: self
+                    .starknet
+                    .environment
+                    .data_gas_price
+                    .unwrap_or(DEFAULT_DATA_GAS_PRICE),
                 invoke_max_steps: self
                     .starknet
                     .environment
@@ -217,6 +297,15 @@ impl KatanaArgs {
             },
         }
     }
+
+    fn execution_engine(&self) -> ExecutionEngine {
+        #[cfg(feature = "native")]
+        if self.starknet.native {
+            return ExecutionEngine::Native;
+        }
+
+        ExecutionEngine::Vm
+    }
 }
 
 fn parse_seed(seed: &str) -> [u8; 32] {
@@ -233,6 +322,8 @@ fn parse_seed(seed: &str) -> [u8; 32] {
 
 #[cfg(test)]
 mod test {
+    use assert_matches::assert_matches;
+
     use super::*;
 
     #[test]
@@ -266,4 +357,81 @@ mod test {
         assert_eq!(block_context.validate_max_n_steps, 100);
         assert_eq!(block_context.invoke_tx_max_n_steps, 200);
     }
+
+    #[test]
+    fn hybrid_mining_from_args() {
+        let args = KatanaArgs::parse_from([
+            "katana",
+            "--block-time",
+            "1000",
+            "--no-empty-blocks",
+            "--max-idle-time",
+            "60000",
+        ]);
+
+        let config = args.sequencer_config();
+
+        assert_eq!(config.block_time, Some(1000));
+        assert!(config.skip_empty_blocks);
+        assert_eq!(config.max_idle_time, Some(60000));
+    }
+
+    #[test]
+    fn no_empty_blocks_requires_block_time() {
+        assert!(KatanaArgs::try_parse_from(["katana", "--no-empty-blocks"]).is_err());
+    }
+
+    #[test]
+    fn default_execution_engine_is_vm() {
+        let args = KatanaArgs::parse_from(["katana"]);
+        assert_eq!(args.starknet_config().execution_engine, ExecutionEngine::Vm);
+    }
+
+    #[cfg(feature = "native")]
+    #[test]
+    fn native_execution_engine_from_args() {
+        let args = KatanaArgs::parse_from(["katana", "--native"]);
+        assert_eq!(args.starknet_config().execution_engine, ExecutionEngine::Native);
+    }
+
+    #[test]
+    fn export_command_from_args() {
+        let args = KatanaArgs::parse_from(["katana", "export", "chain.gz"]);
+        assert_matches!(
+            &args.command,
+            Some(Commands::Export { path, rpc_url }) => {
+                assert_eq!(path, &PathBuf::from("chain.gz"));
+                assert_eq!(rpc_url.as_str(), "http://localhost:5050/");
+            }
+        );
+        assert!(args.starknet_config().init_chain.is_none());
+    }
+
+    #[test]
+    fn import_conflicts_with_fork() {
+        let args = KatanaArgs::parse_from(["katana", "import", "chain.gz"]);
+        assert_matches!(
+            &args.command,
+            Some(Commands::Import { path }) => assert_eq!(path, &PathBuf::from("chain.gz"))
+        );
+        assert!(args.validate().is_ok());
+
+        let args = KatanaArgs::parse_from([
+            "katana",
+            "--rpc-url",
+            "http://localhost:5050",
+            "import",
+            "chain.gz",
+        ]);
+        assert_eq!(args.validate().unwrap_err().kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn data_gas_price_from_args() {
+        let args = KatanaArgs::parse_from(["katana"]);
+        assert_eq!(args.starknet_config().env.data_gas_price, 0);
+
+        let args = KatanaArgs::parse_from(["katana", "--data-gas-price", "10"]);
+        assert_eq!(args.starknet_config().env.data_gas_price, 10);
+    }
 }
@@ -1,9 +1,16 @@
+use std::path::Path;
 use std::sync::Arc;
 use std::{fs, io};
 
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, Shell};
 use console::Style;
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::rpc_params;
+use katana_core::backend::storage::archive::{
+    ArchivedBlock, ArchivedGenesis, ChainArchive, ImportedChain,
+};
 use katana_core::constants::{
     ERC20_CONTRACT_CLASS_HASH, FEE_TOKEN_ADDRESS, UDC_ADDRESS, UDC_CLASS_HASH,
 };
@@ -11,29 +18,51 @@ use katana_core::sequencer::KatanaSequencer;
 use katana_rpc::{spawn, NodeHandle};
 use tokio::signal::ctrl_c;
 use tracing::{error, info};
+use url::Url;
 
 mod args;
 
-use args::Commands::Completions;
+use args::Commands::{Completions, Export, Import};
 use args::KatanaArgs;
 
+/// The number of blocks fetched per request when exporting a chain, which keeps the responses
+/// under the size limit of the server.
+const EXPORT_PAGE_SIZE: u64 = 10;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = KatanaArgs::parse();
+    if let Err(err) = config.validate() {
+        err.exit();
+    }
     config.init_logging()?;
 
-    if let Some(command) = config.command {
+    let mut init_chain = None;
+
+    if let Some(command) = &config.command {
         match command {
             Completions { shell } => {
-                print_completion(shell);
+                print_completion(*shell);
+                return Ok(());
+            }
+            Export { path, rpc_url } => {
+                export_chain(rpc_url, path).await?;
                 return Ok(());
             }
+            Import { path } => {
+                let chain =
+                    ChainArchive::load(path).and_then(ImportedChain::new).map_err(|err| {
+                        format!("failed to load chain archive {}: {err}", path.display())
+                    })?;
+                init_chain = Some(chain);
+            }
         }
     }
 
     let server_config = config.server_config();
     let sequencer_config = config.sequencer_config();
-    let starknet_config = config.starknet_config();
+    let mut starknet_config = config.starknet_config();
+    starknet_config.init_chain = init_chain;
 
     let sequencer = Arc::new(KatanaSequencer::new(sequencer_config, starknet_config).await);
     let NodeHandle { addr, handle, .. } = spawn(Arc::clone(&sequencer), server_config).await?;
@@ -79,6 +108,33 @@ fn print_completion(shell: Shell) {
     generate(shell, &mut command, name, &mut io::stdout());
 }
 
+async fn export_chain(rpc_url: &Url, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let client = HttpClientBuilder::default().build(rpc_url)?;
+
+    let genesis: ArchivedGenesis = client.request("katana_exportGenesis", rpc_params![]).await?;
+
+    let mut blocks = Vec::new();
+    loop {
+        let from = blocks.len() as u64;
+        let page: Vec<ArchivedBlock> =
+            client.request("katana_exportBlocks", rpc_params![from, EXPORT_PAGE_SIZE]).await?;
+
+        let is_last_page = (page.len() as u64) < EXPORT_PAGE_SIZE;
+        blocks.extend(page);
+
+        if is_last_page {
+            break;
+        }
+    }
+
+    let archive = ChainArchive::new(genesis, blocks);
+    archive.save(path)?;
+
+    println!("Exported {} blocks to {}", archive.blocks.len(), path.display());
+
+    Ok(())
+}
+
 fn print_intro(accounts: String, seed: String, address: String, account_class_hash: String) {
     println!(
         "{}",
@@ -7,16 +7,21 @@ use smol_str::SmolStr;
 use tracing::level_filters::LevelFilter;
 use tracing_log::AsTrace;
 
+use crate::commands::account::AccountArgs;
 use crate::commands::auth::AuthArgs;
 use crate::commands::build::BuildArgs;
+use crate::commands::call::CallArgs;
 use crate::commands::completions::CompletionsArgs;
 use crate::commands::dev::DevArgs;
 use crate::commands::events::EventsArgs;
 use crate::commands::execute::ExecuteArgs;
 use crate::commands::init::InitArgs;
+use crate::commands::inspect::InspectArgs;
 use crate::commands::migrate::MigrateArgs;
 use crate::commands::model::ModelArgs;
+use crate::commands::new::NewArgs;
 use crate::commands::register::RegisterArgs;
+use crate::commands::run::RunArgs;
 use crate::commands::test::TestArgs;
 
 #[derive(Parser)]
@@ -38,6 +43,13 @@ pub struct SozoArgs {
     #[command(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 
+    #[arg(long)]
+    #[arg(global = true)]
+    #[arg(env = "DOJO_ENV")]
+    #[arg(value_name = "NAME")]
+    #[arg(help = "The environment to use, declared in [tool.dojo.env.<NAME>].")]
+    pub env: Option<String>,
+
     #[arg(long)]
     #[arg(env = "SOZO_OFFLINE")]
     #[arg(hide_short_help = true)]
@@ -54,6 +66,8 @@ pub enum Commands {
     Build(BuildArgs),
     #[command(about = "Initialize a new project")]
     Init(InitArgs),
+    #[command(about = "Add a model or a system to the project")]
+    New(NewArgs),
     #[command(about = "Run a migration, declaring and deploying contracts as necessary to \
                        update the world")]
     Migrate(Box<MigrateArgs>),
@@ -63,14 +77,22 @@ pub enum Commands {
     Test(TestArgs),
     #[command(about = "Execute a world's system")]
     Execute(ExecuteArgs),
+    #[command(about = "Call a world's system view function")]
+    Call(CallArgs),
+    #[command(about = "Run a script of steps against a world")]
+    Run(RunArgs),
     #[command(about = "Interact with a worlds models")]
     Model(ModelArgs),
     #[command(about = "Register new models")]
     Register(RegisterArgs),
     #[command(about = "Queries world events")]
     Events(EventsArgs),
+    #[command(about = "Inspect a deployed world and compare it with the local build")]
+    Inspect(InspectArgs),
     #[command(about = "Manage world authorization")]
     Auth(AuthArgs),
+    #[command(about = "Create, deploy and import the accounts of the project")]
+    Account(AccountArgs),
     #[command(about = "Generate shell completion file for specified shell")]
     Completions(CompletionsArgs),
 }
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use camino::Utf8PathBuf;
+use clap::{Args, Subcommand};
+use scarb::core::Config;
+use starknet::core::types::FieldElement;
+
+use super::load_env;
+use super::options::account::AccountOptions;
+use super::options::starknet::StarknetOptions;
+use crate::ops::account;
+
+/// Class hash of the OpenZeppelin account, the one of the Katana prefunded accounts.
+pub const DEFAULT_ACCOUNT_CLASS_HASH: &str =
+    "0x04d07e40e93398ed3c76981e72dd1fd22557a78ce36c0515f679e27f0bb5bc5f";
+
+#[derive(Debug, Args)]
+pub struct AccountArgs {
+    #[command(subcommand)]
+    pub command: AccountCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AccountCommand {
+    #[command(about = "Generate a new key pair into an encrypted keystore. The password is read \
+                       from DOJO_KEYSTORE_PASSWORD, or prompted for.")]
+    New {
+        #[arg(help = "Path of the keystore to create.")]
+        keystore: Utf8PathBuf,
+
+        #[arg(long)]
+        #[arg(help = "Overwrite the keystore if it already exists, and replace the private key \
+                      saved in the environment.")]
+        force: bool,
+
+        #[arg(long)]
+        #[arg(default_value = DEFAULT_ACCOUNT_CLASS_HASH)]
+        #[arg(help = "Class hash of the account contract, used to compute its address.")]
+        class_hash: FieldElement,
+
+        #[arg(long)]
+        #[arg(default_value = "0x0")]
+        #[arg(help = "Salt of the account contract, used to compute its address.")]
+        salt: FieldElement,
+    },
+
+    #[command(about = "Deploy the account of a key pair. The account must be funded first.")]
+    Deploy {
+        #[arg(long)]
+        #[arg(default_value = DEFAULT_ACCOUNT_CLASS_HASH)]
+        #[arg(help = "Class hash of the account contract to deploy.")]
+        class_hash: FieldElement,
+
+        #[arg(long)]
+        #[arg(default_value = "0x0")]
+        #[arg(help = "Salt of the account contract to deploy.")]
+        salt: FieldElement,
+
+        #[arg(long)]
+        #[arg(help = "Maximum fee of the deployment, estimated if not given.")]
+        max_fee: Option<FieldElement>,
+
+        #[arg(long)]
+        #[arg(help = "Replace the private key saved in the environment by the keystore.")]
+        force: bool,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+
+    #[command(about = "Import an account already deployed on the network.")]
+    Fetch {
+        #[arg(help = "Address of the account.")]
+        address: FieldElement,
+
+        #[arg(long)]
+        #[arg(help = "Save the account even if its public key can't be read to check that it's \
+                      owned by the key, and replace the private key saved in the environment.")]
+        force: bool,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+
+    #[command(about = "List the accounts saved in the profiles of the project.")]
+    List,
+}
+
+impl AccountArgs {
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let env_metadata = load_env(config, env)?;
+
+        config.tokio_handle().block_on(account::execute(
+            self.command,
+            env_metadata,
+            config.manifest_path(),
+            config.profile().as_str(),
+            env,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_account_commands() {
+        let args = ["sozo", "account", "new", "keystore.json"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Account(AccountArgs {
+                command: AccountCommand::New { force: false, salt, .. }
+            }) if salt == FieldElement::ZERO
+        ));
+
+        let args = ["sozo", "account", "deploy", "--keystore", "keystore.json"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Account(AccountArgs {
+                command: AccountCommand::Deploy { class_hash, max_fee: None, force: false, .. }
+            }) if class_hash == FieldElement::from_hex_be(DEFAULT_ACCOUNT_CLASS_HASH).unwrap()
+        ));
+
+        let args = ["sozo", "account", "fetch", "0x1234", "--keystore", "keystore.json"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Account(AccountArgs {
+                command: AccountCommand::Fetch { address, force: false, .. }
+            }) if address == FieldElement::from(0x1234_u32)
+        ));
+
+        let args = ["sozo", "account", "new", "keystore.json", "--password", "secret"];
+        assert!(SozoArgs::try_parse_from(args).is_err());
+    }
+}
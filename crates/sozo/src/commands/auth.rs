@@ -1,9 +1,9 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
-use dojo_world::metadata::dojo_metadata_from_workspace;
 use scarb::core::Config;
 use starknet::core::types::FieldElement;
 
+use super::load_env;
 use super::options::account::AccountOptions;
 use super::options::starknet::StarknetOptions;
 use super::options::world::WorldOptions;
@@ -34,19 +34,126 @@ pub enum AuthCommand {
         #[command(flatten)]
         account: AccountOptions,
     },
+
+    #[command(about = "Revoke the write access of a contract to a model.")]
+    RevokeWriter {
+        #[arg(help = "Name of the model to revoke write access to.")]
+        model: String,
+
+        #[arg(help = "Address of the contract to revoke writer access from.")]
+        contract: FieldElement,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+
+    #[command(about = "Grant the ownership of a resource to an address.")]
+    GrantOwner {
+        #[arg(help = "Name of the resource to grant ownership of.")]
+        #[arg(long_help = "Name of the resource to grant ownership of. Either a model name, or \
+                           `world` for the World itself.")]
+        resource: String,
+
+        #[arg(help = "Address to grant ownership to.")]
+        owner: FieldElement,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+
+    #[command(about = "Revoke the ownership of a resource from an address.")]
+    RevokeOwner {
+        #[arg(help = "Name of the resource to revoke ownership of.")]
+        #[arg(long_help = "Name of the resource to revoke ownership of. Either a model name, or \
+                           `world` for the World itself.")]
+        resource: String,
+
+        #[arg(help = "Address to revoke ownership from.")]
+        owner: FieldElement,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+
+    #[command(about = "List the current writers and owners of the World.")]
+    #[command(long_about = "List the current writers and owners of the World. The World doesn't \
+                            expose its permissions, so the candidates are gathered from the \
+                            events it emitted, then checked against the World.")]
+    List {
+        #[arg(long)]
+        #[arg(help = "Block number from where to look for permission updates.")]
+        from_block: Option<u64>,
+
+        #[arg(long)]
+        #[arg(default_value_t = 100)]
+        #[arg(help = "Number of events to fetch per request.")]
+        chunk_size: u64,
+
+        #[arg(long)]
+        #[arg(help = "Print the permissions as JSON.")]
+        json: bool,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+    },
 }
 
 impl AuthArgs {
-    pub fn run(self, config: &Config) -> Result<()> {
-        let env_metadata = if config.manifest_path().exists() {
-            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
-
-            // TODO: Check the updated scarb way to read profile specific values
-            dojo_metadata_from_workspace(&ws).and_then(|inner| inner.env().cloned())
-        } else {
-            None
-        };
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let env_metadata = load_env(config, env)?;
 
         config.tokio_handle().block_on(auth::execute(self.command, env_metadata))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_owner_commands() {
+        let args = ["sozo", "auth", "grant-owner", "world", "0x1234"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Auth(AuthArgs { command: AuthCommand::GrantOwner { resource, owner, .. } })
+                if resource == "world" && owner == FieldElement::from(0x1234_u32)
+        ));
+
+        let args = ["sozo", "auth", "list", "--json", "--from-block", "10"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Auth(AuthArgs {
+                command: AuthCommand::List {
+                    json: true,
+                    from_block: Some(10),
+                    chunk_size: 100,
+                    ..
+                }
+            })
+        ));
+    }
+}
@@ -1,19 +1,109 @@
-use anyhow::Result;
-use clap::Args;
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::{Args, ValueEnum};
+use dojo_lang::compiler::remove_build_cache;
+use dojo_world::manifest::Manifest;
 use scarb::core::{Config, TargetKind};
 use scarb::ops::{self, CompileOpts};
 
+use crate::ops::bindings::{self, BindingsGenerator, RustGenerator};
+
 #[derive(Args, Debug)]
-pub struct BuildArgs;
+pub struct BuildArgs {
+    #[arg(long)]
+    #[arg(value_enum)]
+    #[arg(num_args = 0..=1)]
+    #[arg(default_missing_value = "rust")]
+    #[arg(help = "Generate the client bindings of the World in the given language.")]
+    pub bindings: Option<BindingsTarget>,
+
+    #[arg(long)]
+    #[arg(requires = "bindings")]
+    #[arg(help = "Directory of the generated bindings. Defaults to \
+                  target/<profile>/bindings/<language>.")]
+    pub bindings_output: Option<Utf8PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Rebuild every contract, instead of reusing the unchanged ones from the build \
+                  cache.")]
+    pub no_cache: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BindingsTarget {
+    Rust,
+}
+
+impl BindingsTarget {
+    fn generator(self) -> Box<dyn BindingsGenerator> {
+        match self {
+            BindingsTarget::Rust => Box::new(RustGenerator),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BindingsTarget::Rust => "rust",
+        }
+    }
+}
 
 impl BuildArgs {
     pub fn run(self, config: &Config) -> Result<()> {
         let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+        let target_dir = ws.target_dir().path_unchecked().join(config.profile().as_str());
+
+        if self.no_cache {
+            remove_build_cache(target_dir.as_std_path())
+                .with_context(|| "Failed to remove the build cache")?;
+        }
+
         let packages = ws.members().map(|p| p.id).collect();
         ops::compile(
             packages,
             CompileOpts { include_targets: vec![], exclude_targets: vec![TargetKind::TEST] },
             &ws,
-        )
+        )?;
+
+        let Some(target) = self.bindings else { return Ok(()) };
+
+        let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+        let output_dir =
+            self.bindings_output.unwrap_or_else(|| target_dir.join("bindings").join(target.name()));
+
+        for path in bindings::generate(target.generator().as_ref(), &manifest, &output_dir)? {
+            config.ui().print(format!("Generated {path}"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn bindings_default_to_rust() {
+        assert!(matches!(
+            SozoArgs::parse_from(["sozo", "build", "--bindings"]).command,
+            Commands::Build(BuildArgs { bindings: Some(BindingsTarget::Rust), .. })
+        ));
+
+        assert!(matches!(
+            SozoArgs::parse_from(["sozo", "build"]).command,
+            Commands::Build(BuildArgs { bindings: None, no_cache: false, .. })
+        ));
+
+        assert!(matches!(
+            SozoArgs::parse_from(["sozo", "build", "--no-cache"]).command,
+            Commands::Build(BuildArgs { no_cache: true, .. })
+        ));
+
+        let args = ["sozo", "build", "--bindings-output", "out"];
+        assert!(SozoArgs::try_parse_from(args).is_err());
     }
 }
@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use scarb::core::Config;
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+
+use super::load_env_and_manifest;
+use super::options::starknet::StarknetOptions;
+use super::options::world::WorldOptions;
+use crate::ops::call;
+
+#[derive(Debug, Args)]
+#[command(about = "Call a view function of a system and decode its output.")]
+pub struct CallArgs {
+    #[arg(help = "The address or the name of the contract to be called.")]
+    pub contract: String,
+
+    #[arg(help = "The name of the entrypoint to be called.")]
+    pub entrypoint: String,
+
+    #[arg(short, long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(conflicts_with = "args")]
+    #[arg(help = "The calldata to be passed to the entrypoint. Comma seperated values e.g., \
+                  0x12345,0x69420.")]
+    pub calldata: Vec<FieldElement>,
+
+    #[arg(short, long)]
+    #[arg(help = "The arguments to be passed to the entrypoint, written as Cairo values and \
+                  serialized using the ABI of the contract e.g., 'Vec2 { x: 1, y: 2 }'.")]
+    pub args: Option<String>,
+
+    #[arg(short, long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "For a computed entrypoint bound to a model, the keys of the entity passed as \
+                  the model argument. Comma seperated values e.g., 0x12345,0x69420.")]
+    pub keys: Vec<FieldElement>,
+
+    #[arg(short, long)]
+    #[arg(default_value = "pending")]
+    #[arg(value_parser = parse_block_id)]
+    #[arg(help = "The block to call the entrypoint at: a block number, a block hash, `latest` \
+                  or `pending`.")]
+    pub block: BlockId,
+
+    #[command(flatten)]
+    pub world: WorldOptions,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+}
+
+impl CallArgs {
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let (env_metadata, manifest) = load_env_and_manifest(config, env)?;
+        config.tokio_handle().block_on(call::call(self, env_metadata, manifest))
+    }
+}
+
+fn parse_block_id(block: &str) -> Result<BlockId> {
+    match block {
+        "latest" => Ok(BlockId::Tag(BlockTag::Latest)),
+        "pending" => Ok(BlockId::Tag(BlockTag::Pending)),
+        hash if hash.starts_with("0x") => Ok(BlockId::Hash(FieldElement::from_hex_be(hash)?)),
+        number => number.parse().map(BlockId::Number).map_err(|_| {
+            anyhow!("Invalid block `{number}`, expected a number, a hash, `latest` or `pending`.")
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use starknet::macros::felt;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_block_and_keys() {
+        let args = ["sozo", "call", "actions", "quadrant", "--keys", "0x1,0x2"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Call(CallArgs { keys, block: BlockId::Tag(BlockTag::Pending), .. })
+                if keys == [felt!("0x1"), felt!("0x2")]
+        ));
+
+        let args = ["sozo", "call", "actions", "tile_terrain", "--block", "42"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Call(CallArgs { block: BlockId::Number(42), .. })
+        ));
+
+        let args = ["sozo", "call", "actions", "tile_terrain", "--block", "0x42"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Call(CallArgs { block: BlockId::Hash(hash), .. }) if hash == felt!("0x42")
+        ));
+
+        let args = ["sozo", "call", "actions", "spawn", "--block", "soon"];
+        assert!(SozoArgs::try_parse_from(args).is_err());
+    }
+}
@@ -1,15 +1,24 @@
-use std::mem;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
+use std::{fs, mem};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_filesystem::db::{AsFilesGroupMut, FilesGroupEx, PrivRawFileContentQuery};
 use cairo_lang_filesystem::ids::FileId;
 use clap::Args;
-use dojo_world::manifest::Manifest;
+use console::{Style, Term};
+use dojo_world::manifest::{Manifest, ManifestError};
 use dojo_world::migration::world::WorldDiff;
+use katana_core::backend::config::StarknetConfig;
+use katana_core::sequencer::{KatanaSequencer, SequencerConfig};
+use katana_rpc::api::ApiKind;
+use katana_rpc::config::ServerConfig;
+use katana_rpc::NodeHandle;
 use notify_debouncer_mini::notify::RecursiveMode;
 use notify_debouncer_mini::{new_debouncer, DebouncedEvent, DebouncedEventKind};
 use scarb::compiler::CompilationUnit;
@@ -18,13 +27,18 @@ use starknet::accounts::SingleOwnerAccount;
 use starknet::core::types::FieldElement;
 use starknet::providers::Provider;
 use starknet::signers::Signer;
+use tokio::sync::broadcast;
+use torii_server::ToriiConfig;
 use tracing_log::log;
+use url::Url;
 
+use super::env_from_workspace;
 use super::options::account::AccountOptions;
 use super::options::starknet::StarknetOptions;
 use super::options::world::WorldOptions;
 use super::scarb_internal::build_scarb_root_database;
 use crate::ops::migration;
+use crate::ops::migration::journal::MigrationJournal;
 
 #[derive(Args)]
 pub struct DevArgs {
@@ -34,6 +48,35 @@ pub struct DevArgs {
                        contract to avoid address conflicts.")]
     pub name: Option<String>,
 
+    #[arg(long)]
+    #[arg(help = "Start a Katana node in-process and migrate the World to it.")]
+    #[arg(long_help = "Start a Katana node in-process and migrate the World to it, using its \
+                       first prefunded account. The node state lives as long as `sozo dev`.")]
+    pub with_katana: bool,
+
+    #[arg(long)]
+    #[arg(default_value_t = 5050)]
+    #[arg(help = "Port of the JSON-RPC server of the Katana node started with --with-katana.")]
+    pub katana_port: u16,
+
+    #[arg(long)]
+    #[arg(help = "Start Torii in-process to index the World once it's deployed.")]
+    #[arg(long_help = "Start Torii in-process to index the World once it's deployed, using an \
+                       in-memory database. The index lives as long as `sozo dev`.")]
+    pub with_torii: bool,
+
+    #[arg(long)]
+    #[arg(default_value_t = 8080)]
+    #[arg(help = "Port of the API endpoints of the Torii started with --with-torii.")]
+    pub torii_port: u16,
+
+    #[arg(long)]
+    #[arg(help = "Migrate even if model schema changes break the existing entities.")]
+    #[arg(long_help = "Migrate even if model schema changes break the existing entities. \
+                       Without it, a change saved during the session that would make the \
+                       existing entities unreadable is reported and not migrated.")]
+    pub allow_breaking: bool,
+
     #[command(flatten)]
     pub world: WorldOptions,
 
@@ -44,6 +87,174 @@ pub struct DevArgs {
     pub account: AccountOptions,
 }
 
+/// Status panel of the dev session, drawn below the output of the builds and migrations: the
+/// nodes started with the session, the address of the World, the outcome of the last build and
+/// migration, and what the session is doing. The panel is erased before anything else is printed
+/// and drawn again afterwards, so it stays at the bottom of the terminal instead of being
+/// interleaved with the output.
+struct DevUi {
+    term: Term,
+    nodes: Vec<String>,
+    world_address: Option<FieldElement>,
+    last_build: Option<String>,
+    last_migration: Option<String>,
+    status: String,
+    /// The number of lines of the status currently drawn.
+    drawn_lines: usize,
+}
+
+impl DevUi {
+    fn new() -> Self {
+        Self {
+            term: Term::stdout(),
+            nodes: vec![],
+            world_address: None,
+            last_build: None,
+            last_migration: None,
+            status: String::new(),
+            drawn_lines: 0,
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let dim = Style::new().dim();
+
+        let mut lines = vec![Style::new().bold().apply_to("sozo dev").to_string()];
+        lines.extend(self.nodes.iter().map(|node| dim.apply_to(node).to_string()));
+        if let Some(address) = self.world_address {
+            lines.push(dim.apply_to(format!("World: {address:#x}")).to_string());
+        }
+        if let Some(build) = &self.last_build {
+            lines.push(dim.apply_to(format!("Last build: {build}")).to_string());
+        }
+        if let Some(migration) = &self.last_migration {
+            lines.push(dim.apply_to(format!("Last migration: {migration}")).to_string());
+        }
+        lines.push(self.status.clone());
+
+        lines
+    }
+
+    fn draw(&mut self) {
+        if !self.term.is_term() {
+            return;
+        }
+
+        let lines = self.lines();
+        for line in &lines {
+            let _ = self.term.write_line(line);
+        }
+        self.drawn_lines = lines.len();
+    }
+
+    fn erase(&mut self) {
+        if self.drawn_lines > 0 {
+            let _ = self.term.clear_last_lines(self.drawn_lines);
+            self.drawn_lines = 0;
+        }
+    }
+
+    /// Replaces the status. When the output isn't a terminal, the status is printed as a
+    /// regular line instead.
+    fn set_status(&mut self, status: impl Into<String>) {
+        self.erase();
+        self.status = status.into();
+
+        if self.term.is_term() {
+            self.draw();
+        } else {
+            let _ = self.term.write_line(&self.status);
+        }
+    }
+
+    /// Draws the status again, after the nodes or the World address changed.
+    fn redraw(&mut self) {
+        self.erase();
+        self.draw();
+    }
+
+    /// Builds the project with the status erased, and records the outcome of the build.
+    fn build(&mut self, context: &mut DevContext<'_>) -> Result<bool> {
+        let result = self.suspend(|| build(context));
+        self.last_build = Some(match &result {
+            Ok(true) => "succeeded".to_string(),
+            Ok(false) => "succeeded, no changes".to_string(),
+            Err(_) => "failed".to_string(),
+        });
+        result
+    }
+
+    /// Runs `f` with the status erased, so that its output is printed above the status.
+    fn suspend<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        self.erase();
+        let result = f();
+        self.draw();
+        result
+    }
+}
+
+/// A Torii indexing the World in-process on its own runtime, stopped with the dev session.
+struct ToriiNode {
+    addr: SocketAddr,
+    shutdown_tx: broadcast::Sender<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ToriiNode {
+    fn start(world_address: FieldElement, rpc_url: &Url, port: u16) -> Result<Self> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        let config = ToriiConfig {
+            world_address,
+            rpc_url: rpc_url.clone(),
+            database: ":memory:".to_string(),
+            start_block: 0,
+            addr,
+            allowed_origins: vec!["*".to_string()],
+            external_url: None,
+        };
+
+        let (shutdown_tx, _) = broadcast::channel(1);
+        let runtime = tokio::runtime::Runtime::new()?;
+
+        let torii_shutdown_tx = shutdown_tx.clone();
+        let thread = thread::Builder::new().name("torii".into()).spawn(move || {
+            if let Err(error) = runtime.block_on(torii_server::run(config, torii_shutdown_tx)) {
+                log::error!("Torii stopped: {error:?}");
+            }
+        })?;
+
+        Ok(Self { addr, shutdown_tx, thread: Some(thread) })
+    }
+}
+
+impl Drop for ToriiNode {
+    fn drop(&mut self) {
+        let _ = self.shutdown_tx.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Starts a Katana node in-process, serving its JSON-RPC API on `port`.
+async fn start_katana(port: u16) -> Result<(Arc<KatanaSequencer>, NodeHandle)> {
+    let sequencer =
+        Arc::new(KatanaSequencer::new(SequencerConfig::default(), StarknetConfig::default()).await);
+
+    let handle = katana_rpc::spawn(
+        Arc::clone(&sequencer),
+        ServerConfig {
+            port,
+            host: "127.0.0.1".into(),
+            max_connections: 100,
+            apis: vec![ApiKind::Starknet, ApiKind::Katana],
+        },
+    )
+    .await?;
+
+    Ok((sequencer, handle))
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum DevAction {
     None,
@@ -86,31 +297,52 @@ fn load_context(config: &Config) -> Result<DevContext<'_>> {
         .filter(|cu| packages.contains(&cu.main_package_id))
         .collect::<Vec<_>>();
     // we have only 1 unit in projects
-    let unit = compilation_units.first().unwrap();
-    let db = build_scarb_root_database(unit, &ws).unwrap();
-    Ok(DevContext { db, unit: unit.clone(), ws })
+    let unit = compilation_units
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No compilation unit found for the workspace members."))?;
+    let db = build_scarb_root_database(&unit, &ws)?;
+    Ok(DevContext { db, unit, ws })
 }
 
-fn build(context: &mut DevContext<'_>) -> Result<()> {
+/// Builds the project and returns whether the manifest changed. Only the contracts whose sources
+/// changed are rebuilt, and the manifest is left untouched when none did, so that saving a file
+/// without changes doesn't migrate the World again.
+fn build(context: &mut DevContext<'_>) -> Result<bool> {
     let ws = &context.ws;
     let unit = &context.unit;
     let package_name = unit.main_package_id.name.clone();
+
+    let manifest_path =
+        ws.target_dir().path_unchecked().join(ws.config().profile().as_str()).join("manifest.json");
+    let previous_manifest = fs::read(&manifest_path).ok();
+
     ws.config().compilers().compile(unit.clone(), &mut (context.db), ws).map_err(|err| {
         ws.config().ui().anyhow(&err);
 
         anyhow!("could not compile `{package_name}` due to previous error")
     })?;
+
+    if fs::read(&manifest_path).ok() == previous_manifest {
+        ws.config().ui().print("📦 Rebuild done, no changes");
+        return Ok(false);
+    }
+
     ws.config().ui().print("📦 Rebuild done");
-    Ok(())
+    Ok(true)
 }
 
+/// Migrates the World to the last build. The diff is computed against the remote World, so
+/// changed contracts are upgraded in place and unchanged models are kept along with their
+/// entities. Models whose schema changes break their existing entities aren't migrated unless
+/// `allow_breaking` is set.
 async fn migrate<P, S>(
-    mut world_address: Option<FieldElement>,
+    world_address: Option<FieldElement>,
     account: &SingleOwnerAccount<P, S>,
     name: Option<String>,
     ws: &Workspace<'_>,
-    previous_manifest: Option<Manifest>,
-) -> Result<(Manifest, Option<FieldElement>)>
+    allow_breaking: bool,
+) -> Result<Option<FieldElement>>
 where
     P: Provider + Sync + Send + 'static,
     S: Signer + Sync + Send + 'static,
@@ -122,42 +354,60 @@ where
         return Err(anyhow!("manifest.json not found"));
     }
     let new_manifest = Manifest::load_from_path(manifest_path)?;
-    let diff = WorldDiff::compute(new_manifest.clone(), previous_manifest);
+
+    let remote_manifest = match world_address {
+        Some(address) => match Manifest::load_from_remote(account.provider(), address).await {
+            Ok(manifest) => Some(manifest),
+            Err(ManifestError::RemoteWorldNotFound) => None,
+            Err(e) => return Err(anyhow!("Failed to build remote World state: {e}")),
+        },
+        None => None,
+    };
+
+    let diff = WorldDiff::compute(new_manifest.clone(), remote_manifest);
     let total_diffs = diff.count_diffs();
     let config = ws.config();
     config.ui().print(format!("Total diffs found: {total_diffs}"));
     if total_diffs == 0 {
-        return Ok((new_manifest, world_address));
+        return Ok(world_address);
     }
 
-    match migration::apply_diff(ws, target_dir, diff, name.clone(), world_address, account, None)
-        .await
-    {
-        Ok(address) => {
-            config
-                .ui()
-                .print(format!("🎉 World at address {} updated!", format_args!("{:#x}", address)));
-            world_address = Some(address);
-        }
-        Err(err) => {
-            config.ui().error(err.to_string());
-            return Err(err);
-        }
+    if let Some(world_address) = world_address {
+        migration::check_model_schemas(
+            &new_manifest,
+            &diff,
+            world_address,
+            account.provider(),
+            config.ui(),
+            allow_breaking,
+        )
+        .await?;
     }
 
-    Ok((new_manifest, world_address))
+    let address = migration::apply_diff(
+        ws,
+        target_dir,
+        diff,
+        name.clone(),
+        world_address,
+        account,
+        None,
+        &mut MigrationJournal::default(),
+    )
+    .await?;
+
+    config.ui().print(format!("🎉 World at address {} updated!", format_args!("{:#x}", address)));
+    Ok(Some(address))
 }
 
-fn process_event(event: &DebouncedEvent, context: &mut DevContext<'_>) -> DevAction {
+fn process_event(event: &DebouncedEvent, context: &mut DevContext<'_>) -> Result<DevAction> {
     let action = handle_event(event);
     match &action {
         DevAction::None => {}
         DevAction::Build(path) => handle_build_action(path, context),
-        DevAction::Reload => {
-            handle_reload_action(context);
-        }
+        DevAction::Reload => handle_reload_action(context)?,
     }
-    action
+    Ok(action)
 }
 
 fn handle_build_action(path: &Path, context: &mut DevContext<'_>) {
@@ -172,16 +422,18 @@ fn handle_build_action(path: &Path, context: &mut DevContext<'_>) {
     db.override_file_content(file, None);
 }
 
-fn handle_reload_action(context: &mut DevContext<'_>) {
+fn handle_reload_action(context: &mut DevContext<'_>) -> Result<()> {
     let config = context.ws.config();
     config.ui().print("Reloading project");
-    let new_context = load_context(config).expect("Failed to load context");
+    let new_context = load_context(config)?;
     let _ = mem::replace(context, new_context);
+    Ok(())
 }
 
 impl DevArgs {
-    pub fn run(self, config: &Config) -> Result<()> {
+    pub fn run(mut self, config: &Config, env: Option<&str>) -> Result<()> {
         let mut context = load_context(config)?;
+        let env_metadata = env_from_workspace(&context.ws, env)?;
         let (tx, rx) = channel();
         let mut debouncer = new_debouncer(Duration::from_secs(1), None, tx)?;
 
@@ -190,72 +442,220 @@ impl DevArgs {
             RecursiveMode::Recursive,
         )?;
         let name = self.name.clone();
-        let mut previous_manifest: Option<Manifest> = Option::None;
-        let result = build(&mut context);
 
-        let Some((mut world_address, account)) = context
+        let mut ui = DevUi::new();
+
+        // The node must outlive the dev session.
+        let _katana = if self.with_katana {
+            let (sequencer, handle) =
+                config.tokio_handle().block_on(start_katana(self.katana_port))?;
+
+            let account = &sequencer.backend.accounts[0];
+            self.starknet.rpc_url = Url::parse(&format!("http://{}", handle.addr))?;
+            self.account.account_address = Some(account.address);
+            self.account.private_key = Some(format!("{:#x}", account.private_key));
+            self.account.keystore_path = None;
+
+            ui.nodes.push(format!("Katana: {}", self.starknet.rpc_url));
+            Some((sequencer, handle))
+        } else {
+            None
+        };
+        let rpc_url = self.starknet.rpc_url.clone();
+
+        ui.set_status("📦 Building...");
+        let result = ui.build(&mut context);
+
+        let (world_address, account) = context
             .ws
             .config()
             .tokio_handle()
             .block_on(migration::setup_env(
                 &context.ws,
+                env_metadata.as_ref(),
                 self.account,
                 self.starknet,
                 self.world,
                 name.as_ref(),
             ))
-            .ok()
-        else {
-            return Err(anyhow!("Failed to setup environment"));
-        };
+            .context("Failed to setup environment")?;
 
-        match context.ws.config().tokio_handle().block_on(migrate(
-            world_address,
-            &account,
-            name.clone(),
-            &context.ws,
-            previous_manifest.clone(),
-        )) {
-            Ok((manifest, address)) => {
-                previous_manifest = Some(manifest);
-                world_address = address;
-            }
-            Err(error) => {
-                log::error!("Error: {error:?}");
-            }
+        // A World configured for another network can't exist on the spawned node.
+        let mut world_address = if self.with_katana { None } else { world_address };
+        let mut torii = None;
+
+        let mut migrate_and_index =
+            |context: &DevContext<'_>, world_address: &mut Option<FieldElement>, ui: &mut DevUi| {
+                ui.set_status("🌍 Migrating...");
+                let migration = ui.suspend(|| {
+                    context.ws.config().tokio_handle().block_on(migrate(
+                        *world_address,
+                        &account,
+                        name.clone(),
+                        &context.ws,
+                        self.allow_breaking,
+                    ))
+                });
+                match migration {
+                    Ok(address) => {
+                        *world_address = address;
+                        ui.last_migration = Some("succeeded".to_string());
+                    }
+                    Err(error) => {
+                        ui.suspend(|| log::error!("Error: {error:?}"));
+                        ui.last_migration = Some("failed".to_string());
+                    }
+                }
+                ui.world_address = *world_address;
+
+                if let (true, None, Some(address)) = (self.with_torii, &torii, *world_address) {
+                    match ToriiNode::start(address, &rpc_url, self.torii_port) {
+                        Ok(node) => {
+                            ui.nodes.push(format!("Torii: http://{}", node.addr));
+                            torii = Some(node);
+                        }
+                        Err(error) => ui.suspend(|| log::error!("Error: {error:?}")),
+                    }
+                }
+
+                ui.redraw();
+            };
+
+        if result.is_ok() {
+            migrate_and_index(&context, &mut world_address, &mut ui);
         }
+        ui.set_status("👀 Watching for changes...");
+
         loop {
             let action = match rx.recv() {
-                Ok(Ok(events)) => events
-                    .iter()
-                    .map(|event| process_event(event, &mut context))
-                    .last()
-                    .unwrap_or(DevAction::None),
+                Ok(Ok(events)) => {
+                    let changed = events.iter().find_map(|event| match handle_event(event) {
+                        DevAction::Build(path) => Some(path.display().to_string()),
+                        DevAction::Reload => Some("Scarb.toml".to_string()),
+                        DevAction::None => None,
+                    });
+                    if let Some(changed) = changed {
+                        ui.set_status(format!("📦 {changed} changed, rebuilding..."));
+                    }
+
+                    let processed = ui.suspend(|| {
+                        events
+                            .iter()
+                            .map(|event| process_event(event, &mut context))
+                            .collect::<Result<Vec<_>>>()
+                    });
+                    match processed {
+                        Ok(actions) => actions.into_iter().last().unwrap_or(DevAction::None),
+                        Err(error) => {
+                            ui.suspend(|| log::error!("Error: {error:?}"));
+                            DevAction::None
+                        }
+                    }
+                }
                 Ok(Err(_)) => DevAction::None,
                 Err(error) => {
-                    log::error!("Error: {error:?}");
+                    ui.suspend(|| log::error!("Error: {error:?}"));
                     break;
                 }
             };
 
-            if action != DevAction::None && build(&mut context).is_ok() {
-                match context.ws.config().tokio_handle().block_on(migrate(
-                    world_address,
-                    &account,
-                    name.clone(),
-                    &context.ws,
-                    previous_manifest.clone(),
-                )) {
-                    Ok((manifest, address)) => {
-                        previous_manifest = Some(manifest);
-                        world_address = address;
-                    }
-                    Err(error) => {
-                        log::error!("Error: {error:?}");
-                    }
+            if action != DevAction::None {
+                if matches!(ui.build(&mut context), Ok(true)) {
+                    migrate_and_index(&context, &mut world_address, &mut ui);
                 }
+                ui.set_status("👀 Watching for changes...");
             }
         }
-        result
+        result.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_test_utils::compiler::{build_test_config, copy_project};
+    use dojo_test_utils::sequencer::{
+        get_default_test_starknet_config, SequencerConfig, TestSequencer,
+    };
+    use starknet::accounts::Account;
+    use starknet::core::types::{BlockId, BlockTag};
+
+    use super::*;
+
+    #[test]
+    fn rebuild_only_when_sources_change() {
+        let (project_dir, manifest_path) =
+            copy_project("../../examples/spawn-and-move/Scarb.toml").unwrap();
+        let config = build_test_config(manifest_path.as_str()).unwrap();
+        let mut context = load_context(&config).unwrap();
+
+        assert!(build(&mut context).unwrap(), "the first build writes the manifest");
+
+        // Saving a file without changes doesn't change the manifest.
+        let utils_path = project_dir.path().join("src/utils.cairo");
+        let event = DebouncedEvent { path: utils_path.clone(), kind: DebouncedEventKind::Any };
+        assert!(
+            process_event(&event, &mut context).unwrap() == DevAction::Build(utils_path.clone())
+        );
+        assert!(!build(&mut context).unwrap());
+
+        // The edited file is reloaded in the database of the session.
+        let utils = fs::read_to_string(&utils_path).unwrap();
+        fs::write(&utils_path, utils.replace("position.vec.x -= 1;", "position.vec.x -= 2;"))
+            .unwrap();
+        process_event(&event, &mut context).unwrap();
+        assert!(build(&mut context).unwrap());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn breaking_schema_changes_are_not_migrated() {
+        let (project_dir, manifest_path) =
+            copy_project("../../examples/spawn-and-move/Scarb.toml").unwrap();
+        let config = build_test_config(manifest_path.as_str()).unwrap();
+        let mut context = load_context(&config).unwrap();
+        build(&mut context).unwrap();
+
+        let sequencer =
+            TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config())
+                .await;
+        let mut account = sequencer.account();
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+        let name = Some("dojo_examples".to_string());
+        let world_address =
+            migrate(None, &account, name.clone(), &context.ws, false).await.unwrap();
+
+        // Changing the type of a member makes the existing entities unreadable.
+        let models_path = project_dir.path().join("src/models.cairo");
+        let models = fs::read_to_string(&models_path).unwrap();
+        fs::write(&models_path, models.replace("remaining: u8,", "remaining: u16,")).unwrap();
+        let event = DebouncedEvent { path: models_path, kind: DebouncedEventKind::Any };
+        process_event(&event, &mut context).unwrap();
+        assert!(build(&mut context).unwrap());
+
+        let err =
+            migrate(world_address, &account, name.clone(), &context.ws, false).await.unwrap_err();
+        assert!(err.to_string().contains("Breaking schema changes in models Moves"), "{err}");
+
+        let moves_class = |manifest: Manifest| {
+            manifest.models.into_iter().find(|m| m.name == "Moves").unwrap().class_hash
+        };
+        let remote =
+            Manifest::load_from_remote(account.provider(), world_address.unwrap()).await.unwrap();
+        let profile = context.ws.config().profile();
+        let target_dir = context.ws.target_dir().path_unchecked().join(profile.as_str());
+        let local = Manifest::load_from_path(target_dir.join("manifest.json")).unwrap();
+        assert_ne!(
+            moves_class(remote),
+            moves_class(local.clone()),
+            "Moves shouldn't be re-registered"
+        );
+
+        // The change is migrated once the user opts in.
+        migrate(world_address, &account, name, &context.ws, true).await.unwrap();
+        let remote =
+            Manifest::load_from_remote(account.provider(), world_address.unwrap()).await.unwrap();
+        assert_eq!(moves_class(remote), moves_class(local));
+
+        sequencer.stop().unwrap();
     }
 }
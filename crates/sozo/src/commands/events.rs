@@ -1,12 +1,10 @@
-use std::collections::HashMap;
-
 use anyhow::{anyhow, Result};
-use cairo_lang_starknet::abi::{self, Event};
 use clap::Parser;
 use dojo_world::manifest::Manifest;
-use dojo_world::metadata::dojo_metadata_from_workspace;
 use scarb::core::Config;
+use starknet::core::types::FieldElement;
 
+use super::load_env;
 use super::options::starknet::StarknetOptions;
 use super::options::world::WorldOptions;
 use crate::ops::events;
@@ -26,15 +24,37 @@ pub struct EventsArgs {
     pub to_block: Option<u64>,
 
     #[arg(short, long)]
-    #[arg(help = "Number of events to return per page")]
+    #[arg(default_value_t = 100)]
+    #[arg(help = "Number of events to fetch per page")]
     pub chunk_size: u64,
 
     #[arg(long)]
-    #[arg(help = "Continuation string to be passed for rpc request")]
+    #[arg(help = "Continuation string to be passed for the first rpc request")]
     pub continuation_token: Option<String>,
 
     #[arg(long)]
-    #[arg(help = "Print values as raw json")]
+    #[arg(conflicts_with = "to_block")]
+    #[arg(help = "Keep polling for new events once the past ones are printed")]
+    pub follow: bool,
+
+    #[arg(long)]
+    #[arg(value_name = "SECONDS")]
+    #[arg(default_value_t = 2)]
+    #[arg(help = "Interval between two polls for new events when following")]
+    pub interval: u64,
+
+    #[arg(short, long)]
+    #[arg(help = "Only show the records setting the values of this model")]
+    pub model: Option<String>,
+
+    #[arg(short, long)]
+    #[arg(value_delimiter = ',')]
+    #[arg(help = "Only show the records of the entities whose keys start with these ones. Comma \
+                  seperated values e.g., 0x12345,0x69420.")]
+    pub keys: Vec<FieldElement>,
+
+    #[arg(long)]
+    #[arg(help = "Print the events as newline delimited json")]
     pub json: bool,
 
     #[command(flatten)]
@@ -45,7 +65,7 @@ pub struct EventsArgs {
 }
 
 impl EventsArgs {
-    pub fn run(self, config: &Config) -> Result<()> {
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
         let target_dir = config.target_dir_override().unwrap();
         let manifest_path = target_dir.join(config.profile().as_str()).join("manifest.json");
 
@@ -54,52 +74,19 @@ impl EventsArgs {
         }
 
         let manifest = Manifest::load_from_path(manifest_path)?;
-        let events = extract_events(&manifest);
-        let env_metadata = if config.manifest_path().exists() {
-            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
-
-            // TODO: Check the updated scarb way to read profile specific values
-            dojo_metadata_from_workspace(&ws).and_then(|inner| inner.env().cloned())
-        } else {
-            None
+        let env_metadata = load_env(config, env)?;
+
+        // Without a World, the events of every contract are listed and the records aren't decoded.
+        let world_address = match self.world.address(env_metadata.as_ref()) {
+            Ok(address) => Some(address),
+            Err(err) => {
+                config.ui().warn(format!("{err} The model records won't be decoded."));
+                None
+            }
         };
-        config.tokio_handle().block_on(events::execute(self, env_metadata, events))
-    }
-}
-
-fn extract_events(manifest: &Manifest) -> HashMap<String, Vec<Event>> {
-    fn inner_helper(_events: &mut HashMap<String, Vec<Event>>, _contract: &Option<abi::Contract>) {
-        // if let Some(contract) = contract {
-        // for item in &contract.items {
-        //     if let Item::Event(e) = item {
-        //         match e.kind {
-        //             abi::EventKind::Struct { .. } => {
-        //                 let event_name =
-        //                     starknet_keccak(e.name.split("::").last().unwrap().as_bytes());
-        //                 let vec = events.entry(event_name.to_string()).or_default();
-        //                 vec.push(e.clone());
-        //             }
-        //             abi::EventKind::Enum { .. } => (),
-        //         }
-        //     }
-        // }
-        // }
-    }
 
-    let mut events_map = HashMap::new();
-
-    inner_helper(&mut events_map, &manifest.world.abi);
-    inner_helper(&mut events_map, &manifest.executor.abi);
-
-    for contract in &manifest.contracts {
-        inner_helper(&mut events_map, &contract.abi);
+        config.tokio_handle().block_on(events::execute(self, env_metadata, manifest, world_address))
     }
-
-    for model in &manifest.models {
-        inner_helper(&mut events_map, &model.abi);
-    }
-
-    events_map
 }
 
 #[cfg(test)]
@@ -112,5 +99,26 @@ mod test {
         assert!(arg.from_block.is_none());
         assert!(arg.to_block.is_none());
         assert!(arg.chunk_size == 1);
+        assert!(!arg.follow);
+    }
+
+    #[test]
+    fn follow_with_filters() {
+        let arg = EventsArgs::parse_from([
+            "event",
+            "--follow",
+            "--model",
+            "Position",
+            "--keys",
+            "0x123,0x1",
+            "--json",
+        ]);
+        assert!(arg.follow);
+        assert_eq!(arg.interval, 2);
+        assert_eq!(arg.chunk_size, 100);
+        assert_eq!(arg.model.as_deref(), Some("Position"));
+        assert_eq!(arg.keys, vec![FieldElement::from(0x123_u16), FieldElement::ONE]);
+
+        assert!(EventsArgs::try_parse_from(["event", "--follow", "--to-block", "10"]).is_err());
     }
 }
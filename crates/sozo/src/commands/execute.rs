@@ -1,17 +1,18 @@
 use anyhow::Result;
 use clap::Args;
-use dojo_world::metadata::dojo_metadata_from_workspace;
 use scarb::core::Config;
 use starknet::core::types::FieldElement;
 
+use super::load_env_and_manifest;
 use super::options::account::AccountOptions;
 use super::options::starknet::StarknetOptions;
+use super::options::world::WorldOptions;
 use crate::ops::execute;
 
 #[derive(Debug, Args)]
 #[command(about = "Execute a system with the given calldata.")]
 pub struct ExecuteArgs {
-    #[arg(help = "The address of the contract to be executed.")]
+    #[arg(help = "The address or the name of the contract to be executed.")]
     pub contract: String,
 
     #[arg(help = "The name of the entrypoint to be executed.")]
@@ -19,10 +20,20 @@ pub struct ExecuteArgs {
 
     #[arg(short, long)]
     #[arg(value_delimiter = ',')]
+    #[arg(conflicts_with = "args")]
     #[arg(help = "The calldata to be passed to the system. Comma seperated values e.g., \
                   0x12345,0x69420.")]
     pub calldata: Vec<FieldElement>,
 
+    #[arg(short, long)]
+    #[arg(help = "The arguments to be passed to the system, written as Cairo values and \
+                  serialized using the ABI of the contract e.g., 'Direction::Left, 10u64, \
+                  \"name\"'.")]
+    pub args: Option<String>,
+
+    #[command(flatten)]
+    pub world: WorldOptions,
+
     #[command(flatten)]
     pub starknet: StarknetOptions,
 
@@ -31,16 +42,28 @@ pub struct ExecuteArgs {
 }
 
 impl ExecuteArgs {
-    pub fn run(self, config: &Config) -> Result<()> {
-        let env_metadata = if config.manifest_path().exists() {
-            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
-
-            // TODO: Check the updated scarb way to read profile specific values
-            dojo_metadata_from_workspace(&ws).and_then(|inner| inner.env().cloned())
-        } else {
-            None
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let (env_metadata, manifest) = load_env_and_manifest(config, env)?;
+        config.tokio_handle().block_on(execute::execute(self, env_metadata, manifest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_typed_args() {
+        let args = ["sozo", "execute", "actions", "spawn", "--args", "Direction::Left, 10u64"];
+        let Commands::Execute(args) = SozoArgs::parse_from(args).command else {
+            panic!("expected the execute command")
         };
+        assert_eq!(args.contract, "actions");
+        assert_eq!(args.args.as_deref(), Some("Direction::Left, 10u64"));
 
-        config.tokio_handle().block_on(execute::execute(self, env_metadata))
+        let args = ["sozo", "execute", "actions", "spawn", "--args", "1", "--calldata", "0x1"];
+        assert!(SozoArgs::try_parse_from(args).is_err());
     }
 }
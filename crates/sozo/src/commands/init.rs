@@ -3,17 +3,29 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{fs, io};
 
-use anyhow::{ensure, Result};
+use anyhow::{bail, ensure, Result};
 use clap::Args;
 use scarb::core::Config;
 
+use crate::ops::scaffold::{self, TEMPLATES};
+
 #[derive(Args, Debug)]
 pub struct InitArgs {
     #[arg(help = "Target directory")]
     path: Option<PathBuf>,
 
-    #[arg(help = "Parse a full git url or a url path", default_value = "dojoengine/dojo-starter")]
+    #[arg(default_value = "ecs")]
+    #[arg(help = "An embedded template (`minimal`, `ecs` or `messaging`), or the full git url \
+                  or url path of a template to clone")]
     template: String,
+
+    #[arg(long)]
+    #[arg(help = "Name of the package. Defaults to the name of the target directory.")]
+    name: Option<String>,
+
+    #[arg(long)]
+    #[arg(help = "Name of the World. Defaults to the name of the package.")]
+    world_name: Option<String>,
 }
 
 impl InitArgs {
@@ -41,26 +53,51 @@ impl InitArgs {
         config.ui().print("\n\n ⛩️ ====== STARTING ====== ⛩️ \n");
         config.ui().print("Setting up project directory tree...");
 
-        let template = self.template;
-        let repo_url = if template.starts_with("https://") {
-            template
-        } else {
-            "https://github.com/".to_string() + &template
-        };
+        match scaffold::find_template(&self.template) {
+            Some(template) => {
+                config.ui().print(format!(
+                    "Creating project from the `{}` template ({})...",
+                    template.name,
+                    template.description.to_lowercase()
+                ));
+
+                let package_name = match self.name {
+                    Some(name) => name,
+                    None => scaffold::package_name(
+                        &target_dir.file_name().unwrap_or_default().to_string_lossy(),
+                    ),
+                };
+                let world_name = self.world_name.unwrap_or_else(|| package_name.clone());
+
+                template.write(&target_dir, &package_name, &world_name)?;
+            }
 
-        clone_repo(&repo_url, &target_dir, config)?;
+            None => {
+                let template = self.template;
+                let repo_url = if template.starts_with("https://") {
+                    template
+                } else if template.contains('/') {
+                    "https://github.com/".to_string() + &template
+                } else {
+                    let names = TEMPLATES.iter().map(|t| t.name).collect::<Vec<_>>().join(", ");
+                    bail!("Unknown template `{template}`, the embedded ones are: {names}.");
+                };
 
-        // Navigate to the newly cloned repo.
-        let initial_dir = current_dir()?;
-        set_current_dir(&target_dir)?;
+                clone_repo(&repo_url, &target_dir, config)?;
 
-        // Modify the git history.
-        modify_git_history(&repo_url)?;
+                // Navigate to the newly cloned repo.
+                let initial_dir = current_dir()?;
+                set_current_dir(&target_dir)?;
 
-        config.ui().print("\n🎉 Successfully created a new ⛩️ Dojo project!");
+                // Modify the git history.
+                modify_git_history(&repo_url)?;
 
-        // Navigate back.
-        set_current_dir(initial_dir)?;
+                // Navigate back.
+                set_current_dir(initial_dir)?;
+            }
+        }
+
+        config.ui().print("\n🎉 Successfully created a new ⛩️ Dojo project!");
 
         config.ui().print(
             "\n====== SETUP COMPLETE! ======\n\n\nTo start using your new project, try running: \
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use clap::Args;
+use scarb::core::Config;
+
+use super::load_env_and_manifest;
+use super::options::starknet::StarknetOptions;
+use super::options::world::WorldOptions;
+use crate::ops::inspect;
+
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    #[arg(long)]
+    #[arg(help = "Output the report as JSON.")]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub world: WorldOptions,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+}
+
+impl InspectArgs {
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        // The World is compared with the local build when run in a built project.
+        let (env_metadata, local_manifest) = load_env_and_manifest(config, env)?;
+
+        let world_address = self.world.address(env_metadata.as_ref())?;
+        let provider = self.starknet.provider(env_metadata.as_ref())?;
+
+        config.tokio_handle().block_on(inspect::execute(
+            world_address,
+            &provider,
+            local_manifest,
+            self.json,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use starknet::core::types::FieldElement;
+
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_inspect_args() {
+        let args = ["sozo", "inspect", "--world", "0x1234", "--json"];
+        let Commands::Inspect(args) = SozoArgs::parse_from(args).command else {
+            panic!("expected the inspect command")
+        };
+        assert!(args.json);
+        assert_eq!(args.world.world_address, Some(FieldElement::from(0x1234_u32)));
+    }
+}
@@ -1,8 +1,11 @@
 use anyhow::Result;
-use clap::Args;
+use camino::Utf8PathBuf;
+use clap::{Args, Subcommand};
 use scarb::core::{Config, TargetKind};
 use scarb::ops::CompileOpts;
+use starknet::core::types::FieldElement;
 
+use super::env_from_workspace;
 use super::options::account::AccountOptions;
 use super::options::starknet::StarknetOptions;
 use super::options::transaction::TransactionOptions;
@@ -10,7 +13,11 @@ use super::options::world::WorldOptions;
 use crate::ops::migration;
 
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct MigrateArgs {
+    #[command(subcommand)]
+    pub command: Option<MigrateCommand>,
+
     #[arg(short, long)]
     #[arg(help = "Perform a dry run and outputs the plan to be executed.")]
     pub plan: bool,
@@ -21,6 +28,36 @@ pub struct MigrateArgs {
                        contract to avoid address conflicts.")]
     pub name: Option<String>,
 
+    #[arg(long)]
+    #[arg(help = "Resume a previously failed migration from its journal.")]
+    #[arg(long_help = "Resume a previously failed migration from its journal. The steps that \
+                       were completed before the failure are skipped.")]
+    pub resume: bool,
+
+    #[arg(long)]
+    #[arg(help = "Migrate even if model schema changes break the existing entities.")]
+    #[arg(long_help = "Migrate even if model schema changes break the existing entities. \
+                       Entities stored with the previous schema may become unreadable.")]
+    pub allow_breaking: bool,
+
+    #[arg(long)]
+    #[arg(value_name = "PATH")]
+    #[arg(conflicts_with_all = ["plan", "resume"])]
+    #[arg(requires = "max_fee")]
+    #[arg(help = "Export the transactions of the migration to a plan, without sending them.")]
+    #[arg(long_help = "Export the transactions of the migration to a plan, without sending \
+                       them. The plan is signed offline with `sozo migrate sign`, then sent \
+                       with `sozo migrate broadcast`. Only the address of the account is needed.")]
+    pub export_plan: Option<Utf8PathBuf>,
+
+    #[arg(long)]
+    #[arg(requires = "export_plan")]
+    #[arg(help = "Maximum fee of each transaction of the exported plan.")]
+    #[arg(long_help = "Maximum fee of each transaction of the exported plan. Fees can't be \
+                       estimated without signing the transactions, so they are fixed when the \
+                       plan is exported.")]
+    pub max_fee: Option<FieldElement>,
+
     #[command(flatten)]
     pub world: WorldOptions,
 
@@ -34,9 +71,42 @@ pub struct MigrateArgs {
     pub transaction: TransactionOptions,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    #[command(about = "Sign the transactions of an exported migration plan.")]
+    Sign {
+        #[arg(help = "Path of the plan exported with `sozo migrate --export-plan`.")]
+        plan: Utf8PathBuf,
+
+        #[arg(short, long)]
+        #[arg(value_name = "PATH")]
+        #[arg(help = "Where to write the signed plan. Defaults to the plan itself.")]
+        output: Option<Utf8PathBuf>,
+
+        #[command(flatten)]
+        account: AccountOptions,
+    },
+
+    #[command(about = "Send the transactions of a signed migration plan.")]
+    Broadcast {
+        #[arg(help = "Path of the plan signed with `sozo migrate sign`.")]
+        plan: Utf8PathBuf,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+    },
+}
+
 impl MigrateArgs {
-    pub fn run(mut self, config: &Config) -> Result<()> {
+    pub fn run(mut self, config: &Config, env: Option<&str>) -> Result<()> {
+        // The plan is signed where the key is, which may not have the project.
+        if let Some(MigrateCommand::Sign { plan, output, account }) = &self.command {
+            let output = output.clone().unwrap_or_else(|| plan.clone());
+            return migration::sign_plan(config, plan, &output, account);
+        }
+
         let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+        let env_metadata = env_from_workspace(&ws, env)?;
 
         // If `name` was not specified use package name from `Scarb.toml` file if it exists
         if self.name.is_none() {
@@ -48,6 +118,18 @@ impl MigrateArgs {
         let target_dir = ws.target_dir().path_existent().unwrap();
         let target_dir = target_dir.join(ws.config().profile().as_str());
 
+        // Broadcasting a plan doesn't need the project to be built.
+        if let Some(MigrateCommand::Broadcast { plan, starknet }) = self.command.take() {
+            return ws.config().tokio_handle().block_on(migration::broadcast_plan(
+                &ws,
+                &plan,
+                starknet,
+                target_dir,
+                env,
+                env_metadata.as_ref(),
+            ));
+        }
+
         if !target_dir.join("manifest.json").exists() {
             let packages = ws.members().map(|p| p.id).collect();
             scarb::ops::compile(
@@ -57,10 +139,56 @@ impl MigrateArgs {
             )?;
         }
 
-        // TODO: Check the updated scarb way to read profile specific values
-
-        ws.config().tokio_handle().block_on(migration::execute(&ws, self, target_dir))?;
+        ws.config().tokio_handle().block_on(migration::execute(
+            &ws,
+            self,
+            target_dir,
+            env,
+            env_metadata,
+        ))?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_plan_commands() {
+        let args = ["sozo", "migrate", "--export-plan", "plan.json", "--max-fee", "0x1000"];
+        let Commands::Migrate(args) = SozoArgs::parse_from(args).command else {
+            panic!("expected the migrate command")
+        };
+        assert_eq!(args.export_plan, Some(Utf8PathBuf::from("plan.json")));
+        assert_eq!(args.max_fee, Some(FieldElement::from(0x1000_u32)));
+
+        let args = ["sozo", "migrate", "--export-plan", "plan.json"];
+        assert!(SozoArgs::try_parse_from(args).is_err());
+        let args = ["sozo", "migrate", "--export-plan", "plan.json", "--max-fee", "1", "--plan"];
+        assert!(SozoArgs::try_parse_from(args).is_err());
+
+        let args = ["sozo", "migrate", "sign", "plan.json", "--keystore", "key.json"];
+        let Commands::Migrate(args) = SozoArgs::parse_from(args).command else {
+            panic!("expected the migrate command")
+        };
+        assert!(matches!(
+            args.command,
+            Some(MigrateCommand::Sign { output: None, account, .. })
+                if account.keystore_path.as_deref() == Some("key.json")
+        ));
+
+        let args = ["sozo", "migrate", "broadcast", "signed.json"];
+        let Commands::Migrate(args) = SozoArgs::parse_from(args).command else {
+            panic!("expected the migrate command")
+        };
+        assert!(matches!(
+            args.command,
+            Some(MigrateCommand::Broadcast { plan, .. }) if plan == "signed.json"
+        ));
+    }
+}
@@ -1,36 +1,86 @@
 use anyhow::Result;
-use scarb::core::Config;
+use dojo_world::manifest::Manifest;
+use dojo_world::metadata::{dojo_metadata_from_workspace, Environment, Metadata};
+use scarb::core::{Config, Workspace};
 
 use crate::args::Commands;
 
+pub(crate) mod account;
 pub(crate) mod auth;
 pub(crate) mod build;
+pub(crate) mod call;
 pub(crate) mod completions;
 pub(crate) mod dev;
 pub(crate) mod events;
 pub(crate) mod execute;
 pub(crate) mod init;
+pub(crate) mod inspect;
 pub(crate) mod migrate;
 pub(crate) mod model;
+pub(crate) mod new;
 pub(crate) mod options;
 pub(crate) mod register;
+pub(crate) mod run;
 pub(crate) mod test;
 
 // copy of non pub functions from scarb
 pub(crate) mod scarb_internal;
 
-pub fn run(command: Commands, config: &Config) -> Result<()> {
+pub fn run(command: Commands, config: &Config, env: Option<&str>) -> Result<()> {
     match command {
         Commands::Init(args) => args.run(config),
+        Commands::New(args) => args.run(config),
         Commands::Test(args) => args.run(config),
         Commands::Build(args) => args.run(config),
-        Commands::Migrate(args) => args.run(config),
-        Commands::Dev(args) => args.run(config),
-        Commands::Auth(args) => args.run(config),
-        Commands::Execute(args) => args.run(config),
-        Commands::Model(args) => args.run(config),
-        Commands::Register(args) => args.run(config),
-        Commands::Events(args) => args.run(config),
+        Commands::Migrate(args) => args.run(config, env),
+        Commands::Dev(args) => args.run(config, env),
+        Commands::Auth(args) => args.run(config, env),
+        Commands::Account(args) => args.run(config, env),
+        Commands::Execute(args) => args.run(config, env),
+        Commands::Call(args) => args.run(config, env),
+        Commands::Run(args) => args.run(config, env),
+        Commands::Model(args) => args.run(config, env),
+        Commands::Register(args) => args.run(config, env),
+        Commands::Events(args) => args.run(config, env),
+        Commands::Inspect(args) => args.run(config, env),
         Commands::Completions(args) => args.run(),
     }
 }
+
+/// Loads the environment selected with `--env`, or the default one, from the dojo metadata of
+/// the workspace at the manifest path.
+pub(crate) fn load_env(config: &Config, env: Option<&str>) -> Result<Option<Environment>> {
+    if !config.manifest_path().exists() {
+        return Metadata::default().environment(env);
+    }
+
+    let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+    env_from_workspace(&ws, env)
+}
+
+pub(crate) fn env_from_workspace(
+    ws: &Workspace<'_>,
+    env: Option<&str>,
+) -> Result<Option<Environment>> {
+    dojo_metadata_from_workspace(ws)?.unwrap_or_default().environment(env)
+}
+
+/// Loads the selected environment and the local manifest of the current profile, if any.
+pub(crate) fn load_env_and_manifest(
+    config: &Config,
+    env: Option<&str>,
+) -> Result<(Option<Environment>, Option<Manifest>)> {
+    if !config.manifest_path().exists() {
+        return Ok((load_env(config, env)?, None));
+    }
+
+    let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+    let env_metadata = env_from_workspace(&ws, env)?;
+
+    let manifest_path = ws.target_dir().path_unchecked().join(config.profile().as_str());
+    let manifest_path = manifest_path.join("manifest.json");
+    let manifest =
+        if manifest_path.exists() { Some(Manifest::load_from_path(manifest_path)?) } else { None };
+
+    Ok((env_metadata, manifest))
+}
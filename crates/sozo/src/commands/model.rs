@@ -1,9 +1,9 @@
 use anyhow::Result;
-use clap::{Args, Subcommand};
-use dojo_world::metadata::dojo_metadata_from_workspace;
+use clap::{Args, Subcommand, ValueEnum};
 use scarb::core::Config;
 use starknet::core::types::FieldElement;
 
+use super::load_env;
 use super::options::starknet::StarknetOptions;
 use super::options::world::WorldOptions;
 use crate::ops::model;
@@ -14,8 +14,30 @@ pub struct ModelArgs {
     command: ModelCommands,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ModelCommands {
+    #[command(about = "List the models registered to the World")]
+    List {
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[arg(short, long)]
+        #[arg(value_enum, default_value_t = OutputFormat::Table)]
+        #[arg(help_heading = "Display options")]
+        format: OutputFormat,
+    },
+
     #[command(about = "Retrieve the class hash of a model")]
     ClassHash {
         #[arg(help = "The name of the model")]
@@ -54,25 +76,73 @@ pub enum ModelCommands {
         #[arg(help = "Comma seperated values e.g., 0x12345,0x69420,...")]
         keys: Vec<FieldElement>,
 
+        #[arg(short, long)]
+        #[arg(help = "Keep polling the entity and print it again whenever it changes, until \
+                      interrupted")]
+        watch: bool,
+
+        #[arg(long)]
+        #[arg(value_name = "SECONDS")]
+        #[arg(default_value_t = 2)]
+        #[arg(help = "Interval between two polls of the entity when watching")]
+        interval: u64,
+
         #[command(flatten)]
         world: WorldOptions,
 
         #[command(flatten)]
         starknet: StarknetOptions,
     },
+
+    #[command(about = "Dump all the entities of a model")]
+    Entities {
+        #[arg(help = "The name of the model")]
+        name: String,
+
+        #[command(flatten)]
+        world: WorldOptions,
+
+        #[command(flatten)]
+        starknet: StarknetOptions,
+
+        #[arg(short, long)]
+        #[arg(value_enum, default_value_t = OutputFormat::Table)]
+        #[arg(help_heading = "Display options")]
+        format: OutputFormat,
+    },
 }
 
 impl ModelArgs {
-    pub fn run(self, config: &Config) -> Result<()> {
-        let env_metadata = if config.manifest_path().exists() {
-            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
-
-            // TODO: Check the updated scarb way to read profile specific values
-            dojo_metadata_from_workspace(&ws).and_then(|inner| inner.env().cloned())
-        } else {
-            None
-        };
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let env_metadata = load_env(config, env)?;
 
         config.tokio_handle().block_on(model::execute(self.command, env_metadata))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_entities_and_watch() {
+        let args = ["sozo", "model", "entities", "Position", "--format", "csv"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Model(ModelArgs {
+                command: ModelCommands::Entities { name, format: OutputFormat::Csv, .. }
+            }) if name == "Position"
+        ));
+
+        let args = ["sozo", "model", "get", "Position", "0x1", "--watch"];
+        assert!(matches!(
+            SozoArgs::parse_from(args).command,
+            Commands::Model(ModelArgs {
+                command: ModelCommands::Get { watch: true, interval: 2, .. }
+            })
+        ));
+    }
+}
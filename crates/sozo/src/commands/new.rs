@@ -0,0 +1,63 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use scarb::core::Config;
+
+use crate::ops::scaffold;
+
+#[derive(Debug, Args)]
+pub struct NewArgs {
+    #[command(subcommand)]
+    pub command: NewCommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NewCommand {
+    #[command(about = "Add a model and its test to the `models` module of the project.")]
+    Model {
+        #[arg(help = "Name of the model, like `Position`.")]
+        name: String,
+    },
+
+    #[command(about = "Add a system and its test to the `systems` module of the project.")]
+    System {
+        #[arg(help = "Name of the system contract, like `actions`.")]
+        name: String,
+    },
+}
+
+impl NewArgs {
+    pub fn run(self, config: &Config) -> Result<()> {
+        let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
+        let src_dir = ws.current_package()?.root().join("src");
+
+        let path = match self.command {
+            NewCommand::Model { name } => scaffold::new_model(src_dir.as_std_path(), &name)?,
+            NewCommand::System { name } => scaffold::new_system(src_dir.as_std_path(), &name)?,
+        };
+
+        config.ui().print(format!("Created {}", path.display()));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{Commands, SozoArgs};
+
+    #[test]
+    fn parse_new_commands() {
+        assert!(matches!(
+            SozoArgs::parse_from(["sozo", "new", "model", "Position"]).command,
+            Commands::New(NewArgs { command: NewCommand::Model { name } }) if name == "Position"
+        ));
+
+        assert!(matches!(
+            SozoArgs::parse_from(["sozo", "new", "system", "actions"]).command,
+            Commands::New(NewArgs { command: NewCommand::System { name } }) if name == "actions"
+        ));
+    }
+}
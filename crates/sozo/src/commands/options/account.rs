@@ -63,7 +63,7 @@ impl AccountOptions {
         ))
     }
 
-    fn signer(&self, env_metadata: Option<&Environment>) -> Result<LocalWallet> {
+    pub(crate) fn signer(&self, env_metadata: Option<&Environment>) -> Result<LocalWallet> {
         if let Some(private_key) = self
             .private_key
             .as_deref()
@@ -75,7 +75,11 @@ impl AccountOptions {
             )));
         }
 
-        if let Some(path) = &self.keystore_path {
+        if let Some(path) = self
+            .keystore_path
+            .as_deref()
+            .or_else(|| env_metadata.and_then(|env| env.keystore_path()))
+        {
             if let Some(password) = self
                 .keystore_password
                 .as_deref()
@@ -96,7 +100,10 @@ impl AccountOptions {
         ))
     }
 
-    fn account_address(&self, env_metadata: Option<&Environment>) -> Result<FieldElement> {
+    pub(crate) fn account_address(
+        &self,
+        env_metadata: Option<&Environment>,
+    ) -> Result<FieldElement> {
         if let Some(address) = self.account_address {
             Ok(address)
         } else if let Some(address) = env_metadata
@@ -1,4 +1,5 @@
 use clap::Args;
+use dojo_world::metadata::Environment;
 use dojo_world::migration::TxConfig;
 
 #[derive(Debug, Args, Clone)]
@@ -13,6 +14,17 @@ pub struct TransactionOptions {
     pub fee_estimate_multiplier: Option<f64>,
 }
 
+impl TransactionOptions {
+    /// Falls back to the transaction options of the environment for the ones not given.
+    pub fn with_env(self, env_metadata: Option<&Environment>) -> Self {
+        Self {
+            fee_estimate_multiplier: self
+                .fee_estimate_multiplier
+                .or_else(|| env_metadata.and_then(|env| env.fee_estimate_multiplier())),
+        }
+    }
+}
+
 impl From<TransactionOptions> for TxConfig {
     fn from(value: TransactionOptions) -> Self {
         Self { fee_estimate_multiplier: value.fee_estimate_multiplier }
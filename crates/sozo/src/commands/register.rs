@@ -1,9 +1,9 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
-use dojo_world::metadata::dojo_metadata_from_workspace;
 use scarb::core::Config;
 use starknet::core::types::FieldElement;
 
+use super::load_env;
 use super::options::account::AccountOptions;
 use super::options::starknet::StarknetOptions;
 use super::options::world::WorldOptions;
@@ -37,15 +37,8 @@ pub enum RegisterCommand {
 }
 
 impl RegisterArgs {
-    pub fn run(self, config: &Config) -> Result<()> {
-        let env_metadata = if config.manifest_path().exists() {
-            let ws = scarb::ops::read_workspace(config.manifest_path(), config)?;
-
-            // TODO: Check the updated scarb way to read profile specific values
-            dojo_metadata_from_workspace(&ws).and_then(|inner| inner.env().cloned())
-        } else {
-            None
-        };
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let env_metadata = load_env(config, env)?;
 
         config.tokio_handle().block_on(register::execute(self.command, env_metadata))
     }
@@ -0,0 +1,46 @@
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use scarb::core::Config;
+
+use super::load_env_and_manifest;
+use super::options::account::AccountOptions;
+use super::options::starknet::StarknetOptions;
+use super::options::world::WorldOptions;
+use crate::ops::script::{self, Script};
+
+#[derive(Debug, Args)]
+#[command(about = "Run the steps of a script against the World.")]
+pub struct RunArgs {
+    #[arg(help = "The path to the TOML script to run.")]
+    pub script: Utf8PathBuf,
+
+    #[command(flatten)]
+    pub world: WorldOptions,
+
+    #[command(flatten)]
+    pub starknet: StarknetOptions,
+
+    #[command(flatten)]
+    pub account: AccountOptions,
+}
+
+impl RunArgs {
+    pub fn run(self, config: &Config, env: Option<&str>) -> Result<()> {
+        let content = std::fs::read_to_string(&self.script)
+            .with_context(|| format!("Failed to read script {}", self.script))?;
+        let script: Script = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse script {}", self.script))?;
+
+        let (env_metadata, manifest) = load_env_and_manifest(config, env)?;
+
+        config.tokio_handle().block_on(script::run(
+            script,
+            self.world,
+            self.starknet,
+            self.account,
+            env_metadata,
+            manifest,
+        ))
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
 use cairo_lang_compiler::project::{ProjectConfig, ProjectConfigContent};
@@ -11,7 +11,8 @@ use cairo_lang_filesystem::ids::Directory;
 use cairo_lang_starknet::inline_macros::selector::SelectorMacro;
 use cairo_lang_starknet::plugin::StarkNetPlugin;
 use cairo_lang_test_plugin::TestPlugin;
-use cairo_lang_test_runner::{CompiledTestRunner, TestCompiler, TestRunConfig};
+use cairo_lang_test_runner::{TestCompiler, TestRunConfig};
+use camino::Utf8PathBuf;
 use clap::Args;
 use dojo_lang::compiler::{collect_core_crate_ids, collect_external_crate_ids, Props};
 use dojo_lang::inline_macros::emit::EmitMacro;
@@ -24,6 +25,8 @@ use scarb::core::Config;
 use scarb::ops;
 use tracing::trace;
 
+use crate::ops::test;
+
 /// Execute all unit tests of a local package.
 #[derive(Args, Clone)]
 pub struct TestArgs {
@@ -36,6 +39,12 @@ pub struct TestArgs {
     /// Should we run only the ignored tests.
     #[arg(long, default_value_t = false)]
     ignored: bool,
+    /// Print the steps, builtins and L1 gas used by every test and contract entrypoint.
+    #[arg(long, default_value_t = false)]
+    gas_report: bool,
+    /// Write the coverage of the tests as LCOV to the given file.
+    #[arg(long, value_name = "PATH", num_args = 0..=1, default_missing_value = "coverage.lcov")]
+    coverage: Option<Utf8PathBuf>,
 }
 
 impl TestArgs {
@@ -52,36 +61,34 @@ impl TestArgs {
         compilation_units.sort_by_key(|unit| unit.main_package_id);
         compilation_units.dedup_by_key(|unit| unit.main_package_id);
 
-        for unit in compilation_units {
-            let props: Props = unit.target().props()?;
-            let db = build_root_database(&unit)?;
-
-            let mut main_crate_ids = collect_main_crate_ids(&unit, &db);
-            let test_crate_ids = main_crate_ids.clone();
-
-            if unit.main_package_id.name.to_string() != "dojo" {
-                let core_crate_ids = collect_core_crate_ids(&db);
-                main_crate_ids.extend(core_crate_ids);
-            }
-
-            if let Some(external_contracts) = props.build_external_contracts {
-                main_crate_ids.extend(collect_external_crate_ids(&db, external_contracts));
-            }
-
-            if DiagnosticsReporter::stderr().check(&db) {
-                bail!("failed to compile");
-            }
+        let mut lcov = String::new();
 
+        for unit in compilation_units {
+            let compiler = build_test_compiler(&unit)?;
             let config = TestRunConfig {
                 filter: self.filter.clone(),
                 ignored: self.ignored,
                 include_ignored: self.include_ignored,
             };
 
-            let compiler = TestCompiler { db, main_crate_ids, test_crate_ids, starknet: true };
-            let runner = CompiledTestRunner { compiled: compiler.build()?, config };
-            runner.run()?;
+            let report = test::run_tests(compiler.build()?, &config)?;
+            report.print_summary();
+
+            if self.gas_report {
+                report.print_gas_report();
+            }
 
+            if let Some(path) = &self.coverage {
+                lcov.push_str(&test::lcov(
+                    &compiler.db,
+                    &compiler.test_crate_ids,
+                    &report.function_hits,
+                ));
+                std::fs::write(path, &lcov)
+                    .with_context(|| format!("failed to write the coverage to {path}"))?;
+            }
+
+            report.check()?;
             println!();
         }
 
@@ -89,6 +96,30 @@ impl TestArgs {
     }
 }
 
+/// Builds the database of the compilation unit and returns the compiler of its tests.
+pub(crate) fn build_test_compiler(unit: &CompilationUnit) -> Result<TestCompiler> {
+    let props: Props = unit.target().props()?;
+    let db = build_root_database(unit)?;
+
+    let mut main_crate_ids = collect_main_crate_ids(unit, &db);
+    let test_crate_ids = main_crate_ids.clone();
+
+    if unit.main_package_id.name.to_string() != "dojo" {
+        let core_crate_ids = collect_core_crate_ids(&db);
+        main_crate_ids.extend(core_crate_ids);
+    }
+
+    if let Some(external_contracts) = props.build_external_contracts {
+        main_crate_ids.extend(collect_external_crate_ids(&db, external_contracts));
+    }
+
+    if DiagnosticsReporter::stderr().check(&db) {
+        bail!("failed to compile");
+    }
+
+    Ok(TestCompiler { db, main_crate_ids, test_crate_ids, starknet: true })
+}
+
 pub(crate) fn build_root_database(unit: &CompilationUnit) -> Result<RootDatabase> {
     let mut b = RootDatabase::builder();
     b.with_project_config(build_project_config(unit)?);
@@ -46,7 +46,7 @@ fn cli_main(args: SozoArgs) -> Result<()> {
         .compilers(compilers)
         .build()?;
 
-    sozo::commands::run(args.command, &config)
+    sozo::commands::run(args.command, &config, args.env.as_deref())
 }
 
 fn verify_cairo_version_compatibility(manifest_path: &Utf8PathBuf) -> Result<()> {
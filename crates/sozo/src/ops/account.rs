@@ -0,0 +1,390 @@
+use anyhow::{anyhow, bail, Context, Result};
+use camino::Utf8Path;
+use console::Term;
+use dojo_world::metadata::Environment;
+use dojo_world::utils::TransactionWaiter;
+use starknet::accounts::{AccountFactory, OpenZeppelinAccountFactory};
+use starknet::core::types::{BlockId, BlockTag, FieldElement, FunctionCall};
+use starknet::core::utils::{get_contract_address, get_selector_from_name};
+use starknet::providers::Provider;
+use starknet::signers::{Signer, SigningKey};
+use toml_edit::{value, Document, Item, Table};
+
+use crate::commands::account::AccountCommand;
+
+/// The scarb profile whose settings are the top level `[tool]` ones.
+const DEFAULT_PROFILE: &str = "dev";
+
+pub async fn execute(
+    command: AccountCommand,
+    env_metadata: Option<Environment>,
+    manifest_path: &Utf8Path,
+    profile: &str,
+    env: Option<&str>,
+) -> Result<()> {
+    let target = Target { profile, env };
+
+    match command {
+        AccountCommand::New { keystore, force, class_hash, salt } => {
+            if keystore.exists() && !force {
+                bail!("Keystore {keystore} already exists, use --force to overwrite it.");
+            }
+            check_account(manifest_path, target, Some(keystore.as_str()), force)?;
+
+            let password = match std::env::var("DOJO_KEYSTORE_PASSWORD") {
+                Ok(password) => password,
+                Err(_) => prompt_password()?,
+            };
+
+            let key = SigningKey::from_random();
+            key.save_as_keystore(&keystore, &password)
+                .with_context(|| format!("Failed to write keystore {keystore}"))?;
+
+            let public_key = key.verifying_key().scalar();
+            let address = get_contract_address(salt, class_hash, &[public_key], FieldElement::ZERO);
+
+            println!("Public key: {public_key:#x}");
+            println!("Account address: {address:#x}");
+            println!("Fund the account, then deploy it with `sozo account deploy`.");
+
+            save_account(manifest_path, target, address, Some(keystore.as_str()), force)?;
+        }
+
+        AccountCommand::Deploy { class_hash, salt, max_fee, force, starknet, account } => {
+            let keystore = account.keystore_path.as_deref();
+            check_account(manifest_path, target, keystore, force)?;
+
+            let provider = starknet.provider(env_metadata.as_ref())?;
+            let signer = account.signer(env_metadata.as_ref())?;
+            let chain_id = provider
+                .chain_id()
+                .await
+                .with_context(|| "Failed to retrieve network chain id.")?;
+
+            let factory =
+                OpenZeppelinAccountFactory::new(class_hash, chain_id, signer, &provider).await?;
+            let deployment = factory.deploy(salt);
+            let address = deployment.address();
+            println!("Account address: {address:#x}");
+
+            let deployment = match max_fee {
+                Some(max_fee) => deployment.max_fee(max_fee),
+                None => deployment,
+            };
+            let res = deployment
+                .send()
+                .await
+                .with_context(|| "Failed to deploy the account, is it funded?")?;
+            println!("Transaction: {:#x}", res.transaction_hash);
+
+            TransactionWaiter::new(res.transaction_hash, &provider).await?;
+            println!("Account deployed.");
+
+            save_account(manifest_path, target, address, keystore, force)?;
+        }
+
+        AccountCommand::Fetch { address, force, starknet, account } => {
+            let keystore = account.keystore_path.as_deref();
+            check_account(manifest_path, target, keystore, force)?;
+
+            let provider = starknet.provider(env_metadata.as_ref())?;
+            let signer = account.signer(env_metadata.as_ref())?;
+
+            provider
+                .get_class_hash_at(BlockId::Tag(BlockTag::Pending), address)
+                .await
+                .with_context(|| format!("No account deployed at {address:#x}"))?;
+
+            // Both the Cairo 0 and the Cairo 1 names of the entrypoint are tried.
+            let public_key = signer.get_public_key().await?.scalar();
+            let mut account_public_key = None;
+            for entrypoint in ["get_public_key", "getPublicKey"] {
+                let call = FunctionCall {
+                    contract_address: address,
+                    entry_point_selector: get_selector_from_name(entrypoint)?,
+                    calldata: vec![],
+                };
+
+                if let Ok(output) = provider.call(call, BlockId::Tag(BlockTag::Pending)).await {
+                    account_public_key = output.first().copied();
+                    break;
+                }
+            }
+
+            match account_public_key {
+                Some(key) if key == public_key => {}
+                Some(_) => bail!("The account at {address:#x} isn't owned by the given key."),
+                None if force => {
+                    println!("Public key of the account at {address:#x} unknown, saving it anyway.")
+                }
+                None => bail!(
+                    "Failed to read the public key of the account at {address:#x}, use --force to \
+                     save it without checking that it's owned by the given key."
+                ),
+            }
+
+            println!("Account address: {address:#x}");
+            save_account(manifest_path, target, address, keystore, force)?;
+        }
+
+        AccountCommand::List => {
+            let content = std::fs::read_to_string(manifest_path)
+                .with_context(|| format!("Failed to read {manifest_path}"))?;
+
+            for account in saved_accounts(&content)? {
+                let target = match &account.env {
+                    Some(env) => format!("{}, env {env}", account.profile),
+                    None => account.profile,
+                };
+                println!(
+                    "{target}: {} {}",
+                    account.address.as_deref().unwrap_or("-"),
+                    account.keystore.map(|k| format!("(keystore {k})")).unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt_password() -> Result<String> {
+    let term = Term::stderr();
+
+    term.write_str("Keystore password: ")?;
+    let password = term.read_secure_line()?;
+    term.write_str("Confirm password: ")?;
+    if term.read_secure_line()? != password {
+        bail!("Passwords don't match.");
+    }
+
+    Ok(password)
+}
+
+/// The environment an account is saved in: the default one of a profile, or a named one.
+#[derive(Clone, Copy)]
+struct Target<'a> {
+    profile: &'a str,
+    env: Option<&'a str>,
+}
+
+/// Checks that the account can be saved in the environment of `target`, before the keystore is
+/// written or the account deployed.
+fn check_account(
+    manifest_path: &Utf8Path,
+    target: Target<'_>,
+    keystore: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {manifest_path}"))?;
+    with_account(&content, target, FieldElement::ZERO, keystore, force)?;
+    Ok(())
+}
+
+/// Saves the account in the environment of `target` in the manifest, keeping its formatting.
+fn save_account(
+    manifest_path: &Utf8Path,
+    target: Target<'_>,
+    address: FieldElement,
+    keystore: Option<&str>,
+    force: bool,
+) -> Result<()> {
+    let content = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {manifest_path}"))?;
+    let content = with_account(&content, target, address, keystore, force)?;
+    std::fs::write(manifest_path, content)?;
+
+    match target.env {
+        Some(env) => println!(
+            "Account saved to the `{env}` environment of the `{}` profile of {manifest_path}.",
+            target.profile
+        ),
+        None => println!("Account saved to the `{}` profile of {manifest_path}.", target.profile),
+    }
+    Ok(())
+}
+
+/// Returns the manifest with the account saved in the environment of `target`.
+///
+/// A private key would take precedence over the keystore, so saving a keystore in an environment
+/// with a private key fails unless `force` is set, in which case the private key is removed.
+fn with_account(
+    content: &str,
+    target: Target<'_>,
+    address: FieldElement,
+    keystore: Option<&str>,
+    force: bool,
+) -> Result<String> {
+    let mut doc = content.parse::<Document>()?;
+
+    let env = env_table(&mut doc, target)?;
+    env["account_address"] = value(format!("{address:#x}"));
+    if let Some(keystore) = keystore {
+        if env.contains_key("private_key") && !force {
+            let env = match target.env {
+                Some(env) => format!("`{env}` environment of the `{}` profile", target.profile),
+                None => format!("`{}` profile", target.profile),
+            };
+            bail!(
+                "The {env} has a private key, which the keystore would replace. Remove it, or use \
+                 --force to replace it."
+            );
+        }
+        env["keystore_path"] = value(keystore);
+        env.remove("private_key");
+    }
+
+    Ok(doc.to_string())
+}
+
+/// Returns the `[tool.dojo.env]` table of the target, created if missing.
+fn env_table<'a>(doc: &'a mut Document, target: Target<'_>) -> Result<&'a mut Table> {
+    let mut path = vec![];
+    if target.profile != DEFAULT_PROFILE {
+        path.extend(["profile", target.profile]);
+    }
+    path.extend(["tool", "dojo", "env"]);
+    if let Some(env) = target.env {
+        path.push(env);
+    }
+
+    let mut table = doc.as_table_mut();
+    for (i, key) in path.iter().enumerate() {
+        let entry = table.entry(key).or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(i + 1 < path.len());
+            Item::Table(table)
+        });
+
+        table = entry
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("`{}` isn't a table in Scarb.toml.", path[..=i].join(".")))?;
+    }
+
+    Ok(table)
+}
+
+#[derive(Debug, PartialEq)]
+struct SavedAccount {
+    profile: String,
+    env: Option<String>,
+    address: Option<String>,
+    keystore: Option<String>,
+}
+
+fn saved_accounts(content: &str) -> Result<Vec<SavedAccount>> {
+    let doc = content.parse::<Document>()?;
+
+    let account = |profile: &str, name: Option<&str>, env: &Item| {
+        let field = |name: &str| env.get(name).and_then(Item::as_str).map(str::to_string);
+        let address = field("account_address");
+        let keystore = field("keystore_path");
+
+        (address.is_some() || keystore.is_some()).then(|| SavedAccount {
+            profile: profile.to_string(),
+            env: name.map(str::to_string),
+            address,
+            keystore,
+        })
+    };
+
+    let mut accounts = vec![];
+    let mut profile_accounts = |profile: &str, tool: Option<&Item>| {
+        let Some(env) = tool.and_then(|tool| tool.get("dojo")).and_then(|dojo| dojo.get("env"))
+        else {
+            return;
+        };
+
+        accounts.extend(account(profile, None, env));
+        // The named environments are the tables of the default one.
+        if let Some(env) = env.as_table_like() {
+            for (name, named) in env.iter().filter(|(_, item)| item.is_table_like()) {
+                accounts.extend(account(profile, Some(name), named));
+            }
+        }
+    };
+
+    profile_accounts(DEFAULT_PROFILE, doc.get("tool"));
+    if let Some(profiles) = doc.get("profile").and_then(Item::as_table) {
+        for (profile, item) in profiles.iter() {
+            profile_accounts(profile, item.get("tool"));
+        }
+    }
+
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[package]
+name = "dojo_examples"
+version = "0.3.3"
+
+# Local Katana.
+[tool.dojo.env]
+rpc_url = "http://localhost:5050/"
+private_key = "0x1800000000300000180000000000030000000000003006001800006600"
+"#;
+
+    const DEV: Target<'_> = Target { profile: "dev", env: None };
+
+    #[test]
+    fn save_account_per_profile() {
+        // The private key of the manifest is only replaced by the keystore if forced.
+        let address = FieldElement::from(0x1234_u32);
+        assert!(with_account(MANIFEST, DEV, address, Some("dev.json"), false).is_err());
+
+        let content = with_account(MANIFEST, DEV, address, Some("dev.json"), true).unwrap();
+        assert!(content.contains("# Local Katana."));
+        assert!(content.contains("account_address = \"0x1234\""));
+        assert!(!content.contains("private_key"));
+
+        let testnet = Target { profile: "testnet", env: None };
+        let content = with_account(
+            &content,
+            testnet,
+            FieldElement::from(0x5678_u32),
+            Some("testnet.json"),
+            false,
+        )
+        .unwrap();
+        assert!(content.contains("[profile.testnet.tool.dojo.env]"));
+
+        let sepolia = Target { profile: "dev", env: Some("sepolia") };
+        let content = with_account(
+            &content,
+            sepolia,
+            FieldElement::from(0x9abc_u32),
+            Some("sepolia.json"),
+            false,
+        )
+        .unwrap();
+        assert!(content.contains("[tool.dojo.env.sepolia]"));
+
+        let account =
+            |profile: &str, env: Option<&str>, address: &str, keystore: &str| SavedAccount {
+                profile: profile.to_string(),
+                env: env.map(str::to_string),
+                address: Some(address.to_string()),
+                keystore: Some(keystore.to_string()),
+            };
+        assert_eq!(
+            saved_accounts(&content).unwrap(),
+            vec![
+                account("dev", None, "0x1234", "dev.json"),
+                account("dev", Some("sepolia"), "0x9abc", "sepolia.json"),
+                account("testnet", None, "0x5678", "testnet.json"),
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_private_key_without_keystore() {
+        let content = with_account(MANIFEST, DEV, FieldElement::ONE, None, false).unwrap();
+        assert!(content.contains("private_key"));
+        assert!(content.contains("account_address = \"0x1\""));
+    }
+}
@@ -1,26 +1,292 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::{Context, Result};
-use dojo_world::contracts::world::WorldContract;
+use dojo_world::contracts::world::WorldContractReader;
 use dojo_world::metadata::Environment;
+use dojo_world::migration::auth::{AuthUpdate, WORLD_RESOURCE};
+use serde::Serialize;
+use starknet::accounts::Account;
+use starknet::core::types::{
+    BlockId, EmittedEvent, EventFilter, FieldElement, InvokeTransaction, Transaction,
+};
+use starknet::core::utils::parse_cairo_short_string;
+use starknet::macros::selector;
+use starknet::providers::Provider;
 
 use crate::commands::auth::AuthCommand;
+use crate::commands::options::account::AccountOptions;
+use crate::commands::options::starknet::StarknetOptions;
+use crate::commands::options::world::WorldOptions;
 
 pub async fn execute(command: AuthCommand, env_metadata: Option<Environment>) -> Result<()> {
     match command {
         AuthCommand::Writer { model, contract, world, starknet, account } => {
+            let update = AuthUpdate::GrantWriter { model, contract };
+            send_update(update, world, starknet, account, env_metadata.as_ref()).await?;
+        }
+        AuthCommand::RevokeWriter { model, contract, world, starknet, account } => {
+            let update = AuthUpdate::RevokeWriter { model, contract };
+            send_update(update, world, starknet, account, env_metadata.as_ref()).await?;
+        }
+        AuthCommand::GrantOwner { resource, owner, world, starknet, account } => {
+            let update = AuthUpdate::GrantOwner { resource, address: owner };
+            send_update(update, world, starknet, account, env_metadata.as_ref()).await?;
+        }
+        AuthCommand::RevokeOwner { resource, owner, world, starknet, account } => {
+            let update = AuthUpdate::RevokeOwner { resource, address: owner };
+            send_update(update, world, starknet, account, env_metadata.as_ref()).await?;
+        }
+        AuthCommand::List { from_block, chunk_size, json, world, starknet } => {
             let world_address = world.address(env_metadata.as_ref())?;
             let provider = starknet.provider(env_metadata.as_ref())?;
 
-            let account = account.account(provider, env_metadata.as_ref()).await?;
-            let world = WorldContract::new(world_address, &account);
-
-            let res = world
-                .grant_writer(&model, contract)
-                .await
-                .with_context(|| "Failed to send transaction")?;
+            let permissions =
+                fetch_permissions(&provider, world_address, from_block, chunk_size).await?;
 
-            println!("Transaction: {:#x}", res.transaction_hash);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&permissions)?);
+            } else {
+                permissions.print();
+            }
         }
     }
 
     Ok(())
 }
+
+/// Sends the transaction applying a permission update to the World.
+async fn send_update(
+    update: AuthUpdate,
+    world: WorldOptions,
+    starknet: StarknetOptions,
+    account: AccountOptions,
+    env_metadata: Option<&Environment>,
+) -> Result<()> {
+    let world_address = world.address(env_metadata)?;
+    let provider = starknet.provider(env_metadata)?;
+    let account = account.account(provider, env_metadata).await?;
+
+    let res = account
+        .execute(vec![update.call(world_address)?])
+        .send()
+        .await
+        .with_context(|| "Failed to send transaction")?;
+
+    println!("Transaction: {:#x}", res.transaction_hash);
+    Ok(())
+}
+
+/// The writers and owners of a World, by model and resource name.
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+pub struct WorldPermissions {
+    pub writers: BTreeMap<String, BTreeSet<String>>,
+    pub owners: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl WorldPermissions {
+    fn print(&self) {
+        println!("Writers");
+        println!("{:<32} Contract", "Model");
+        for (model, writers) in &self.writers {
+            for writer in writers {
+                println!("{model:<32} {writer}");
+            }
+        }
+
+        println!("\nOwners");
+        println!("{:<32} Address", "Resource");
+        for (resource, owners) in &self.owners {
+            for owner in owners {
+                println!("{resource:<32} {owner}");
+            }
+        }
+    }
+}
+
+/// The permissions which may be held, gathered from the events of the World. They are confirmed
+/// against the World, so the order of the events doesn't matter.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Candidates {
+    /// The `(model, contract)` pairs which may be writers.
+    writers: BTreeSet<(FieldElement, FieldElement)>,
+    /// The `(resource, address)` pairs which may be owners.
+    owners: BTreeSet<(FieldElement, FieldElement)>,
+    /// The models registered, with the transaction registering them whose sender owns them.
+    registrations: BTreeSet<(FieldElement, FieldElement)>,
+}
+
+impl Candidates {
+    /// Adds the permissions an event may have granted. Other events are ignored.
+    fn add(&mut self, event: &EmittedEvent) {
+        let Some(&name) = event.keys.first() else { return };
+
+        match event.data.as_slice() {
+            [model, contract, _] if name == selector!("WriterUpdated") => {
+                self.writers.insert((*model, *contract));
+            }
+            [address, resource, _] if name == selector!("OwnerUpdated") => {
+                self.owners.insert((*resource, *address));
+            }
+            // The creator of the World owns it, without an `OwnerUpdated` event.
+            [_, creator] if name == selector!("WorldSpawned") => {
+                self.owners.insert((FieldElement::ZERO, *creator));
+            }
+            // So does the caller registering a model.
+            [model, _, _] if name == selector!("ModelRegistered") => {
+                self.registrations.insert((*model, event.transaction_hash));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Fetches the permissions of the World. The World can't enumerate them, so the candidates are
+/// gathered from its events then checked against the World.
+async fn fetch_permissions<P>(
+    provider: &P,
+    world_address: FieldElement,
+    from_block: Option<u64>,
+    chunk_size: u64,
+) -> Result<WorldPermissions>
+where
+    P: Provider + Sync + Send,
+    P::Error: 'static,
+{
+    let filter = EventFilter {
+        from_block: from_block.map(BlockId::Number),
+        to_block: None,
+        address: Some(world_address),
+        keys: Some(vec![vec![
+            selector!("WriterUpdated"),
+            selector!("OwnerUpdated"),
+            selector!("WorldSpawned"),
+            selector!("ModelRegistered"),
+        ]]),
+    };
+
+    let mut candidates = Candidates::default();
+    let mut continuation_token = None;
+
+    loop {
+        let page = provider
+            .get_events(filter.clone(), continuation_token, chunk_size)
+            .await
+            .with_context(|| "Failed to fetch World events")?;
+
+        page.events.iter().for_each(|event| candidates.add(event));
+
+        continuation_token = page.continuation_token;
+        if continuation_token.is_none() {
+            break;
+        }
+    }
+
+    for (model, transaction_hash) in std::mem::take(&mut candidates.registrations) {
+        if let Some(sender) = transaction_sender(provider, transaction_hash).await? {
+            candidates.owners.insert((model, sender));
+        }
+    }
+
+    let world = WorldContractReader::new(world_address, provider);
+    let mut permissions = WorldPermissions::default();
+
+    for (model, contract) in candidates.writers {
+        let model = resource_name(model);
+        if world.is_writer(&model, contract).await.with_context(|| "Failed to check writer")? {
+            permissions.writers.entry(model).or_default().insert(format!("{contract:#x}"));
+        }
+    }
+
+    for (resource, address) in candidates.owners {
+        if world.is_owner(address, resource).await.with_context(|| "Failed to check owner")? {
+            let owners = permissions.owners.entry(resource_name(resource)).or_default();
+            owners.insert(format!("{address:#x}"));
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Returns the account which sent the transaction, if it's an invoke transaction.
+async fn transaction_sender<P>(
+    provider: &P,
+    transaction_hash: FieldElement,
+) -> Result<Option<FieldElement>>
+where
+    P: Provider + Sync + Send,
+    P::Error: 'static,
+{
+    let transaction = provider
+        .get_transaction_by_hash(transaction_hash)
+        .await
+        .with_context(|| format!("Failed to fetch transaction {transaction_hash:#x}"))?;
+
+    Ok(match transaction {
+        Transaction::Invoke(InvokeTransaction::V0(tx)) => Some(tx.contract_address),
+        Transaction::Invoke(InvokeTransaction::V1(tx)) => Some(tx.sender_address),
+        _ => None,
+    })
+}
+
+fn resource_name(resource: FieldElement) -> String {
+    if resource == FieldElement::ZERO {
+        WORLD_RESOURCE.to_string()
+    } else {
+        parse_cairo_short_string(&resource).unwrap_or_else(|_| format!("{resource:#x}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::utils::cairo_short_string_to_felt;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    fn event(name: FieldElement, data: Vec<FieldElement>) -> EmittedEvent {
+        EmittedEvent {
+            from_address: felt!("0x1"),
+            keys: vec![name],
+            data,
+            block_hash: FieldElement::ZERO,
+            block_number: 0,
+            transaction_hash: FieldElement::ZERO,
+        }
+    }
+
+    #[test]
+    fn candidates_include_implicit_owners() {
+        let mut candidates = Candidates::default();
+
+        let position = cairo_short_string_to_felt("Position").unwrap();
+        let mut registration =
+            event(selector!("ModelRegistered"), vec![position, felt!("0xd"), FieldElement::ZERO]);
+        registration.transaction_hash = felt!("0x99");
+
+        for e in [
+            event(selector!("WorldSpawned"), vec![felt!("0x1"), felt!("0xe")]),
+            event(selector!("WriterUpdated"), vec![position, felt!("0xa"), FieldElement::ONE]),
+            event(selector!("WriterUpdated"), vec![position, felt!("0xa"), FieldElement::ZERO]),
+            event(
+                selector!("OwnerUpdated"),
+                vec![felt!("0xc"), FieldElement::ZERO, FieldElement::ONE],
+            ),
+            registration,
+            event(selector!("ContractDeployed"), vec![felt!("0x1"), felt!("0x2"), felt!("0x3")]),
+        ] {
+            candidates.add(&e);
+        }
+
+        assert_eq!(
+            candidates,
+            Candidates {
+                writers: BTreeSet::from([(position, felt!("0xa"))]),
+                owners: BTreeSet::from([
+                    (FieldElement::ZERO, felt!("0xc")),
+                    (FieldElement::ZERO, felt!("0xe")),
+                ]),
+                registrations: BTreeSet::from([(position, felt!("0x99"))]),
+            }
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Generation of typed client bindings from the manifest of a World.
+//!
+//! The manifest is first resolved into [`Bindings`], a description of the models, of the types
+//! they use and of the systems of the World that doesn't depend on the target language. A
+//! [`BindingsGenerator`] then turns it into source files.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use cairo_lang_starknet::abi;
+use camino::{Utf8Path, Utf8PathBuf};
+use dojo_types::primitive::Primitive;
+use dojo_world::contracts::calldata::{abi_items, short_name, split_types, tuple_items};
+use dojo_world::manifest::Manifest;
+
+mod rust;
+
+pub use rust::RustGenerator;
+
+/// Generates the bindings of a World for a target language.
+pub trait BindingsGenerator {
+    /// Returns the generated files, as paths relative to the output directory and contents.
+    fn generate(&self, bindings: &Bindings) -> Result<Vec<(Utf8PathBuf, String)>>;
+}
+
+/// Generates the bindings of the World of `manifest` and writes them into `output_dir`.
+pub fn generate(
+    generator: &dyn BindingsGenerator,
+    manifest: &Manifest,
+    output_dir: &Utf8Path,
+) -> Result<Vec<Utf8PathBuf>> {
+    let bindings = Bindings::from_manifest(manifest)?;
+
+    let mut written = vec![];
+    for (path, content) in generator.generate(&bindings)? {
+        let path = output_dir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// A type used by the models or the systems of the World.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeRef {
+    /// A primitive of the model schemas, without value.
+    Primitive(Primitive),
+    Array(Box<TypeRef>),
+    Option(Box<TypeRef>),
+    Tuple(Vec<TypeRef>),
+    /// A struct or an enum of [`Bindings::types`], by name.
+    Named(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberDef {
+    pub name: String,
+    pub ty: TypeRef,
+    pub key: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeDef {
+    Struct {
+        name: String,
+        members: Vec<MemberDef>,
+    },
+    /// An enum, whose variants may have a payload.
+    Enum {
+        name: String,
+        variants: Vec<(String, Option<TypeRef>)>,
+    },
+}
+
+impl TypeDef {
+    pub fn name(&self) -> &str {
+        match self {
+            TypeDef::Struct { name, .. } | TypeDef::Enum { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelDef {
+    pub name: String,
+    pub members: Vec<MemberDef>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef {
+    pub name: String,
+    pub inputs: Vec<(String, TypeRef)>,
+}
+
+/// A contract of the World and its external functions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemDef {
+    pub name: String,
+    pub functions: Vec<FunctionDef>,
+}
+
+/// The models, types and systems of a World.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Bindings {
+    pub models: Vec<ModelDef>,
+    /// The structs and enums used by the models and the systems, models excluded.
+    pub types: Vec<TypeDef>,
+    pub systems: Vec<SystemDef>,
+}
+
+impl Bindings {
+    /// Resolves the models and the systems of the manifest. The types of the model members are
+    /// only known by name, so they are looked up in the ABIs of the contracts.
+    pub fn from_manifest(manifest: &Manifest) -> Result<Self> {
+        let abis = manifest
+            .contracts
+            .iter()
+            .map(|c| c.abi.as_ref())
+            .chain(manifest.models.iter().map(|m| m.abi.as_ref()))
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let mut resolver =
+            Resolver { abis, types: HashMap::new(), names: HashSet::new(), order: vec![] };
+
+        let mut models = vec![];
+        for model in &manifest.models {
+            let members = model
+                .members
+                .iter()
+                .map(|m| {
+                    let ty = resolver.resolve(&m.ty).map_err(|e| {
+                        anyhow!(
+                            "Failed to resolve member `{}` of model `{}`: {e}",
+                            m.name,
+                            model.name
+                        )
+                    })?;
+                    Ok(MemberDef { name: m.name.clone(), ty, key: m.key })
+                })
+                .collect::<Result<_>>()?;
+            models.push(ModelDef { name: model.name.clone(), members });
+        }
+
+        let mut systems = vec![];
+        for contract in &manifest.contracts {
+            let Some(abi) = &contract.abi else { continue };
+
+            let mut functions = vec![];
+            for function in external_functions(abi) {
+                let inputs = function
+                    .inputs
+                    .iter()
+                    .map(|input| Ok((input.name.clone(), resolver.resolve(&input.ty)?)))
+                    .collect::<Result<_>>()
+                    .map_err(|e| {
+                        anyhow!(
+                            "Failed to resolve inputs of `{}::{}`: {e}",
+                            contract.name,
+                            function.name
+                        )
+                    })?;
+                functions.push(FunctionDef { name: function.name.clone(), inputs });
+            }
+
+            systems.push(SystemDef { name: short_name(&contract.name).to_string(), functions });
+        }
+
+        // Models are generated from the manifest, even if they're also found in an ABI.
+        let types = resolver
+            .order
+            .iter()
+            .map(|path| resolver.types.remove(path).unwrap())
+            .filter(|def| !models.iter().any(|m| m.name == def.name()))
+            .collect();
+
+        Ok(Self { models, types, systems })
+    }
+}
+
+fn external_functions(abi: &abi::Contract) -> Vec<abi::Function> {
+    abi_items(abi)
+        .flat_map(|item| match item {
+            abi::Item::Function(f) => vec![f],
+            abi::Item::Interface(interface) => interface
+                .items
+                .into_iter()
+                .filter_map(|item| match item {
+                    abi::Item::Function(f) => Some(f),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        })
+        .filter(|f| f.state_mutability == abi::StateMutability::External)
+        .collect()
+}
+
+struct Resolver<'a> {
+    abis: Vec<&'a abi::Contract>,
+    /// The resolved types, by full path including their generic arguments.
+    types: HashMap<String, TypeDef>,
+    /// The names given to the resolved types.
+    names: HashSet<String>,
+    /// Full paths of the resolved types, in the order they were first seen.
+    order: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    /// Resolves a type given either by its full path, like in the ABIs, or by its name, like in
+    /// the members of the manifest models.
+    fn resolve(&mut self, ty: &str) -> Result<TypeRef> {
+        let ty = ty.trim_start_matches('@');
+
+        if let Some(primitive) = primitive(ty) {
+            return Ok(TypeRef::Primitive(primitive));
+        }
+
+        if let Some(item) = generic_argument(ty, "core::array::Array")
+            .or_else(|| generic_argument(ty, "core::array::Span"))
+        {
+            return Ok(TypeRef::Array(Box::new(self.resolve(item)?)));
+        }
+
+        if let Some(item) = generic_argument(ty, "core::option::Option") {
+            return Ok(TypeRef::Option(Box::new(self.resolve(item)?)));
+        }
+
+        if let Some(items) = tuple_items(ty) {
+            let items = items.into_iter().map(|item| self.resolve(item));
+            return Ok(TypeRef::Tuple(items.collect::<Result<_>>()?));
+        }
+
+        // Generic types are only found by their full path, their arguments are part of it.
+        let matches = |path: &str| path == ty || (!path.contains('<') && short_name(path) == ty);
+        let item = self.abis.iter().flat_map(|abi| abi_items(abi)).find(|item| match item {
+            abi::Item::Struct(s) => matches(&s.name),
+            abi::Item::Enum(e) => matches(&e.name),
+            _ => false,
+        });
+
+        let path = match &item {
+            Some(abi::Item::Struct(abi::Struct { name, .. }))
+            | Some(abi::Item::Enum(abi::Enum { name, .. })) => name.clone(),
+            _ => bail!("Type `{ty}` isn't supported or wasn't found in the ABIs of the manifest."),
+        };
+
+        if let Some(def) = self.types.get(&path) {
+            return Ok(TypeRef::Named(def.name().to_string()));
+        }
+
+        // Reserve the name first, the type may be recursive.
+        let name = self.name(&path);
+        self.types.insert(path.clone(), TypeDef::Struct { name: name.clone(), members: vec![] });
+        self.order.push(path.clone());
+
+        let def = match item {
+            Some(abi::Item::Struct(s)) => {
+                let members = s
+                    .members
+                    .iter()
+                    .map(|m| {
+                        Ok(MemberDef { name: m.name.clone(), ty: self.resolve(&m.ty)?, key: false })
+                    })
+                    .collect::<Result<_>>()?;
+                TypeDef::Struct { name: name.clone(), members }
+            }
+            Some(abi::Item::Enum(e)) => {
+                let variants = e
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        let payload = match v.ty.as_str() {
+                            "()" => None,
+                            ty => Some(self.resolve(ty)?),
+                        };
+                        Ok((v.name.clone(), payload))
+                    })
+                    .collect::<Result<_>>()?;
+                TypeDef::Enum { name: name.clone(), variants }
+            }
+            _ => unreachable!("the item is a struct or an enum"),
+        };
+
+        self.types.insert(path, def);
+        Ok(TypeRef::Named(name))
+    }
+
+    /// Returns a unique name for the type at `path`: its name followed by the names of its generic
+    /// arguments, prefixed by its module if another type already has this name.
+    fn name(&mut self, path: &str) -> String {
+        let mut name = type_name(path);
+        if self.names.contains(&name) {
+            let module = path.split("::<").next().unwrap_or(path).rsplit_once("::").map(|(m, _)| m);
+            name = format!("{}{name}", module.map(camel_case).unwrap_or_default());
+        }
+
+        self.names.insert(name.clone());
+        name
+    }
+}
+
+/// Returns the generic argument of `ty` if it's an instance of the generic type at `path`.
+fn generic_argument<'a>(ty: &'a str, path: &str) -> Option<&'a str> {
+    ty.strip_prefix(path)?.strip_prefix("::<")?.strip_suffix('>')
+}
+
+/// Returns the name of a type followed by the names of its generic arguments, like `FooU8` for
+/// `foo::Foo::<core::integer::u8>`.
+fn type_name(path: &str) -> String {
+    if let Some(items) = tuple_items(path) {
+        return items.into_iter().map(type_name).fold("Tuple".into(), |n, i| n + &i);
+    }
+
+    let arguments = path
+        .split_once("::<")
+        .and_then(|(_, arguments)| arguments.strip_suffix('>'))
+        .map(split_types)
+        .unwrap_or_default();
+
+    arguments.into_iter().map(type_name).fold(camel_case(short_name(path)), |n, i| n + &i)
+}
+
+/// Converts a snake case path to camel case, like `DojoExamplesModels` for
+/// `dojo_examples::models`.
+fn camel_case(path: &str) -> String {
+    path.split(['_', ':'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+/// Returns the primitive of the model schemas matching a core type, given by its path or name.
+fn primitive(ty: &str) -> Option<Primitive> {
+    let name = match ty.strip_prefix("core::") {
+        Some(_) if ty.contains("::<") => return None,
+        Some(_) => short_name(ty),
+        None if ty.contains("::") => return None,
+        None => ty,
+    };
+
+    Primitive::from_str(name).ok()
+}
@@ -0,0 +1,921 @@
+//! Rust bindings: a struct or an enum per type, converting the models to and from their
+//! [`Ty`](dojo_types::schema::Ty) schema, and a typed call builder per system.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use camino::Utf8PathBuf;
+use dojo_types::primitive::Primitive;
+
+use super::{
+    camel_case, Bindings, BindingsGenerator, MemberDef, ModelDef, SystemDef, TypeDef, TypeRef,
+};
+
+const HEADER: &str = "
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use starknet::accounts::{AccountError, Call, ConnectedAccount};
+use starknet::core::types::{FieldElement, InvokeTransactionResult};
+use starknet::core::utils::get_selector_from_name;
+
+/// Error returned when a schema doesn't match the type it's converted into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaMismatch;
+
+impl std::fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, \"schema mismatch\")
+    }
+}
+
+impl std::error::Error for SchemaMismatch {}
+
+/// Serialization of the values passed to the systems, like Cairo's `Serde`.
+pub trait CairoSerde {
+    fn serialize(&self, out: &mut Vec<FieldElement>);
+}
+
+impl CairoSerde for FieldElement {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        out.push(*self);
+    }
+}
+
+impl CairoSerde for bool {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        out.push(if *self { FieldElement::ONE } else { FieldElement::ZERO });
+    }
+}
+
+macro_rules! integer_serde {
+    ($($ty:ty)+) => {
+        $(impl CairoSerde for $ty {
+            fn serialize(&self, out: &mut Vec<FieldElement>) {
+                out.push(FieldElement::from(*self));
+            }
+        })+
+    };
+}
+
+integer_serde!(u8 u16 u32 u64 u128);
+
+impl<T: CairoSerde> CairoSerde for Option<T> {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        match self {
+            Some(value) => {
+                out.push(FieldElement::ZERO);
+                value.serialize(out);
+            }
+            None => out.push(FieldElement::ONE),
+        }
+    }
+}
+
+impl<T: CairoSerde> CairoSerde for Vec<T> {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        out.push(FieldElement::from(self.len()));
+        self.iter().for_each(|item| item.serialize(out));
+    }
+}
+
+impl CairoSerde for () {
+    fn serialize(&self, _: &mut Vec<FieldElement>) {}
+}
+
+macro_rules! tuple_serde {
+    ($($name:ident)+) => {
+        impl<$($name: CairoSerde),+> CairoSerde for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn serialize(&self, out: &mut Vec<FieldElement>) {
+                let ($($name,)+) = self;
+                $($name.serialize(out);)+
+            }
+        }
+    };
+}
+
+tuple_serde!(A);
+tuple_serde!(A B);
+tuple_serde!(A B C);
+tuple_serde!(A B C D);
+tuple_serde!(A B C D E);
+tuple_serde!(A B C D E F);
+tuple_serde!(A B C D E F G);
+tuple_serde!(A B C D E F G H);
+tuple_serde!(A B C D E F G H I);
+tuple_serde!(A B C D E F G H I J);
+tuple_serde!(A B C D E F G H I J K);
+tuple_serde!(A B C D E F G H I J K L);
+";
+
+const U256_SERDE: &str = "
+impl CairoSerde for U256 {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        let bytes = self.to_be_bytes();
+        out.push(FieldElement::from_byte_slice_be(&bytes[16..]).unwrap());
+        out.push(FieldElement::from_byte_slice_be(&bytes[..16]).unwrap());
+    }
+}
+";
+
+/// The longest tuples the bindings can serialize, the longest implementing the standard traits.
+const MAX_TUPLE_LEN: usize = 12;
+
+/// Generates a single `bindings.rs` module.
+#[derive(Debug, Default)]
+pub struct RustGenerator;
+
+impl BindingsGenerator for RustGenerator {
+    fn generate(&self, bindings: &Bindings) -> Result<Vec<(Utf8PathBuf, String)>> {
+        let too_long =
+            |ty: &TypeRef| matches!(ty, TypeRef::Tuple(items) if items.len() > MAX_TUPLE_LEN);
+        if any_type(bindings, too_long) {
+            bail!(
+                "Tuples of more than {MAX_TUPLE_LEN} items aren't supported by the Rust bindings."
+            );
+        }
+
+        let uses_u256 =
+            any_type(bindings, |ty| matches!(ty, TypeRef::Primitive(Primitive::U256(_))));
+        let crates = if uses_u256 {
+            "`crypto-bigint` (0.5), `dojo-types` and `starknet`"
+        } else {
+            "`dojo-types` and `starknet`"
+        };
+
+        let mut out = format!(
+            "// Generated by `sozo build --bindings rust`, don't edit.\n//\n// The bindings \
+             depend on the {crates} crates.\n"
+        );
+        if uses_u256 {
+            out.push_str("\nuse crypto_bigint::{Encoding, U256};");
+        }
+        out.push_str(HEADER);
+        if uses_u256 {
+            out.push_str(U256_SERDE);
+        }
+
+        // Only the types of the models are converted to and from a schema.
+        let schema_types = schema_types(bindings);
+
+        for model in &bindings.models {
+            out.push_str(&generate_model(model)?);
+        }
+
+        for def in &bindings.types {
+            match def {
+                TypeDef::Struct { name, members } => {
+                    out.push_str(&generate_struct(name, members, ""));
+                    if schema_types.contains(name.as_str()) {
+                        out.push_str(&struct_schema(name, members)?);
+                    }
+                }
+                TypeDef::Enum { name, variants } => {
+                    out.push_str(&generate_enum(name, variants));
+                    if schema_types.contains(name.as_str()) {
+                        if variants.iter().any(|(_, payload)| payload.is_some()) {
+                            bail!(
+                                "Enum `{name}` of a model has variants with a payload, which \
+                                 aren't supported by the schemas."
+                            );
+                        }
+                        out.push_str(&enum_schema(name, variants));
+                    }
+                }
+            }
+        }
+
+        for system in &bindings.systems {
+            out.push_str(&generate_system(system));
+        }
+
+        Ok(vec![(Utf8PathBuf::from("bindings.rs"), out)])
+    }
+}
+
+fn generate_model(model: &ModelDef) -> Result<String> {
+    let name = &model.name;
+    let doc = format!("/// The `{name}` model.\n");
+
+    let mut out = generate_struct(name, &model.members, &doc);
+    out.push_str(&struct_schema(name, &model.members)?);
+
+    let keys = model
+        .members
+        .iter()
+        .filter(|m| m.key)
+        .map(|m| format!("        self.{}.serialize(&mut keys);\n", ident(&m.name)))
+        .collect::<String>();
+
+    write!(
+        out,
+        "
+impl {name} {{
+    /// The name of the model in the World.
+    pub const NAME: &'static str = \"{name}\";
+
+    /// Returns the keys of the entity, to read it from the World.
+    pub fn keys(&self) -> Vec<FieldElement> {{
+        let mut keys = vec![];
+{keys}        keys
+    }}
+}}
+"
+    )?;
+
+    Ok(out)
+}
+
+fn generate_struct(name: &str, members: &[MemberDef], doc: &str) -> String {
+    let fields = members
+        .iter()
+        .map(|m| format!("    pub {}: {},\n", ident(&m.name), rust_type(&m.ty)))
+        .collect::<String>();
+    let serialize = members
+        .iter()
+        .map(|m| format!("        self.{}.serialize(out);\n", ident(&m.name)))
+        .collect::<String>();
+
+    format!(
+        "
+{doc}#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct {name} {{
+{fields}}}
+
+impl CairoSerde for {name} {{
+    fn serialize(&self, out: &mut Vec<FieldElement>) {{
+{serialize}    }}
+}}
+"
+    )
+}
+
+fn generate_enum(name: &str, variants: &[(String, Option<TypeRef>)]) -> String {
+    let mut definition = String::new();
+    let mut serialize = String::new();
+
+    for (index, (variant, payload)) in variants.iter().enumerate() {
+        match payload {
+            Some(ty) => {
+                definition.push_str(&format!("    {variant}({}),\n", rust_type(ty)));
+                serialize.push_str(&format!(
+                    "            {name}::{variant}(value) => {{
+                out.push(FieldElement::from({index}_usize));
+                value.serialize(out);
+            }}\n"
+                ));
+            }
+            None => {
+                definition.push_str(&format!("    {variant},\n"));
+                serialize.push_str(&format!(
+                    "            {name}::{variant} => \
+                     out.push(FieldElement::from({index}_usize)),\n"
+                ));
+            }
+        }
+    }
+
+    format!(
+        "
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum {name} {{
+{definition}}}
+
+impl CairoSerde for {name} {{
+    fn serialize(&self, out: &mut Vec<FieldElement>) {{
+        match self {{
+{serialize}        }}
+    }}
+}}
+"
+    )
+}
+
+fn struct_schema(name: &str, members: &[MemberDef]) -> Result<String> {
+    let children = |value: bool| -> Result<String> {
+        members
+            .iter()
+            .map(|m| {
+                let field = format!("value.{}", ident(&m.name));
+                let ty = ty_expr(&m.ty, value.then_some(field.as_str()))?;
+                Ok(format!(
+                    "                Member {{ name: \"{}\".to_string(), ty: {ty}, key: {} }},\n",
+                    m.name, m.key
+                ))
+            })
+            .collect()
+    };
+
+    let fields = members
+        .iter()
+        .map(|m| {
+            let member = format!("s.get(\"{}\").ok_or(SchemaMismatch)?", m.name);
+            format!("            {}: {},\n", ident(&m.name), from_ty_expr(&m.ty, &member))
+        })
+        .collect::<String>();
+
+    Ok(format!(
+        "
+impl {name} {{
+    /// Returns the schema of the type, without values.
+    pub fn schema() -> Ty {{
+        Ty::Struct(Struct {{
+            name: \"{name}\".to_string(),
+            children: vec![
+{schema}            ],
+        }})
+    }}
+}}
+
+impl From<&{name}> for Ty {{
+    fn from(value: &{name}) -> Self {{
+        Ty::Struct(Struct {{
+            name: \"{name}\".to_string(),
+            children: vec![
+{values}            ],
+        }})
+    }}
+}}
+
+impl TryFrom<&Ty> for {name} {{
+    type Error = SchemaMismatch;
+
+    fn try_from(ty: &Ty) -> Result<Self, Self::Error> {{
+        let s = ty.as_struct().ok_or(SchemaMismatch)?;
+        Ok(Self {{
+{fields}        }})
+    }}
+}}
+",
+        schema = children(false)?,
+        values = children(true)?,
+    ))
+}
+
+fn enum_schema(name: &str, variants: &[(String, Option<TypeRef>)]) -> String {
+    let options = variants
+        .iter()
+        .map(|(variant, _)| {
+            format!(
+                "                EnumOption {{ name: \"{variant}\".to_string(), ty: \
+                 Ty::Tuple(vec![]) }},\n"
+            )
+        })
+        .collect::<String>();
+    let indices = variants
+        .iter()
+        .enumerate()
+        .map(|(index, (variant, _))| format!("                {name}::{variant} => {index},\n"))
+        .collect::<String>();
+    let from_indices = variants
+        .iter()
+        .enumerate()
+        .map(|(index, (variant, _))| {
+            format!("            Some({index}) => Ok({name}::{variant}),\n")
+        })
+        .collect::<String>();
+
+    format!(
+        "
+impl {name} {{
+    /// Returns the schema of the type, without values.
+    pub fn schema() -> Ty {{
+        Ty::Enum(Enum {{
+            name: \"{name}\".to_string(),
+            option: None,
+            options: vec![
+{options}            ],
+        }})
+    }}
+}}
+
+impl From<&{name}> for Ty {{
+    fn from(value: &{name}) -> Self {{
+        let option = match value {{
+{indices}        }};
+
+        Ty::Enum(Enum {{
+            name: \"{name}\".to_string(),
+            option: Some(option),
+            options: vec![
+{options}            ],
+        }})
+    }}
+}}
+
+impl TryFrom<&Ty> for {name} {{
+    type Error = SchemaMismatch;
+
+    fn try_from(ty: &Ty) -> Result<Self, Self::Error> {{
+        match ty.as_enum().ok_or(SchemaMismatch)?.option {{
+{from_indices}            _ => Err(SchemaMismatch),
+        }}
+    }}
+}}
+"
+    )
+}
+
+fn generate_system(system: &SystemDef) -> String {
+    let name = camel_case(&system.name);
+
+    let mut functions = String::new();
+    for function in &system.functions {
+        let params = function
+            .inputs
+            .iter()
+            .map(|(input, ty)| format!(", {}: &{}", ident(input), rust_type(ty)))
+            .collect::<String>();
+        let args = function.inputs.iter().map(|(input, _)| ident(input)).collect::<Vec<_>>();
+
+        let calldata = if function.inputs.is_empty() {
+            "        let calldata = vec![];\n".to_string()
+        } else {
+            let serialize = args
+                .iter()
+                .map(|arg| format!("        {arg}.serialize(&mut calldata);\n"))
+                .collect::<String>();
+            format!("        let mut calldata = vec![];\n{serialize}")
+        };
+
+        let entrypoint = &function.name;
+        let call_fn = format!("{entrypoint}_call");
+        write!(
+            functions,
+            "
+    /// Returns the call to `{entrypoint}`, to be executed along other calls.
+    pub fn {call_fn}(&self{params}) -> Call {{
+{calldata}        Call {{
+            to: self.address,
+            selector: get_selector_from_name(\"{entrypoint}\").unwrap(),
+            calldata,
+        }}
+    }}
+
+    /// Executes `{entrypoint}`.
+    pub async fn {function}(
+        &self{params}
+    ) -> Result<InvokeTransactionResult, AccountError<A::SignError>> {{
+        self.account.execute(vec![self.{call_fn}({args})]).send().await
+    }}
+",
+            function = ident(entrypoint),
+            args = args.join(", "),
+        )
+        .unwrap();
+    }
+
+    format!(
+        "
+/// The systems of the `{contract}` contract.
+pub struct {name}<'a, A>
+where
+    A: ConnectedAccount,
+{{
+    pub address: FieldElement,
+    account: &'a A,
+}}
+
+impl<'a, A> {name}<'a, A>
+where
+    A: ConnectedAccount + Sync,
+{{
+    pub fn new(address: FieldElement, account: &'a A) -> Self {{
+        Self {{ address, account }}
+    }}
+{functions}}}
+",
+        contract = system.name,
+    )
+}
+
+fn rust_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Primitive(p) => match p {
+            Primitive::U8(_) => "u8",
+            Primitive::U16(_) => "u16",
+            Primitive::U32(_) | Primitive::USize(_) => "u32",
+            Primitive::U64(_) => "u64",
+            Primitive::U128(_) => "u128",
+            Primitive::U256(_) => "U256",
+            Primitive::Bool(_) => "bool",
+            Primitive::Felt252(_) | Primitive::ClassHash(_) | Primitive::ContractAddress(_) => {
+                "FieldElement"
+            }
+        }
+        .to_string(),
+        TypeRef::Array(item) => format!("Vec<{}>", rust_type(item)),
+        TypeRef::Option(item) => format!("Option<{}>", rust_type(item)),
+        TypeRef::Tuple(items) if items.len() == 1 => format!("({},)", rust_type(&items[0])),
+        TypeRef::Tuple(items) => {
+            format!("({})", items.iter().map(rust_type).collect::<Vec<_>>().join(", "))
+        }
+        TypeRef::Named(name) => name.clone(),
+    }
+}
+
+/// Returns the expression building the schema of a value of type `ty`, set to `value` if any.
+fn ty_expr(ty: &TypeRef, value: Option<&str>) -> Result<String> {
+    Ok(match ty {
+        TypeRef::Primitive(p) => {
+            let value = value.map(|value| format!("Some({value})"));
+            let (variant, _) = primitive_variant(p);
+            format!("Ty::Primitive(Primitive::{variant}({}))", value.as_deref().unwrap_or("None"))
+        }
+        TypeRef::Named(name) => match value {
+            Some(value) => format!("Ty::from(&{value})"),
+            None => format!("{name}::schema()"),
+        },
+        TypeRef::Tuple(items) => {
+            let items = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| ty_expr(item, value.map(|v| format!("{v}.{i}")).as_deref()))
+                .collect::<Result<Vec<_>>>()?;
+            format!("Ty::Tuple(vec![{}])", items.join(", "))
+        }
+        TypeRef::Array(_) => bail!("Arrays aren't supported by the schemas of the models."),
+        TypeRef::Option(_) => bail!("Options aren't supported by the schemas of the models."),
+    })
+}
+
+/// Returns the expression reading a value of type `ty` from the schema `src`, a `&Ty`.
+fn from_ty_expr(ty: &TypeRef, src: &str) -> String {
+    match ty {
+        TypeRef::Primitive(p) => {
+            let (_, getter) = primitive_variant(p);
+            format!("{src}.as_primitive().and_then(Primitive::{getter}).ok_or(SchemaMismatch)?")
+        }
+        TypeRef::Named(name) => format!("{name}::try_from({src})?"),
+        TypeRef::Tuple(items) => {
+            let items = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    from_ty_expr(item, &format!("items.get({i}).ok_or(SchemaMismatch)?"))
+                })
+                .collect::<Vec<_>>();
+            format!(
+                "{{ let items = {src}.as_tuple().ok_or(SchemaMismatch)?; ({},) }}",
+                items.join(", ")
+            )
+        }
+        // Rejected when building the schema.
+        TypeRef::Array(_) => unreachable!("arrays aren't supported by the schemas"),
+        TypeRef::Option(_) => unreachable!("options aren't supported by the schemas"),
+    }
+}
+
+/// Returns the name of the variant of a primitive and the name of its getter.
+fn primitive_variant(primitive: &Primitive) -> (&'static str, &'static str) {
+    match primitive {
+        Primitive::U8(_) => ("U8", "as_u8"),
+        Primitive::U16(_) => ("U16", "as_u16"),
+        Primitive::U32(_) => ("U32", "as_u32"),
+        Primitive::U64(_) => ("U64", "as_u64"),
+        Primitive::U128(_) => ("U128", "as_u128"),
+        Primitive::U256(_) => ("U256", "as_u256"),
+        Primitive::USize(_) => ("USize", "as_usize"),
+        Primitive::Bool(_) => ("Bool", "as_bool"),
+        Primitive::Felt252(_) => ("Felt252", "as_felt252"),
+        Primitive::ClassHash(_) => ("ClassHash", "as_class_hash"),
+        Primitive::ContractAddress(_) => ("ContractAddress", "as_contract_address"),
+    }
+}
+
+/// Returns the names of the types reachable from the members of the models.
+fn schema_types(bindings: &Bindings) -> HashSet<&str> {
+    fn visit<'a>(ty: &'a TypeRef, bindings: &'a Bindings, names: &mut HashSet<&'a str>) {
+        match ty {
+            TypeRef::Named(name) => {
+                if !names.insert(name) {
+                    return;
+                }
+                match bindings.types.iter().find(|def| def.name() == name) {
+                    Some(TypeDef::Struct { members, .. }) => {
+                        members.iter().for_each(|m| visit(&m.ty, bindings, names))
+                    }
+                    Some(TypeDef::Enum { variants, .. }) => variants
+                        .iter()
+                        .filter_map(|(_, payload)| payload.as_ref())
+                        .for_each(|payload| visit(payload, bindings, names)),
+                    None => {}
+                }
+            }
+            TypeRef::Array(item) | TypeRef::Option(item) => visit(item, bindings, names),
+            TypeRef::Tuple(items) => items.iter().for_each(|item| visit(item, bindings, names)),
+            TypeRef::Primitive(_) => {}
+        }
+    }
+
+    let mut names = HashSet::new();
+    for model in &bindings.models {
+        model.members.iter().for_each(|m| visit(&m.ty, bindings, &mut names));
+    }
+    names
+}
+
+/// Returns `true` if `f` holds for one of the types used by the bindings, nested types included.
+fn any_type(bindings: &Bindings, f: impl Fn(&TypeRef) -> bool) -> bool {
+    fn visit(ty: &TypeRef, f: &dyn Fn(&TypeRef) -> bool) -> bool {
+        f(ty)
+            || match ty {
+                TypeRef::Array(item) | TypeRef::Option(item) => visit(item, f),
+                TypeRef::Tuple(items) => items.iter().any(|item| visit(item, f)),
+                TypeRef::Primitive(_) | TypeRef::Named(_) => false,
+            }
+    }
+
+    let members = bindings.models.iter().flat_map(|m| &m.members).chain(
+        bindings.types.iter().flat_map(|def| match def {
+            TypeDef::Struct { members, .. } => members.as_slice(),
+            TypeDef::Enum { .. } => &[],
+        }),
+    );
+    let variants = bindings.types.iter().flat_map(|def| match def {
+        TypeDef::Enum { variants, .. } => variants.iter().filter_map(|(_, p)| p.as_ref()).collect(),
+        TypeDef::Struct { .. } => vec![],
+    });
+    let inputs = bindings.systems.iter().flat_map(|s| &s.functions).flat_map(|f| &f.inputs);
+
+    members.map(|m| &m.ty).chain(variants).chain(inputs.map(|(_, ty)| ty)).any(|ty| visit(ty, &f))
+}
+
+/// Escapes the identifiers which are reserved in Rust, like the `move` system of the examples.
+fn ident(name: &str) -> String {
+    const KEYWORDS: [&str; 38] = [
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "true", "type",
+        "unsafe", "use", "where", "while", "yield", "try",
+    ];
+
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// The bindings generated for the manifest of the tests, compiled to check that they're valid.
+#[cfg(test)]
+#[rustfmt::skip]
+#[allow(dead_code, unused_imports, clippy::all)]
+#[path = "test_data/bindings.rs"]
+mod generated;
+
+#[cfg(test)]
+mod tests {
+    use dojo_world::manifest::Manifest;
+
+    use super::*;
+    use crate::ops::bindings::FunctionDef;
+
+    fn manifest() -> Manifest {
+        serde_json::from_value(serde_json::json!({
+            "world": { "name": "world", "address": null, "class_hash": "0x1", "abi": null, "reads": [], "writes": [], "computed": [] },
+            "executor": { "name": "executor", "address": null, "class_hash": "0x2", "abi": null, "reads": [], "writes": [], "computed": [] },
+            "base": { "name": "base", "class_hash": "0x3", "abi": null },
+            "contracts": [{
+                "name": "dojo_examples::actions::actions",
+                "address": "0x10",
+                "class_hash": "0x4",
+                "reads": [],
+                "writes": [],
+                "computed": [],
+                "abi": [
+                    {
+                        "type": "enum",
+                        "name": "dojo_examples::models::Direction",
+                        "variants": [
+                            { "name": "None", "type": "()" },
+                            { "name": "Left", "type": "()" },
+                            { "name": "Right", "type": "()" }
+                        ]
+                    },
+                    {
+                        "type": "struct",
+                        "name": "dojo_examples::models::Vec2",
+                        "members": [
+                            { "name": "x", "type": "core::integer::u32" },
+                            { "name": "y", "type": "core::integer::u32" }
+                        ]
+                    },
+                    {
+                        "type": "struct",
+                        "name": "core::integer::u256",
+                        "members": [
+                            { "name": "low", "type": "core::integer::u128" },
+                            { "name": "high", "type": "core::integer::u128" }
+                        ]
+                    },
+                    {
+                        "type": "struct",
+                        "name": "dojo_examples::models::Pair::<core::integer::u8>",
+                        "members": [
+                            { "name": "first", "type": "core::integer::u8" },
+                            { "name": "second", "type": "core::integer::u8" }
+                        ]
+                    },
+                    {
+                        "type": "struct",
+                        "name": "dojo_examples::other::Vec2",
+                        "members": [
+                            { "name": "x", "type": "core::felt252" },
+                            { "name": "y", "type": "core::felt252" }
+                        ]
+                    },
+                    {
+                        "type": "enum",
+                        "name": "core::option::Option::<dojo_examples::models::Vec2>",
+                        "variants": [
+                            { "name": "Some", "type": "dojo_examples::models::Vec2" },
+                            { "name": "None", "type": "()" }
+                        ]
+                    },
+                    {
+                        "type": "interface",
+                        "name": "dojo_examples::actions::IActions",
+                        "items": [
+                            {
+                                "type": "function",
+                                "name": "spawn",
+                                "inputs": [],
+                                "outputs": [],
+                                "state_mutability": "external"
+                            },
+                            {
+                                "type": "function",
+                                "name": "move",
+                                "inputs": [
+                                    { "name": "direction", "type": "dojo_examples::models::Direction" },
+                                    { "name": "path", "type": "core::array::Span::<dojo_examples::models::Vec2>" }
+                                ],
+                                "outputs": [],
+                                "state_mutability": "external"
+                            },
+                            {
+                                "type": "function",
+                                "name": "configure",
+                                "inputs": [
+                                    { "name": "amount", "type": "core::integer::u256" },
+                                    { "name": "target", "type": "core::option::Option::<dojo_examples::models::Vec2>" },
+                                    { "name": "pair", "type": "dojo_examples::models::Pair::<core::integer::u8>" },
+                                    { "name": "origin", "type": "dojo_examples::other::Vec2" }
+                                ],
+                                "outputs": [],
+                                "state_mutability": "external"
+                            },
+                            {
+                                "type": "function",
+                                "name": "name",
+                                "inputs": [],
+                                "outputs": [{ "type": "core::felt252" }],
+                                "state_mutability": "view"
+                            }
+                        ]
+                    }
+                ]
+            }],
+            "models": [
+                {
+                    "name": "Moves",
+                    "members": [
+                        { "name": "player", "type": "ContractAddress", "key": true },
+                        { "name": "remaining", "type": "u8", "key": false },
+                        { "name": "last_direction", "type": "Direction", "key": false }
+                    ],
+                    "class_hash": "0x5",
+                    "abi": null
+                },
+                {
+                    "name": "Position",
+                    "members": [
+                        { "name": "player", "type": "ContractAddress", "key": true },
+                        { "name": "vec", "type": "Vec2", "key": false }
+                    ],
+                    "class_hash": "0x6",
+                    "abi": null
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn resolve_bindings() {
+        let bindings = Bindings::from_manifest(&manifest()).unwrap();
+
+        assert_eq!(bindings.models.len(), 2);
+        assert_eq!(bindings.models[0].members[2].ty, TypeRef::Named("Direction".to_string()));
+        assert_eq!(
+            bindings.types.iter().map(TypeDef::name).collect::<Vec<_>>(),
+            vec!["Direction", "Vec2", "PairU8", "DojoExamplesOtherVec2"]
+        );
+
+        let actions = &bindings.systems[0];
+        assert_eq!(actions.name, "actions");
+        assert_eq!(
+            actions.functions.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+            ["spawn", "move", "configure"]
+        );
+        assert_eq!(
+            actions.functions[1].inputs[1].1,
+            TypeRef::Array(Box::new(TypeRef::Named("Vec2".to_string())))
+        );
+        assert_eq!(
+            actions.functions[2].inputs.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>(),
+            vec![
+                TypeRef::Primitive(Primitive::U256(None)),
+                TypeRef::Option(Box::new(TypeRef::Named("Vec2".to_string()))),
+                TypeRef::Named("PairU8".to_string()),
+                TypeRef::Named("DojoExamplesOtherVec2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_rust_bindings() {
+        let bindings = Bindings::from_manifest(&manifest()).unwrap();
+        let files = RustGenerator.generate(&bindings).unwrap();
+        assert_eq!(files.len(), 1);
+
+        let (path, content) = &files[0];
+        assert_eq!(path, "bindings.rs");
+        assert!(content.contains(
+            "pub struct Moves {\n    pub player: FieldElement,\n    pub remaining: u8,\n    pub \
+             last_direction: Direction,\n}"
+        ));
+        assert!(content.contains("Ty::Primitive(Primitive::ContractAddress(Some(value.player)))"));
+        assert!(content.contains("impl TryFrom<&Ty> for Vec2"));
+        assert!(content.contains("pub struct Actions<'a, A>"));
+        assert!(content.contains(
+            "pub async fn r#move(\n        &self, direction: &Direction, path: &Vec<Vec2>\n    )"
+        ));
+        assert!(content.contains("`crypto-bigint` (0.5), `dojo-types` and `starknet` crates"));
+        assert!(content.contains("use crypto_bigint::{Encoding, U256};"));
+        assert!(content.contains(
+            "pub async fn configure(\n        &self, amount: &U256, target: &Option<Vec2>, pair: \
+             &PairU8, origin: &DojoExamplesOtherVec2\n    )"
+        ));
+
+        // The generated bindings are compiled with the tests, as the `generated` module.
+        assert_eq!(content, include_str!("test_data/bindings.rs"));
+    }
+
+    #[test]
+    fn use_generated_bindings() {
+        use crypto_bigint::U256;
+        use dojo_types::schema::Ty;
+        use starknet::core::types::FieldElement;
+
+        use super::generated::{CairoSerde, Direction, DojoExamplesOtherVec2, Moves, PairU8, Vec2};
+
+        let moves =
+            Moves { player: FieldElement::ONE, remaining: 10, last_direction: Direction::Right };
+        let ty = Ty::from(&moves);
+        assert_eq!(ty.name(), "Moves");
+        assert_eq!(Moves::try_from(&ty).unwrap(), moves);
+        assert_eq!(moves.keys(), vec![FieldElement::ONE]);
+        assert!(Moves::try_from(&Moves::schema()).is_err());
+
+        let mut calldata = vec![];
+        let target = Some(Vec2 { x: 1, y: 2 });
+        let origin = DojoExamplesOtherVec2 { x: FieldElement::TWO, y: FieldElement::from(3_u8) };
+        (U256::from(4_u128), target, PairU8 { first: 5, second: 6 }, origin)
+            .serialize(&mut calldata);
+        None::<Vec2>.serialize(&mut calldata);
+
+        let expected = [4_u8, 0, 0, 1, 2, 5, 6, 2, 3, 1];
+        assert_eq!(calldata, expected.map(FieldElement::from));
+    }
+
+    #[test]
+    fn reject_long_tuples() {
+        let tuple = |len| TypeRef::Tuple(vec![TypeRef::Primitive(Primitive::U8(None)); len]);
+        let bindings = |len| Bindings {
+            systems: vec![SystemDef {
+                name: "actions".to_string(),
+                functions: vec![FunctionDef {
+                    name: "spawn".to_string(),
+                    inputs: vec![("items".to_string(), tuple(len))],
+                }],
+            }],
+            ..Default::default()
+        };
+
+        assert!(RustGenerator.generate(&bindings(MAX_TUPLE_LEN)).is_ok());
+        let err = RustGenerator.generate(&bindings(MAX_TUPLE_LEN + 1)).unwrap_err();
+        assert!(err.to_string().contains("Tuples of more than 12 items"), "{err}");
+    }
+
+    #[test]
+    fn reject_unknown_types() {
+        let mut manifest = manifest();
+        manifest.models[1].members[1].ty = "Vec3".to_string();
+
+        let err = Bindings::from_manifest(&manifest).unwrap_err();
+        assert!(err.to_string().contains("member `vec` of model `Position`"));
+    }
+}
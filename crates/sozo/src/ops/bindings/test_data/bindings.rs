@@ -0,0 +1,454 @@
+// Generated by `sozo build --bindings rust`, don't edit.
+//
+// The bindings depend on the `crypto-bigint` (0.5), `dojo-types` and `starknet` crates.
+
+use crypto_bigint::{Encoding, U256};
+use dojo_types::primitive::Primitive;
+use dojo_types::schema::{Enum, EnumOption, Member, Struct, Ty};
+use starknet::accounts::{AccountError, Call, ConnectedAccount};
+use starknet::core::types::{FieldElement, InvokeTransactionResult};
+use starknet::core::utils::get_selector_from_name;
+
+/// Error returned when a schema doesn't match the type it's converted into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaMismatch;
+
+impl std::fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "schema mismatch")
+    }
+}
+
+impl std::error::Error for SchemaMismatch {}
+
+/// Serialization of the values passed to the systems, like Cairo's `Serde`.
+pub trait CairoSerde {
+    fn serialize(&self, out: &mut Vec<FieldElement>);
+}
+
+impl CairoSerde for FieldElement {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        out.push(*self);
+    }
+}
+
+impl CairoSerde for bool {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        out.push(if *self { FieldElement::ONE } else { FieldElement::ZERO });
+    }
+}
+
+macro_rules! integer_serde {
+    ($($ty:ty)+) => {
+        $(impl CairoSerde for $ty {
+            fn serialize(&self, out: &mut Vec<FieldElement>) {
+                out.push(FieldElement::from(*self));
+            }
+        })+
+    };
+}
+
+integer_serde!(u8 u16 u32 u64 u128);
+
+impl<T: CairoSerde> CairoSerde for Option<T> {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        match self {
+            Some(value) => {
+                out.push(FieldElement::ZERO);
+                value.serialize(out);
+            }
+            None => out.push(FieldElement::ONE),
+        }
+    }
+}
+
+impl<T: CairoSerde> CairoSerde for Vec<T> {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        out.push(FieldElement::from(self.len()));
+        self.iter().for_each(|item| item.serialize(out));
+    }
+}
+
+impl CairoSerde for () {
+    fn serialize(&self, _: &mut Vec<FieldElement>) {}
+}
+
+macro_rules! tuple_serde {
+    ($($name:ident)+) => {
+        impl<$($name: CairoSerde),+> CairoSerde for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn serialize(&self, out: &mut Vec<FieldElement>) {
+                let ($($name,)+) = self;
+                $($name.serialize(out);)+
+            }
+        }
+    };
+}
+
+tuple_serde!(A);
+tuple_serde!(A B);
+tuple_serde!(A B C);
+tuple_serde!(A B C D);
+tuple_serde!(A B C D E);
+tuple_serde!(A B C D E F);
+tuple_serde!(A B C D E F G);
+tuple_serde!(A B C D E F G H);
+tuple_serde!(A B C D E F G H I);
+tuple_serde!(A B C D E F G H I J);
+tuple_serde!(A B C D E F G H I J K);
+tuple_serde!(A B C D E F G H I J K L);
+
+impl CairoSerde for U256 {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        let bytes = self.to_be_bytes();
+        out.push(FieldElement::from_byte_slice_be(&bytes[16..]).unwrap());
+        out.push(FieldElement::from_byte_slice_be(&bytes[..16]).unwrap());
+    }
+}
+
+/// The `Moves` model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Moves {
+    pub player: FieldElement,
+    pub remaining: u8,
+    pub last_direction: Direction,
+}
+
+impl CairoSerde for Moves {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        self.player.serialize(out);
+        self.remaining.serialize(out);
+        self.last_direction.serialize(out);
+    }
+}
+
+impl Moves {
+    /// Returns the schema of the type, without values.
+    pub fn schema() -> Ty {
+        Ty::Struct(Struct {
+            name: "Moves".to_string(),
+            children: vec![
+                Member { name: "player".to_string(), ty: Ty::Primitive(Primitive::ContractAddress(None)), key: true },
+                Member { name: "remaining".to_string(), ty: Ty::Primitive(Primitive::U8(None)), key: false },
+                Member { name: "last_direction".to_string(), ty: Direction::schema(), key: false },
+            ],
+        })
+    }
+}
+
+impl From<&Moves> for Ty {
+    fn from(value: &Moves) -> Self {
+        Ty::Struct(Struct {
+            name: "Moves".to_string(),
+            children: vec![
+                Member { name: "player".to_string(), ty: Ty::Primitive(Primitive::ContractAddress(Some(value.player))), key: true },
+                Member { name: "remaining".to_string(), ty: Ty::Primitive(Primitive::U8(Some(value.remaining))), key: false },
+                Member { name: "last_direction".to_string(), ty: Ty::from(&value.last_direction), key: false },
+            ],
+        })
+    }
+}
+
+impl TryFrom<&Ty> for Moves {
+    type Error = SchemaMismatch;
+
+    fn try_from(ty: &Ty) -> Result<Self, Self::Error> {
+        let s = ty.as_struct().ok_or(SchemaMismatch)?;
+        Ok(Self {
+            player: s.get("player").ok_or(SchemaMismatch)?.as_primitive().and_then(Primitive::as_contract_address).ok_or(SchemaMismatch)?,
+            remaining: s.get("remaining").ok_or(SchemaMismatch)?.as_primitive().and_then(Primitive::as_u8).ok_or(SchemaMismatch)?,
+            last_direction: Direction::try_from(s.get("last_direction").ok_or(SchemaMismatch)?)?,
+        })
+    }
+}
+
+impl Moves {
+    /// The name of the model in the World.
+    pub const NAME: &'static str = "Moves";
+
+    /// Returns the keys of the entity, to read it from the World.
+    pub fn keys(&self) -> Vec<FieldElement> {
+        let mut keys = vec![];
+        self.player.serialize(&mut keys);
+        keys
+    }
+}
+
+/// The `Position` model.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub player: FieldElement,
+    pub vec: Vec2,
+}
+
+impl CairoSerde for Position {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        self.player.serialize(out);
+        self.vec.serialize(out);
+    }
+}
+
+impl Position {
+    /// Returns the schema of the type, without values.
+    pub fn schema() -> Ty {
+        Ty::Struct(Struct {
+            name: "Position".to_string(),
+            children: vec![
+                Member { name: "player".to_string(), ty: Ty::Primitive(Primitive::ContractAddress(None)), key: true },
+                Member { name: "vec".to_string(), ty: Vec2::schema(), key: false },
+            ],
+        })
+    }
+}
+
+impl From<&Position> for Ty {
+    fn from(value: &Position) -> Self {
+        Ty::Struct(Struct {
+            name: "Position".to_string(),
+            children: vec![
+                Member { name: "player".to_string(), ty: Ty::Primitive(Primitive::ContractAddress(Some(value.player))), key: true },
+                Member { name: "vec".to_string(), ty: Ty::from(&value.vec), key: false },
+            ],
+        })
+    }
+}
+
+impl TryFrom<&Ty> for Position {
+    type Error = SchemaMismatch;
+
+    fn try_from(ty: &Ty) -> Result<Self, Self::Error> {
+        let s = ty.as_struct().ok_or(SchemaMismatch)?;
+        Ok(Self {
+            player: s.get("player").ok_or(SchemaMismatch)?.as_primitive().and_then(Primitive::as_contract_address).ok_or(SchemaMismatch)?,
+            vec: Vec2::try_from(s.get("vec").ok_or(SchemaMismatch)?)?,
+        })
+    }
+}
+
+impl Position {
+    /// The name of the model in the World.
+    pub const NAME: &'static str = "Position";
+
+    /// Returns the keys of the entity, to read it from the World.
+    pub fn keys(&self) -> Vec<FieldElement> {
+        let mut keys = vec![];
+        self.player.serialize(&mut keys);
+        keys
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    None,
+    Left,
+    Right,
+}
+
+impl CairoSerde for Direction {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        match self {
+            Direction::None => out.push(FieldElement::from(0_usize)),
+            Direction::Left => out.push(FieldElement::from(1_usize)),
+            Direction::Right => out.push(FieldElement::from(2_usize)),
+        }
+    }
+}
+
+impl Direction {
+    /// Returns the schema of the type, without values.
+    pub fn schema() -> Ty {
+        Ty::Enum(Enum {
+            name: "Direction".to_string(),
+            option: None,
+            options: vec![
+                EnumOption { name: "None".to_string(), ty: Ty::Tuple(vec![]) },
+                EnumOption { name: "Left".to_string(), ty: Ty::Tuple(vec![]) },
+                EnumOption { name: "Right".to_string(), ty: Ty::Tuple(vec![]) },
+            ],
+        })
+    }
+}
+
+impl From<&Direction> for Ty {
+    fn from(value: &Direction) -> Self {
+        let option = match value {
+                Direction::None => 0,
+                Direction::Left => 1,
+                Direction::Right => 2,
+        };
+
+        Ty::Enum(Enum {
+            name: "Direction".to_string(),
+            option: Some(option),
+            options: vec![
+                EnumOption { name: "None".to_string(), ty: Ty::Tuple(vec![]) },
+                EnumOption { name: "Left".to_string(), ty: Ty::Tuple(vec![]) },
+                EnumOption { name: "Right".to_string(), ty: Ty::Tuple(vec![]) },
+            ],
+        })
+    }
+}
+
+impl TryFrom<&Ty> for Direction {
+    type Error = SchemaMismatch;
+
+    fn try_from(ty: &Ty) -> Result<Self, Self::Error> {
+        match ty.as_enum().ok_or(SchemaMismatch)?.option {
+            Some(0) => Ok(Direction::None),
+            Some(1) => Ok(Direction::Left),
+            Some(2) => Ok(Direction::Right),
+            _ => Err(SchemaMismatch),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vec2 {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl CairoSerde for Vec2 {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        self.x.serialize(out);
+        self.y.serialize(out);
+    }
+}
+
+impl Vec2 {
+    /// Returns the schema of the type, without values.
+    pub fn schema() -> Ty {
+        Ty::Struct(Struct {
+            name: "Vec2".to_string(),
+            children: vec![
+                Member { name: "x".to_string(), ty: Ty::Primitive(Primitive::U32(None)), key: false },
+                Member { name: "y".to_string(), ty: Ty::Primitive(Primitive::U32(None)), key: false },
+            ],
+        })
+    }
+}
+
+impl From<&Vec2> for Ty {
+    fn from(value: &Vec2) -> Self {
+        Ty::Struct(Struct {
+            name: "Vec2".to_string(),
+            children: vec![
+                Member { name: "x".to_string(), ty: Ty::Primitive(Primitive::U32(Some(value.x))), key: false },
+                Member { name: "y".to_string(), ty: Ty::Primitive(Primitive::U32(Some(value.y))), key: false },
+            ],
+        })
+    }
+}
+
+impl TryFrom<&Ty> for Vec2 {
+    type Error = SchemaMismatch;
+
+    fn try_from(ty: &Ty) -> Result<Self, Self::Error> {
+        let s = ty.as_struct().ok_or(SchemaMismatch)?;
+        Ok(Self {
+            x: s.get("x").ok_or(SchemaMismatch)?.as_primitive().and_then(Primitive::as_u32).ok_or(SchemaMismatch)?,
+            y: s.get("y").ok_or(SchemaMismatch)?.as_primitive().and_then(Primitive::as_u32).ok_or(SchemaMismatch)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PairU8 {
+    pub first: u8,
+    pub second: u8,
+}
+
+impl CairoSerde for PairU8 {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        self.first.serialize(out);
+        self.second.serialize(out);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DojoExamplesOtherVec2 {
+    pub x: FieldElement,
+    pub y: FieldElement,
+}
+
+impl CairoSerde for DojoExamplesOtherVec2 {
+    fn serialize(&self, out: &mut Vec<FieldElement>) {
+        self.x.serialize(out);
+        self.y.serialize(out);
+    }
+}
+
+/// The systems of the `actions` contract.
+pub struct Actions<'a, A>
+where
+    A: ConnectedAccount,
+{
+    pub address: FieldElement,
+    account: &'a A,
+}
+
+impl<'a, A> Actions<'a, A>
+where
+    A: ConnectedAccount + Sync,
+{
+    pub fn new(address: FieldElement, account: &'a A) -> Self {
+        Self { address, account }
+    }
+
+    /// Returns the call to `spawn`, to be executed along other calls.
+    pub fn spawn_call(&self) -> Call {
+        let calldata = vec![];
+        Call {
+            to: self.address,
+            selector: get_selector_from_name("spawn").unwrap(),
+            calldata,
+        }
+    }
+
+    /// Executes `spawn`.
+    pub async fn spawn(
+        &self
+    ) -> Result<InvokeTransactionResult, AccountError<A::SignError>> {
+        self.account.execute(vec![self.spawn_call()]).send().await
+    }
+
+    /// Returns the call to `move`, to be executed along other calls.
+    pub fn move_call(&self, direction: &Direction, path: &Vec<Vec2>) -> Call {
+        let mut calldata = vec![];
+        direction.serialize(&mut calldata);
+        path.serialize(&mut calldata);
+        Call {
+            to: self.address,
+            selector: get_selector_from_name("move").unwrap(),
+            calldata,
+        }
+    }
+
+    /// Executes `move`.
+    pub async fn r#move(
+        &self, direction: &Direction, path: &Vec<Vec2>
+    ) -> Result<InvokeTransactionResult, AccountError<A::SignError>> {
+        self.account.execute(vec![self.move_call(direction, path)]).send().await
+    }
+
+    /// Returns the call to `configure`, to be executed along other calls.
+    pub fn configure_call(&self, amount: &U256, target: &Option<Vec2>, pair: &PairU8, origin: &DojoExamplesOtherVec2) -> Call {
+        let mut calldata = vec![];
+        amount.serialize(&mut calldata);
+        target.serialize(&mut calldata);
+        pair.serialize(&mut calldata);
+        origin.serialize(&mut calldata);
+        Call {
+            to: self.address,
+            selector: get_selector_from_name("configure").unwrap(),
+            calldata,
+        }
+    }
+
+    /// Executes `configure`.
+    pub async fn configure(
+        &self, amount: &U256, target: &Option<Vec2>, pair: &PairU8, origin: &DojoExamplesOtherVec2
+    ) -> Result<InvokeTransactionResult, AccountError<A::SignError>> {
+        self.account.execute(vec![self.configure_call(amount, target, pair, origin)]).send().await
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Context, Result};
+use dojo_world::contracts::calldata::{self, Value};
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::manifest::Manifest;
+use dojo_world::metadata::Environment;
+use starknet::core::types::{BlockId, FieldElement, FunctionCall};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::Provider;
+
+use super::execute::{contract_address, find_contract};
+use crate::commands::call::CallArgs;
+use crate::commands::options::world::WorldOptions;
+
+pub async fn call(
+    args: CallArgs,
+    env_metadata: Option<Environment>,
+    manifest: Option<Manifest>,
+) -> Result<()> {
+    let CallArgs { contract, entrypoint, calldata, args, keys, block, world, starknet } = args;
+
+    let provider = starknet.provider(env_metadata.as_ref())?;
+
+    let output = call_entrypoint(
+        manifest.as_ref(),
+        &contract,
+        &entrypoint,
+        calldata,
+        args.as_deref(),
+        &keys,
+        block,
+        &world,
+        env_metadata.as_ref(),
+        &provider,
+    )
+    .await?;
+
+    output.iter().for_each(|value| println!("{value}"));
+
+    Ok(())
+}
+
+/// Calls `entrypoint` of `contract` and returns its outputs, decoded using the ABI of the contract
+/// if possible or as raw felts otherwise.
+///
+/// The model argument of a computed entrypoint is read from the World using the entity `keys`, at
+/// the same `block` as the call.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn call_entrypoint<P>(
+    manifest: Option<&Manifest>,
+    contract: &str,
+    entrypoint: &str,
+    mut calldata: Vec<FieldElement>,
+    args: Option<&str>,
+    keys: &[FieldElement],
+    block: BlockId,
+    world: &WorldOptions,
+    env_metadata: Option<&Environment>,
+    provider: P,
+) -> Result<Vec<String>>
+where
+    P: Provider + Sync + Send,
+{
+    let local_contract = manifest.and_then(|m| find_contract(m, contract));
+    let contract_address =
+        contract_address(contract, local_contract, world, env_metadata, &provider).await?;
+
+    let local_abi = local_contract.and_then(|c| c.abi.as_ref());
+    let computed = local_contract
+        .and_then(|c| c.computed.iter().find(|computed| computed.entrypoint == entrypoint));
+
+    let mut skipped_inputs = 0;
+    if !keys.is_empty() {
+        let model = computed.and_then(|c| c.model.as_ref()).ok_or_else(|| {
+            anyhow!("`{entrypoint}` isn't a computed entrypoint bound to a model.")
+        })?;
+
+        let world_address = world.address(env_metadata)?;
+        let world = WorldContractReader::new(world_address, &provider).with_block(block);
+
+        // The model is the first argument of the entrypoint, so it goes before the calldata.
+        let entity = world.model(model).await?.entity(keys).await?;
+        calldata = entity.serialize()?.into_iter().chain(calldata).collect();
+        skipped_inputs = 1;
+    }
+
+    if let Some(args) = args {
+        let abi = local_abi.ok_or_else(|| {
+            anyhow!("No ABI found for contract `{contract}`, use `--calldata` instead.")
+        })?;
+        let function = calldata::find_function(abi, entrypoint)
+            .ok_or_else(|| anyhow!("Entrypoint `{entrypoint}` not found in the ABI."))?;
+
+        let args = Value::parse_list(args)?;
+        let inputs = &function.inputs[skipped_inputs.min(function.inputs.len())..];
+        if inputs.len() != args.len() {
+            return Err(calldata::CalldataError::ArgumentCount {
+                expected: inputs.len(),
+                got: args.len(),
+            }
+            .into());
+        }
+
+        for (input, arg) in inputs.iter().zip(&args) {
+            calldata::encode(abi, &input.ty, arg, &mut calldata)?;
+        }
+    }
+
+    let output = provider
+        .call(
+            FunctionCall {
+                contract_address,
+                entry_point_selector: get_selector_from_name(entrypoint)?,
+                calldata,
+            },
+            block,
+        )
+        .await
+        .with_context(|| format!("Failed to call `{entrypoint}`"))?;
+
+    Ok(match local_abi.map(|abi| calldata::decode_outputs(abi, entrypoint, &output)) {
+        Some(Ok(values)) => values.iter().map(|value| value.to_string()).collect(),
+        _ => output.iter().map(|felt| format!("{felt:#x}")).collect(),
+    })
+}
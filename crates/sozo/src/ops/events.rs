@@ -1,162 +1,188 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
+use std::time::Duration;
 
 use anyhow::Result;
-use cairo_lang_starknet::abi::{Event, EventKind};
-use cairo_lang_starknet::plugin::events::EventFieldKind;
+use cairo_lang_starknet::abi;
+use dojo_types::schema::Ty;
+use dojo_world::contracts::calldata::Value;
+use dojo_world::contracts::events::{decode_event, StoreSetRecord};
+use dojo_world::contracts::model::ModelReader;
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::manifest::Manifest;
 use dojo_world::metadata::Environment;
-use starknet::core::types::{BlockId, EventFilter};
-use starknet::core::utils::{parse_cairo_short_string, starknet_keccak};
+use starknet::core::types::{BlockId, BlockTag, EmittedEvent, EventFilter, FieldElement};
+use starknet::core::utils::{cairo_short_string_to_felt, starknet_keccak};
 use starknet::providers::Provider;
 
+use super::execute::manifest_abis;
 use crate::commands::events::EventsArgs;
 
+/// Prints the events of the World at `world_address`, or of every contract if it's not known.
 pub async fn execute(
     args: EventsArgs,
     env_metadata: Option<Environment>,
-    events_map: HashMap<String, Vec<Event>>,
+    manifest: Manifest,
+    world_address: Option<FieldElement>,
 ) -> Result<()> {
     let EventsArgs {
         chunk_size,
         starknet,
-        world,
+        world: _,
         from_block,
         to_block,
         events,
         continuation_token,
+        follow,
+        interval,
+        model,
+        keys,
         json,
     } = args;
 
-    let from_block = from_block.map(BlockId::Number);
-    let to_block = to_block.map(BlockId::Number);
+    let model = model.map(|name| cairo_short_string_to_felt(&name)).transpose()?;
+    let filter_records = model.is_some() || !keys.is_empty();
+
     // Currently dojo doesn't use custom keys for events. In future if custom keys are used this
     // needs to be updated for granular queries.
-    let keys =
-        events.map(|e| vec![e.iter().map(|event| starknet_keccak(event.as_bytes())).collect()]);
+    let mut selectors =
+        events.map(|e| e.iter().map(|event| starknet_keccak(event.as_bytes())).collect::<Vec<_>>());
+    if filter_records {
+        let store_set_record = starknet_keccak(b"StoreSetRecord");
+        selectors.get_or_insert_with(Vec::new).push(store_set_record);
+    }
 
     let provider = starknet.provider(env_metadata.as_ref())?;
-    let event_filter = EventFilter { from_block, to_block, address: world.world_address, keys };
+    let world_reader = world_address.map(|address| {
+        WorldContractReader::new(address, &provider).with_block(BlockId::Tag(BlockTag::Pending))
+    });
+
+    let mut printer = EventPrinter {
+        abis: manifest_abis(&manifest),
+        world: world_reader.as_ref(),
+        schemas: HashMap::new(),
+        model,
+        keys,
+        json,
+    };
+
+    let mut filter = EventFilter {
+        from_block: from_block.map(BlockId::Number),
+        to_block: to_block.map(BlockId::Number),
+        address: world_address,
+        keys: selectors.map(|selectors| vec![selectors]),
+    };
+    let mut continuation_token = continuation_token;
+
+    loop {
+        // When following, the range is bounded so the next poll starts where this one ended.
+        let latest = if follow { Some(provider.block_number().await?) } else { None };
+        if let Some(latest) = latest {
+            filter.to_block = Some(BlockId::Number(latest));
+        }
+
+        loop {
+            let page =
+                provider.get_events(filter.clone(), continuation_token.take(), chunk_size).await?;
 
-    let res = provider.get_events(event_filter, continuation_token, chunk_size).await?;
+            for event in page.events {
+                printer.print(event).await?;
+            }
+
+            match page.continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
 
-    if json {
-        let value = serde_json::to_value(res)?;
-        println!("{}", serde_json::to_string_pretty(&value)?);
-    } else {
-        parse_and_print_events(res, events_map);
+        let Some(latest) = latest else { break };
+        filter.from_block = Some(BlockId::Number(latest + 1));
+
+        while provider.block_number().await? <= latest {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
     }
+
     Ok(())
 }
 
-fn parse_and_print_events(
-    res: starknet::core::types::EventsPage,
-    events_map: HashMap<String, Vec<Event>>,
-) {
-    println!("Continuation token: {:?}", res.continuation_token);
-    println!("----------------------------------------------");
-    for event in res.events {
-        if let Some(e) = parse_event(event.clone(), &events_map) {
-            println!("{}\n", e);
-        } else {
-            // Couldn't parse event
-            println!("{}\n", serde_json::to_string_pretty(&event).unwrap());
-        }
-    }
+struct EventPrinter<'a, P> {
+    abis: Vec<&'a abi::Contract>,
+    world: Option<&'a WorldContractReader<P>>,
+    /// Schemas of the models, fetched from the World the first time a record is seen.
+    schemas: HashMap<String, Option<Ty>>,
+    model: Option<FieldElement>,
+    keys: Vec<FieldElement>,
+    json: bool,
 }
 
-fn parse_event(
-    event: starknet::core::types::EmittedEvent,
-    events_map: &HashMap<String, Vec<Event>>,
-) -> Option<String> {
-    let keys = event.keys;
-    let event_hash = keys[0].to_string();
-    let events = events_map.get(&event_hash)?;
-
-    'outer: for e in events {
-        let mut ret = format!("Event name: {}\n", e.name);
-        let mut data = VecDeque::from(event.data.clone());
-
-        // Length is two only when its custom event
-        if keys.len() == 2 {
-            let name = parse_cairo_short_string(&keys[1]).ok()?;
-            ret.push_str(&format!("Model name: {}\n", name));
+impl<'a, P> EventPrinter<'a, P>
+where
+    P: Provider + Sync + Send,
+{
+    async fn print(&mut self, event: EmittedEvent) -> Result<()> {
+        let record = StoreSetRecord::parse(&event.keys, &event.data);
+        if !self.matches(record.as_ref()) {
+            return Ok(());
+        }
+
+        let decoded = match (record, self.world) {
+            (Some(record), Some(world)) => decode_record(&record, world, &mut self.schemas)
+                .await
+                .map(|value| format!("StoreSetRecord: {value}")),
+            _ => None,
         }
+        .or_else(|| decode_event(&self.abis, &event.keys, &event.data).map(|e| e.to_string()));
 
-        match &e.kind {
-            EventKind::Struct { members } => {
-                for field in members {
-                    if field.kind != EventFieldKind::DataSerde {
-                        continue;
-                    }
-                    match field.ty.as_str() {
-                        "core::starknet::contract_address::ContractAddress"
-                        | "core::starknet::class_hash::ClassHash" => {
-                            let value = match data.pop_front() {
-                                Some(addr) => addr,
-                                None => continue 'outer,
-                            };
-                            ret.push_str(&format!("{}: {:#x}\n", field.name, value));
-                        }
-                        "core::felt252" => {
-                            let value = match data.pop_front() {
-                                Some(addr) => addr,
-                                None => continue 'outer,
-                            };
-                            let value = match parse_cairo_short_string(&value) {
-                                Ok(v) => v,
-                                Err(_) => format!("{:#x}", value),
-                            };
-                            ret.push_str(&format!("{}: {}\n", field.name, value));
-                        }
-                        "core::integer::u8" => {
-                            let value = match data.pop_front() {
-                                Some(addr) => addr,
-                                None => continue 'outer,
-                            };
-                            let num = match value.to_string().parse::<u8>() {
-                                Ok(num) => num,
-                                Err(_) => continue 'outer,
-                            };
-
-                            ret.push_str(&format!("{}: {}\n", field.name, num));
-                        }
-                        "dojo_examples::systems::move::Direction" => {
-                            let value = match data.pop_front() {
-                                Some(addr) => addr,
-                                None => continue 'outer,
-                            };
-                            ret.push_str(&format!("{}: {}\n", field.name, value));
-                        }
-                        "core::array::Span::<core::felt252>" => {
-                            let length = match data.pop_front() {
-                                Some(addr) => addr,
-                                None => continue 'outer,
-                            };
-                            let length = match length.to_string().parse::<usize>() {
-                                Ok(len) => len,
-                                Err(_) => continue 'outer,
-                            };
-                            ret.push_str(&format!("{}: ", field.name));
-                            if data.len() >= length {
-                                ret.push_str(&format!(
-                                    "{:?}\n",
-                                    data.drain(..length)
-                                        .map(|e| format!("{:#x}", e))
-                                        .collect::<Vec<_>>()
-                                ));
-                            } else {
-                                continue 'outer;
-                            }
-                        }
-                        _ => {
-                            return None;
-                        }
-                    }
-                }
-                return Some(ret);
+        if self.json {
+            let mut value = serde_json::to_value(&event)?;
+            if let (Some(object), Some(decoded)) = (value.as_object_mut(), decoded) {
+                object.insert("decoded".to_string(), decoded.into());
+            }
+            println!("{}", serde_json::to_string(&value)?);
+        } else {
+            match decoded {
+                Some(decoded) => println!("{decoded}\n"),
+                // Couldn't parse event
+                None => println!("{}\n", serde_json::to_string_pretty(&event)?),
             }
-            EventKind::Enum { .. } => unreachable!("shouldn't reach here"),
         }
+
+        Ok(())
+    }
+
+    /// Whether the event passes the model and keys filters, matched against the table and the
+    /// keys of `StoreSetRecord` events.
+    fn matches(&self, record: Option<&StoreSetRecord>) -> bool {
+        if self.model.is_none() && self.keys.is_empty() {
+            return true;
+        }
+
+        record.is_some_and(|record| {
+            self.model.map_or(true, |model| record.table == model)
+                && record.keys.starts_with(&self.keys)
+        })
+    }
+}
+
+/// Decodes a `StoreSetRecord` event into the value of its model, fetching the schema of the model
+/// from the World the first time it's seen.
+async fn decode_record<P>(
+    record: &StoreSetRecord,
+    world: &WorldContractReader<P>,
+    schemas: &mut HashMap<String, Option<Ty>>,
+) -> Option<Value>
+where
+    P: Provider + Sync + Send,
+{
+    let model = record.model()?;
+
+    if !schemas.contains_key(&model) {
+        let schema = match world.model(&model).await {
+            Ok(reader) => reader.schema().await.ok(),
+            Err(_) => None,
+        };
+        schemas.insert(model.clone(), schema);
     }
 
-    None
+    record.decode(schemas.get(&model)?.as_ref()?).ok()
 }
@@ -1,60 +1,175 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use cairo_lang_starknet::abi;
+use dojo_world::contracts::calldata::{self, Value};
+use dojo_world::contracts::events::decode_event;
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::manifest::{Contract, Manifest};
 use dojo_world::metadata::Environment;
 use dojo_world::migration::strategy::generate_salt;
-use starknet::accounts::{Account, Call};
-use starknet::core::types::{BlockId, BlockTag, FieldElement, FunctionCall};
+use dojo_world::utils::TransactionWaiter;
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, Event, FieldElement, MaybePendingTransactionReceipt,
+    PendingTransactionReceipt, TransactionReceipt,
+};
 use starknet::core::utils::{get_contract_address, get_selector_from_name};
-use starknet::macros::selector;
 use starknet::providers::Provider;
 
 use crate::commands::execute::ExecuteArgs;
+use crate::commands::options::world::WorldOptions;
 
-pub async fn execute(args: ExecuteArgs, env_metadata: Option<Environment>) -> Result<()> {
-    let ExecuteArgs { contract, entrypoint, calldata, starknet, account } = args;
+pub async fn execute(
+    args: ExecuteArgs,
+    env_metadata: Option<Environment>,
+    manifest: Option<Manifest>,
+) -> Result<()> {
+    let ExecuteArgs { contract, entrypoint, calldata, args, world, starknet, account } = args;
 
     let provider = starknet.provider(env_metadata.as_ref())?;
 
-    let contract_address = if contract.starts_with("0x") {
-        FieldElement::from_hex_be(&contract)?
-    } else {
-        let world_address = env_metadata
-            .as_ref()
-            .and_then(|env| env.world_address.as_ref())
-            .cloned()
-            .ok_or_else(|| anyhow::anyhow!("No World Address found"))?;
-
-        let contract_class_hash = provider
-            .call(
-                FunctionCall {
-                    contract_address: FieldElement::from_hex_be(&world_address).unwrap(),
-                    entry_point_selector: selector!("base"),
-                    calldata: [].to_vec(),
-                },
-                BlockId::Tag(BlockTag::Latest),
-            )
-            .await?;
-
-        get_contract_address(
-            generate_salt(&contract),
-            contract_class_hash[0],
-            &[],
-            FieldElement::from_hex_be(&world_address).unwrap(),
-        )
-    };
+    let call = prepare_call(
+        manifest.as_ref(),
+        &contract,
+        &entrypoint,
+        calldata,
+        args.as_deref(),
+        &world,
+        env_metadata.as_ref(),
+        &provider,
+    )
+    .await?;
 
     let account = account.account(provider, env_metadata.as_ref()).await?;
 
-    let res = account
-        .execute(vec![Call {
-            calldata,
-            to: contract_address,
-            selector: get_selector_from_name(&entrypoint).unwrap(),
-        }])
-        .send()
-        .await
-        .with_context(|| "Failed to send transaction")?;
+    let res =
+        account.execute(vec![call]).send().await.with_context(|| "Failed to send transaction")?;
 
     println!("Transaction: {:#x}", res.transaction_hash);
 
+    let events = wait_for_events(account.provider(), res.transaction_hash).await?;
+    let abis = manifest.as_ref().map(manifest_abis).unwrap_or_default();
+
+    if !events.is_empty() {
+        println!("\nEvents:");
+    }
+    for event in events {
+        match decode_event(&abis, &event.keys, &event.data) {
+            Some(decoded) => println!("  {decoded}"),
+            None => println!(
+                "  {:#x}: keys {:?} data {:?}",
+                event.from_address,
+                event.keys.iter().map(|k| format!("{k:#x}")).collect::<Vec<_>>(),
+                event.data.iter().map(|d| format!("{d:#x}")).collect::<Vec<_>>()
+            ),
+        }
+    }
+
     Ok(())
 }
+
+/// Builds the call to `entrypoint` of `contract`. If any, the typed `args` are serialized using
+/// the ABI of the contract, otherwise the raw `calldata` is used.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn prepare_call<P>(
+    manifest: Option<&Manifest>,
+    contract: &str,
+    entrypoint: &str,
+    calldata: Vec<FieldElement>,
+    args: Option<&str>,
+    world: &WorldOptions,
+    env_metadata: Option<&Environment>,
+    provider: P,
+) -> Result<Call>
+where
+    P: Provider + Sync + Send,
+{
+    let local_contract = manifest.and_then(|m| find_contract(m, contract));
+    let contract_address =
+        contract_address(contract, local_contract, world, env_metadata, provider).await?;
+
+    let calldata = match args {
+        Some(args) => {
+            let abi = local_contract.and_then(|c| c.abi.as_ref()).ok_or_else(|| {
+                anyhow!("No ABI found for contract `{contract}`, use `--calldata` instead.")
+            })?;
+
+            let args = Value::parse_list(args)?;
+            calldata::encode_inputs(abi, entrypoint, &args)?
+        }
+        None => calldata,
+    };
+
+    Ok(Call { calldata, to: contract_address, selector: get_selector_from_name(entrypoint)? })
+}
+
+/// Waits for the transaction to be accepted and returns the events it emitted.
+pub(crate) async fn wait_for_events<P>(
+    provider: &P,
+    transaction_hash: FieldElement,
+) -> Result<Vec<Event>>
+where
+    P: Provider + Send,
+{
+    let receipt = TransactionWaiter::new(transaction_hash, provider).await?;
+
+    Ok(match receipt {
+        MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt)) => {
+            receipt.events
+        }
+        MaybePendingTransactionReceipt::PendingReceipt(PendingTransactionReceipt::Invoke(
+            receipt,
+        )) => receipt.events,
+        _ => vec![],
+    })
+}
+
+/// Returns the ABIs of the World and of the contracts of the manifest.
+pub(crate) fn manifest_abis(manifest: &Manifest) -> Vec<&abi::Contract> {
+    std::iter::once(manifest.world.abi.as_ref())
+        .chain(manifest.contracts.iter().map(|c| c.abi.as_ref()))
+        .flatten()
+        .collect()
+}
+
+/// Finds a contract of the manifest by its full name, the last segment of its name or its address.
+pub(crate) fn find_contract<'a>(manifest: &'a Manifest, name: &str) -> Option<&'a Contract> {
+    manifest
+        .contracts
+        .iter()
+        .find(|c| c.name == name)
+        .or_else(|| manifest.contracts.iter().find(|c| c.name.rsplit("::").next() == Some(name)))
+        .or_else(|| {
+            let address = FieldElement::from_hex_be(name).ok()?;
+            manifest.contracts.iter().find(|c| c.address == Some(address))
+        })
+}
+
+/// Returns the address of `contract`, given either as an address or as a name. The address of a
+/// contract missing from the manifest is derived from its name, like during the migration.
+pub(crate) async fn contract_address<P>(
+    contract: &str,
+    local_contract: Option<&Contract>,
+    world: &WorldOptions,
+    env_metadata: Option<&Environment>,
+    provider: P,
+) -> Result<FieldElement>
+where
+    P: Provider + Sync + Send,
+{
+    if contract.starts_with("0x") {
+        return Ok(FieldElement::from_hex_be(contract)?);
+    }
+
+    if let Some(address) = local_contract.and_then(|c| c.address) {
+        return Ok(address);
+    }
+
+    let world_address = world.address(env_metadata)?;
+    let base_class_hash = WorldContractReader::new(world_address, provider)
+        .with_block(BlockId::Tag(BlockTag::Pending))
+        .base()
+        .await?;
+
+    let name = local_contract.map(|c| c.name.as_str()).unwrap_or(contract);
+    Ok(get_contract_address(generate_salt(name), base_class_hash, &[], world_address))
+}
@@ -0,0 +1,328 @@
+//! Audit of a deployed World.
+//!
+//! Gathers everything worth checking when a World misbehaves: its core contracts, the models
+//! registered and their schemas, the contracts deployed, its metadata and how it differs from the
+//! local build.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use dojo_types::schema::Ty;
+use dojo_world::contracts::model::ModelReader;
+use dojo_world::contracts::world::WorldContractReader;
+use dojo_world::manifest::Manifest;
+use dojo_world::metadata::WorldMetadata;
+use dojo_world::migration::world::WorldDiff;
+use dojo_world::migration::StateDiff;
+use serde::Serialize;
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::providers::Provider;
+
+#[derive(Debug, Serialize)]
+pub struct WorldReport {
+    pub world: CoreContract,
+    pub executor: CoreContract,
+    pub base: CoreContract,
+    pub models: Vec<ModelReport>,
+    pub contracts: Vec<ContractReport>,
+    pub metadata: MetadataReport,
+    /// The differences with the local build, unless the project isn't built.
+    pub mismatches: Option<Vec<Mismatch>>,
+}
+
+/// The World, its executor or its base class. The contracts don't expose a version, so the class
+/// hash is compared with the one of the local build instead.
+#[derive(Debug, Serialize)]
+pub struct CoreContract {
+    pub address: Option<FieldElement>,
+    pub class_hash: FieldElement,
+    pub local_class_hash: Option<FieldElement>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelReport {
+    pub name: String,
+    pub class_hash: FieldElement,
+    pub schema: Ty,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContractReport {
+    pub name: String,
+    pub address: Option<FieldElement>,
+    pub class_hash: FieldElement,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetadataReport {
+    pub uri: String,
+    pub content: Option<WorldMetadata>,
+    /// Why the content couldn't be fetched, the rest of the report is still useful without it.
+    pub error: Option<String>,
+}
+
+/// A class of the World which differs from the local build.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Mismatch {
+    pub name: String,
+    /// The local class hash, unset when the class is only on the World.
+    pub local: Option<FieldElement>,
+    /// The remote class hash, unset when the class isn't on the World yet.
+    pub remote: Option<FieldElement>,
+}
+
+pub async fn execute<P>(
+    world_address: FieldElement,
+    provider: P,
+    local_manifest: Option<Manifest>,
+    json: bool,
+) -> Result<()>
+where
+    P: Provider + Sync + Send,
+{
+    let report = inspect(world_address, &provider, local_manifest).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    Ok(())
+}
+
+async fn inspect<P>(
+    world_address: FieldElement,
+    provider: P,
+    local_manifest: Option<Manifest>,
+) -> Result<WorldReport>
+where
+    P: Provider + Sync + Send,
+{
+    let remote = Manifest::load_from_remote(&provider, world_address)
+        .await
+        .with_context(|| format!("Failed to load World {world_address:#x}"))?;
+
+    let world = WorldContractReader::new(world_address, &provider)
+        .with_block(BlockId::Tag(BlockTag::Pending));
+
+    let mut models = vec![];
+    for model in &remote.models {
+        let schema = world
+            .model(&model.name)
+            .await?
+            .schema()
+            .await
+            .with_context(|| format!("Failed to fetch the schema of model {}", model.name))?;
+        models.push(ModelReport { name: model.name.clone(), class_hash: model.class_hash, schema });
+    }
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut contracts = remote
+        .contracts
+        .iter()
+        .map(|c| ContractReport {
+            name: c.name.to_string(),
+            address: c.address,
+            class_hash: c.class_hash,
+        })
+        .collect::<Vec<_>>();
+    contracts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let uri = world.metadata_uri(FieldElement::ZERO).await?;
+    let metadata = match uri.as_str() {
+        "" => MetadataReport { uri, content: None, error: None },
+        _ => match WorldMetadata::fetch(&uri).await {
+            Ok(content) => MetadataReport { uri, content: Some(content), error: None },
+            Err(e) => MetadataReport { uri, content: None, error: Some(format!("{e:#}")) },
+        },
+    };
+
+    let local = local_manifest.as_ref();
+    let core = |address: Option<FieldElement>, class_hash, local_class_hash| CoreContract {
+        address,
+        class_hash,
+        local_class_hash,
+    };
+
+    Ok(WorldReport {
+        world: core(
+            Some(world_address),
+            remote.world.class_hash,
+            local.map(|m| m.world.class_hash),
+        ),
+        executor: core(
+            remote.executor.address,
+            remote.executor.class_hash,
+            local.map(|m| m.executor.class_hash),
+        ),
+        base: core(None, remote.base.class_hash, local.map(|m| m.base.class_hash)),
+        mismatches: local_manifest.map(|local| mismatches(local, &remote)),
+        models,
+        contracts,
+        metadata,
+    })
+}
+
+/// Returns the classes which differ between the local build and the World, including the models
+/// and contracts which are only on one side.
+fn mismatches(local: Manifest, remote: &Manifest) -> Vec<Mismatch> {
+    let local_models = local.models.iter().map(|m| m.name.clone()).collect::<HashSet<_>>();
+    let diff = WorldDiff::compute(local, Some(remote.clone()));
+
+    let contracts = [&diff.world, &diff.executor]
+        .into_iter()
+        .chain(&diff.contracts)
+        .filter(|c| !c.is_same())
+        .map(|c| Mismatch { name: c.name.clone(), local: Some(c.local), remote: c.remote });
+    let classes = [&diff.base]
+        .into_iter()
+        .chain(&diff.models)
+        .filter(|c| !c.is_same())
+        .map(|c| Mismatch { name: c.name.clone(), local: Some(c.local), remote: c.remote });
+
+    let matched = diff.contracts.iter().filter_map(|c| c.address).collect::<HashSet<_>>();
+    let remote_contracts = remote
+        .contracts
+        .iter()
+        .filter(|c| c.address.map_or(true, |address| !matched.contains(&address)))
+        .map(|c| Mismatch { name: c.name.to_string(), local: None, remote: Some(c.class_hash) });
+    let remote_models = remote
+        .models
+        .iter()
+        .filter(|m| !local_models.contains(&m.name))
+        .map(|m| Mismatch { name: m.name.clone(), local: None, remote: Some(m.class_hash) });
+
+    contracts.chain(classes).chain(remote_contracts).chain(remote_models).collect()
+}
+
+fn print_report(report: &WorldReport) {
+    let core = |name: &str, contract: &CoreContract| {
+        let status = match contract.local_class_hash {
+            Some(local) if local == contract.class_hash => "same as local build".to_string(),
+            Some(local) => format!("local build has {local:#x}"),
+            None => "no local build".to_string(),
+        };
+        match contract.address {
+            Some(address) => println!("  {name:<9}{address:#x}"),
+            None => println!("  {name}"),
+        }
+        println!("    class {:#x} ({status})", contract.class_hash);
+    };
+
+    println!("Core contracts");
+    core("world", &report.world);
+    core("executor", &report.executor);
+    core("base", &report.base);
+
+    println!("\nModels ({})", report.models.len());
+    for model in &report.models {
+        println!("  {}", model.name);
+        println!("    class {:#x}", model.class_hash);
+        for line in model.schema.to_string().lines() {
+            println!("    {line}");
+        }
+    }
+
+    println!("\nContracts ({})", report.contracts.len());
+    for contract in &report.contracts {
+        let name = if contract.name.is_empty() { "<unnamed>" } else { contract.name.as_str() };
+        println!("  {name}");
+        if let Some(address) = contract.address {
+            println!("    address {address:#x}");
+        }
+        println!("    class {:#x}", contract.class_hash);
+    }
+
+    println!("\nMetadata");
+    let metadata = &report.metadata;
+    if metadata.uri.is_empty() {
+        println!("  No metadata set");
+    } else {
+        println!("  uri {}", metadata.uri);
+    }
+    if let Some(content) = &metadata.content {
+        let content = serde_json::to_string_pretty(content).unwrap_or_default();
+        for line in content.lines() {
+            println!("  {line}");
+        }
+    }
+    if let Some(error) = &metadata.error {
+        println!("  {error}");
+    }
+
+    println!("\nLocal build");
+    match &report.mismatches {
+        None => println!("  Not built, run `sozo build` to compare it with the World"),
+        Some(mismatches) if mismatches.is_empty() => println!("  Same as the World"),
+        Some(mismatches) => {
+            for mismatch in mismatches {
+                let hash = |hash: Option<FieldElement>| {
+                    hash.map(|hash| format!("{hash:#x}")).unwrap_or_else(|| "-".to_string())
+                };
+                println!("  {}", mismatch.name);
+                println!("    local  {}", hash(mismatch.local));
+                println!("    remote {}", hash(mismatch.remote));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_world::manifest::{Class, Contract, Model};
+    use starknet::core::utils::get_contract_address;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn mismatches_with_local_build() {
+        let model = |name: &str, class_hash| Model {
+            name: name.to_string(),
+            class_hash,
+            ..Default::default()
+        };
+
+        let local = Manifest {
+            world: Contract {
+                name: "world".into(),
+                class_hash: felt!("0x1"),
+                ..Default::default()
+            },
+            executor: Contract {
+                name: "executor".into(),
+                class_hash: felt!("0x2"),
+                ..Default::default()
+            },
+            base: Class { name: "base".into(), class_hash: felt!("0x3"), ..Default::default() },
+            contracts: vec![],
+            models: vec![model("Moves", felt!("0x4")), model("Position", felt!("0x5"))],
+        };
+
+        let world_address = felt!("0x1234");
+        let mut remote = local.clone();
+        remote.world.address = Some(world_address);
+        remote.models = vec![model("Moves", felt!("0x40")), model("Legacy", felt!("0x6"))];
+        remote.contracts = vec![Contract {
+            name: "old_actions".into(),
+            address: Some(get_contract_address(felt!("0x99"), felt!("0x3"), &[], world_address)),
+            class_hash: felt!("0x7"),
+            ..Default::default()
+        }];
+
+        let mismatch = |name: &str, local: Option<FieldElement>, remote: Option<FieldElement>| {
+            Mismatch { name: name.to_string(), local, remote }
+        };
+
+        assert_eq!(
+            mismatches(local, &remote),
+            vec![
+                mismatch("Moves", Some(felt!("0x4")), Some(felt!("0x40"))),
+                mismatch("Position", Some(felt!("0x5")), None),
+                mismatch("old_actions", None, Some(felt!("0x7"))),
+                mismatch("Legacy", None, Some(felt!("0x6"))),
+            ]
+        );
+    }
+}
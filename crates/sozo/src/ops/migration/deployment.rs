@@ -0,0 +1,296 @@
+//! Record of the deployment of a World to an environment.
+//!
+//! Once a migration completes, a record of what went live is written to
+//! `target/<profile>/deployments/<environment>.json`: the address of the World and the block it
+//! was deployed at and, for every migration applied to it, the transactions sent and their fees,
+//! the classes declared and the permissions granted or revoked. Unlike `manifest.json`, it is
+//! meant to be consumed by other tools, like indexers looking for the block to start from.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use dojo_world::migration::auth::AuthUpdate;
+use dojo_world::migration::world::WorldDiff;
+use dojo_world::utils::{actual_fee_from_receipt, block_number_from_receipt};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{FieldElement, MaybePendingTransactionReceipt};
+use starknet::providers::Provider;
+
+use super::journal::{self, MigrationJournal};
+
+pub const DEPLOYMENTS_DIR: &str = "deployments";
+
+/// The name of the record of the environment used when none is selected with `--env`.
+pub const DEFAULT_ENVIRONMENT: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldDeployment {
+    pub address: FieldElement,
+    /// The transaction which deployed the World, unknown if it was deployed by another tool.
+    pub transaction_hash: Option<FieldElement>,
+    pub block_number: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentTransaction {
+    /// The migration step of the transaction, as recorded in the journal.
+    pub step: String,
+    /// The steps of the World calls sent by a multicall transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<String>,
+    pub transaction_hash: FieldElement,
+    pub block_number: Option<u64>,
+    pub actual_fee: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationRecord {
+    /// The transactions of the migration, in the order they were sent.
+    pub transactions: Vec<DeploymentTransaction>,
+    /// The classes declared by the migration, by contract or model name.
+    pub classes: BTreeMap<String, FieldElement>,
+    /// The permissions updated by the migration.
+    pub permissions: Vec<AuthUpdate>,
+    pub total_fee: FieldElement,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeploymentRecord {
+    pub environment: String,
+    pub world: WorldDeployment,
+    /// The migrations applied to the World, oldest first.
+    pub migrations: Vec<MigrationRecord>,
+}
+
+impl MigrationRecord {
+    /// Builds the record of a migration from the transactions of its journal, whose receipts are
+    /// fetched for the fees and blocks.
+    pub async fn new<P>(
+        journal: &MigrationJournal,
+        classes: BTreeMap<String, FieldElement>,
+        permissions: Vec<AuthUpdate>,
+        provider: &P,
+    ) -> Result<Self>
+    where
+        P: Provider + Sync + Send,
+    {
+        let mut transactions = vec![];
+        for entry in &journal.completed {
+            let Some(transaction_hash) = entry.transaction_hash else { continue };
+
+            let receipt =
+                provider.get_transaction_receipt(transaction_hash).await.with_context(|| {
+                    format!("Failed to fetch receipt of transaction {transaction_hash:#x}")
+                })?;
+
+            let block_number = match &receipt {
+                MaybePendingTransactionReceipt::Receipt(receipt) => {
+                    Some(block_number_from_receipt(receipt))
+                }
+                MaybePendingTransactionReceipt::PendingReceipt(_) => None,
+            };
+
+            transactions.push(DeploymentTransaction {
+                step: entry.step.clone(),
+                calls: entry.calls.clone(),
+                transaction_hash,
+                block_number,
+                actual_fee: actual_fee_from_receipt(&receipt),
+            });
+        }
+
+        let total_fee = transactions.iter().fold(FieldElement::ZERO, |acc, tx| acc + tx.actual_fee);
+
+        Ok(Self { transactions, classes, permissions, total_fee })
+    }
+}
+
+impl DeploymentRecord {
+    /// Returns the path of the record of `environment` in the given target directory.
+    pub fn path(target_dir: impl AsRef<Path>, environment: &str) -> PathBuf {
+        target_dir.as_ref().join(DEPLOYMENTS_DIR).join(format!("{environment}.json"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read deployment record {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse deployment record {}", path.display()))
+    }
+
+    /// Records the migration of the World of `environment` after the ones of the `previous`
+    /// record, unless the previous record is of another World. When the World wasn't deployed
+    /// by this migration, its deployment is taken from the previous record.
+    pub fn new(
+        environment: &str,
+        world_address: FieldElement,
+        migration: MigrationRecord,
+        previous: Option<DeploymentRecord>,
+    ) -> Self {
+        let previous = previous.filter(|record| record.world.address == world_address);
+
+        let world = match migration.transactions.iter().find(|tx| journal::is_world_step(&tx.step))
+        {
+            Some(tx) => WorldDeployment {
+                address: world_address,
+                transaction_hash: Some(tx.transaction_hash),
+                block_number: tx.block_number,
+            },
+            None => {
+                previous.as_ref().map(|record| record.world.clone()).unwrap_or(WorldDeployment {
+                    address: world_address,
+                    transaction_hash: None,
+                    block_number: None,
+                })
+            }
+        };
+
+        let mut migrations = previous.map(|record| record.migrations).unwrap_or_default();
+        migrations.push(migration);
+
+        Self { environment: environment.to_string(), world, migrations }
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write deployment record {}", path.display()))
+    }
+}
+
+/// Returns the classes the migration of `diff` declares, by contract or model name.
+pub fn declared_classes(diff: &WorldDiff) -> BTreeMap<String, FieldElement> {
+    let contracts = [&diff.world, &diff.executor]
+        .into_iter()
+        .chain(&diff.contracts)
+        .map(|c| (&c.name, c.local, c.remote));
+    let classes =
+        [&diff.base].into_iter().chain(&diff.models).map(|c| (&c.name, c.local, c.remote));
+
+    contracts
+        .chain(classes)
+        .filter(|(_, local, remote)| Some(*local) != *remote)
+        .map(|(name, local, _)| (name.to_string(), local))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_world::migration::class::ClassDiff;
+    use dojo_world::migration::contract::ContractDiff;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    fn contract(name: &str, local: FieldElement, remote: Option<FieldElement>) -> ContractDiff {
+        ContractDiff { name: name.to_string(), local, remote, address: None }
+    }
+
+    fn class(name: &str, local: FieldElement, remote: Option<FieldElement>) -> ClassDiff {
+        ClassDiff { name: name.to_string(), local, remote }
+    }
+
+    #[test]
+    fn declared_classes_are_the_changed_ones() {
+        let diff = WorldDiff {
+            world: contract("world", felt!("0x1"), Some(felt!("0x1"))),
+            executor: contract("executor", felt!("0x2"), Some(felt!("0x2"))),
+            base: class("base", felt!("0x3"), Some(felt!("0x3"))),
+            contracts: vec![contract("actions", felt!("0x4"), Some(felt!("0x40")))],
+            models: vec![class("Moves", felt!("0x5"), None), class("Position", felt!("0x6"), None)],
+        };
+
+        assert_eq!(
+            declared_classes(&diff),
+            BTreeMap::from([
+                ("Moves".to_string(), felt!("0x5")),
+                ("Position".to_string(), felt!("0x6")),
+                ("actions".to_string(), felt!("0x4")),
+            ])
+        );
+    }
+
+    fn migration(step: &str, transaction_hash: FieldElement) -> MigrationRecord {
+        MigrationRecord {
+            transactions: vec![DeploymentTransaction {
+                step: step.to_string(),
+                calls: vec![],
+                transaction_hash,
+                block_number: Some(3),
+                actual_fee: felt!("0x10"),
+            }],
+            classes: BTreeMap::from([("world".to_string(), felt!("0x1"))]),
+            permissions: vec![AuthUpdate::GrantWriter {
+                model: "Moves".to_string(),
+                contract: felt!("0x5678"),
+            }],
+            total_fee: felt!("0x10"),
+        }
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = DeploymentRecord::path(dir.path(), DEFAULT_ENVIRONMENT);
+
+        let record = DeploymentRecord::new(
+            DEFAULT_ENVIRONMENT,
+            felt!("0x1234"),
+            migration(&journal::world_step(felt!("0x1")), felt!("0xabc")),
+            None,
+        );
+        assert_eq!(
+            record.world,
+            WorldDeployment {
+                address: felt!("0x1234"),
+                transaction_hash: Some(felt!("0xabc")),
+                block_number: Some(3),
+            }
+        );
+
+        record.write(&path).unwrap();
+        assert!(path.ends_with("deployments/default.json"));
+        assert_eq!(DeploymentRecord::load(&path).unwrap(), record);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["migrations"][0]["permissions"][0]["action"], "grant_writer");
+    }
+
+    #[test]
+    fn migrations_are_appended_to_the_record_of_the_same_world() {
+        let first = DeploymentRecord::new(
+            DEFAULT_ENVIRONMENT,
+            felt!("0x1234"),
+            migration(&journal::world_step(felt!("0x1")), felt!("0xabc")),
+            None,
+        );
+
+        let second = DeploymentRecord::new(
+            DEFAULT_ENVIRONMENT,
+            felt!("0x1234"),
+            migration("batch:0x1", felt!("0xdef")),
+            Some(first.clone()),
+        );
+        assert_eq!(second.world, first.world);
+        assert_eq!(second.migrations.len(), 2);
+        assert_eq!(second.migrations[1].transactions[0].transaction_hash, felt!("0xdef"));
+
+        // A new World starts a new record.
+        let other = DeploymentRecord::new(
+            DEFAULT_ENVIRONMENT,
+            felt!("0x5678"),
+            migration("batch:0x1", felt!("0x123")),
+            Some(second),
+        );
+        assert_eq!(other.migrations.len(), 1);
+        assert_eq!(other.world.transaction_hash, None);
+    }
+}
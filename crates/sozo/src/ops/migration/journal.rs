@@ -0,0 +1,259 @@
+//! Journal of the steps completed by a migration.
+//!
+//! Every transaction sent during a migration is recorded in the journal as soon as it has been
+//! accepted, so that a failed migration can be resumed with `sozo migrate --resume` without
+//! repeating the steps that already succeeded. The journal is removed once the migration
+//! completes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use dojo_world::migration::auth::AuthUpdate;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+
+pub const JOURNAL_FILE_NAME: &str = "migration_journal.json";
+
+/// The step of the multicall transactions grouping World calls.
+pub const MULTICALL_STEP: &str = "multicall";
+
+/// A completed migration step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub step: String,
+    /// The steps of the World calls sent by a multicall transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<String>,
+    pub transaction_hash: Option<FieldElement>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MigrationJournal {
+    /// Where the journal is persisted. An in-memory journal is never written to disk.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// The address of the World being migrated, once known.
+    pub world_address: Option<FieldElement>,
+    pub completed: Vec<JournalEntry>,
+}
+
+impl MigrationJournal {
+    /// Creates an empty journal persisted in the given target directory.
+    pub fn new(target_dir: impl AsRef<Path>) -> Self {
+        Self { path: Some(target_dir.as_ref().join(JOURNAL_FILE_NAME)), ..Default::default() }
+    }
+
+    /// Loads the journal of a previous migration from the given target directory. Fails if the
+    /// journal migrated another World than `world_address`, when given.
+    pub fn load(target_dir: impl AsRef<Path>, world_address: Option<FieldElement>) -> Result<Self> {
+        let path = target_dir.as_ref().join(JOURNAL_FILE_NAME);
+
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!("No migration to resume, journal not found at {}", path.display())
+        })?;
+
+        let mut journal: Self =
+            serde_json::from_str(&content).with_context(|| "Failed to parse migration journal.")?;
+        journal.path = Some(path);
+
+        if let (Some(expected), Some(journaled)) = (world_address, journal.world_address) {
+            if expected != journaled {
+                bail!(
+                    "The migration to resume is for World {journaled:#x}, not {expected:#x}. \
+                     Migrate without `--resume` to start a new migration."
+                );
+            }
+        }
+
+        Ok(journal)
+    }
+
+    /// Returns `true` if a journal from an unfinished migration exists in the target directory.
+    pub fn exists(target_dir: impl AsRef<Path>) -> bool {
+        target_dir.as_ref().join(JOURNAL_FILE_NAME).exists()
+    }
+
+    /// Returns `true` if the step was completed, either by its own transaction or as one of the
+    /// calls of a multicall.
+    pub fn is_completed(&self, step: &str) -> bool {
+        self.completed
+            .iter()
+            .any(|entry| entry.step == step || entry.calls.iter().any(|c| c == step))
+    }
+
+    /// Records a completed step and persists the journal.
+    pub fn record(
+        &mut self,
+        step: impl Into<String>,
+        transaction_hash: Option<FieldElement>,
+    ) -> Result<()> {
+        self.completed.push(JournalEntry { step: step.into(), calls: vec![], transaction_hash });
+        self.write()
+    }
+
+    /// Records the steps of the World calls sent by a multicall and persists the journal.
+    pub fn record_multicall(
+        &mut self,
+        calls: Vec<String>,
+        transaction_hash: FieldElement,
+    ) -> Result<()> {
+        self.completed.push(JournalEntry {
+            step: MULTICALL_STEP.to_string(),
+            calls,
+            transaction_hash: Some(transaction_hash),
+        });
+        self.write()
+    }
+
+    pub fn set_world_address(&mut self, world_address: FieldElement) -> Result<()> {
+        self.world_address = Some(world_address);
+        self.write()
+    }
+
+    /// Removes the persisted journal once the migration has completed.
+    pub fn finish(self) -> Result<()> {
+        match self.path {
+            Some(path) if path.exists() => fs::remove_file(path).map_err(Into::into),
+            _ => Ok(()),
+        }
+    }
+
+    fn write(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            fs::write(path, serde_json::to_string_pretty(self)?)
+                .with_context(|| format!("Failed to write migration journal {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+// The steps are named after the resources they migrate and the class they migrate them to, so
+// that they stay the same when a migration is resumed, whatever was already migrated, but not
+// when a class changed since the step was completed.
+
+pub fn executor_step(class_hash: FieldElement) -> String {
+    format!("executor:{class_hash:#x}")
+}
+
+pub fn base_step(class_hash: FieldElement) -> String {
+    format!("base:{class_hash:#x}")
+}
+
+pub fn world_step(class_hash: FieldElement) -> String {
+    format!("world:{class_hash:#x}")
+}
+
+/// Returns `true` if the step deployed the World.
+pub fn is_world_step(step: &str) -> bool {
+    step.starts_with("world:")
+}
+
+pub fn declare_model_step(name: &str, class_hash: FieldElement) -> String {
+    format!("declare_model:{name}:{class_hash:#x}")
+}
+
+pub fn declare_contract_step(name: &str, class_hash: FieldElement) -> String {
+    format!("declare_contract:{name}:{class_hash:#x}")
+}
+
+pub fn register_model_step(name: &str, class_hash: FieldElement) -> String {
+    format!("register_model:{name}:{class_hash:#x}")
+}
+
+pub fn deploy_contract_step(name: &str, class_hash: FieldElement) -> String {
+    format!("deploy_contract:{name}:{class_hash:#x}")
+}
+
+pub fn world_metadata_step(class_hash: FieldElement) -> String {
+    format!("world_metadata:{class_hash:#x}")
+}
+
+pub fn set_executor_step(executor: FieldElement) -> String {
+    format!("set_executor:{executor:#x}")
+}
+
+pub fn auth_step(update: &AuthUpdate) -> String {
+    match update {
+        AuthUpdate::GrantWriter { model, contract } => {
+            format!("grant_writer:{model}:{contract:#x}")
+        }
+        AuthUpdate::RevokeWriter { model, contract } => {
+            format!("revoke_writer:{model}:{contract:#x}")
+        }
+        AuthUpdate::GrantOwner { resource, address } => {
+            format!("grant_owner:{resource}:{address:#x}")
+        }
+        AuthUpdate::RevokeOwner { resource, address } => {
+            format!("revoke_owner:{resource}:{address:#x}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::TempDir;
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn journal_is_persisted_until_finished() {
+        let dir = TempDir::new().unwrap();
+
+        let mut journal = MigrationJournal::new(dir.path());
+        journal.set_world_address(felt!("0x1234")).unwrap();
+        journal.record(executor_step(felt!("0x10")), Some(felt!("0x1"))).unwrap();
+
+        assert!(MigrationJournal::load(dir.path(), Some(felt!("0x5678"))).is_err());
+
+        let journal = MigrationJournal::load(dir.path(), Some(felt!("0x1234"))).unwrap();
+        assert_eq!(journal.world_address, Some(felt!("0x1234")));
+        assert!(journal.is_completed(&executor_step(felt!("0x10"))));
+        assert!(!journal.is_completed(&world_step(felt!("0x20"))));
+
+        journal.finish().unwrap();
+        assert!(!MigrationJournal::exists(dir.path()));
+    }
+
+    #[test]
+    fn calls_of_a_multicall_are_completed() {
+        let dir = TempDir::new().unwrap();
+
+        let mut journal = MigrationJournal::new(dir.path());
+        let calls = vec![
+            register_model_step("Moves", felt!("0x10")),
+            deploy_contract_step("actions", felt!("0x20")),
+        ];
+        journal.record_multicall(calls, felt!("0x1")).unwrap();
+
+        let journal = MigrationJournal::load(dir.path(), None).unwrap();
+        assert!(journal.is_completed(&register_model_step("Moves", felt!("0x10"))));
+        assert!(journal.is_completed(&deploy_contract_step("actions", felt!("0x20"))));
+        assert!(!journal.is_completed(&register_model_step("Position", felt!("0x30"))));
+    }
+
+    #[test]
+    fn declare_steps_depend_on_kind() {
+        assert_ne!(
+            declare_model_step("actions", felt!("0x10")),
+            declare_contract_step("actions", felt!("0x10"))
+        );
+    }
+
+    #[test]
+    fn steps_of_a_changed_class_are_not_completed() {
+        let mut journal = MigrationJournal::default();
+        journal
+            .record(declare_contract_step("actions", felt!("0x10")), Some(felt!("0x1")))
+            .unwrap();
+        journal
+            .record_multicall(vec![deploy_contract_step("actions", felt!("0x10"))], felt!("0x2"))
+            .unwrap();
+
+        assert!(journal.is_completed(&declare_contract_step("actions", felt!("0x10"))));
+        assert!(!journal.is_completed(&declare_contract_step("actions", felt!("0x11"))));
+        assert!(!journal.is_completed(&deploy_contract_step("actions", felt!("0x11"))));
+    }
+}
@@ -1,23 +1,32 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
 use camino::Utf8PathBuf;
 use dojo_test_utils::compiler::build_test_config;
 use dojo_test_utils::migration::prepare_migration;
 use dojo_test_utils::sequencer::{
     get_default_test_starknet_config, SequencerConfig, StarknetConfig, TestSequencer,
 };
+use dojo_world::contracts::world::WorldContractReader;
 use dojo_world::manifest::Manifest;
-use dojo_world::migration::strategy::prepare_for_migration;
+use dojo_world::migration::auth::AuthUpdate;
+use dojo_world::migration::strategy::{prepare_for_migration, MigrationStrategy};
 use dojo_world::migration::world::WorldDiff;
+use dojo_world::utils::TransactionWaiter;
 use scarb::ops;
-use starknet::accounts::{ExecutionEncoding, SingleOwnerAccount};
+use starknet::accounts::{Account, Call, ConnectedAccount, ExecutionEncoding, SingleOwnerAccount};
 use starknet::core::chain_id;
-use starknet::core::types::{BlockId, BlockTag};
-use starknet::macros::felt;
+use starknet::core::crypto::Signature;
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::macros::{felt, selector};
 use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
-use starknet::signers::{LocalWallet, SigningKey};
+use starknet::providers::{JsonRpcClient, Provider};
+use starknet::signers::{LocalWallet, Signer, SigningKey, VerifyingKey};
 
+use super::journal::{self, MigrationJournal};
+use super::plan::{self, MigrationPlan, TransactionKind};
 use crate::commands::options::transaction::TransactionOptions;
-use crate::ops::migration::execute_strategy;
+use crate::ops::migration::{execute_strategy, execute_strategy_with_journal};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn migrate_with_auto_mine() {
@@ -59,6 +68,83 @@ async fn migrate_with_block_time() {
     sequencer.stop().unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn resumed_migration_skips_completed_world_calls() {
+    let config = build_test_config("../../examples/spawn-and-move/Scarb.toml").unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)
+        .unwrap_or_else(|op| panic!("Error building workspace: {op:?}"));
+
+    let migration = prepare_migration("../../examples/spawn-and-move/target/dev".into()).unwrap();
+
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let mut account = sequencer.account();
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    let mut journal = MigrationJournal::default();
+    execute_strategy_with_journal(&ws, &migration, &account, None, &mut journal, vec![])
+        .await
+        .unwrap();
+
+    // The World calls are recorded by resource, whatever the multicall they were sent in.
+    let moves = migration.models.iter().find(|m| m.diff.name == "Moves").unwrap().diff.local;
+    assert!(journal.is_completed(&journal::declare_model_step("Moves", moves)));
+    assert!(journal.is_completed(&journal::register_model_step("Moves", moves)));
+    let actions = actions_class_hash(&migration);
+    let deploy_actions = journal::deploy_contract_step(ACTIONS, actions);
+    assert!(journal.is_completed(&deploy_actions));
+
+    // Running the migration again with the same journal sends no transaction.
+    let completed = journal.completed.len();
+    execute_strategy_with_journal(&ws, &migration, &account, None, &mut journal, vec![])
+        .await
+        .unwrap();
+    assert_eq!(journal.completed.len(), completed);
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn resumed_migration_redeploys_changed_classes() {
+    let config = build_test_config("../../examples/spawn-and-move/Scarb.toml").unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)
+        .unwrap_or_else(|op| panic!("Error building workspace: {op:?}"));
+
+    let migration = prepare_migration("../../examples/spawn-and-move/target/dev".into()).unwrap();
+
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+
+    let mut account = sequencer.account();
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    // The journal of a migration interrupted before the actions contract was rebuilt with
+    // another class.
+    let previous_class = felt!("0x1234");
+    let mut journal = MigrationJournal::default();
+    journal.record(journal::declare_contract_step(ACTIONS, previous_class), None).unwrap();
+    let calls = vec![journal::deploy_contract_step(ACTIONS, previous_class)];
+    journal.record_multicall(calls, felt!("0x1")).unwrap();
+
+    execute_strategy_with_journal(&ws, &migration, &account, None, &mut journal, vec![])
+        .await
+        .unwrap();
+
+    // The new class is declared and deployed, instead of being taken for the completed one.
+    let actions = actions_class_hash(&migration);
+    assert!(journal.is_completed(&journal::declare_contract_step(ACTIONS, actions)));
+    assert!(journal.is_completed(&journal::deploy_contract_step(ACTIONS, actions)));
+
+    sequencer.stop().unwrap();
+}
+
+const ACTIONS: &str = "dojo_examples::actions::actions";
+
+fn actions_class_hash(migration: &MigrationStrategy) -> FieldElement {
+    migration.contracts.iter().find(|c| c.diff.name == ACTIONS).unwrap().diff.local
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn migrate_with_small_fee_multiplier_will_fail() {
     let config = build_test_config("../../examples/spawn-and-move/Scarb.toml").unwrap();
@@ -150,3 +236,184 @@ async fn migration_from_remote() {
     assert_eq!(local_manifest.executor.class_hash, remote_manifest.executor.class_hash);
     assert_eq!(local_manifest.models.len(), remote_manifest.models.len());
 }
+
+/// Max fee of the plan transactions, the test sequencer doesn't charge fees.
+const PLAN_MAX_FEE: FieldElement = felt!("0x10000000000000");
+
+/// A signer recording the hashes it signs.
+struct RecordingSigner {
+    wallet: LocalWallet,
+    hashes: Arc<Mutex<Vec<FieldElement>>>,
+}
+
+#[async_trait]
+impl Signer for RecordingSigner {
+    type GetPublicKeyError = <LocalWallet as Signer>::GetPublicKeyError;
+    type SignError = <LocalWallet as Signer>::SignError;
+
+    async fn get_public_key(&self) -> Result<VerifyingKey, Self::GetPublicKeyError> {
+        self.wallet.get_public_key().await
+    }
+
+    async fn sign_hash(&self, hash: &FieldElement) -> Result<Signature, Self::SignError> {
+        self.hashes.lock().unwrap().push(*hash);
+        self.wallet.sign_hash(hash).await
+    }
+}
+
+async fn build_plan(sequencer: &TestSequencer, permissions: Vec<AuthUpdate>) -> MigrationPlan {
+    let migration = prepare_migration("../../examples/spawn-and-move/target/dev".into()).unwrap();
+    let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+    let base_class_hash = migration.base.as_ref().unwrap().diff.local;
+
+    MigrationPlan::build(
+        &migration,
+        &provider,
+        sequencer.raw_account().account_address,
+        base_class_hash,
+        PLAN_MAX_FEE,
+        permissions,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plan_transaction_hashes_match_starknet_rs() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let plan = build_plan(&sequencer, vec![]).await;
+
+    let hashes = Arc::new(Mutex::new(vec![]));
+    let signer = RecordingSigner {
+        wallet: LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
+            sequencer.raw_account().private_key,
+        )),
+        hashes: hashes.clone(),
+    };
+    let account = SingleOwnerAccount::new(
+        JsonRpcClient::new(HttpTransport::new(sequencer.url())),
+        signer,
+        plan.sender_address,
+        chain_id::TESTNET,
+        ExecutionEncoding::Legacy,
+    );
+
+    // Send the planned transactions as V2 declares and V1 invokes built by starknet-rs.
+    for tx in &plan.transactions {
+        let transaction_hash = match &tx.kind {
+            TransactionKind::Declare { contract_class, compiled_class_hash, .. } => {
+                account
+                    .declare(Arc::new(contract_class.clone()), *compiled_class_hash)
+                    .nonce(tx.nonce)
+                    .max_fee(tx.max_fee)
+                    .send()
+                    .await
+                    .unwrap()
+                    .transaction_hash
+            }
+            TransactionKind::Invoke { calls, .. } => {
+                let calls = calls
+                    .iter()
+                    .map(|call| Call {
+                        to: call.to,
+                        selector: call.selector,
+                        calldata: call.calldata.clone(),
+                    })
+                    .collect();
+                account
+                    .execute(calls)
+                    .nonce(tx.nonce)
+                    .max_fee(tx.max_fee)
+                    .send()
+                    .await
+                    .unwrap()
+                    .transaction_hash
+            }
+        };
+
+        let expected = tx.transaction_hash(plan.chain_id, plan.sender_address);
+        assert_eq!(transaction_hash, expected, "hash of `{}`", tx.step);
+        assert_eq!(hashes.lock().unwrap().last(), Some(&expected), "signed hash of `{}`", tx.step);
+
+        TransactionWaiter::new(transaction_hash, account.provider()).await.unwrap();
+    }
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn build_sign_and_broadcast_plan() {
+    let sequencer =
+        TestSequencer::start(SequencerConfig::default(), get_default_test_starknet_config()).await;
+    let provider = JsonRpcClient::new(HttpTransport::new(sequencer.url()));
+
+    // The address the actions contract is deployed at, to grant it permissions.
+    let actions = build_plan(&sequencer, vec![])
+        .await
+        .transactions
+        .iter()
+        .find_map(|tx| match &tx.kind {
+            TransactionKind::Invoke { deployed, .. } => {
+                deployed.get("dojo_examples::actions::actions").copied()
+            }
+            TransactionKind::Declare { .. } => None,
+        })
+        .unwrap();
+
+    let grant = AuthUpdate::GrantWriter { model: "Moves".to_string(), contract: actions };
+    let mut plan = build_plan(&sequencer, vec![grant.clone()]).await;
+
+    // Every class and contract is planned, with consecutive nonces from the account's one.
+    let declared = plan.declared_classes();
+    for name in ["executor", "base", "world", "dojo_examples::actions::actions", "Moves"] {
+        assert!(declared.contains_key(name), "`{name}` isn't declared");
+    }
+    let first_nonce = plan.transactions[0].nonce;
+    for (i, tx) in plan.transactions.iter().enumerate() {
+        assert_eq!(tx.nonce, first_nonce + FieldElement::from(i));
+        assert_eq!(tx.max_fee, PLAN_MAX_FEE);
+    }
+    assert_eq!(plan.permissions, vec![grant]);
+
+    // The permissions are granted in the same multicall as the contract deployments.
+    assert!(plan.transactions.iter().any(|tx| match &tx.kind {
+        TransactionKind::Invoke { calls, deployed, .. } => {
+            deployed.contains_key("dojo_examples::actions::actions")
+                && calls.iter().any(|call| call.selector == selector!("grant_writer"))
+        }
+        TransactionKind::Declare { .. } => false,
+    }));
+
+    plan.sign(&LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
+        sequencer.raw_account().private_key,
+    )))
+    .await
+    .unwrap();
+
+    let mut journal = MigrationJournal::default();
+    plan::broadcast(&plan, &provider, &mut journal).await.unwrap();
+    assert_eq!(journal.completed.len(), plan.transactions.len());
+
+    // The contracts are deployed where the plan predicted, and the permissions are granted.
+    provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), plan.world_address).await.unwrap();
+    provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), actions).await.unwrap();
+    let world = WorldContractReader::new(plan.world_address, &provider);
+    assert!(world.is_writer("Moves", actions).await.unwrap());
+
+    // Broadcasting the plan again finds the transactions already sent instead of sending them.
+    let nonce =
+        provider.get_nonce(BlockId::Tag(BlockTag::Pending), plan.sender_address).await.unwrap();
+    let mut resumed = MigrationJournal::default();
+    plan::broadcast(&plan, &provider, &mut resumed).await.unwrap();
+    let hashes = |journal: &MigrationJournal| {
+        journal.completed.iter().map(|entry| entry.transaction_hash).collect::<Vec<_>>()
+    };
+    assert_eq!(hashes(&resumed), hashes(&journal));
+    assert_eq!(
+        provider.get_nonce(BlockId::Tag(BlockTag::Pending), plan.sender_address).await.unwrap(),
+        nonce
+    );
+
+    sequencer.stop().unwrap();
+}
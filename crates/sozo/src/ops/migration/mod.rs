@@ -1,28 +1,37 @@
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 use anyhow::{anyhow, bail, Context, Result};
-use dojo_world::contracts::world::WorldContract;
+use camino::Utf8Path;
+use dojo_world::contracts::model::ModelReader;
+use dojo_world::contracts::world::{WorldContract, WorldContractReader};
 use dojo_world::manifest::{Manifest, ManifestError};
-use dojo_world::metadata::dojo_metadata_from_workspace;
+use dojo_world::metadata::{dojo_metadata_from_workspace, Environment};
+use dojo_world::migration::auth::{AuthUpdate, Permissions};
 use dojo_world::migration::contract::ContractMigration;
+use dojo_world::migration::schema::SchemaDiff;
 use dojo_world::migration::strategy::{generate_salt, prepare_for_migration, MigrationStrategy};
 use dojo_world::migration::world::WorldDiff;
 use dojo_world::migration::{
-    Declarable, DeployOutput, Deployable, MigrationError, RegisterOutput, StateDiff,
+    Declarable, DeployOutput, Deployable, MigrationError, MigrationType, StateDiff, TxConfig,
 };
 use dojo_world::utils::TransactionWaiter;
-use scarb::core::Workspace;
+use scarb::core::{Config, Workspace};
 use scarb_ui::Ui;
-use starknet::accounts::{Account, ConnectedAccount, SingleOwnerAccount};
+use starknet::accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount};
 use starknet::core::types::{
     BlockId, BlockTag, FieldElement, InvokeTransactionResult, StarknetError,
 };
 use starknet::core::utils::{cairo_short_string_to_felt, get_contract_address};
+use starknet::macros::selector;
 use starknet::providers::jsonrpc::HttpTransport;
 
+pub mod deployment;
+pub mod journal;
 #[cfg(test)]
 #[path = "migration_test.rs"]
 mod migration_test;
+pub mod plan;
 mod ui;
 
 use starknet::providers::{
@@ -31,6 +40,9 @@ use starknet::providers::{
 use starknet::signers::{LocalWallet, Signer};
 use ui::MigrationUi;
 
+use self::deployment::{declared_classes, DeploymentRecord, MigrationRecord, DEFAULT_ENVIRONMENT};
+use self::journal::MigrationJournal;
+use self::plan::MigrationPlan;
 use self::ui::{bold_message, italic_message};
 use crate::commands::migrate::MigrateArgs;
 use crate::commands::options::account::AccountOptions;
@@ -38,21 +50,52 @@ use crate::commands::options::starknet::StarknetOptions;
 use crate::commands::options::transaction::TransactionOptions;
 use crate::commands::options::world::WorldOptions;
 
-pub async fn execute<U>(ws: &Workspace<'_>, args: MigrateArgs, target_dir: U) -> Result<()>
+pub async fn execute<U>(
+    ws: &Workspace<'_>,
+    args: MigrateArgs,
+    target_dir: U,
+    env: Option<&str>,
+    env_metadata: Option<Environment>,
+) -> Result<()>
 where
     U: AsRef<Path>,
 {
     let ui = ws.config().ui();
-    let MigrateArgs { account, starknet, world, name, .. } = args;
+    let env_metadata = env_metadata.as_ref();
+
+    if let Some(path) = args.export_plan.clone() {
+        return export_plan(ws, args, target_dir, env_metadata, &path).await;
+    }
+
+    let MigrateArgs { account, starknet, world, name, resume, plan, allow_breaking, .. } = args;
 
     // Setup account for migration and fetch world address if it exists.
 
-    let (world_address, account) = setup_env(ws, account, starknet, world, name.as_ref()).await?;
+    let (world_address, account) =
+        setup_env(ws, env_metadata, account, starknet, world, name.as_ref()).await?;
+
+    // Load the journal of the migration to resume, or start a new one.
+
+    let mut journal = if resume {
+        let journal = MigrationJournal::load(&target_dir, world_address)?;
+        ui.print(format!("\nResuming migration ({} steps completed)", journal.completed.len()));
+        journal
+    } else {
+        if MigrationJournal::exists(&target_dir) {
+            ui.warn(
+                "Found the journal of an unfinished migration, it will be discarded. Use \
+                 `--resume` to resume it instead.",
+            );
+        }
+        MigrationJournal::new(&target_dir)
+    };
+
+    let world_address = world_address.or(journal.world_address);
 
     // Load local and remote World manifests.
 
     let (local_manifest, remote_manifest) =
-        load_world_manifests(&target_dir, &account, world_address, ui).await?;
+        load_world_manifests(&target_dir, account.provider(), world_address, ui).await?;
 
     // Calculate diff between local and remote World manifests.
 
@@ -61,25 +104,350 @@ where
     let total_diffs = diff.count_diffs();
     ui.print_sub(format!("Total diffs found: {total_diffs}"));
 
-    if total_diffs == 0 {
-        ui.print("\n✨ No changes to be made. Remote World is already up to date!")
+    if let Some(world_address) = world_address {
+        check_model_schemas(
+            &local_manifest,
+            &diff,
+            world_address,
+            account.provider(),
+            ui,
+            allow_breaking,
+        )
+        .await?;
+    }
+
+    if plan {
+        if total_diffs > 0 {
+            let strategy = prepare_migration(&target_dir, diff, name, world_address, ui)?;
+            print_plan(&strategy, ui);
+        }
+
+        return Ok(());
+    }
+
+    let txn_config = Some(args.transaction.with_env(env_metadata));
+    let classes = declared_classes(&diff);
+
+    let (world_address, permissions) = if total_diffs == 0 {
+        ui.print("\n✨ No changes to be made. Remote World is already up to date!");
+
+        // Enforce the permissions declared in the metadata, even if nothing else changed.
+        let permissions = match world_address {
+            Some(world_address) => {
+                let updates = permission_updates(
+                    ws,
+                    &local_manifest,
+                    remote_manifest.as_ref(),
+                    world_address,
+                    account.provider(),
+                )
+                .await?;
+                execute_batches(
+                    auth_calls(&updates, world_address)?,
+                    &account,
+                    ui,
+                    &txn_config,
+                    &mut journal,
+                )
+                .await?;
+                updates
+            }
+            None => vec![],
+        };
+
+        (world_address, permissions)
     } else {
-        // Mirate according to the diff.
-        let world_address = apply_diff(
+        // Mirate according to the diff, granting the declared permissions in the same
+        // multicalls as the other World calls.
+        let strategy = prepare_migration(&target_dir, diff, name, world_address, ui)?;
+        let world_address = strategy.world_address()?;
+
+        let permissions = permission_updates(
+            ws,
+            &local_manifest,
+            remote_manifest.as_ref(),
+            world_address,
+            account.provider(),
+        )
+        .await?;
+        let calls = auth_calls(&permissions, world_address)?;
+
+        execute_migration(ws, &strategy, &account, txn_config.clone(), &mut journal, calls).await?;
+
+        update_world_manifest(
             ws,
+            local_manifest.clone(),
+            remote_manifest.clone(),
+            &target_dir,
+            world_address,
+        )
+        .await?;
+
+        (Some(world_address), permissions)
+    };
+
+    if let Some(world_address) = world_address {
+        record_deployment(
             &target_dir,
-            diff,
-            name,
+            env,
             world_address,
-            &account,
-            Some(args.transaction),
+            &journal,
+            classes,
+            permissions,
+            account.provider(),
+            ui,
         )
         .await?;
+    }
+
+    journal.finish()?;
+
+    Ok(())
+}
 
-        update_world_manifest(ws, local_manifest, remote_manifest, target_dir, world_address)
+/// Exports the transactions of the migration to a plan, without signing them. The plan is signed
+/// offline with `sozo migrate sign` and sent with `sozo migrate broadcast`.
+async fn export_plan<U>(
+    ws: &Workspace<'_>,
+    args: MigrateArgs,
+    target_dir: U,
+    env_metadata: Option<&Environment>,
+    path: &Utf8Path,
+) -> Result<()>
+where
+    U: AsRef<Path>,
+{
+    let ui = ws.config().ui();
+    let MigrateArgs { account, starknet, world, name, allow_breaking, max_fee, .. } = args;
+    let max_fee = max_fee.ok_or_else(|| anyhow!("A max fee is required to export a plan."))?;
+
+    // Only the address of the account is needed, its key may not be available here.
+
+    let provider = starknet.provider(env_metadata)?;
+    let sender_address = account.account_address(env_metadata)?;
+    let world_address = world.address(env_metadata).ok();
+    ui.print(format!("\nMigration account: {sender_address:#x}"));
+
+    let (local_manifest, remote_manifest) =
+        load_world_manifests(&target_dir, &provider, world_address, ui).await?;
+
+    ui.print_step(2, "🧰", "Evaluating Worlds diff...");
+    let diff = WorldDiff::compute(local_manifest.clone(), remote_manifest.clone());
+    let total_diffs = diff.count_diffs();
+    ui.print_sub(format!("Total diffs found: {total_diffs}"));
+
+    if total_diffs == 0 {
+        ui.print("\n✨ No changes to be made. Remote World is already up to date!");
+        return Ok(());
+    }
+
+    if let Some(world_address) = world_address {
+        check_model_schemas(&local_manifest, &diff, world_address, &provider, ui, allow_breaking)
             .await?;
     }
 
+    let base_class_hash = match &remote_manifest {
+        Some(manifest) => manifest.base.class_hash,
+        None => local_manifest.base.class_hash,
+    };
+
+    let strategy = prepare_migration(&target_dir, diff, name, world_address, ui)?;
+    let world_address = strategy.world_address()?;
+
+    let permissions =
+        permission_updates(ws, &local_manifest, remote_manifest.as_ref(), world_address, &provider)
+            .await?;
+
+    let plan = MigrationPlan::build(
+        &strategy,
+        &provider,
+        sender_address,
+        base_class_hash,
+        max_fee,
+        permissions,
+    )
+    .await?;
+    plan.write(path)?;
+
+    ui.print(format!(
+        "\n✨ Exported {} transactions to {path}, sign them with `sozo migrate sign`.",
+        plan.transactions.len()
+    ));
+
+    Ok(())
+}
+
+/// Signs the transactions of an exported plan with the key of the account. The key is usually
+/// kept on a machine without the project, so the account is only read from the command line and
+/// the environment variables.
+pub fn sign_plan(
+    config: &Config,
+    plan_path: &Utf8Path,
+    output: &Utf8Path,
+    account: &AccountOptions,
+) -> Result<()> {
+    let ui = config.ui();
+    let mut plan = MigrationPlan::load(plan_path)?;
+
+    if let Ok(address) = account.account_address(None) {
+        if address != plan.sender_address {
+            bail!("The plan is sent from account {:#x}, not {address:#x}.", plan.sender_address);
+        }
+    }
+
+    let signer = account.signer(None)?;
+    config.tokio_handle().block_on(plan.sign(&signer))?;
+    plan.write(output)?;
+
+    ui.print(format!(
+        "\n✨ Signed {} transactions to {output}, send them with `sozo migrate broadcast`.",
+        plan.transactions.len()
+    ));
+
+    Ok(())
+}
+
+/// Sends the transactions of a signed plan, then records the deployment like a migration does.
+pub async fn broadcast_plan<U>(
+    ws: &Workspace<'_>,
+    plan_path: &Utf8Path,
+    starknet: StarknetOptions,
+    target_dir: U,
+    env: Option<&str>,
+    env_metadata: Option<&Environment>,
+) -> Result<()>
+where
+    U: AsRef<Path>,
+{
+    let ui = ws.config().ui();
+    let plan = MigrationPlan::load(plan_path)?;
+    let provider = starknet.provider(env_metadata)?;
+
+    ui.print(format!("\nBroadcasting {} transactions...", plan.transactions.len()));
+
+    // The steps are only kept in memory, the receipts tell which ones were already sent.
+    let mut journal = MigrationJournal::default();
+    plan::broadcast(&plan, &provider, &mut journal).await?;
+    for entry in &journal.completed {
+        ui.print_sub(format!("{}: {:#x}", entry.step, entry.transaction_hash.unwrap_or_default()));
+    }
+
+    let (local_manifest, remote_manifest) =
+        load_world_manifests(&target_dir, &provider, Some(plan.world_address), ui).await?;
+    update_world_manifest(ws, local_manifest, remote_manifest, &target_dir, plan.world_address)
+        .await?;
+
+    record_deployment(
+        &target_dir,
+        env,
+        plan.world_address,
+        &journal,
+        plan.declared_classes(),
+        plan.permissions.clone(),
+        &provider,
+        ui,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Appends the migration to the deployment record of the environment. Nothing is recorded if the
+/// migration didn't send any transaction, so that the record keeps the last actual migration.
+#[allow(clippy::too_many_arguments)]
+async fn record_deployment<U, P>(
+    target_dir: U,
+    env: Option<&str>,
+    world_address: FieldElement,
+    journal: &MigrationJournal,
+    classes: BTreeMap<String, FieldElement>,
+    permissions: Vec<AuthUpdate>,
+    provider: &P,
+    ui: &Ui,
+) -> Result<()>
+where
+    U: AsRef<Path>,
+    P: Provider + Sync + Send,
+{
+    if journal.completed.iter().all(|entry| entry.transaction_hash.is_none()) {
+        return Ok(());
+    }
+
+    let environment = env.unwrap_or(DEFAULT_ENVIRONMENT);
+    let path = DeploymentRecord::path(&target_dir, environment);
+    let previous = if path.exists() { Some(DeploymentRecord::load(&path)?) } else { None };
+
+    let migration = MigrationRecord::new(journal, classes, permissions, provider).await?;
+    DeploymentRecord::new(environment, world_address, migration, previous).write(&path)?;
+
+    ui.print(format!("\n✨ Deployment recorded in {}", path.display()));
+
+    Ok(())
+}
+
+/// Compares the schema of the models registered to the World with their local schema, and fails
+/// on changes which would make the existing entities unreadable unless `allow_breaking` is set.
+pub(crate) async fn check_model_schemas<P>(
+    local_manifest: &Manifest,
+    diff: &WorldDiff,
+    world_address: FieldElement,
+    provider: &P,
+    ui: &Ui,
+    allow_breaking: bool,
+) -> Result<()>
+where
+    P: Provider + Sync + Send,
+{
+    // Models are registered from scratch to a new World.
+    if !diff.world.is_same() {
+        return Ok(());
+    }
+
+    let world = WorldContractReader::new(world_address, provider);
+    let mut breaking = vec![];
+
+    for model in diff.models.iter().filter(|m| m.remote.is_some() && !m.is_same()) {
+        let local = match local_manifest.models.iter().find(|m| m.name == model.name) {
+            Some(local) => local,
+            None => continue,
+        };
+
+        let remote = world
+            .model(&model.name)
+            .await?
+            .schema()
+            .await
+            .with_context(|| format!("Failed to fetch the schema of model {}", model.name))?;
+
+        let schema_diff =
+            SchemaDiff::compute(&model.name, &remote, &local.members, local.abi.as_ref());
+
+        if schema_diff.changes.is_empty() {
+            continue;
+        }
+
+        ui.print(format!("\nSchema changes of model {}:", bold_message(&model.name)));
+        for line in schema_diff.to_string().lines() {
+            ui.print_sub(line);
+        }
+
+        if schema_diff.is_breaking() {
+            breaking.push(model.name.clone());
+        }
+    }
+
+    if !breaking.is_empty() {
+        if !allow_breaking {
+            bail!(
+                "Breaking schema changes in models {}, the existing entities would become \
+                 unreadable. Use `--allow-breaking` to migrate anyway.",
+                breaking.join(", ")
+            );
+        }
+
+        ui.warn(format!("Migrating breaking schema changes in models {}.", breaking.join(", ")));
+    }
+
     Ok(())
 }
 
@@ -113,6 +481,92 @@ where
     Ok(())
 }
 
+/// Returns the updates granting and revoking permissions so that the World matches the ones
+/// declared in the `[tool.dojo.auth]` section of the package metadata. The permissions of a World
+/// which isn't deployed yet are all granted.
+async fn permission_updates<P>(
+    ws: &Workspace<'_>,
+    local_manifest: &Manifest,
+    remote_manifest: Option<&Manifest>,
+    world_address: FieldElement,
+    provider: &P,
+) -> Result<Vec<AuthUpdate>>
+where
+    P: Provider + Sync + Send,
+{
+    let (permissions, contracts) =
+        match declared_permissions(ws, local_manifest, remote_manifest, world_address)? {
+            Some(declared) => declared,
+            None => return Ok(vec![]),
+        };
+
+    let ui = ws.config().ui();
+    ui.print_step(4, "🔐", "Evaluating permissions...");
+
+    let updates = match remote_manifest {
+        Some(_) => {
+            let world = WorldContractReader::new(world_address, provider);
+            permissions
+                .diff(&world, &contracts.values().copied().collect::<Vec<_>>())
+                .await
+                .with_context(|| "Failed to fetch World permissions.")?
+        }
+        None => permissions.grants(),
+    };
+
+    if updates.is_empty() {
+        ui.print_sub("Permissions are already up to date");
+    }
+
+    for update in &updates {
+        ui.print_sub(update.to_string());
+    }
+
+    Ok(updates)
+}
+
+/// Returns the World calls applying the permission updates, with their journal steps.
+fn auth_calls(updates: &[AuthUpdate], world_address: FieldElement) -> Result<Vec<(String, Call)>> {
+    updates
+        .iter()
+        .map(|update| Ok((journal::auth_step(update), update.call(world_address)?)))
+        .collect()
+}
+
+/// Resolves the permissions declared in the `[tool.dojo.auth]` section of the package metadata,
+/// along with the addresses of the contracts of the World they may reference, by name. Returns
+/// `None` if no permissions are declared.
+fn declared_permissions(
+    ws: &Workspace<'_>,
+    local_manifest: &Manifest,
+    remote_manifest: Option<&Manifest>,
+    world_address: FieldElement,
+) -> Result<Option<(Permissions, HashMap<String, FieldElement>)>> {
+    let auth = match dojo_metadata_from_workspace(ws)?.and_then(|inner| inner.auth().cloned()) {
+        Some(auth) => auth.load(ws.root())?,
+        None => return Ok(None),
+    };
+
+    let base_class_hash = match remote_manifest {
+        Some(manifest) => manifest.base.class_hash,
+        None => local_manifest.base.class_hash,
+    };
+
+    let contracts = local_manifest
+        .contracts
+        .iter()
+        .map(|c| {
+            let salt = generate_salt(&c.name);
+            (c.name.to_string(), get_contract_address(salt, base_class_hash, &[], world_address))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let permissions = Permissions::resolve(&auth.writers, &auth.owners, &contracts)
+        .with_context(|| "Failed to resolve declared permissions.")?;
+
+    Ok(Some((permissions, contracts)))
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn apply_diff<U, P, S>(
     ws: &Workspace<'_>,
@@ -122,6 +576,7 @@ pub(crate) async fn apply_diff<U, P, S>(
     world_address: Option<FieldElement>,
     account: &SingleOwnerAccount<P, S>,
     txn_config: Option<TransactionOptions>,
+    journal: &mut MigrationJournal,
 ) -> Result<FieldElement>
 where
     U: AsRef<Path>,
@@ -131,13 +586,36 @@ where
     let ui = ws.config().ui();
     let strategy = prepare_migration(target_dir, diff, name, world_address, ui)?;
 
+    execute_migration(ws, &strategy, account, txn_config, journal, vec![]).await
+}
+
+/// Executes the migration strategy, sending the given World calls along the ones of the
+/// migration, and returns the address of the World.
+async fn execute_migration<P, S>(
+    ws: &Workspace<'_>,
+    strategy: &MigrationStrategy,
+    account: &SingleOwnerAccount<P, S>,
+    txn_config: Option<TransactionOptions>,
+    journal: &mut MigrationJournal,
+    calls: Vec<(String, Call)>,
+) -> Result<FieldElement>
+where
+    P: Provider + Sync + Send + 'static,
+    S: Signer + Sync + Send + 'static,
+{
+    let ui = ws.config().ui();
+
     println!("  ");
 
-    let block_height = execute_strategy(ws, &strategy, account, txn_config)
-        .await
-        .map_err(|e| anyhow!(e))
-        .with_context(|| "Problem trying to migrate.")?;
+    if journal.world_address.is_none() {
+        journal.set_world_address(strategy.world_address()?)?;
+    }
 
+    let block_height =
+        execute_strategy_with_journal(ws, strategy, account, txn_config, journal, calls)
+            .await
+            .map_err(|e| anyhow!(e))
+            .with_context(|| "Problem trying to migrate.")?;
     if let Some(block_height) = block_height {
         ui.print(format!(
             "\n🎉 Successfully migrated World on block #{} at address {}",
@@ -162,14 +640,13 @@ where
 
 pub(crate) async fn setup_env(
     ws: &Workspace<'_>,
+    env: Option<&Environment>,
     account: AccountOptions,
     starknet: StarknetOptions,
     world: WorldOptions,
     name: Option<&String>,
 ) -> Result<(Option<FieldElement>, SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>)> {
     let ui = ws.config().ui();
-    let metadata = dojo_metadata_from_workspace(ws);
-    let env = metadata.as_ref().and_then(|inner| inner.env());
 
     let world_address = world.address(env).ok();
 
@@ -199,23 +676,22 @@ pub(crate) async fn setup_env(
     Ok((world_address, account))
 }
 
-async fn load_world_manifests<U, P, S>(
+async fn load_world_manifests<U, P>(
     target_dir: U,
-    account: &SingleOwnerAccount<P, S>,
+    provider: &P,
     world_address: Option<FieldElement>,
     ui: &Ui,
 ) -> Result<(Manifest, Option<Manifest>)>
 where
     U: AsRef<Path>,
-    P: Provider + Sync + Send + 'static,
-    S: Signer + Sync + Send + 'static,
+    P: Provider + Sync + Send,
 {
     ui.print_step(1, "🌎", "Building World state...");
 
     let local_manifest = Manifest::load_from_path(target_dir.as_ref().join("manifest.json"))?;
 
     let remote_manifest = if let Some(address) = world_address {
-        match Manifest::load_from_remote(account.provider(), address).await {
+        match Manifest::load_from_remote(provider, address).await {
             Ok(manifest) => {
                 ui.print_sub(format!("Found remote World: {address:#x}"));
                 Some(manifest)
@@ -262,15 +738,63 @@ fn prepare_migration(
     let info = migration.info();
 
     ui.print_sub(format!(
-        "Total items to be migrated ({}): New {} Update {}",
-        info.new + info.update,
+        "Total items to be migrated ({}): New {} Update {} Upgrade {}",
+        info.new + info.update + info.upgrade,
         info.new,
-        info.update
+        info.update,
+        info.upgrade
     ));
 
     Ok(migration)
 }
 
+/// Prints the actions the migration would perform, without sending any transaction.
+fn print_plan(strategy: &MigrationStrategy, ui: &Ui) {
+    if let Some(executor) = &strategy.executor {
+        ui.print_header("# Executor");
+        ui.print_sub(format!("Deploy class {:#x}", executor.diff.local));
+    }
+
+    if let Some(world) = &strategy.world {
+        ui.print_header("# World");
+        ui.print_sub(format!("Deploy class {:#x}", world.diff.local));
+        ui.print_sub(format!("Contract address: {:#x}", world.contract_address));
+    }
+
+    if !strategy.models.is_empty() {
+        ui.print_header(format!("# Models ({})", strategy.models.len()));
+
+        for model in &strategy.models {
+            ui.print(italic_message(&model.diff.name).to_string());
+            ui.print_sub(format!("Register class {:#x}", model.diff.local));
+        }
+    }
+
+    if !strategy.contracts.is_empty() {
+        ui.print_header(format!("# Contracts ({})", strategy.contracts.len()));
+
+        for contract in &strategy.contracts {
+            ui.print(italic_message(&contract.diff.name).to_string());
+
+            match contract.migration_type() {
+                MigrationType::Upgrade => {
+                    ui.print_sub(format!("Upgrade to class {:#x}", contract.diff.local));
+                    ui.print_sub(format!(
+                        "Contract address: {:#x} (storage is preserved)",
+                        contract.contract_address
+                    ));
+                }
+                MigrationType::New | MigrationType::Update => {
+                    ui.print_sub(format!("Deploy class {:#x}", contract.diff.local));
+                }
+            }
+        }
+    }
+}
+
+/// The maximum number of calls sent in a single multicall transaction.
+const MAX_CALLS_PER_BATCH: usize = 32;
+
 // returns the Some(block number) at which migration world is deployed, returns none if world was
 // not redeployed
 pub async fn execute_strategy<P, S>(
@@ -279,28 +803,57 @@ pub async fn execute_strategy<P, S>(
     migrator: &SingleOwnerAccount<P, S>,
     txn_config: Option<TransactionOptions>,
 ) -> Result<Option<u64>>
+where
+    P: Provider + Sync + Send + 'static,
+    S: Signer + Sync + Send + 'static,
+{
+    let mut journal = MigrationJournal::default();
+    execute_strategy_with_journal(ws, strategy, migrator, txn_config, &mut journal, vec![]).await
+}
+
+/// Executes the migration strategy, skipping the steps already completed in the journal.
+///
+/// Classes are declared one transaction at a time, but the World calls (setting the executor,
+/// registering models, deploying contracts and the given `calls`, like permission updates) are
+/// grouped into multicall transactions.
+pub async fn execute_strategy_with_journal<P, S>(
+    ws: &Workspace<'_>,
+    strategy: &MigrationStrategy,
+    migrator: &SingleOwnerAccount<P, S>,
+    txn_config: Option<TransactionOptions>,
+    journal: &mut MigrationJournal,
+    world_calls: Vec<(String, Call)>,
+) -> Result<Option<u64>>
 where
     P: Provider + Sync + Send + 'static,
     S: Signer + Sync + Send + 'static,
 {
     let ui = ws.config().ui();
 
+    let mut calls = vec![];
+
     match &strategy.executor {
         Some(executor) => {
             ui.print_header("# Executor");
-            deploy_contract(executor, "executor", vec![], migrator, ui, &txn_config).await?;
 
-            // There is no world migration, so it exists already.
-            if strategy.world.is_none() {
-                let addr = strategy.world_address()?;
-                let InvokeTransactionResult { transaction_hash } =
-                    WorldContract::new(addr, &migrator)
-                        .set_executor(executor.contract_address)
+            let step = journal::executor_step(executor.diff.local);
+            if journal.is_completed(&step) {
+                ui.print_sub("Already deployed in a previous run");
+            } else {
+                let output =
+                    deploy_contract(executor, "executor", vec![], migrator, ui, &txn_config)
                         .await?;
+                journal.record(step, output.transaction_hash())?;
+            }
 
-                TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
-
-                ui.print_hidden_sub(format!("Updated at: {transaction_hash:#x}"));
+            // There is no world migration, so it exists already.
+            if strategy.world.is_none() {
+                let call = Call {
+                    to: strategy.world_address()?,
+                    selector: selector!("set_executor"),
+                    calldata: vec![executor.contract_address],
+                };
+                calls.push((journal::set_executor_step(executor.contract_address), call));
             }
 
             ui.print_sub(format!("Contract address: {:#x}", executor.contract_address));
@@ -312,18 +865,24 @@ where
         Some(base) => {
             ui.print_header("# Base Contract");
 
-            match base
-                .declare(migrator, txn_config.clone().map(|c| c.into()).unwrap_or_default())
-                .await
-            {
-                Ok(res) => {
-                    ui.print_sub(format!("Class Hash: {:#x}", res.class_hash));
-                }
-                Err(MigrationError::ClassAlreadyDeclared) => {
-                    ui.print_sub(format!("Already declared: {:#x}", base.diff.local));
-                }
-                Err(e) => return Err(e.into()),
-            };
+            let step = journal::base_step(base.diff.local);
+            if journal.is_completed(&step) {
+                ui.print_sub("Already declared in a previous run");
+            } else {
+                match base
+                    .declare(migrator, txn_config.clone().map(|c| c.into()).unwrap_or_default())
+                    .await
+                {
+                    Ok(res) => {
+                        ui.print_sub(format!("Class Hash: {:#x}", res.class_hash));
+                        journal.record(step, Some(res.transaction_hash))?;
+                    }
+                    Err(MigrationError::ClassAlreadyDeclared) => {
+                        ui.print_sub(format!("Already declared: {:#x}", base.diff.local));
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+            }
         }
         None => {}
     };
@@ -332,29 +891,46 @@ where
         Some(world) => {
             ui.print_header("# World");
 
-            let calldata = vec![
-                strategy.executor.as_ref().unwrap().contract_address,
-                strategy.base.as_ref().unwrap().diff.local,
-            ];
-            deploy_contract(world, "world", calldata.clone(), migrator, ui, &txn_config).await?;
+            let step = journal::world_step(world.diff.local);
+            if journal.is_completed(&step) {
+                ui.print_sub("Already deployed in a previous run");
+            } else {
+                let calldata = vec![
+                    strategy.executor.as_ref().unwrap().contract_address,
+                    strategy.base.as_ref().unwrap().diff.local,
+                ];
+                let output =
+                    deploy_contract(world, "world", calldata.clone(), migrator, ui, &txn_config)
+                        .await?;
+                journal.record(step, output.transaction_hash())?;
+            }
 
             ui.print_sub(format!("Contract address: {:#x}", world.contract_address));
 
-            let metadata = dojo_metadata_from_workspace(ws);
+            let metadata = dojo_metadata_from_workspace(ws)?;
+            let step = journal::world_metadata_step(world.diff.local);
             if let Some(meta) = metadata.as_ref().and_then(|inner| inner.world()) {
-                match meta.upload().await {
-                    Ok(hash) => {
-                        let InvokeTransactionResult { transaction_hash } =
-                            WorldContract::new(world.contract_address, migrator)
-                                .set_metadata_uri(FieldElement::ZERO, format!("ipfs://{hash}"))
-                                .await
-                                .map_err(|e| anyhow!("Failed to set World metadata: {e}"))?;
-
-                        ui.print_sub(format!("Set Metadata transaction: {:#x}", transaction_hash));
-                        ui.print_sub(format!("Metadata uri: ipfs://{hash}"));
-                    }
-                    Err(err) => {
-                        ui.print_sub(format!("Failed to set World metadata:\n{err}"));
+                if journal.is_completed(&step) {
+                    ui.print_sub("Metadata already set in a previous run");
+                } else {
+                    match meta.upload().await {
+                        Ok(hash) => {
+                            let InvokeTransactionResult { transaction_hash } =
+                                WorldContract::new(world.contract_address, migrator)
+                                    .set_metadata_uri(FieldElement::ZERO, format!("ipfs://{hash}"))
+                                    .await
+                                    .map_err(|e| anyhow!("Failed to set World metadata: {e}"))?;
+                            journal.record(step, Some(transaction_hash))?;
+
+                            ui.print_sub(format!(
+                                "Set Metadata transaction: {:#x}",
+                                transaction_hash
+                            ));
+                            ui.print_sub(format!("Metadata uri: ipfs://{hash}"));
+                        }
+                        Err(err) => {
+                            ui.print_sub(format!("Failed to set World metadata:\n{err}"));
+                        }
                     }
                 }
             }
@@ -362,8 +938,11 @@ where
         None => {}
     };
 
-    register_models(strategy, migrator, ui, txn_config.clone()).await?;
-    deploy_contracts(strategy, migrator, ui, txn_config).await?;
+    calls.extend(register_models(strategy, migrator, ui, txn_config.clone(), journal).await?);
+    calls.extend(deploy_contracts(strategy, migrator, ui, txn_config.clone(), journal).await?);
+    calls.extend(world_calls);
+
+    execute_batches(calls, migrator, ui, &txn_config, journal).await?;
 
     // This gets current block numder if helpful
     // let block_height = migrator.provider().block_number().await.ok();
@@ -371,11 +950,70 @@ where
     Ok(None)
 }
 
+/// Sends the calls in multicall transactions of at most [`MAX_CALLS_PER_BATCH`] calls each,
+/// waiting for every transaction to be accepted before sending the next one. The calls whose step
+/// was completed by a previous run are skipped.
+async fn execute_batches<P, S>(
+    calls: Vec<(String, Call)>,
+    migrator: &SingleOwnerAccount<P, S>,
+    ui: &Ui,
+    txn_config: &Option<TransactionOptions>,
+    journal: &mut MigrationJournal,
+) -> Result<()>
+where
+    P: Provider + Sync + Send + 'static,
+    S: Signer + Sync + Send + 'static,
+{
+    if calls.is_empty() {
+        return Ok(());
+    }
+
+    ui.print_header(format!("# World calls ({})", calls.len()));
+
+    let (completed, calls): (Vec<_>, Vec<_>) =
+        calls.into_iter().partition(|(step, _)| journal.is_completed(step));
+    if !completed.is_empty() {
+        ui.print_sub(format!("{} calls already sent in a previous run", completed.len()));
+    }
+
+    for batch in calls.chunks(MAX_CALLS_PER_BATCH) {
+        let (steps, batch): (Vec<_>, Vec<_>) = batch.iter().cloned().unzip();
+
+        let mut txn = migrator.execute(batch.clone());
+
+        if let Some(TxConfig { fee_estimate_multiplier: Some(multiplier) }) =
+            txn_config.clone().map(TxConfig::from)
+        {
+            txn = txn.fee_estimate_multiplier(multiplier);
+        }
+
+        let InvokeTransactionResult { transaction_hash } =
+            txn.send().await.map_err(|e| anyhow!("Failed to send batch of World calls: {e}"))?;
+
+        TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
+
+        journal.record_multicall(steps, transaction_hash)?;
+
+        ui.print_sub(format!("Sent {} calls at: {transaction_hash:#x}", batch.len()));
+    }
+
+    Ok(())
+}
+
 enum ContractDeploymentOutput {
     AlreadyDeployed(FieldElement),
     Output(DeployOutput),
 }
 
+impl ContractDeploymentOutput {
+    fn transaction_hash(&self) -> Option<FieldElement> {
+        match self {
+            ContractDeploymentOutput::AlreadyDeployed(_) => None,
+            ContractDeploymentOutput::Output(output) => Some(output.transaction_hash),
+        }
+    }
+}
+
 async fn deploy_contract<P, S>(
     contract: &ContractMigration,
     contract_id: &str,
@@ -416,12 +1054,14 @@ where
     }
 }
 
+/// Declares the models and returns the World calls registering them, with their journal steps.
 async fn register_models<P, S>(
     strategy: &MigrationStrategy,
     migrator: &SingleOwnerAccount<P, S>,
     ui: &Ui,
     txn_config: Option<TransactionOptions>,
-) -> Result<Option<RegisterOutput>>
+    journal: &mut MigrationJournal,
+) -> Result<Vec<(String, Call)>>
 where
     P: Provider + Sync + Send + 'static,
     S: Signer + Sync + Send + 'static,
@@ -429,23 +1069,26 @@ where
     let models = &strategy.models;
 
     if models.is_empty() {
-        return Ok(None);
+        return Ok(vec![]);
     }
 
     ui.print_header(format!("# Models ({})", models.len()));
 
-    let mut declare_output = vec![];
-
     for c in models.iter() {
         ui.print(italic_message(&c.diff.name).to_string());
 
+        let step = journal::declare_model_step(&c.diff.name, c.diff.local);
+        if journal.is_completed(&step) {
+            ui.print_sub(format!("Declared in a previous run: {:#x}", c.diff.local));
+            continue;
+        }
+
         let res =
             c.declare(migrator, txn_config.clone().map(|c| c.into()).unwrap_or_default()).await;
         match res {
             Ok(output) => {
                 ui.print_hidden_sub(format!("Declare transaction: {:#x}", output.transaction_hash));
-
-                declare_output.push(output);
+                journal.record(step, Some(output.transaction_hash))?;
             }
 
             // Continue if model is already declared
@@ -461,24 +1104,28 @@ where
 
     let world_address = strategy.world_address()?;
 
-    let InvokeTransactionResult { transaction_hash } = WorldContract::new(world_address, migrator)
-        .register_models(&models.iter().map(|c| c.diff.local).collect::<Vec<_>>())
-        .await
-        .map_err(|e| anyhow!("Failed to register models to World: {e}"))?;
-
-    TransactionWaiter::new(transaction_hash, migrator.provider()).await?;
-
-    ui.print_sub(format!("Registered at: {transaction_hash:#x}"));
-
-    Ok(Some(RegisterOutput { transaction_hash, declare_output }))
+    Ok(models
+        .iter()
+        .map(|c| {
+            let call = Call {
+                to: world_address,
+                selector: selector!("register_model"),
+                calldata: vec![c.diff.local],
+            };
+            (journal::register_model_step(&c.diff.name, c.diff.local), call)
+        })
+        .collect())
 }
 
+/// Declares the contracts and returns the World calls deploying or upgrading them, with their
+/// journal steps.
 async fn deploy_contracts<P, S>(
     strategy: &MigrationStrategy,
     migrator: &SingleOwnerAccount<P, S>,
     ui: &Ui,
     txn_config: Option<TransactionOptions>,
-) -> Result<Vec<Option<DeployOutput>>>
+    journal: &mut MigrationJournal,
+) -> Result<Vec<(String, Call)>>
 where
     P: Provider + Sync + Send + 'static,
     S: Signer + Sync + Send + 'static,
@@ -491,41 +1138,43 @@ where
 
     ui.print_header(format!("# Contracts ({})", contracts.len()));
 
-    let mut deploy_output = vec![];
+    let mut calls = vec![];
 
     let world_address = strategy.world_address()?;
 
     for contract in strategy.contracts.iter() {
         let name = &contract.diff.name;
         ui.print(italic_message(name).to_string());
-        match contract
-            .world_deploy(
-                world_address,
-                contract.diff.local,
-                migrator,
-                txn_config.clone().map(|c| c.into()).unwrap_or_default(),
-            )
-            .await
-        {
-            Ok(output) => {
-                if let Some(ref declare) = output.declare {
+
+        let step = journal::declare_contract_step(name, contract.diff.local);
+        if !journal.is_completed(&step) {
+            match contract
+                .declare(migrator, txn_config.clone().map(|c| c.into()).unwrap_or_default())
+                .await
+            {
+                Ok(output) => {
                     ui.print_hidden_sub(format!(
                         "Declare transaction: {:#x}",
-                        declare.transaction_hash
+                        output.transaction_hash
                     ));
+                    journal.record(step, Some(output.transaction_hash))?;
                 }
+                Err(MigrationError::ClassAlreadyDeclared) => {}
+                Err(e) => return Err(anyhow!("Failed to migrate {}: {:?}", name, e)),
+            }
+        }
 
-                ui.print_hidden_sub(format!("Deploy transaction: {:#x}", output.transaction_hash));
-                ui.print_sub(format!("Contract address: {:#x}", output.contract_address));
-                deploy_output.push(Some(output));
+        match contract.prepare_world_deploy(world_address, contract.diff.local, migrator).await {
+            Ok((call, contract_address)) => {
+                ui.print_sub(format!("Contract address: {:#x}", contract_address));
+                calls.push((journal::deploy_contract_step(name, contract.diff.local), call));
             }
             Err(MigrationError::ContractAlreadyDeployed(contract_address)) => {
                 ui.print_sub(format!("Already deployed: {:#x}", contract_address));
-                deploy_output.push(None);
             }
             Err(e) => return Err(anyhow!("Failed to migrate {}: {:?}", name, e)),
         }
     }
 
-    Ok(deploy_output)
+    Ok(calls)
 }
@@ -0,0 +1,685 @@
+//! Migration plans, to migrate a World with a key kept offline.
+//!
+//! `sozo migrate --export-plan` resolves the migration into the ordered list of transactions it
+//! would send, with their calldata, nonces and fees, without signing them. The plan is then
+//! signed on the machine holding the key with `sozo migrate sign`, and sent with
+//! `sozo migrate broadcast`.
+//!
+//! Fees can't be estimated without a signature, so the max fee of the transactions is fixed when
+//! the plan is exported. The nonces follow the one of the account at that time: no other
+//! transaction must be sent from the account until the plan is broadcast.
+//!
+//! The calls of the invoke transactions are encoded for the account as stated by the `encoding`
+//! of the plan. Plans are exported for legacy (Cairo 0) accounts, like the ones `sozo` sends
+//! transactions with.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use dojo_world::migration::auth::AuthUpdate;
+use dojo_world::migration::contract::ContractMigration;
+use dojo_world::migration::strategy::MigrationStrategy;
+use dojo_world::migration::{prepare_contract_declaration_params, MigrationType};
+use dojo_world::utils::TransactionWaiter;
+use serde::{Deserialize, Serialize};
+use starknet::accounts::Call;
+use starknet::core::crypto::compute_hash_on_elements;
+use starknet::core::types::contract::FlattenedSierraClass;
+use starknet::core::types::{
+    BlockId, BlockTag, BroadcastedDeclareTransaction, BroadcastedDeclareTransactionV2,
+    BroadcastedInvokeTransaction, FieldElement, StarknetError,
+};
+use starknet::core::utils::get_contract_address;
+use starknet::macros::{felt, selector};
+use starknet::providers::{
+    MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage,
+};
+use starknet::signers::Signer;
+
+use super::journal::{self, MigrationJournal, MULTICALL_STEP};
+use super::MAX_CALLS_PER_BATCH;
+
+/// The Universal Deployer Contract, deploying the executor and the World.
+const UDC_ADDRESS: FieldElement =
+    felt!("0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf");
+
+/// Cairo short string `invoke`, prefix of the invoke transaction hashes.
+const PREFIX_INVOKE: FieldElement = felt!("0x696e766f6b65");
+
+/// Cairo short string `declare`, prefix of the declare transaction hashes.
+const PREFIX_DECLARE: FieldElement = felt!("0x6465636c617265");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedCall {
+    pub to: FieldElement,
+    pub selector: FieldElement,
+    pub calldata: Vec<FieldElement>,
+}
+
+impl From<Call> for PlannedCall {
+    fn from(call: Call) -> Self {
+        Self { to: call.to, selector: call.selector, calldata: call.calldata }
+    }
+}
+
+/// How the calls of a multicall are encoded into the calldata of the account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallEncoding {
+    /// The calls then their concatenated calldata, for legacy (Cairo 0) accounts.
+    #[default]
+    Legacy,
+    /// Every call followed by its calldata, for Cairo 1 accounts.
+    New,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransactionKind {
+    /// A V2 declare transaction.
+    Declare {
+        name: String,
+        class_hash: FieldElement,
+        compiled_class_hash: FieldElement,
+        contract_class: FlattenedSierraClass,
+    },
+    /// A V1 invoke transaction.
+    Invoke {
+        calls: Vec<PlannedCall>,
+        /// The calls encoded for the account, as signed.
+        calldata: Vec<FieldElement>,
+        /// The predicted addresses of the contracts deployed by the calls, by name.
+        #[serde(default)]
+        deployed: BTreeMap<String, FieldElement>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedTransaction {
+    /// The migration step of the transaction, as recorded in the migration journal.
+    pub step: String,
+    pub nonce: FieldElement,
+    pub max_fee: FieldElement,
+    #[serde(flatten)]
+    pub kind: TransactionKind,
+    /// The signature of the transaction, once the plan is signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Vec<FieldElement>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationPlan {
+    pub chain_id: FieldElement,
+    pub sender_address: FieldElement,
+    pub world_address: FieldElement,
+    /// The encoding of the calls in the calldata of the invoke transactions.
+    #[serde(default)]
+    pub encoding: CallEncoding,
+    pub transactions: Vec<PlannedTransaction>,
+    /// The permissions granted and revoked by the transactions, to record them on broadcast.
+    #[serde(default)]
+    pub permissions: Vec<AuthUpdate>,
+}
+
+impl MigrationPlan {
+    /// Resolves the strategy into the transactions sent by `sender_address`, skipping the classes
+    /// already declared and the contracts already deployed. `base_class_hash` is the class the
+    /// World deploys contracts with, to predict their addresses. The `permissions` updates are
+    /// applied once the World is migrated.
+    pub async fn build<P>(
+        strategy: &MigrationStrategy,
+        provider: &P,
+        sender_address: FieldElement,
+        base_class_hash: FieldElement,
+        max_fee: FieldElement,
+        permissions: Vec<AuthUpdate>,
+    ) -> Result<Self>
+    where
+        P: Provider + Sync + Send,
+    {
+        let chain_id = provider.chain_id().await?;
+        let world_address = strategy.world_address()?;
+
+        let mut builder = PlanBuilder {
+            provider,
+            // The same encoding as the accounts `sozo` sends the transactions of a migration with.
+            encoding: CallEncoding::Legacy,
+            nonce: provider
+                .get_nonce(BlockId::Tag(BlockTag::Pending), sender_address)
+                .await
+                .with_context(|| format!("Failed to fetch the nonce of {sender_address:#x}"))?,
+            max_fee,
+            transactions: vec![],
+        };
+        // The World calls, with the name and address of the contract they deploy if any.
+        let mut calls: Vec<(Call, Option<(String, FieldElement)>)> = vec![];
+
+        if let Some(executor) = &strategy.executor {
+            builder
+                .declare(
+                    "executor",
+                    journal::declare_contract_step("executor", executor.diff.local),
+                    &executor.artifact_path,
+                )
+                .await?;
+            let step = journal::executor_step(executor.diff.local);
+            builder.udc_deploy("executor", step, executor, vec![]).await?;
+
+            // There is no world migration, so it exists already.
+            if strategy.world.is_none() {
+                let call = Call {
+                    to: world_address,
+                    selector: selector!("set_executor"),
+                    calldata: vec![executor.contract_address],
+                };
+                calls.push((call, None));
+            }
+        }
+
+        if let Some(base) = &strategy.base {
+            builder
+                .declare("base", journal::base_step(base.diff.local), &base.artifact_path)
+                .await?;
+        }
+
+        if let Some(world) = &strategy.world {
+            let calldata = vec![
+                strategy.executor.as_ref().unwrap().contract_address,
+                strategy.base.as_ref().unwrap().diff.local,
+            ];
+            let step = journal::declare_contract_step("world", world.diff.local);
+            builder.declare("world", step, &world.artifact_path).await?;
+            builder
+                .udc_deploy("world", journal::world_step(world.diff.local), world, calldata)
+                .await?;
+        }
+
+        for model in &strategy.models {
+            let step = journal::declare_model_step(&model.diff.name, model.diff.local);
+            builder.declare(&model.diff.name, step, &model.artifact_path).await?;
+            let call = Call {
+                to: world_address,
+                selector: selector!("register_model"),
+                calldata: vec![model.diff.local],
+            };
+            calls.push((call, None));
+        }
+
+        for contract in &strategy.contracts {
+            let step = journal::declare_contract_step(&contract.diff.name, contract.diff.local);
+            builder.declare(&contract.diff.name, step, &contract.artifact_path).await?;
+            let name = contract.diff.name.clone();
+            calls.push(match contract.migration_type() {
+                MigrationType::Upgrade => {
+                    let call = Call {
+                        to: world_address,
+                        selector: selector!("upgrade_contract"),
+                        calldata: vec![contract.contract_address, contract.diff.local],
+                    };
+                    (call, Some((name, contract.contract_address)))
+                }
+                MigrationType::New | MigrationType::Update => {
+                    let call = Call {
+                        to: world_address,
+                        selector: selector!("deploy_contract"),
+                        calldata: vec![contract.salt, contract.diff.local],
+                    };
+                    let address =
+                        get_contract_address(contract.salt, base_class_hash, &[], world_address);
+                    (call, Some((name, address)))
+                }
+            });
+        }
+
+        // The permissions are updated in the same multicalls as the other World calls.
+        for update in &permissions {
+            calls.push((update.call(world_address)?, None));
+        }
+
+        for batch in calls.chunks(MAX_CALLS_PER_BATCH) {
+            let (batch, deployed): (Vec<_>, Vec<_>) = batch.iter().cloned().unzip();
+            let deployed = deployed.into_iter().flatten().collect();
+            builder.invoke(MULTICALL_STEP.to_string(), batch, deployed);
+        }
+
+        Ok(Self {
+            chain_id,
+            sender_address,
+            world_address,
+            encoding: builder.encoding,
+            transactions: builder.transactions,
+            permissions,
+        })
+    }
+
+    /// Returns the classes the plan declares, by contract or model name.
+    pub fn declared_classes(&self) -> BTreeMap<String, FieldElement> {
+        self.transactions
+            .iter()
+            .filter_map(|tx| match &tx.kind {
+                TransactionKind::Declare { name, class_hash, .. } => {
+                    Some((name.clone(), *class_hash))
+                }
+                TransactionKind::Invoke { .. } => None,
+            })
+            .collect()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read migration plan {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse migration plan {}", path.display()))
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write migration plan {}", path.display()))
+    }
+
+    /// Signs every transaction of the plan, which must be sent from the account of the signer.
+    pub async fn sign<S>(&mut self, signer: &S) -> Result<()>
+    where
+        S: Signer + Sync + Send,
+    {
+        for tx in &mut self.transactions {
+            let hash = tx.transaction_hash(self.chain_id, self.sender_address);
+            let signature = signer
+                .sign_hash(&hash)
+                .await
+                .map_err(|e| anyhow!("Failed to sign transaction `{}`: {e}", tx.step))?;
+            tx.signature = Some(vec![signature.r, signature.s]);
+        }
+
+        Ok(())
+    }
+}
+
+impl PlannedTransaction {
+    /// Returns the hash of the transaction, the one signed.
+    pub fn transaction_hash(
+        &self,
+        chain_id: FieldElement,
+        sender_address: FieldElement,
+    ) -> FieldElement {
+        match &self.kind {
+            TransactionKind::Declare { class_hash, compiled_class_hash, .. } => {
+                compute_hash_on_elements(&[
+                    PREFIX_DECLARE,
+                    FieldElement::TWO, // version
+                    sender_address,
+                    FieldElement::ZERO, // entry_point_selector
+                    compute_hash_on_elements(&[*class_hash]),
+                    self.max_fee,
+                    chain_id,
+                    self.nonce,
+                    *compiled_class_hash,
+                ])
+            }
+            TransactionKind::Invoke { calldata, .. } => compute_hash_on_elements(&[
+                PREFIX_INVOKE,
+                FieldElement::ONE, // version
+                sender_address,
+                FieldElement::ZERO, // entry_point_selector
+                compute_hash_on_elements(calldata),
+                self.max_fee,
+                chain_id,
+                self.nonce,
+            ]),
+        }
+    }
+
+    /// Sends the signed transaction and returns its hash.
+    pub async fn send<P>(&self, provider: &P, sender_address: FieldElement) -> Result<FieldElement>
+    where
+        P: Provider + Sync + Send,
+    {
+        let signature = self
+            .signature
+            .clone()
+            .ok_or_else(|| anyhow!("Transaction `{}` isn't signed.", self.step))?;
+
+        let transaction_hash = match &self.kind {
+            TransactionKind::Declare { compiled_class_hash, contract_class, .. } => {
+                let tx = BroadcastedDeclareTransaction::V2(BroadcastedDeclareTransactionV2 {
+                    max_fee: self.max_fee,
+                    signature,
+                    nonce: self.nonce,
+                    contract_class: Arc::new(contract_class.clone()),
+                    compiled_class_hash: *compiled_class_hash,
+                    sender_address,
+                    is_query: false,
+                });
+                provider.add_declare_transaction(tx).await?.transaction_hash
+            }
+            TransactionKind::Invoke { calldata, .. } => {
+                let tx = BroadcastedInvokeTransaction {
+                    max_fee: self.max_fee,
+                    signature,
+                    nonce: self.nonce,
+                    sender_address,
+                    calldata: calldata.clone(),
+                    is_query: false,
+                };
+                provider.add_invoke_transaction(tx).await?.transaction_hash
+            }
+        };
+
+        TransactionWaiter::new(transaction_hash, provider).await?;
+
+        Ok(transaction_hash)
+    }
+}
+
+struct PlanBuilder<'a, P> {
+    provider: &'a P,
+    encoding: CallEncoding,
+    nonce: FieldElement,
+    max_fee: FieldElement,
+    transactions: Vec<PlannedTransaction>,
+}
+
+impl<'a, P> PlanBuilder<'a, P>
+where
+    P: Provider + Sync + Send,
+{
+    fn push(&mut self, step: String, kind: TransactionKind) {
+        self.transactions.push(PlannedTransaction {
+            step,
+            nonce: self.nonce,
+            max_fee: self.max_fee,
+            kind,
+            signature: None,
+        });
+        self.nonce += FieldElement::ONE;
+    }
+
+    /// Declares the class of the artifact as the journal step `step`, unless it's already declared.
+    async fn declare(&mut self, name: &str, step: String, artifact_path: &PathBuf) -> Result<()> {
+        let (contract_class, compiled_class_hash) =
+            prepare_contract_declaration_params(artifact_path)?;
+        let class_hash = contract_class.class_hash();
+
+        match self.provider.get_class(BlockId::Tag(BlockTag::Pending), class_hash).await {
+            Ok(_) => return Ok(()),
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::ClassHashNotFound),
+                ..
+            })) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        self.push(
+            step,
+            TransactionKind::Declare {
+                name: name.to_string(),
+                class_hash,
+                compiled_class_hash,
+                contract_class,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Deploys the contract with the UDC as the journal step `step`, unless it's already deployed.
+    async fn udc_deploy(
+        &mut self,
+        name: &str,
+        step: String,
+        contract: &ContractMigration,
+        constructor_calldata: Vec<FieldElement>,
+    ) -> Result<()> {
+        match self
+            .provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), contract.contract_address)
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::ContractNotFound),
+                ..
+            })) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let calldata = [
+            vec![
+                contract.diff.local,                            // class hash
+                contract.salt,                                  // salt
+                FieldElement::ZERO,                             // unique
+                FieldElement::from(constructor_calldata.len()), // constructor calldata len
+            ],
+            constructor_calldata,
+        ]
+        .concat();
+
+        let call = Call { to: UDC_ADDRESS, selector: selector!("deployContract"), calldata };
+        let deployed = BTreeMap::from([(name.to_string(), contract.contract_address)]);
+        self.invoke(step, vec![call], deployed);
+
+        Ok(())
+    }
+
+    fn invoke(&mut self, step: String, calls: Vec<Call>, deployed: BTreeMap<String, FieldElement>) {
+        let calldata = encode_calls(&calls, self.encoding);
+        let calls = calls.into_iter().map(PlannedCall::from).collect();
+        self.push(step, TransactionKind::Invoke { calls, calldata, deployed });
+    }
+}
+
+/// Encodes the calls of a multicall into the calldata of the account.
+fn encode_calls(calls: &[Call], encoding: CallEncoding) -> Vec<FieldElement> {
+    let mut calldata = vec![FieldElement::from(calls.len())];
+
+    match encoding {
+        CallEncoding::Legacy => {
+            let mut offset = 0;
+            for call in calls {
+                calldata.extend([
+                    call.to,
+                    call.selector,
+                    FieldElement::from(offset),
+                    FieldElement::from(call.calldata.len()),
+                ]);
+                offset += call.calldata.len();
+            }
+
+            calldata.push(FieldElement::from(offset));
+            for call in calls {
+                calldata.extend(&call.calldata);
+            }
+        }
+        CallEncoding::New => {
+            for call in calls {
+                calldata.extend([call.to, call.selector, FieldElement::from(call.calldata.len())]);
+                calldata.extend(&call.calldata);
+            }
+        }
+    }
+
+    calldata
+}
+
+/// Sends the transactions of a signed plan in order, recording them in the journal. A failed
+/// broadcast can be resumed: the transactions whose nonce was already used by the account must
+/// have been sent by a previous broadcast of the plan, which their receipt tells.
+pub async fn broadcast<P>(
+    plan: &MigrationPlan,
+    provider: &P,
+    journal: &mut MigrationJournal,
+) -> Result<()>
+where
+    P: Provider + Sync + Send,
+{
+    let chain_id = provider.chain_id().await?;
+    if chain_id != plan.chain_id {
+        bail!(
+            "The plan was exported for chain {:#x}, but the network is chain {chain_id:#x}.",
+            plan.chain_id
+        );
+    }
+
+    if let Some(tx) = plan.transactions.iter().find(|tx| tx.signature.is_none()) {
+        bail!("Transaction `{}` isn't signed, sign the plan with `sozo migrate sign`.", tx.step);
+    }
+
+    let mut nonce =
+        provider.get_nonce(BlockId::Tag(BlockTag::Pending), plan.sender_address).await?;
+
+    for tx in &plan.transactions {
+        let transaction_hash = if tx.nonce < nonce {
+            let transaction_hash = tx.transaction_hash(plan.chain_id, plan.sender_address);
+            match provider.get_transaction_receipt(transaction_hash).await {
+                Ok(_) => {}
+                Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                    code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
+                    ..
+                })) => bail!(
+                    "The nonce {:#x} of transaction `{}` was used by another transaction of the \
+                     account, export a new plan.",
+                    tx.nonce,
+                    tx.step
+                ),
+                Err(e) => return Err(e.into()),
+            }
+
+            // Fails if the transaction reverted.
+            TransactionWaiter::new(transaction_hash, provider)
+                .await
+                .with_context(|| format!("Transaction `{}` failed", tx.step))?;
+            transaction_hash
+        } else if tx.nonce == nonce {
+            nonce += FieldElement::ONE;
+            tx.send(provider, plan.sender_address)
+                .await
+                .with_context(|| format!("Failed to send transaction `{}`", tx.step))?
+        } else {
+            bail!(
+                "Transaction `{}` has nonce {:#x}, but the account is at nonce {nonce:#x}. The \
+                 plan was exported for another state of the account, export a new plan.",
+                tx.step,
+                tx.nonce
+            );
+        };
+
+        journal.record(tx.step.clone(), Some(transaction_hash))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::core::crypto::Signature;
+    use starknet::signers::{LocalWallet, SigningKey};
+
+    use super::*;
+
+    fn invoke(nonce: u64, calls: Vec<Call>) -> PlannedTransaction {
+        PlannedTransaction {
+            step: "executor".to_string(),
+            nonce: FieldElement::from(nonce),
+            max_fee: felt!("0x1000"),
+            kind: TransactionKind::Invoke {
+                calldata: encode_calls(&calls, CallEncoding::Legacy),
+                calls: calls.into_iter().map(PlannedCall::from).collect(),
+                deployed: BTreeMap::new(),
+            },
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn encode_multicall() {
+        let calls = vec![
+            Call { to: felt!("0x1"), selector: felt!("0x2"), calldata: vec![felt!("0x3")] },
+            Call {
+                to: felt!("0x4"),
+                selector: felt!("0x5"),
+                calldata: vec![felt!("0x6"), felt!("0x7")],
+            },
+        ];
+
+        assert_eq!(
+            encode_calls(&calls, CallEncoding::Legacy),
+            vec![
+                felt!("0x2"),
+                felt!("0x1"),
+                felt!("0x2"),
+                felt!("0x0"),
+                felt!("0x1"),
+                felt!("0x4"),
+                felt!("0x5"),
+                felt!("0x1"),
+                felt!("0x2"),
+                felt!("0x3"),
+                felt!("0x3"),
+                felt!("0x6"),
+                felt!("0x7"),
+            ]
+        );
+
+        assert_eq!(
+            encode_calls(&calls, CallEncoding::New),
+            vec![
+                felt!("0x2"),
+                felt!("0x1"),
+                felt!("0x2"),
+                felt!("0x1"),
+                felt!("0x3"),
+                felt!("0x4"),
+                felt!("0x5"),
+                felt!("0x2"),
+                felt!("0x6"),
+                felt!("0x7"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn sign_plan() {
+        let key = SigningKey::from_secret_scalar(felt!(
+            "0x1800000000300000180000000000030000000000003006001800006600"
+        ));
+        let public_key = key.verifying_key();
+        let signer = LocalWallet::from_signing_key(key);
+
+        let call =
+            Call { to: UDC_ADDRESS, selector: selector!("deployContract"), calldata: vec![] };
+        let mut plan = MigrationPlan {
+            chain_id: felt!("0x4b4154414e41"),
+            sender_address: felt!("0x1234"),
+            world_address: felt!("0x5678"),
+            encoding: CallEncoding::Legacy,
+            transactions: vec![invoke(0, vec![call.clone()]), invoke(1, vec![call])],
+            permissions: vec![],
+        };
+        plan.sign(&signer).await.unwrap();
+
+        let hashes = plan
+            .transactions
+            .iter()
+            .map(|tx| tx.transaction_hash(plan.chain_id, plan.sender_address))
+            .collect::<Vec<_>>();
+        assert_ne!(hashes[0], hashes[1], "nonces are part of the hash");
+
+        for (tx, hash) in plan.transactions.iter().zip(hashes) {
+            let signature = tx.signature.as_ref().unwrap();
+            assert!(
+                public_key.verify(&hash, &Signature { r: signature[0], s: signature[1] }).unwrap()
+            );
+        }
+
+        // The signed plan roundtrips through its JSON representation.
+        let json = serde_json::to_string(&plan).unwrap();
+        let parsed: MigrationPlan = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.transactions[1].signature, plan.transactions[1].signature);
+        assert!(json.contains("\"type\":\"invoke\""));
+    }
+}
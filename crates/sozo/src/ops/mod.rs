@@ -1,6 +1,13 @@
+pub mod account;
 pub mod auth;
+pub mod bindings;
+pub mod call;
 pub mod events;
 pub mod execute;
+pub mod inspect;
 pub mod migration;
 pub mod model;
 pub mod register;
+pub mod scaffold;
+pub mod script;
+pub mod test;
@@ -1,13 +1,42 @@
+use std::time::Duration;
+
 use anyhow::Result;
+use dojo_types::schema::Ty;
 use dojo_world::contracts::model::ModelReader;
 use dojo_world::contracts::world::WorldContractReader;
+use dojo_world::manifest::load_remote_models;
 use dojo_world::metadata::Environment;
+use serde_json::{Map, Value};
 use starknet::core::types::{BlockId, BlockTag};
 
-use crate::commands::model::ModelCommands;
+use crate::commands::model::{ModelCommands, OutputFormat};
 
 pub async fn execute(command: ModelCommands, env_metadata: Option<Environment>) -> Result<()> {
     match command {
+        ModelCommands::List { world, starknet, format } => {
+            let world_address = world.address(env_metadata.as_ref())?;
+            let provider = starknet.provider(env_metadata.as_ref())?;
+
+            let mut models = load_remote_models(&provider, world_address).await?;
+            models.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let world = WorldContractReader::new(world_address, &provider)
+                .with_block(BlockId::Tag(BlockTag::Pending));
+
+            let mut rows = Vec::with_capacity(models.len());
+            for model in models {
+                let reader = world.model(&model.name).await?;
+                rows.push(vec![
+                    model.name,
+                    format!("{:#x}", reader.class_hash()),
+                    reader.packed_size().await?.to_string(),
+                    reader.unpacked_size().await?.to_string(),
+                ]);
+            }
+
+            print_rows(format, &["name", "class_hash", "packed_size", "unpacked_size"], rows)?;
+        }
+
         ModelCommands::ClassHash { name, world, starknet } => {
             let world_address = world.address(env_metadata.as_ref())?;
             let provider = starknet.provider(env_metadata.as_ref())?;
@@ -37,7 +66,7 @@ pub async fn execute(command: ModelCommands, env_metadata: Option<Environment>)
             }
         }
 
-        ModelCommands::Get { name, keys, starknet, world, .. } => {
+        ModelCommands::Get { name, keys, watch, interval, starknet, world } => {
             let world_address = world.address(env_metadata.as_ref())?;
             let provider = starknet.provider(env_metadata.as_ref())?;
 
@@ -45,11 +74,132 @@ pub async fn execute(command: ModelCommands, env_metadata: Option<Environment>)
                 .with_block(BlockId::Tag(BlockTag::Pending));
 
             let model = world.model(&name).await?;
-            let entity = model.entity(&keys).await?;
+            let mut entity = model.entity(&keys).await?;
+
+            println!("{entity}");
 
-            println!("{entity}")
+            while watch {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => break,
+                    _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                }
+
+                let current = model.entity(&keys).await?;
+                if current != entity {
+                    println!("\n{current}");
+                    entity = current;
+                }
+            }
+        }
+
+        ModelCommands::Entities { name, world, starknet, format } => {
+            let world_address = world.address(env_metadata.as_ref())?;
+            let provider = starknet.provider(env_metadata.as_ref())?;
+
+            let world = WorldContractReader::new(world_address, &provider)
+                .with_block(BlockId::Tag(BlockTag::Pending));
+
+            let model = world.model(&name).await?;
+            let entities = model.entities().await?;
+
+            let schema = model.schema().await?;
+            let headers = match schema.as_struct() {
+                Some(s) => s.children.iter().map(|m| m.name.clone()).collect(),
+                None => vec![],
+            };
+
+            let rows = entities
+                .into_iter()
+                .map(|entity| match entity.as_struct() {
+                    Some(s) => s.children.iter().map(|m| format_value(&m.ty)).collect(),
+                    None => vec![],
+                })
+                .collect::<Vec<_>>();
+
+            let headers = headers.iter().map(String::as_str).collect::<Vec<_>>();
+            print_rows(format, &headers, rows)?;
         }
     }
 
     Ok(())
 }
+
+/// Formats the value of a deserialized type.
+pub(crate) fn format_value(ty: &Ty) -> String {
+    match ty {
+        Ty::Primitive(p) => p.to_sql_value().unwrap_or_default(),
+        Ty::Enum(e) => e.option().unwrap_or_default(),
+        Ty::Struct(s) => format!(
+            "{{ {} }}",
+            s.children
+                .iter()
+                .map(|m| format!("{}: {}", m.name, format_value(&m.ty)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Ty::Tuple(tys) => {
+            format!("({})", tys.iter().map(format_value).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+fn print_rows(format: OutputFormat, headers: &[&str], rows: Vec<Vec<String>>) -> Result<()> {
+    match format {
+        OutputFormat::Table => {
+            let mut widths = headers.iter().map(|h| h.len()).collect::<Vec<_>>();
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+
+            let print_row = |cells: Vec<&str>| {
+                let line = cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{cell:<width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ");
+                println!("{}", line.trim_end());
+            };
+
+            print_row(headers.to_vec());
+            for row in &rows {
+                print_row(row.iter().map(String::as_str).collect());
+            }
+        }
+
+        OutputFormat::Json => {
+            let objects = rows
+                .into_iter()
+                .map(|row| {
+                    let object = headers
+                        .iter()
+                        .zip(row)
+                        .map(|(header, cell)| (header.to_string(), Value::String(cell)))
+                        .collect::<Map<_, _>>();
+                    Value::Object(object)
+                })
+                .collect::<Vec<_>>();
+
+            println!("{}", serde_json::to_string_pretty(&objects)?);
+        }
+
+        OutputFormat::Csv => {
+            println!("{}", headers.iter().map(|h| csv_field(h)).collect::<Vec<_>>().join(","));
+            for row in rows {
+                println!("{}", row.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(","));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,339 @@
+//! Project scaffolding from the templates embedded in the binary, so that `sozo init` and
+//! `sozo new` work without git or network access.
+//!
+//! Templates are plain files whose `{{name}}` placeholders are replaced when rendered.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Context, Result};
+
+macro_rules! template_file {
+    ($path:literal, $template:literal) => {
+        ($path, include_str!(concat!("../../templates/", $template)))
+    };
+}
+
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The files of the project, by path relative to its root.
+    files: &'static [(&'static str, &'static str)],
+}
+
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "minimal",
+        description: "A counter model and the system incrementing it",
+        files: &[
+            template_file!(".gitignore", "gitignore.tmpl"),
+            template_file!("Scarb.toml", "minimal/Scarb.toml.tmpl"),
+            template_file!("src/lib.cairo", "minimal/src/lib.cairo.tmpl"),
+            template_file!("src/models.cairo", "minimal/src/models.cairo.tmpl"),
+            template_file!("src/systems.cairo", "minimal/src/systems.cairo.tmpl"),
+        ],
+    },
+    Template {
+        name: "ecs",
+        description: "A game where players spawn and move on a grid",
+        files: &[
+            template_file!(".gitignore", "gitignore.tmpl"),
+            template_file!("Scarb.toml", "ecs/Scarb.toml.tmpl"),
+            template_file!("src/lib.cairo", "ecs/src/lib.cairo.tmpl"),
+            template_file!("src/models.cairo", "ecs/src/models.cairo.tmpl"),
+            template_file!("src/actions.cairo", "ecs/src/actions.cairo.tmpl"),
+            template_file!("src/utils.cairo", "ecs/src/utils.cairo.tmpl"),
+        ],
+    },
+    Template {
+        name: "messaging",
+        description: "A contract exchanging messages with L1 through Katana",
+        files: &[
+            template_file!(".gitignore", "gitignore.tmpl"),
+            template_file!("Scarb.toml", "messaging/Scarb.toml.tmpl"),
+            template_file!("src/lib.cairo", "messaging/src/lib.cairo.tmpl"),
+            template_file!("src/models.cairo", "messaging/src/models.cairo.tmpl"),
+            template_file!("src/messaging.cairo", "messaging/src/messaging.cairo.tmpl"),
+        ],
+    },
+];
+
+const MODEL_TEMPLATE: &str = include_str!("../../templates/new/model.cairo.tmpl");
+const SYSTEM_TEMPLATE: &str = include_str!("../../templates/new/system.cairo.tmpl");
+
+pub fn find_template(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|template| template.name == name)
+}
+
+impl Template {
+    /// Writes the files of the template to `dir`, for a package and a World of the given names.
+    pub fn write(&self, dir: &Path, package_name: &str, world_name: &str) -> Result<()> {
+        let vars = [
+            ("package_name", package_name),
+            ("world_name", world_name),
+            ("dojo_version", env!("CARGO_PKG_VERSION")),
+        ];
+
+        for (path, content) in self.files {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, render(content, &vars))
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the `{{name}}` placeholders of a template with their values.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter().fold(template.to_string(), |content, (name, value)| {
+        content.replace(&format!("{{{{{name}}}}}"), value)
+    })
+}
+
+/// Returns a valid package name derived from the name of the project directory.
+pub fn package_name(dir_name: &str) -> String {
+    let name = snake_case(dir_name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect::<String>();
+    let name = name.trim_start_matches(|c: char| c == '_' || c.is_ascii_digit());
+
+    if name.is_empty() {
+        "dojo_project".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Adds a model to the package whose sources are in `src_dir`, in the `models` module. Returns
+/// the path of the new file.
+pub fn new_model(src_dir: &Path, name: &str) -> Result<PathBuf> {
+    let module_name = identifier(name)?;
+    let model_name = pascal_case(&module_name);
+    let vars = [("model_name", model_name.as_str()), ("module_name", module_name.as_str())];
+    let content = render(MODEL_TEMPLATE, &vars);
+
+    add_module(src_dir, "models", &module_name, &content)
+}
+
+/// Adds a system to the package whose sources are in `src_dir`, in the `systems` module. Returns
+/// the path of the new file.
+pub fn new_system(src_dir: &Path, name: &str) -> Result<PathBuf> {
+    let module_name = identifier(name)?;
+    let system_trait = pascal_case(&module_name);
+    let vars = [("system_trait", system_trait.as_str()), ("module_name", module_name.as_str())];
+    let content = render(SYSTEM_TEMPLATE, &vars);
+
+    add_module(src_dir, "systems", &module_name, &content)
+}
+
+/// Writes `src/<parent>/<module>.cairo` and declares it in `src/<parent>.cairo`, itself declared
+/// in `src/lib.cairo` if it's new.
+fn add_module(src_dir: &Path, parent: &str, module: &str, content: &str) -> Result<PathBuf> {
+    let path = src_dir.join(parent).join(format!("{module}.cairo"));
+    if path.exists() {
+        bail!("{} already exists.", path.display());
+    }
+
+    let parent_path = src_dir.join(format!("{parent}.cairo"));
+    if !parent_path.exists() {
+        declare_module(&src_dir.join("lib.cairo"), parent)?;
+    }
+    declare_module(&parent_path, module)?;
+
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Declares `module` after the other module declarations of the Cairo file at `path`, which is
+/// created if missing.
+fn declare_module(path: &Path, module: &str) -> Result<()> {
+    let content = if path.exists() {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let declaration = format!("mod {module};");
+    let mut lines = content.lines().collect::<Vec<_>>();
+    if lines.iter().any(|line| line.trim() == declaration) {
+        return Ok(());
+    }
+
+    let is_declaration = |line: &&str| line.starts_with("mod ") && line.ends_with(';');
+    match lines.iter().rposition(is_declaration) {
+        Some(i) => lines.insert(i + 1, &declaration),
+        None if lines.is_empty() => lines.push(&declaration),
+        None => {
+            lines.insert(0, "");
+            lines.insert(0, &declaration);
+        }
+    }
+
+    fs::write(path, lines.join("\n") + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Returns the snake case module name of a model or a system, given in any case.
+fn identifier(name: &str) -> Result<String> {
+    let module = snake_case(name);
+    ensure!(
+        module.starts_with(|c: char| c.is_ascii_lowercase())
+            && module.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        "`{name}` isn't a valid name, it must start with a letter and contain only letters, \
+         digits and underscores."
+    );
+
+    Ok(module)
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else if c == '-' || c == ' ' || c == '.' {
+            snake.push('_');
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
+
+fn pascal_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+                .collect::<String>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_test_utils::compiler::build_test_config;
+    use scarb::core::TargetKind;
+    use scarb::ops::{self, CompileOpts};
+
+    use super::*;
+
+    #[test]
+    fn render_templates() {
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        for template in TEMPLATES {
+            let root = dir.path().join(template.name);
+            template.write(&root, "my_game", "my_world").unwrap();
+
+            let manifest = fs::read_to_string(root.join("Scarb.toml")).unwrap();
+            assert!(manifest.contains("name = \"my_game\""));
+            assert!(manifest.contains("name = \"my_world\""));
+            assert!(manifest.contains(&format!("tag = \"v{}\"", env!("CARGO_PKG_VERSION"))));
+
+            for (path, _) in template.files {
+                let content = fs::read_to_string(root.join(path)).unwrap();
+                assert!(!content.contains("{{"), "{path} of {} isn't rendered", template.name);
+            }
+        }
+    }
+
+    #[test]
+    fn derive_names() {
+        assert_eq!(package_name("My-Game"), "my_game");
+        assert_eq!(package_name(".tmp3Xy"), "tmp3_xy");
+        assert_eq!(package_name("42"), "dojo_project");
+
+        assert_eq!(identifier("PlayerPosition").unwrap(), "player_position");
+        assert_eq!(identifier("player_position").unwrap(), "player_position");
+        assert_eq!(pascal_case("player_position"), "PlayerPosition");
+        assert!(identifier("1st").is_err());
+        assert!(identifier("moves!").is_err());
+    }
+
+    #[test]
+    fn add_model_and_system() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("lib.cairo"), "mod models;\n").unwrap();
+        fs::write(src.join("models.cairo"), "use starknet::ContractAddress;\n").unwrap();
+
+        let path = new_model(&src, "PlayerPosition").unwrap();
+        assert_eq!(path, src.join("models/player_position.cairo"));
+        let model = fs::read_to_string(&path).unwrap();
+        assert!(model.contains("struct PlayerPosition {"));
+        assert!(model.contains("use super::{player_position, PlayerPosition};"));
+        assert_eq!(
+            fs::read_to_string(src.join("models.cairo")).unwrap(),
+            "mod player_position;\n\nuse starknet::ContractAddress;\n"
+        );
+
+        new_model(&src, "Moves").unwrap();
+        assert!(fs::read_to_string(src.join("models.cairo"))
+            .unwrap()
+            .starts_with("mod player_position;\nmod moves;\n"));
+        assert!(new_model(&src, "moves").is_err());
+
+        let path = new_system(&src, "actions").unwrap();
+        assert!(fs::read_to_string(path).unwrap().contains("impl ActionsImpl of IActions"));
+        assert_eq!(
+            fs::read_to_string(src.join("lib.cairo")).unwrap(),
+            "mod models;\nmod systems;\n"
+        );
+        assert_eq!(fs::read_to_string(src.join("systems.cairo")).unwrap(), "mod actions;\n");
+    }
+
+    #[test]
+    fn templates_compile() {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let dojo_core = Path::new(env!("CARGO_MANIFEST_DIR")).join("../dojo-core");
+        let dojo_core = dojo_core.canonicalize().unwrap();
+
+        for template in TEMPLATES {
+            let root = dir.path().join(template.name);
+            template.write(&root, "my_game", "my_world").unwrap();
+            new_model(&root.join("src"), "PlayerScore").unwrap();
+            new_system(&root.join("src"), "rewards").unwrap();
+
+            // Builds against the dojo-core of this tree rather than the released tag.
+            let manifest_path = root.join("Scarb.toml");
+            let manifest = fs::read_to_string(&manifest_path).unwrap();
+            let dependency = format!(
+                "dojo = {{ git = \"https://github.com/dojoengine/dojo\", tag = \"v{}\" }}",
+                env!("CARGO_PKG_VERSION")
+            );
+            assert!(manifest.contains(&dependency));
+            let manifest = manifest
+                .replace(&dependency, &format!("dojo = {{ path = {:?} }}", dojo_core.display()));
+            fs::write(&manifest_path, manifest).unwrap();
+
+            let config = build_test_config(manifest_path.to_str().unwrap()).unwrap();
+            let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+            let packages = ws.members().map(|p| p.id).collect();
+            ops::compile(
+                packages,
+                CompileOpts { include_targets: vec![], exclude_targets: vec![TargetKind::TEST] },
+                &ws,
+            )
+            .unwrap_or_else(|err| panic!("{} doesn't compile: {err:?}", template.name));
+        }
+    }
+}
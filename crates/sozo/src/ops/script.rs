@@ -0,0 +1,444 @@
+//! Scripts running a sequence of steps against a World.
+//!
+//! ```toml
+//! [vars]
+//! player = "0x517ececd29116499f4a1b64b094da79ba08dfd54a3edaa316134c41f8160973"
+//!
+//! [[steps]]
+//! name = "spawn"
+//! execute = [
+//!     { contract = "actions", entrypoint = "spawn" },
+//!     { contract = "actions", entrypoint = "move", args = "Direction::Left" },
+//! ]
+//! capture = { direction = "Moved.direction" }
+//!
+//! [[steps]]
+//! name = "check position"
+//! assert = { model = "Position", keys = ["${player}"], values = { "vec.x" = "9" } }
+//! ```
+//!
+//! The invokes and permission updates of a step are sent in a single multicall. Values can be
+//! captured from the events emitted by a step, or from the output of its call, and used by the
+//! next steps with `${name}`.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use dojo_types::schema::Ty;
+use dojo_world::contracts::calldata::{short_name, Value};
+use dojo_world::contracts::events::decode_event;
+use dojo_world::contracts::WorldContractReader;
+use dojo_world::manifest::Manifest;
+use dojo_world::metadata::{self, Environment};
+use dojo_world::migration::auth::AuthUpdate;
+use serde::Deserialize;
+use starknet::accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount};
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use starknet::signers::LocalWallet;
+
+use super::call::call_entrypoint;
+use super::execute::{
+    contract_address, find_contract, manifest_abis, prepare_call, wait_for_events,
+};
+use super::model::format_value;
+use crate::commands::options::account::AccountOptions;
+use crate::commands::options::starknet::StarknetOptions;
+use crate::commands::options::world::WorldOptions;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    #[serde(default)]
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub name: Option<String>,
+    /// Invokes sent in a single multicall, along with the permission updates.
+    #[serde(default)]
+    pub execute: Vec<Invoke>,
+    #[serde(default)]
+    pub auth: Vec<AuthAction>,
+    pub call: Option<ViewCall>,
+    pub assert: Option<ModelAssert>,
+    /// Variables to set, from either `<Event>.<member>` of an emitted event, `output` or
+    /// `output.<index>` of the call.
+    #[serde(default)]
+    pub capture: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Invoke {
+    pub contract: String,
+    pub entrypoint: String,
+    pub args: Option<String>,
+    #[serde(default)]
+    pub calldata: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AuthAction {
+    GrantWriter { model: String, contract: String },
+    RevokeWriter { model: String, contract: String },
+    GrantOwner { resource: String, owner: String },
+    RevokeOwner { resource: String, owner: String },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ViewCall {
+    pub contract: String,
+    pub entrypoint: String,
+    pub args: Option<String>,
+    #[serde(default)]
+    pub calldata: Vec<String>,
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// The expected outputs, written as displayed by `sozo call`.
+    pub expect: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelAssert {
+    pub model: String,
+    pub keys: Vec<String>,
+    /// The expected values of the entity by member path, e.g. `vec.x`.
+    pub values: HashMap<String, String>,
+}
+
+/// What a step produced, from which variables are captured.
+#[derive(Debug, Default)]
+struct StepOutput {
+    events: Vec<Value>,
+    call: Vec<String>,
+}
+
+pub async fn run(
+    script: Script,
+    world: WorldOptions,
+    starknet: StarknetOptions,
+    account: AccountOptions,
+    env_metadata: Option<Environment>,
+    manifest: Option<Manifest>,
+) -> Result<()> {
+    let provider = starknet.provider(env_metadata.as_ref())?;
+    let account = account.account(provider, env_metadata.as_ref()).await?;
+
+    let runner = Runner {
+        manifest: manifest.as_ref(),
+        world: &world,
+        env_metadata: env_metadata.as_ref(),
+        account: &account,
+    };
+
+    let mut vars = script.vars;
+    for (i, step) in script.steps.iter().enumerate() {
+        let name = step.name.clone().unwrap_or_else(|| format!("#{}", i + 1));
+        println!("▶ {name}");
+
+        runner.run_step(step, &mut vars).await.with_context(|| format!("Step `{name}` failed"))?;
+    }
+
+    Ok(())
+}
+
+struct Runner<'a> {
+    manifest: Option<&'a Manifest>,
+    world: &'a WorldOptions,
+    env_metadata: Option<&'a Environment>,
+    account: &'a SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
+}
+
+impl<'a> Runner<'a> {
+    async fn run_step(&self, step: &Step, vars: &mut HashMap<String, String>) -> Result<()> {
+        let provider = self.account.provider();
+        let mut output = StepOutput::default();
+
+        let mut calls = vec![];
+        for invoke in &step.execute {
+            let args = invoke.args.as_deref().map(|args| interpolate(args, vars)).transpose()?;
+            calls.push(
+                prepare_call(
+                    self.manifest,
+                    &interpolate(&invoke.contract, vars)?,
+                    &invoke.entrypoint,
+                    parse_felts(&invoke.calldata, vars)?,
+                    args.as_deref(),
+                    self.world,
+                    self.env_metadata,
+                    provider,
+                )
+                .await?,
+            );
+        }
+        for action in &step.auth {
+            calls.push(self.auth_call(action, vars).await?);
+        }
+
+        if !calls.is_empty() {
+            let res = self
+                .account
+                .execute(calls)
+                .send()
+                .await
+                .with_context(|| "Failed to send transaction")?;
+            println!("  Transaction: {:#x}", res.transaction_hash);
+
+            let abis = self.manifest.map(manifest_abis).unwrap_or_default();
+            for event in wait_for_events(provider, res.transaction_hash).await? {
+                if let Some(event) = decode_event(&abis, &event.keys, &event.data) {
+                    println!("  {event}");
+                    output.events.push(event);
+                }
+            }
+        }
+
+        if let Some(call) = &step.call {
+            let args = call.args.as_deref().map(|args| interpolate(args, vars)).transpose()?;
+            output.call = call_entrypoint(
+                self.manifest,
+                &interpolate(&call.contract, vars)?,
+                &call.entrypoint,
+                parse_felts(&call.calldata, vars)?,
+                args.as_deref(),
+                &parse_felts(&call.keys, vars)?,
+                BlockId::Tag(BlockTag::Pending),
+                self.world,
+                self.env_metadata,
+                provider,
+            )
+            .await?;
+            println!("  {}: {}", call.entrypoint, output.call.join(", "));
+
+            if let Some(expected) = &call.expect {
+                let expected =
+                    expected.iter().map(|e| interpolate(e, vars)).collect::<Result<Vec<_>>>()?;
+
+                if expected.len() != output.call.len()
+                    || expected.iter().zip(&output.call).any(|(e, o)| !values_eq(e, o))
+                {
+                    bail!(
+                        "`{}` returned ({}), expected ({})",
+                        call.entrypoint,
+                        output.call.join(", "),
+                        expected.join(", ")
+                    );
+                }
+            }
+        }
+
+        if let Some(assert) = &step.assert {
+            let world_address = self.world.address(self.env_metadata)?;
+            let world = WorldContractReader::new(world_address, provider)
+                .with_block(BlockId::Tag(BlockTag::Pending));
+
+            let keys = parse_felts(&assert.keys, vars)?;
+            let entity = world.model(&assert.model).await?.entity(&keys).await?;
+
+            for (path, expected) in &assert.values {
+                let expected = interpolate(expected, vars)?;
+                let actual = member(&entity, path)
+                    .map(format_value)
+                    .ok_or_else(|| anyhow!("Model `{}` has no member `{path}`", assert.model))?;
+
+                if !values_eq(&expected, &actual) {
+                    bail!("`{}.{path}` is {actual}, expected {expected}", assert.model);
+                }
+            }
+            println!("  {} matches", assert.model);
+        }
+
+        for (var, source) in &step.capture {
+            vars.insert(var.clone(), capture(source, &output)?);
+        }
+
+        Ok(())
+    }
+
+    async fn auth_call(&self, action: &AuthAction, vars: &HashMap<String, String>) -> Result<Call> {
+        let world_address = self.world.address(self.env_metadata)?;
+
+        let update = match action {
+            AuthAction::GrantWriter { model, contract } => AuthUpdate::GrantWriter {
+                model: interpolate(model, vars)?,
+                contract: self.resolve_contract(&interpolate(contract, vars)?).await?,
+            },
+            AuthAction::RevokeWriter { model, contract } => AuthUpdate::RevokeWriter {
+                model: interpolate(model, vars)?,
+                contract: self.resolve_contract(&interpolate(contract, vars)?).await?,
+            },
+            AuthAction::GrantOwner { resource, owner } => AuthUpdate::GrantOwner {
+                resource: interpolate(resource, vars)?,
+                address: FieldElement::from_str(&interpolate(owner, vars)?)?,
+            },
+            AuthAction::RevokeOwner { resource, owner } => AuthUpdate::RevokeOwner {
+                resource: interpolate(resource, vars)?,
+                address: FieldElement::from_str(&interpolate(owner, vars)?)?,
+            },
+        };
+
+        Ok(update.call(world_address)?)
+    }
+
+    async fn resolve_contract(&self, contract: &str) -> Result<FieldElement> {
+        let local_contract = self.manifest.and_then(|m| find_contract(m, contract));
+        contract_address(
+            contract,
+            local_contract,
+            self.world,
+            self.env_metadata,
+            self.account.provider(),
+        )
+        .await
+    }
+}
+
+/// Replaces the `${name}` variables of `input` by their value.
+fn interpolate(input: &str, vars: &HashMap<String, String>) -> Result<String> {
+    metadata::interpolate(input, |name| {
+        vars.get(name).cloned().ok_or_else(|| anyhow!("Undefined variable `{name}`"))
+    })
+}
+
+fn parse_felts(values: &[String], vars: &HashMap<String, String>) -> Result<Vec<FieldElement>> {
+    values
+        .iter()
+        .map(|value| {
+            let value = interpolate(value, vars)?;
+            FieldElement::from_str(&value).map_err(|_| anyhow!("Invalid felt `{value}`"))
+        })
+        .collect()
+}
+
+/// Compares two displayed values, as felts if both are numbers.
+fn values_eq(a: &str, b: &str) -> bool {
+    match (FieldElement::from_str(a), FieldElement::from_str(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Returns the member of an entity at the dotted `path`.
+fn member<'a>(ty: &'a Ty, path: &str) -> Option<&'a Ty> {
+    path.split('.').try_fold(ty, |ty, name| {
+        ty.as_struct()?.children.iter().find(|m| m.name == name).map(|m| &m.ty)
+    })
+}
+
+fn capture(source: &str, output: &StepOutput) -> Result<String> {
+    let (head, path) = source.split_once('.').unwrap_or((source, ""));
+
+    if head == "output" {
+        if path.is_empty() {
+            return Ok(output.call.join(", "));
+        }
+
+        return path
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| output.call.get(index))
+            .cloned()
+            .ok_or_else(|| anyhow!("No call output `{source}`"));
+    }
+
+    output
+        .events
+        .iter()
+        .filter(|event| matches!(event, Value::Struct { name, .. } if short_name(name) == head))
+        .find_map(|event| {
+            path.split('.').filter(|name| !name.is_empty()).try_fold(event, |value, name| {
+                match value {
+                    Value::Struct { members, .. } => {
+                        members.iter().find(|(member, _)| member == name).map(|(_, v)| v)
+                    }
+                    _ => None,
+                }
+            })
+        })
+        .map(|value| value.to_string())
+        .ok_or_else(|| anyhow!("No event value `{source}` to capture"))
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn parse_script() {
+        let script: Script = toml::from_str(
+            r#"
+            [vars]
+            player = "0x1"
+
+            [[steps]]
+            name = "spawn"
+            execute = [{ contract = "actions", entrypoint = "spawn" }]
+            auth = [{ action = "grant_writer", model = "Position", contract = "actions" }]
+            capture = { direction = "Moved.direction" }
+
+            [[steps]]
+            assert = { model = "Position", keys = ["${player}"], values = { "vec.x" = "10" } }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(script.steps.len(), 2);
+        assert!(matches!(
+            &script.steps[0].auth[0],
+            AuthAction::GrantWriter { model, contract } if model == "Position" && contract == "actions"
+        ));
+        assert_eq!(script.steps[1].assert.as_ref().unwrap().values["vec.x"], "10");
+
+        assert!(toml::from_str::<Script>("[[steps]]\nexecut = []").is_err());
+    }
+
+    #[test]
+    fn interpolate_variables() {
+        let vars = HashMap::from([("player".to_string(), "0x1".to_string())]);
+
+        assert_eq!(interpolate("${player}, 2", &vars).unwrap(), "0x1, 2");
+        assert_eq!(parse_felts(&["${player}".to_string()], &vars).unwrap(), vec![felt!("0x1")]);
+        assert!(interpolate("${unknown}", &vars).is_err());
+        assert!(interpolate("${player", &vars).is_err());
+    }
+
+    #[test]
+    fn capture_from_events_and_outputs() {
+        let output = StepOutput {
+            events: vec![Value::Struct {
+                name: "dojo_examples::actions::actions::Moved".into(),
+                members: vec![
+                    ("player".into(), Value::Felt(felt!("0x1"))),
+                    (
+                        "direction".into(),
+                        Value::Enum {
+                            name: "dojo_examples::models::Direction".into(),
+                            variant: "Left".into(),
+                            payload: None,
+                        },
+                    ),
+                ],
+            }],
+            call: vec!["3".into(), "0x4".into()],
+        };
+
+        assert_eq!(capture("Moved.player", &output).unwrap(), "0x1");
+        assert_eq!(capture("Moved.direction", &output).unwrap(), "Direction::Left");
+        assert_eq!(capture("output.1", &output).unwrap(), "0x4");
+        assert_eq!(capture("output", &output).unwrap(), "3, 0x4");
+        assert!(capture("Spawned.player", &output).is_err());
+        assert!(values_eq("10", "0xa"));
+    }
+}
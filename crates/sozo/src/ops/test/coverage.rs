@@ -0,0 +1,141 @@
+//! Writes the coverage of a test run as LCOV.
+//!
+//! Cairo 2.3 doesn't map the Sierra statements back to the source, so the coverage is collected per
+//! function: every line of a function is hit as many times as the function was called, the body of
+//! a loop being a function of its own. Functions generated by plugins, such as the code of
+//! `#[dojo::contract]` and `#[derive(Model)]`, are mapped back to the code they were generated
+//! from, the way their diagnostics are.
+
+use std::collections::BTreeMap;
+
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_defs::ids::LanguageElementId;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::{CrateId, FileId, FileLongId, VirtualFile};
+use cairo_lang_filesystem::span::TextSpan;
+use cairo_lang_lowering::db::LoweringGroup;
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_sierra::ids::FunctionId;
+use cairo_lang_sierra_generator::db::SierraGenGroup;
+use cairo_lang_utils::Upcast;
+
+/// The coverage of a source file.
+#[derive(Default)]
+struct FileCoverage {
+    /// The first line, name and hits of every function.
+    functions: Vec<(usize, String, usize)>,
+    /// The hits of every line, with the number of lines of the functions they are taken from.
+    lines: BTreeMap<usize, (usize, usize)>,
+}
+
+impl FileCoverage {
+    fn add(&mut self, name: String, (start, end): (usize, usize), hits: usize) {
+        self.functions.push((start, name, hits));
+
+        // A line takes the hits of the innermost function it belongs to. The functions mapped to
+        // the same lines, like the ones generated for an item, add up.
+        let size = end - start;
+        for line in start..=end {
+            match self.lines.get_mut(&line) {
+                Some((existing, total)) if *existing == size => *total += hits,
+                Some((existing, _)) if *existing < size => {}
+                _ => {
+                    self.lines.insert(line, (size, hits));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the LCOV records of the functions of `crate_ids`, given the number of times every
+/// function of the Sierra program was called.
+pub fn lcov(
+    db: &RootDatabase,
+    crate_ids: &[CrateId],
+    function_hits: &[(FunctionId, usize)],
+) -> String {
+    let mut files = BTreeMap::<String, FileCoverage>::new();
+    for (function, hits) in function_hits {
+        let Some((path, lines)) = function_lines(db, crate_ids, function) else {
+            continue;
+        };
+        files.entry(path).or_default().add(function.to_string(), lines, *hits);
+    }
+
+    let mut lcov = String::new();
+    for (path, coverage) in files {
+        lcov.push_str(&format!("TN:\nSF:{path}\n"));
+        for (line, name, _) in &coverage.functions {
+            lcov.push_str(&format!("FN:{line},{name}\n"));
+        }
+        for (_, name, hits) in &coverage.functions {
+            lcov.push_str(&format!("FNDA:{hits},{name}\n"));
+        }
+        let functions_hit = coverage.functions.iter().filter(|(_, _, hits)| *hits > 0).count();
+        lcov.push_str(&format!("FNF:{}\nFNH:{functions_hit}\n", coverage.functions.len()));
+
+        for (line, (_, hits)) in &coverage.lines {
+            lcov.push_str(&format!("DA:{line},{hits}\n"));
+        }
+        let lines_hit = coverage.lines.values().filter(|(_, hits)| *hits > 0).count();
+        lcov.push_str(&format!("LF:{}\nLH:{lines_hit}\n", coverage.lines.len()));
+        lcov.push_str("end_of_record\n");
+    }
+
+    lcov
+}
+
+/// Returns the source file of a function of `crate_ids`, and the first and last lines of its code.
+fn function_lines(
+    db: &RootDatabase,
+    crate_ids: &[CrateId],
+    function: &FunctionId,
+) -> Option<(String, (usize, usize))> {
+    let files_db: &dyn FilesGroup = db.upcast();
+    let defs_db: &dyn DefsGroup = db.upcast();
+    let semantic_db: &dyn SemanticGroup = db.upcast();
+    let lowering_db: &dyn LoweringGroup = db.upcast();
+
+    let body = db.lookup_intern_sierra_function(function.clone()).body(lowering_db).ok()??;
+    let semantic_function =
+        body.base_semantic_function(lowering_db).function_with_body_id(semantic_db);
+    if !crate_ids.contains(&semantic_function.parent_module(defs_db).owning_crate(defs_db)) {
+        return None;
+    }
+
+    let location = body.stable_location(lowering_db).ok()?.diagnostic_location(defs_db);
+    let (file, span) = user_span(files_db, location.file_id, location.span)?;
+    let start = span.start.position_in_file(files_db, file)?.line + 1;
+    let end = span.end.position_in_file(files_db, file)?.line + 1;
+
+    Some((file.full_path(files_db), (start, end)))
+}
+
+/// Maps a span of a file generated by a plugin back to the file on disk it was generated from.
+/// Generated code that wasn't copied from the source is attributed to the closest source node
+/// copied before it.
+fn user_span(
+    db: &dyn FilesGroup,
+    mut file: FileId,
+    mut span: TextSpan,
+) -> Option<(FileId, TextSpan)> {
+    while let FileLongId::Virtual(VirtualFile {
+        parent: Some(parent), diagnostics_mappings, ..
+    }) = db.lookup_intern_file(file)
+    {
+        span = diagnostics_mappings.iter().find_map(|mapping| mapping.translate(span)).or_else(
+            || {
+                diagnostics_mappings
+                    .iter()
+                    .filter(|mapping| mapping.span.start <= span.start)
+                    .max_by_key(|mapping| mapping.span.start)
+                    .or_else(|| diagnostics_mappings.first())
+                    .and_then(|mapping| mapping.translate(mapping.span))
+            },
+        )?;
+        file = parent;
+    }
+
+    matches!(db.lookup_intern_file(file), FileLongId::OnDisk(_)).then_some((file, span))
+}
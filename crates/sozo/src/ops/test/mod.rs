@@ -0,0 +1,406 @@
+//! Runs the tests of a compiled Dojo project.
+//!
+//! The tests are run and reported like `cairo-lang-test-runner` does, but on VMs kept by
+//! [`runner`], so that the same run also measures the resources of every test and of every contract
+//! entrypoint it calls, and the functions it executes.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use cairo_lang_runner::short_string::as_cairo_short_string;
+use cairo_lang_runner::RunResultValue;
+use cairo_lang_sierra::ids::FunctionId;
+use cairo_lang_test_plugin::test_config::{PanicExpectation, TestExpectation};
+use cairo_lang_test_plugin::{TestCompilation, TestConfig};
+use cairo_lang_test_runner::{filter_test_cases, TestRunConfig};
+use console::Style;
+use rayon::prelude::*;
+
+use self::runner::{TestRunner, Usage};
+
+mod coverage;
+mod runner;
+
+pub use coverage::lcov;
+
+/// The execution resources of a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resources {
+    pub steps: usize,
+    /// The instances used of every builtin, by name.
+    pub builtins: BTreeMap<String, usize>,
+    /// The L1 gas paid to publish the storage and classes written by the run.
+    pub l1_gas: usize,
+}
+
+impl Resources {
+    fn add(&mut self, other: &Resources) {
+        self.steps += other.steps;
+        self.l1_gas += other.l1_gas;
+        for (name, count) in &other.builtins {
+            *self.builtins.entry(name.clone()).or_default() += count;
+        }
+    }
+
+    fn builtins_summary(&self, calls: usize) -> String {
+        self.builtins
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(name, count)| {
+                format!("{}: {}", name.trim_end_matches("_builtin"), count / calls)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// The calls made to an entrypoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntrypointUsage {
+    pub calls: usize,
+    /// The resources of all the calls, including the contracts they called.
+    pub resources: Resources,
+}
+
+impl EntrypointUsage {
+    fn add(&mut self, resources: &Resources) {
+        self.calls += 1;
+        self.resources.add(resources);
+    }
+
+    fn merge(&mut self, other: &EntrypointUsage) {
+        self.calls += other.calls;
+        self.resources.add(&other.resources);
+    }
+}
+
+/// The resources used by a single test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestUsage {
+    pub name: String,
+    /// Only available for the tests with an `available_gas` attribute.
+    pub gas_used: Option<i64>,
+    /// The resources of the test, including the contracts it called.
+    pub resources: Resources,
+}
+
+/// The outcome of a test run.
+#[derive(Debug, Default)]
+pub struct TestReport {
+    pub passed: Vec<String>,
+    pub failed: Vec<(String, RunResultValue)>,
+    pub ignored: Vec<String>,
+    pub filtered_out: usize,
+    pub tests: Vec<TestUsage>,
+    /// The calls made to every entrypoint, by name.
+    pub entrypoints: BTreeMap<String, EntrypointUsage>,
+    /// The number of times every function of the Sierra program was called.
+    pub function_hits: Vec<(FunctionId, usize)>,
+}
+
+impl TestReport {
+    /// Prints the summary of the run, and the failed tests.
+    pub fn print_summary(&self) {
+        if self.failed.is_empty() {
+            println!(
+                "test result: {}. {} passed; {} failed; {} ignored; {} filtered out;",
+                Style::new().green().apply_to("ok"),
+                self.passed.len(),
+                self.failed.len(),
+                self.ignored.len(),
+                self.filtered_out
+            );
+            return;
+        }
+
+        println!("failures:");
+        for (name, value) in &self.failed {
+            print!("   {name} - ");
+            match value {
+                RunResultValue::Success(_) => println!("expected panic but finished successfully."),
+                RunResultValue::Panic(values) => {
+                    print!("panicked with [");
+                    for value in values {
+                        match as_cairo_short_string(value) {
+                            Some(as_string) => print!("{value} ('{as_string}'), "),
+                            None => print!("{value}, "),
+                        }
+                    }
+                    println!("].")
+                }
+            }
+        }
+        println!();
+    }
+
+    /// Fails if any test failed.
+    pub fn check(&self) -> Result<()> {
+        if !self.failed.is_empty() {
+            bail!(
+                "test result: {}. {} passed; {} failed; {} ignored",
+                Style::new().red().apply_to("FAILED"),
+                self.passed.len(),
+                self.failed.len(),
+                self.ignored.len()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn print_gas_report(&self) {
+        println!("\n{}", Style::new().bold().apply_to("Resources per test"));
+        println!("{:<70} {:>12} {:>12} {:>12}  Builtins", "Test", "Gas", "Steps", "L1 gas");
+        for test in &self.tests {
+            let gas = test.gas_used.map(|gas| gas.to_string()).unwrap_or("-".to_string());
+            println!(
+                "{:<70} {:>12} {:>12} {:>12}  {}",
+                test.name,
+                gas,
+                test.resources.steps,
+                test.resources.l1_gas,
+                test.resources.builtins_summary(1)
+            );
+        }
+
+        if self.entrypoints.is_empty() {
+            return;
+        }
+
+        println!("\n{}", Style::new().bold().apply_to("Resources per entrypoint call (average)"));
+        println!("{:<70} {:>12} {:>12} {:>12}  Builtins", "Entrypoint", "Calls", "Steps", "L1 gas");
+        for (name, usage) in &self.entrypoints {
+            println!(
+                "{:<70} {:>12} {:>12} {:>12}  {}",
+                name,
+                usage.calls,
+                usage.resources.steps / usage.calls,
+                usage.resources.l1_gas / usage.calls,
+                usage.resources.builtins_summary(usage.calls)
+            );
+        }
+    }
+}
+
+/// A test that was run.
+struct TestRun {
+    value: RunResultValue,
+    gas_used: Option<i64>,
+    /// The gas printed with the outcome, estimated from the function costs without a gas limit.
+    gas_estimate: Option<i64>,
+    resources: Resources,
+    usage: Usage,
+}
+
+/// Runs the compiled tests that match the run configuration, printing their outcome.
+pub fn run_tests(compiled: TestCompilation, config: &TestRunConfig) -> Result<TestReport> {
+    let (compiled, filtered_out) =
+        filter_test_cases(compiled, config.include_ignored, config.ignored, config.filter.clone());
+    let runner = TestRunner::new(&compiled).context("failed to set up the test runner")?;
+
+    println!("running {} tests", compiled.named_tests.len());
+    let runs = compiled
+        .named_tests
+        .par_iter()
+        .map(|(name, test)| run_test(&runner, name, test))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut report = TestReport { filtered_out, ..Default::default() };
+    let mut hits = runner.usage().hits;
+
+    for ((name, test), run) in compiled.named_tests.iter().zip(runs) {
+        let Some(run) = run else {
+            println!("test {name} ... {}", Style::new().yellow().apply_to("ignored"));
+            report.ignored.push(name.clone());
+            continue;
+        };
+
+        let passed = match (&run.value, &test.expectation) {
+            (RunResultValue::Success(_), TestExpectation::Success) => true,
+            (RunResultValue::Panic(value), TestExpectation::Panics(expectation)) => {
+                !matches!(expectation, PanicExpectation::Exact(expected) if value != expected)
+            }
+            _ => false,
+        };
+
+        let status = if passed {
+            Style::new().green().apply_to("ok")
+        } else {
+            Style::new().red().apply_to("fail")
+        };
+        match run.gas_estimate {
+            Some(gas) => println!("test {name} ... {status} (gas usage est.: {gas})"),
+            None => println!("test {name} ... {status}"),
+        }
+
+        if passed {
+            report.passed.push(name.clone());
+        } else {
+            report.failed.push((name.clone(), run.value));
+        }
+
+        report.tests.push(TestUsage {
+            name: name.clone(),
+            gas_used: run.gas_used,
+            resources: run.resources,
+        });
+        for (name, usage) in &run.usage.entrypoints {
+            report.entrypoints.entry(name.clone()).or_default().merge(usage);
+        }
+        for (total, count) in hits.iter_mut().zip(&run.usage.hits) {
+            *total += count;
+        }
+    }
+
+    report.function_hits = runner.function_hits(&hits);
+    Ok(report)
+}
+
+/// Runs a single test, unless it's ignored.
+fn run_test(runner: &TestRunner<'_>, name: &str, test: &TestConfig) -> Result<Option<TestRun>> {
+    if test.ignored {
+        return Ok(None);
+    }
+
+    let func = runner.runner.find_function(name)?;
+    let mut usage = runner.usage();
+    let run = runner
+        .run(func, &[], test.available_gas, Default::default(), &mut usage)
+        .with_context(|| format!("failed to run the function `{name}`"))?;
+
+    let gas_used = test.available_gas.zip(run.gas_counter).and_then(|(available, remaining)| {
+        Some(i64::try_from(available).ok()? - i64::try_from(remaining.to_bigint()).ok()?)
+    });
+    let gas_estimate = gas_used.or_else(|| {
+        runner.runner.initial_required_gas(func).and_then(|gas| i64::try_from(gas).ok())
+    });
+
+    Ok(Some(TestRun { value: run.value, gas_used, gas_estimate, resources: run.resources, usage }))
+}
+
+#[cfg(test)]
+mod tests {
+    use dojo_test_utils::compiler::build_test_config;
+    use scarb::ops;
+
+    use super::*;
+    use crate::commands::test::build_test_compiler;
+
+    fn run_fixture() -> (TestReport, String) {
+        let config = build_test_config("./tests/test_data/gas_report/Scarb.toml").unwrap();
+        let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+        let resolve = ops::resolve_workspace(&ws).unwrap();
+        let unit = ops::generate_compilation_units(&resolve, &ws)
+            .unwrap()
+            .into_iter()
+            .find(|unit| unit.main_package_id.name.to_string() == "gas_report")
+            .unwrap();
+
+        let compiler = build_test_compiler(&unit).unwrap();
+        let run_config =
+            TestRunConfig { filter: String::new(), ignored: false, include_ignored: false };
+        let report = run_tests(compiler.build().unwrap(), &run_config).unwrap();
+        let lcov = lcov(&compiler.db, &compiler.test_crate_ids, &report.function_hits);
+
+        (report, lcov)
+    }
+
+    #[test]
+    fn resources_follow_the_executed_steps() {
+        let (report, _) = run_fixture();
+        assert!(report.failed.is_empty());
+
+        let test = |name: &str| {
+            let name = format!("gas_report::tests::{name}");
+            report.tests.iter().find(|test| test.name == name).unwrap().clone()
+        };
+
+        // Every iteration of the loop executes the same steps, so the gas grows linearly.
+        let (count_0, count_10, count_20) = (test("count_0"), test("count_10"), test("count_20"));
+        let gas = |test: &TestUsage| test.gas_used.unwrap();
+        assert!(gas(&count_0) > 0);
+        assert!(gas(&count_10) > gas(&count_0));
+        assert_eq!(gas(&count_20) - gas(&count_10), gas(&count_10) - gas(&count_0));
+
+        let steps = |test: &TestUsage| test.resources.steps;
+        assert!(steps(&count_10) > steps(&count_0));
+        assert_eq!(steps(&count_20) - steps(&count_10), steps(&count_10) - steps(&count_0));
+        assert_eq!(count_20.resources.l1_gas, 0);
+
+        assert_eq!(test("without_gas").gas_used, None);
+    }
+
+    #[test]
+    fn contract_calls_are_measured_per_entrypoint() {
+        let (report, _) = run_fixture();
+
+        let increment = &report.entrypoints["gas_report::counter::CounterImpl__increment"];
+        let get = &report.entrypoints["gas_report::counter::CounterImpl__get"];
+        assert_eq!(increment.calls, 2);
+        assert_eq!(get.calls, 1);
+        assert!(!report.entrypoints.contains_key("gas_report::counter::CounterImpl__reset"));
+
+        // Each increment writes a single slot of a single contract.
+        assert_eq!(increment.resources.l1_gas, 2 * (2 + 2) * 612);
+        assert_eq!(get.resources.l1_gas, 0);
+        assert!(increment.resources.builtins["range_check_builtin"] > 0);
+
+        // The test also deploys the contract, and its resources include the calls it makes.
+        let test = report
+            .tests
+            .iter()
+            .find(|test| test.name == "gas_report::tests::increment_counter")
+            .unwrap();
+        assert_eq!(test.resources.l1_gas, (2 + 1 + 2) * 612);
+        assert!(test.resources.steps > increment.resources.steps + get.resources.steps);
+    }
+
+    #[test]
+    fn dojo_contracts_and_models_are_measured() {
+        let (report, _) = run_fixture();
+        assert!(report.failed.is_empty());
+
+        // The system reads and writes the model through the World, which hashes its keys.
+        let add = &report.entrypoints["gas_report::scores::ScoresImpl__add"];
+        assert_eq!(add.calls, 2);
+        assert!(add.resources.l1_gas > 0);
+        assert!(add.resources.builtins["poseidon_builtin"] > 0);
+
+        // The World reads the name of the model from its class when registering it.
+        assert_eq!(report.entrypoints["gas_report::score::name"].calls, 1);
+
+        let test =
+            report.tests.iter().find(|test| test.name == "gas_report::tests::add_score").unwrap();
+        assert!(test.gas_used.unwrap() > 0);
+        assert!(test.resources.steps > add.resources.steps);
+        assert!(test.resources.l1_gas >= add.resources.l1_gas);
+    }
+
+    #[test]
+    fn coverage_maps_functions_to_their_sources() {
+        let (_, lcov) = run_fixture();
+
+        let record = lcov
+            .split("end_of_record\n")
+            .find(|record| record.contains("gas_report/src/lib.cairo\n"))
+            .expect("no record for the package sources");
+
+        let hits = |name: &str| {
+            record
+                .lines()
+                .find_map(|line| line.strip_prefix("FNDA:")?.strip_suffix(&format!(",{name}")))
+                .unwrap_or_else(|| panic!("no coverage for `{name}`"))
+                .parse::<usize>()
+                .unwrap()
+        };
+
+        // The entrypoint wrappers are generated by the Starknet plugin.
+        assert_eq!(hits("gas_report::counter::__wrapper__CounterImpl__increment"), 2);
+        assert_eq!(hits("gas_report::counter::__wrapper__CounterImpl__reset"), 0);
+        assert_eq!(hits("gas_report::tests::count_10"), 1);
+        assert_eq!(hits("gas_report::scores::__wrapper__ScoresImpl__add"), 2);
+        assert_eq!(hits("gas_report::score::__wrapper__name"), 1);
+
+        assert!(record.lines().any(|line| line.starts_with("DA:") && line.ends_with(",0")));
+        assert!(!lcov.contains("corelib"));
+    }
+}
@@ -0,0 +1,622 @@
+//! Runs Sierra functions on VMs kept by the runner, so their resources can be read from them.
+//!
+//! `CairoHintProcessor` runs the contracts called by a test on VMs of its own and drops their
+//! traces. The syscalls that enter a contract or change its class are handled here instead, on the
+//! same state, and every other hint is passed on to it.
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use blockifier::abi::constants::{
+    CALL_CONTRACT_GAS_COST, DEPLOY_GAS_COST, LIBRARY_CALL_GAS_COST, REPLACE_CLASS_GAS_COST,
+    STORAGE_WRITE_GAS_COST, SYSCALL_BASE_GAS_COST,
+};
+use blockifier::fee::eth_gas_constants::SHARP_GAS_PER_MEMORY_WORD;
+use cairo_lang_casm::hints::{Hint, StarknetHint};
+use cairo_lang_runner::casm_run::{extract_relocatable, MemBuffer, VMWrapper};
+use cairo_lang_runner::{
+    build_hints_dict, Arg, CairoHintProcessor, RunResultValue, RunnerError, SierraCasmRunner,
+    StarknetState,
+};
+use cairo_lang_sierra::ids::FunctionId;
+use cairo_lang_sierra::program::{Function, Program};
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_starknet::contract::ContractInfo;
+use cairo_lang_test_plugin::TestCompilation;
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use cairo_vm::felt::Felt252;
+use cairo_vm::hint_processor::hint_processor_definition::{HintProcessorLogic, HintReference};
+use cairo_vm::serde::deserialize_program::ApTracking;
+use cairo_vm::types::exec_scope::ExecutionScopes;
+use cairo_vm::types::relocatable::{MaybeRelocatable, Relocatable};
+use cairo_vm::vm::errors::hint_errors::HintError;
+use cairo_vm::vm::errors::memory_errors::MemoryError;
+use cairo_vm::vm::errors::vm_errors::VirtualMachineError;
+use cairo_vm::vm::runners::cairo_runner::{ResourceTracker, RunResources};
+use cairo_vm::vm::vm_core::VirtualMachine;
+
+use super::{EntrypointUsage, Resources};
+
+/// The L1 gas paid per felt published on L1.
+const L1_GAS_PER_FELT: usize = SHARP_GAS_PER_MEMORY_WORD;
+
+/// The memory cells used by an instance of each builtin.
+const BUILTIN_CELLS: [(&str, usize); 5] = [
+    ("pedersen_builtin", 3),
+    ("range_check_builtin", 1),
+    ("bitwise_builtin", 5),
+    ("ec_op_builtin", 7),
+    ("poseidon_builtin", 6),
+];
+
+/// Resulting options from a syscall: its return values, or the reason it failed.
+type SyscallResult = Result<Vec<MaybeRelocatable>, Vec<Felt252>>;
+
+macro_rules! fail_syscall {
+    ($reason:expr) => {
+        return Ok(Err(vec![Felt252::from_bytes_be($reason)]))
+    };
+    ($existing:ident, $reason:expr) => {{
+        $existing.push(Felt252::from_bytes_be($reason));
+        return Ok(Err($existing));
+    }};
+}
+
+/// Deducts the gas of a syscall from the given gas counter, or fails the syscall if there is not
+/// enough gas.
+macro_rules! deduct_gas {
+    ($gas:ident, $cost:ident) => {
+        if *$gas < syscall_gas($cost) {
+            fail_syscall!(b"Syscall out of gas");
+        }
+        *$gas -= syscall_gas($cost);
+    };
+}
+
+/// The gas charged by a syscall, given its cost in blockifier. `CairoHintProcessor` charges the
+/// same, from a private copy of these costs. The base cost of a syscall is already charged by the
+/// libfunc making it.
+fn syscall_gas(cost: u64) -> usize {
+    (cost - SYSCALL_BASE_GAS_COST) as usize
+}
+
+/// The state a run starts from, and leaves behind.
+#[derive(Clone, Default)]
+pub struct State {
+    pub starknet: StarknetState,
+    /// The class hash of every deployed contract, which `StarknetState` keeps to itself.
+    pub deployed: HashMap<Felt252, Felt252>,
+    /// The address of the contract the code runs as.
+    pub contract_address: Felt252,
+}
+
+/// The storage slots and contract classes written by a run.
+#[derive(Default)]
+struct StateDiff {
+    storage: HashSet<(Felt252, Felt252)>,
+    classes: HashSet<Felt252>,
+}
+
+impl StateDiff {
+    fn extend(&mut self, other: StateDiff) {
+        self.storage.extend(other.storage);
+        self.classes.extend(other.classes);
+    }
+
+    /// The L1 gas paid to publish the diff, sized like the onchain data of blockifier.
+    fn l1_gas(&self) -> usize {
+        let contracts: HashSet<_> =
+            self.storage.iter().map(|(address, _)| address).chain(&self.classes).collect();
+        (contracts.len() * 2 + self.classes.len() + self.storage.len() * 2) * L1_GAS_PER_FELT
+    }
+}
+
+/// What the runs of a test collect.
+pub struct Usage {
+    /// The calls to every entrypoint, by name.
+    pub entrypoints: BTreeMap<String, EntrypointUsage>,
+    /// The number of times each instruction of the CASM program was executed, by offset.
+    pub hits: Vec<usize>,
+}
+
+/// The outcome of a run.
+pub struct Run {
+    pub value: RunResultValue,
+    pub gas_counter: Option<Felt252>,
+    pub memory: Vec<Option<Felt252>>,
+    /// The resources of the run, including the contracts it called.
+    pub resources: Resources,
+    pub state: State,
+    diff: StateDiff,
+}
+
+/// Runs the functions of a test compilation.
+pub struct TestRunner<'a> {
+    pub runner: SierraCasmRunner,
+    program: &'a Program,
+    contracts: &'a OrderedHashMap<Felt252, ContractInfo>,
+    functions: HashMap<u64, &'a Function>,
+}
+
+impl<'a> TestRunner<'a> {
+    pub fn new(compiled: &'a TestCompilation) -> Result<Self, RunnerError> {
+        let runner = SierraCasmRunner::new(
+            compiled.sierra_program.clone(),
+            Some(MetadataComputationConfig {
+                function_set_costs: compiled.function_set_costs.clone(),
+            }),
+            compiled.contracts_info.clone(),
+        )?;
+        let functions = compiled
+            .sierra_program
+            .funcs
+            .iter()
+            .map(|function| (function.id.id, function))
+            .collect();
+
+        Ok(Self {
+            runner,
+            program: &compiled.sierra_program,
+            contracts: &compiled.contracts_info,
+            functions,
+        })
+    }
+
+    /// Returns an empty usage for the runs of a test.
+    pub fn usage(&self) -> Usage {
+        let size =
+            self.runner.get_casm_program().instructions.iter().map(|i| i.body.op_size()).sum();
+        Usage { entrypoints: BTreeMap::new(), hits: vec![0; size] }
+    }
+
+    /// Returns the number of times each function of the program was called, given the hits of
+    /// the instructions.
+    pub fn function_hits(&self, hits: &[usize]) -> Vec<(FunctionId, usize)> {
+        let statements = &self.runner.get_casm_program().debug_info.sierra_statement_info;
+        self.program
+            .funcs
+            .iter()
+            .map(|function| {
+                (function.id.clone(), hits[statements[function.entry_point.0].code_offset])
+            })
+            .collect()
+    }
+
+    /// Runs a function from `state` and records what it executed in `usage`.
+    pub fn run(
+        &self,
+        func: &Function,
+        args: &[Arg],
+        available_gas: Option<usize>,
+        state: State,
+        usage: &mut Usage,
+    ) -> Result<Run, RunnerError> {
+        let initial_gas = self.runner.get_initial_available_gas(func, available_gas)?;
+        let (entry_code, builtins) = self.runner.create_entry_code(func, args, initial_gas)?;
+        let footer = self.runner.create_code_footer();
+        let instructions =
+            entry_code.iter().chain(&self.runner.get_casm_program().instructions).chain(&footer);
+        let (hints_dict, string_to_hint) = build_hints_dict(instructions.clone());
+
+        let mut processor = TestHintProcessor {
+            inner: CairoHintProcessor {
+                runner: Some(&self.runner),
+                string_to_hint,
+                starknet_state: state.starknet,
+                run_resources: RunResources::default(),
+            },
+            runner: self,
+            deployed: state.deployed,
+            contract_address: state.contract_address,
+            diff: StateDiff::default(),
+            calls: Resources::default(),
+            usage,
+        };
+
+        let mut vm = VirtualMachine::new(true);
+        let result = self.runner.run_function_with_vm(
+            func,
+            &mut vm,
+            &mut processor,
+            hints_dict,
+            instructions,
+            builtins,
+        )?;
+
+        let trace = vm.get_relocated_trace().map(Vec::as_slice).unwrap_or_default();
+        let entry_size: usize = entry_code.iter().map(|i| i.body.op_size()).sum();
+        for entry in trace {
+            // The program is loaded at address 1, after the entry code.
+            let offset = entry.pc.checked_sub(1 + entry_size);
+            if let Some(hits) = offset.and_then(|offset| processor.usage.hits.get_mut(offset)) {
+                *hits += 1;
+            }
+        }
+
+        let mut resources = processor.calls;
+        resources.steps += trace.len();
+        for builtin in vm.get_builtin_runners() {
+            let Some((name, cells)) =
+                BUILTIN_CELLS.iter().find(|(name, _)| *name == builtin.name())
+            else {
+                continue;
+            };
+            let used = vm.get_segment_used_size(builtin.base()).unwrap_or_default();
+            *resources.builtins.entry(name.to_string()).or_default() += used / cells;
+        }
+        resources.l1_gas = processor.diff.l1_gas();
+
+        Ok(Run {
+            value: result.value,
+            gas_counter: result.gas_counter,
+            memory: result.memory,
+            resources,
+            state: State {
+                starknet: processor.inner.starknet_state,
+                deployed: processor.deployed,
+                contract_address: processor.contract_address,
+            },
+            diff: processor.diff,
+        })
+    }
+}
+
+/// Hint processor of a run, entering the called contracts through the [`TestRunner`].
+struct TestHintProcessor<'a, 'b> {
+    inner: CairoHintProcessor<'a>,
+    runner: &'a TestRunner<'a>,
+    deployed: HashMap<Felt252, Felt252>,
+    contract_address: Felt252,
+    diff: StateDiff,
+    /// The resources of the contracts called by the run.
+    calls: Resources,
+    usage: &'b mut Usage,
+}
+
+impl HintProcessorLogic for TestHintProcessor<'_, '_> {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+        constants: &HashMap<String, Felt252>,
+    ) -> Result<(), HintError> {
+        match hint_data.downcast_ref::<Hint>() {
+            Some(Hint::Starknet(StarknetHint::SystemCall { system })) => {
+                let system_ptr = extract_relocatable(vm, system)?;
+                let selector = vm.get_integer(system_ptr)?.to_bytes_be();
+                match selector.as_slice() {
+                    b"CallContract" | b"LibraryCall" | b"Deploy" | b"ReplaceClass" => {
+                        return self.execute_syscall(&selector, system_ptr, vm);
+                    }
+                    b"StorageWrite" => self.record_storage_write(system_ptr, vm)?,
+                    _ => {}
+                }
+            }
+            Some(Hint::Starknet(StarknetHint::Cheatcode { selector, input_start, .. }))
+                if selector.value.to_bytes_be().1 == b"set_contract_address" =>
+            {
+                let input = extract_relocatable(vm, input_start)?;
+                self.contract_address = vm.get_integer(input)?.into_owned();
+            }
+            _ => {}
+        }
+
+        self.inner.execute_hint(vm, exec_scopes, hint_data, constants)
+    }
+
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        ap_tracking_data: &ApTracking,
+        reference_ids: &HashMap<String, usize>,
+        references: &[HintReference],
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        self.inner.compile_hint(hint_code, ap_tracking_data, reference_ids, references)
+    }
+}
+
+impl ResourceTracker for TestHintProcessor<'_, '_> {
+    fn consumed(&self) -> bool {
+        self.inner.consumed()
+    }
+
+    fn consume_step(&mut self) {
+        self.inner.consume_step()
+    }
+
+    fn get_n_steps(&self) -> Option<usize> {
+        self.inner.get_n_steps()
+    }
+
+    fn run_resources(&self) -> &RunResources {
+        self.inner.run_resources()
+    }
+}
+
+impl TestHintProcessor<'_, '_> {
+    /// Records the slot written by a `StorageWrite` syscall, which is then run by the inner
+    /// processor.
+    fn record_storage_write(
+        &mut self,
+        system_ptr: Relocatable,
+        vm: &mut VirtualMachine,
+    ) -> Result<(), HintError> {
+        let mut system_buffer = MemBuffer::new(vm, system_ptr);
+        system_buffer.next_felt252()?;
+        let gas_counter = system_buffer.next_usize()?;
+        let address_domain = system_buffer.next_felt252()?.into_owned();
+        let key = system_buffer.next_felt252()?.into_owned();
+
+        // Only address domain 0 is supported, the syscall fails otherwise.
+        if gas_counter >= syscall_gas(STORAGE_WRITE_GAS_COST) && address_domain == Felt252::from(0)
+        {
+            self.diff.storage.insert((self.contract_address.clone(), key));
+        }
+
+        Ok(())
+    }
+
+    /// Executes a syscall that enters a contract or changes its class.
+    fn execute_syscall(
+        &mut self,
+        selector: &[u8],
+        system_ptr: Relocatable,
+        vm: &mut VirtualMachine,
+    ) -> Result<(), HintError> {
+        let mut system_buffer = MemBuffer::new(vm, system_ptr);
+        system_buffer.next_felt252()?;
+        let mut gas_counter = system_buffer.next_usize()?;
+
+        let result = match selector {
+            b"CallContract" => {
+                let contract_address = system_buffer.next_felt252()?.into_owned();
+                let selector = system_buffer.next_felt252()?.into_owned();
+                let calldata = system_buffer.next_arr()?;
+                self.call_contract(
+                    &mut gas_counter,
+                    contract_address,
+                    selector,
+                    calldata,
+                    &mut system_buffer,
+                )?
+            }
+            b"LibraryCall" => {
+                let class_hash = system_buffer.next_felt252()?.into_owned();
+                let selector = system_buffer.next_felt252()?.into_owned();
+                let calldata = system_buffer.next_arr()?;
+                self.library_call(
+                    &mut gas_counter,
+                    class_hash,
+                    selector,
+                    calldata,
+                    &mut system_buffer,
+                )?
+            }
+            b"Deploy" => {
+                let class_hash = system_buffer.next_felt252()?.into_owned();
+                system_buffer.next_felt252()?;
+                let calldata = system_buffer.next_arr()?;
+                let deploy_from_zero = *system_buffer.next_felt252()? != Felt252::from(0);
+                self.deploy(
+                    &mut gas_counter,
+                    class_hash,
+                    calldata,
+                    deploy_from_zero,
+                    &mut system_buffer,
+                )?
+            }
+            _ => {
+                let class_hash = system_buffer.next_felt252()?.into_owned();
+                self.replace_class(&mut gas_counter, class_hash)?
+            }
+        };
+
+        system_buffer.write(gas_counter)?;
+        match result {
+            Ok(values) => {
+                system_buffer.write(Felt252::from(0))?;
+                system_buffer.write_data(values.into_iter())?;
+            }
+            Err(revert_reason) => {
+                system_buffer.write(Felt252::from(1))?;
+                system_buffer.write_arr(revert_reason.into_iter())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes the `call_contract_syscall` syscall.
+    fn call_contract(
+        &mut self,
+        gas_counter: &mut usize,
+        contract_address: Felt252,
+        selector: Felt252,
+        calldata: Vec<Felt252>,
+        vm: &mut dyn VMWrapper,
+    ) -> Result<SyscallResult, HintError> {
+        deduct_gas!(gas_counter, CALL_CONTRACT_GAS_COST);
+
+        let runner = self.runner;
+        let Some(class_hash) = self.deployed.get(&contract_address) else {
+            fail_syscall!(b"CONTRACT_NOT_DEPLOYED");
+        };
+        let Some(entry_point) =
+            runner.contracts.get(class_hash).and_then(|info| info.externals.get(&selector))
+        else {
+            fail_syscall!(b"ENTRYPOINT_NOT_FOUND");
+        };
+
+        let context = (contract_address, self.contract_address.clone());
+        match self.call_entry_point(gas_counter, entry_point, calldata, Some(context), vm)? {
+            Ok((res_data_start, res_data_end)) => {
+                Ok(Ok(vec![res_data_start.into(), res_data_end.into()]))
+            }
+            Err(mut revert_reason) => fail_syscall!(revert_reason, b"ENTRYPOINT_FAILED"),
+        }
+    }
+
+    /// Executes the `library_call_syscall` syscall.
+    fn library_call(
+        &mut self,
+        gas_counter: &mut usize,
+        class_hash: Felt252,
+        selector: Felt252,
+        calldata: Vec<Felt252>,
+        vm: &mut dyn VMWrapper,
+    ) -> Result<SyscallResult, HintError> {
+        deduct_gas!(gas_counter, LIBRARY_CALL_GAS_COST);
+
+        let runner = self.runner;
+        let Some(contract_info) = runner.contracts.get(&class_hash) else {
+            fail_syscall!(b"CLASS_HASH_NOT_DECLARED");
+        };
+        let Some(entry_point) = contract_info.externals.get(&selector) else {
+            fail_syscall!(b"ENTRYPOINT_NOT_FOUND");
+        };
+
+        match self.call_entry_point(gas_counter, entry_point, calldata, None, vm)? {
+            Ok((res_data_start, res_data_end)) => {
+                Ok(Ok(vec![res_data_start.into(), res_data_end.into()]))
+            }
+            Err(mut revert_reason) => fail_syscall!(revert_reason, b"ENTRYPOINT_FAILED"),
+        }
+    }
+
+    /// Executes the `deploy_syscall` syscall.
+    fn deploy(
+        &mut self,
+        gas_counter: &mut usize,
+        class_hash: Felt252,
+        calldata: Vec<Felt252>,
+        deploy_from_zero: bool,
+        vm: &mut dyn VMWrapper,
+    ) -> Result<SyscallResult, HintError> {
+        deduct_gas!(gas_counter, DEPLOY_GAS_COST);
+
+        // Assign an arbitrary address to the contract, as `CairoHintProcessor` does.
+        let contract_address = self.inner.starknet_state.get_next_id();
+
+        let runner = self.runner;
+        let Some(contract_info) = runner.contracts.get(&class_hash) else {
+            fail_syscall!(b"CLASS_HASH_NOT_FOUND");
+        };
+
+        let (res_data_start, res_data_end) = if let Some(constructor) = &contract_info.constructor {
+            let caller_address =
+                if deploy_from_zero { Felt252::from(0) } else { self.contract_address.clone() };
+            let context = (contract_address.clone(), caller_address);
+            match self.call_entry_point(gas_counter, constructor, calldata, Some(context), vm)? {
+                Ok(res_data) => res_data,
+                Err(mut revert_reason) => fail_syscall!(revert_reason, b"CONSTRUCTOR_FAILED"),
+            }
+        } else if calldata.is_empty() {
+            (Relocatable::from((0, 0)), Relocatable::from((0, 0)))
+        } else {
+            fail_syscall!(b"INVALID_CALLDATA_LEN");
+        };
+
+        self.deployed.insert(contract_address.clone(), class_hash);
+        self.diff.classes.insert(contract_address.clone());
+
+        Ok(Ok(vec![contract_address.into(), res_data_start.into(), res_data_end.into()]))
+    }
+
+    /// Executes the `replace_class_syscall` syscall.
+    fn replace_class(
+        &mut self,
+        gas_counter: &mut usize,
+        class_hash: Felt252,
+    ) -> Result<SyscallResult, HintError> {
+        deduct_gas!(gas_counter, REPLACE_CLASS_GAS_COST);
+
+        if !self.runner.contracts.contains_key(&class_hash) {
+            fail_syscall!(b"CLASS_HASH_NOT_FOUND");
+        }
+
+        self.deployed.insert(self.contract_address.clone(), class_hash);
+        self.diff.classes.insert(self.contract_address.clone());
+
+        Ok(Ok(vec![]))
+    }
+
+    /// Runs an entry point on a VM of its own, as the contract and caller addresses of `context`
+    /// if given. The state it leaves is kept only if it succeeds.
+    fn call_entry_point(
+        &mut self,
+        gas_counter: &mut usize,
+        entry_point: &FunctionId,
+        calldata: Vec<Felt252>,
+        context: Option<(Felt252, Felt252)>,
+        vm: &mut dyn VMWrapper,
+    ) -> Result<Result<(Relocatable, Relocatable), Vec<Felt252>>, HintError> {
+        let function = self.runner.functions[&entry_point.id];
+
+        let contract_address =
+            context.as_ref().map_or(&self.contract_address, |(address, _)| address).clone();
+        let old_addrs =
+            context.map(|context| self.inner.starknet_state.open_caller_context(context));
+        let state = State {
+            starknet: self.inner.starknet_state.clone(),
+            deployed: self.deployed.clone(),
+            contract_address,
+        };
+
+        let run = self
+            .runner
+            .run(function, &[Arg::Array(calldata)], Some(*gas_counter), state, self.usage)
+            .map_err(|err| HintError::CustomHint(err.to_string().into_boxed_str()))?;
+
+        *gas_counter = run
+            .gas_counter
+            .and_then(|gas| usize::try_from(gas.to_bigint()).ok())
+            .unwrap_or_default();
+        self.calls.add(&run.resources);
+        self.usage.entrypoints.entry(entrypoint_name(function)).or_default().add(&run.resources);
+
+        let result = match run.value {
+            RunResultValue::Success(value) => {
+                self.inner.starknet_state = run.state.starknet;
+                self.deployed = run.state.deployed;
+                self.diff.extend(run.diff);
+                Ok(segment_with_data(vm, read_array(&run.memory, &value).into_iter())?)
+            }
+            RunResultValue::Panic(panic_data) => Err(panic_data),
+        };
+
+        if let Some(old_addrs) = old_addrs {
+            self.inner.starknet_state.close_caller_context(old_addrs);
+        }
+
+        Ok(result)
+    }
+}
+
+/// The name of an entry point, without the prefix of the wrapper the Starknet plugin generates.
+fn entrypoint_name(function: &Function) -> String {
+    function.id.to_string().replace("__wrapper__", "")
+}
+
+/// Creates a new segment in the VM memory and writes data to it, returning the start and end
+/// pointers of the segment.
+fn segment_with_data(
+    vm: &mut dyn VMWrapper,
+    data: impl Iterator<Item = Felt252>,
+) -> Result<(Relocatable, Relocatable), MemoryError> {
+    let mut segment = MemBuffer::new_segment(vm);
+    let start = segment.ptr;
+    segment.write_data(data)?;
+    Ok((start, segment.ptr))
+}
+
+/// Reads the array returned by an entry point, given by its start and end in the relocated
+/// memory.
+fn read_array(memory: &[Option<Felt252>], value: &[Felt252]) -> Vec<Felt252> {
+    let [start, end] = value else {
+        return vec![];
+    };
+    let start = usize::try_from(start.to_bigint()).unwrap_or_default();
+    let end = usize::try_from(end.to_bigint()).unwrap_or_default();
+    (start..end).filter_map(|i| memory.get(i).cloned().flatten()).collect()
+}
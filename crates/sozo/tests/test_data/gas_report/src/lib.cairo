@@ -0,0 +1,133 @@
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    loop {
+        if i == n {
+            break;
+        }
+        i += 1;
+    };
+    i
+}
+
+#[starknet::interface]
+trait ICounter<TContractState> {
+    fn increment(ref self: TContractState, by: u32);
+    fn get(self: @TContractState) -> u32;
+    fn reset(ref self: TContractState);
+}
+
+#[starknet::contract]
+mod counter {
+    #[storage]
+    struct Storage {
+        value: u32,
+    }
+
+    #[external(v0)]
+    impl CounterImpl of super::ICounter<ContractState> {
+        fn increment(ref self: ContractState, by: u32) {
+            self.value.write(self.value.read() + by);
+        }
+
+        fn get(self: @ContractState) -> u32 {
+            self.value.read()
+        }
+
+        fn reset(ref self: ContractState) {
+            self.value.write(0);
+        }
+    }
+}
+
+#[derive(Model, Copy, Drop, Serde)]
+struct Score {
+    #[key]
+    player: starknet::ContractAddress,
+    points: u32,
+}
+
+#[starknet::interface]
+trait IScores<TContractState> {
+    fn add(self: @TContractState, points: u32);
+}
+
+#[dojo::contract]
+mod scores {
+    use starknet::get_caller_address;
+
+    use super::{IScores, Score};
+
+    #[external(v0)]
+    impl ScoresImpl of IScores<ContractState> {
+        fn add(self: @ContractState, points: u32) {
+            let world = self.world_dispatcher.read();
+            let player = get_caller_address();
+            let score = get!(world, player, (Score));
+            set!(world, (Score { player, points: score.points + points }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::syscalls::deploy_syscall;
+
+    use dojo::test_utils::spawn_test_world;
+    use dojo::world::{IWorldDispatcher, IWorldDispatcherTrait};
+
+    use super::{count, counter, ICounterDispatcher, ICounterDispatcherTrait};
+    use super::{score, scores, IScoresDispatcher, IScoresDispatcherTrait, Score};
+
+    #[test]
+    #[available_gas(1000000)]
+    fn count_0() {
+        assert(count(0) == 0, 'wrong count');
+    }
+
+    #[test]
+    #[available_gas(1000000)]
+    fn count_10() {
+        assert(count(10) == 10, 'wrong count');
+    }
+
+    #[test]
+    #[available_gas(1000000)]
+    fn count_20() {
+        assert(count(20) == 20, 'wrong count');
+    }
+
+    #[test]
+    fn without_gas() {
+        assert(1 + 1 == 2, 'wrong sum');
+    }
+
+    #[test]
+    #[available_gas(3000000)]
+    fn increment_counter() {
+        let (address, _) = deploy_syscall(
+            counter::TEST_CLASS_HASH.try_into().unwrap(), 0, array![].span(), false
+        )
+            .unwrap();
+        let counter = ICounterDispatcher { contract_address: address };
+
+        counter.increment(2);
+        counter.increment(3);
+        assert(counter.get() == 5, 'wrong value');
+    }
+
+    #[test]
+    #[available_gas(30000000)]
+    fn add_score() {
+        let world = spawn_test_world(array![score::TEST_CLASS_HASH]);
+        let contract_address = world
+            .deploy_contract('salt', scores::TEST_CLASS_HASH.try_into().unwrap());
+        let scores = IScoresDispatcher { contract_address };
+
+        scores.add(2);
+        scores.add(3);
+
+        let caller = starknet::contract_address_const::<0x0>();
+        let score = get!(world, caller, Score);
+        assert(score.points == 5, 'wrong score');
+    }
+}
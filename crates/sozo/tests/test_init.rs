@@ -10,4 +10,9 @@ fn test_init() {
     let assert = get_snapbox().arg("init").current_dir(&pt).assert().success();
 
     assert.stdout_eq(expected_stdout("init"));
+
+    // The embedded template is used, without cloning it.
+    assert!(pt.path().join("Scarb.toml").exists());
+    assert!(pt.path().join("src/actions.cairo").exists());
+    assert!(!pt.path().join(".git").exists());
 }
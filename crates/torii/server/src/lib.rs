@@ -0,0 +1,145 @@
+//! Torii indexes the events of a World into a database, and serves its entities through GraphQL
+//! and gRPC.
+
+pub mod proxy;
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use dojo_world::contracts::world::WorldContractReader;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use starknet::core::types::FieldElement;
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::JsonRpcClient;
+use tokio::sync::broadcast::Sender;
+use tokio_stream::StreamExt;
+use torii_core::engine::{Engine, EngineConfig, Processors};
+use torii_core::processors::metadata_update::MetadataUpdateProcessor;
+use torii_core::processors::register_model::RegisterModelProcessor;
+use torii_core::processors::store_set_record::StoreSetRecordProcessor;
+use torii_core::processors::store_transaction::StoreTransactionProcessor;
+use torii_core::simple_broker::SimpleBroker;
+use torii_core::sql::Sql;
+use torii_core::types::Model;
+use tracing::info;
+use url::Url;
+
+use crate::proxy::Proxy;
+
+#[derive(Debug, Clone)]
+pub struct ToriiConfig {
+    /// The World to index.
+    pub world_address: FieldElement,
+    /// The JSON-RPC endpoint of the chain of the World.
+    pub rpc_url: Url,
+    /// The path of the database, or `:memory:` for an in-memory database.
+    pub database: String,
+    /// The block to start indexing from, ignored if the database already has a head.
+    pub start_block: u64,
+    /// The address the API endpoints are served at.
+    pub addr: SocketAddr,
+    /// The origins allowed to access the API endpoints, or `*` for all.
+    pub allowed_origins: Vec<String>,
+    /// The external url of the server, used for the GraphQL Playground.
+    pub external_url: Option<Url>,
+}
+
+/// Indexes the World and serves its entities until a shutdown signal is sent on `shutdown_tx`.
+pub async fn run(config: ToriiConfig, shutdown_tx: Sender<()>) -> anyhow::Result<()> {
+    let database_url = format!("sqlite:{}", &config.database);
+    let options = SqliteConnectOptions::from_str(&database_url)?.create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+
+    sqlx::migrate!("../migrations").run(&pool).await?;
+
+    let provider: Arc<_> = JsonRpcClient::new(HttpTransport::new(config.rpc_url)).into();
+
+    // Get world address
+    let world = WorldContractReader::new(config.world_address, &provider);
+
+    let mut db = Sql::new(pool.clone(), config.world_address).await?;
+    let processors = Processors {
+        event: vec![
+            Box::new(RegisterModelProcessor),
+            Box::new(StoreSetRecordProcessor),
+            Box::new(MetadataUpdateProcessor),
+        ],
+        transaction: vec![Box::new(StoreTransactionProcessor)],
+        ..Processors::default()
+    };
+
+    let (block_tx, block_rx) = tokio::sync::mpsc::channel(100);
+
+    let mut engine = Engine::new(
+        world,
+        &mut db,
+        &provider,
+        processors,
+        EngineConfig { start_block: config.start_block, ..Default::default() },
+        shutdown_tx.clone(),
+        Some(block_tx),
+    );
+
+    let addr = config.addr;
+
+    let shutdown_rx = shutdown_tx.subscribe();
+    let (grpc_addr, grpc_server) = torii_grpc::server::new(
+        shutdown_rx,
+        &pool,
+        block_rx,
+        config.world_address,
+        Arc::clone(&provider),
+    )
+    .await?;
+
+    let proxy_server = Arc::new(Proxy::new(addr, config.allowed_origins, Some(grpc_addr), None));
+
+    let graphql_server = spawn_rebuilding_graphql_server(
+        shutdown_tx.clone(),
+        pool.into(),
+        config.external_url,
+        proxy_server.clone(),
+    );
+
+    info!("🚀 Torii listening at {}", format!("http://{}", addr));
+    info!("Graphql playground: {}\n", format!("http://{}/graphql", addr));
+
+    tokio::select! {
+        _ = engine.start() => {},
+        _ = proxy_server.start(shutdown_tx.subscribe()) => {},
+        _ = graphql_server => {},
+        _ = grpc_server => {},
+    };
+
+    Ok(())
+}
+
+async fn spawn_rebuilding_graphql_server(
+    shutdown_tx: Sender<()>,
+    pool: Arc<SqlitePool>,
+    external_url: Option<Url>,
+    proxy_server: Arc<Proxy>,
+) {
+    let mut broker = SimpleBroker::<Model>::subscribe();
+
+    loop {
+        let shutdown_rx = shutdown_tx.subscribe();
+        let (new_addr, new_server) =
+            torii_graphql::server::new(shutdown_rx, &pool, external_url.clone()).await;
+
+        tokio::spawn(new_server);
+
+        proxy_server.set_graphql_addr(new_addr).await;
+
+        // Break the loop if there are no more events
+        if broker.next().await.is_none() {
+            break;
+        }
+    }
+}
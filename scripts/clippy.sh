@@ -1,7 +1,9 @@
 #!/bin/bash
 
+source "$(dirname "$0")/features.sh"
+
 run_clippy() {
-  cargo clippy --all-targets --all-features "$@" -- -D warnings -D future-incompatible -D nonstandard-style -D rust-2018-idioms -D unused
+  cargo clippy --workspace --all-targets --features "$FEATURES" "$@" -- -D warnings -D future-incompatible -D nonstandard-style -D rust-2018-idioms -D unused
 }
 
 run_clippy
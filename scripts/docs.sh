@@ -1,3 +1,5 @@
 #!/bin/bash
 
-RUSTDOCFLAGS="-Dwarnings" cargo doc --document-private-items --no-deps --all-features
+source "$(dirname "$0")/features.sh"
+
+RUSTDOCFLAGS="-Dwarnings" cargo doc --document-private-items --no-deps --workspace --features "$FEATURES"
@@ -0,0 +1,5 @@
+#!/bin/bash
+
+# Every feature of the workspace, except the `native` feature of Katana which needs LLVM and a newer
+# Rust toolchain. It's checked by the `katana-native` job of the CI instead.
+export FEATURES="dojo-lang/testing,dojo-test-utils/build-examples,dojo-world/contracts,dojo-world/manifest,dojo-world/metadata,dojo-world/migration,katana/messaging,katana/starknet-messaging,torii-grpc/client,torii-grpc/server,torii-server/sqlite"